@@ -0,0 +1,40 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use sqlx_plus::{bulk_insert_sql, PlaceHolders};
+
+macro_rules! wide_row {
+    ( $name:ident, $db:ty ) => {
+        #[allow(dead_code)]
+        #[derive(sqlx_plus::Insertable)]
+        #[insertable($db, "wide_row")]
+        struct $name {
+            column_0: i64,
+            column_1: i64,
+            column_2: i64,
+            column_3: i64,
+            column_4: i64,
+            column_5: String,
+            column_6: String,
+            column_7: String,
+            column_8: String,
+            column_9: String,
+        }
+    };
+}
+
+wide_row!(SqliteRow, sqlx::Sqlite);
+wide_row!(PostgresRow, sqlx::Postgres);
+
+fn bench_sql(c: &mut Criterion) {
+    c.bench_function("bulk_insert_sql sqlite 3000 rows", |b| {
+        b.iter(|| bulk_insert_sql::<SqliteRow>(black_box("wide_row"), black_box(3000)))
+    });
+    c.bench_function("bulk_insert_sql postgres 3000 rows", |b| {
+        b.iter(|| bulk_insert_sql::<PostgresRow>(black_box("wide_row"), black_box(3000)))
+    });
+    c.bench_function("placeholders postgres 30000", |b| {
+        b.iter(|| sqlx::Postgres::placeholders(black_box(30000), None))
+    });
+}
+
+criterion_group!(benches, bench_sql);
+criterion_main!(benches);
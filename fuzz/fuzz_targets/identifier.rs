@@ -0,0 +1,18 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use sqlx_plus::{sanitize, Dialect};
+
+fuzz_target!(|input: (u8, &str)| {
+    let (dialect, name) = input;
+    sanitize::check_identifier(dialect_of(dialect), name);
+});
+
+fn dialect_of(n: u8) -> Dialect {
+    match n % 4 {
+        0 => Dialect::Sqlite,
+        1 => Dialect::MySql,
+        2 => Dialect::Postgres,
+        _ => Dialect::Mssql,
+    }
+}
@@ -0,0 +1,18 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use sqlx_plus::{sanitize, Dialect};
+
+fuzz_target!(|input: (u8, u16, u16)| {
+    let (dialect, rows, columns) = input;
+    sanitize::check_placeholders(dialect_of(dialect), rows.into(), columns.into());
+});
+
+fn dialect_of(n: u8) -> Dialect {
+    match n % 4 {
+        0 => Dialect::Sqlite,
+        1 => Dialect::MySql,
+        2 => Dialect::Postgres,
+        _ => Dialect::Mssql,
+    }
+}
@@ -0,0 +1,482 @@
+//! Parsing of the `#[insertable(...)]` attributes, kept apart from the code generation so it
+//! can be tested on its own.
+
+pub(crate) struct InsertableAttr {
+    pub(crate) db: syn::Path,
+    pub(crate) table_name: String,
+    pub(crate) key: Option<syn::Type>,
+    pub(crate) key_column: Option<String>,
+    /// `key_columns("tenant_id", "id")`, the columns of a composite key in the order of the
+    /// elements of the tuple `key`.
+    pub(crate) key_columns: Vec<String>,
+    /// The canonical struct of the table, whose columns must be a superset of ours.
+    pub(crate) same_table_as: Option<syn::Path>,
+    /// `column_order = "alphabetical"`, which keeps the generated SQL stable when fields are
+    /// reordered.
+    pub(crate) alphabetical: bool,
+    /// `on_unique("constraint", "Variant")`, mapping violations of the constraint to a variant.
+    pub(crate) on_unique: Vec<(String, syn::Ident)>,
+    /// `unique("tenant_id", "slug")`, once per unique constraint of the table.
+    pub(crate) unique: Vec<Vec<String>>,
+    /// `smoke_test`, generating `__sqlx_plus_check` for tests. Requires `Default`.
+    pub(crate) smoke_test: bool,
+    /// `extra = "field"`, a map of semi-structured attributes inserted into a single JSON column
+    /// named like the field. Requires the `json` feature of sqlx-plus.
+    pub(crate) extra: Option<String>,
+    /// `builder`, generating `<Name>Builder`.
+    pub(crate) builder: bool,
+    /// `as_row`, implementing `AsRow`. Requires `Debug` on every inserted field.
+    pub(crate) as_row: bool,
+    /// `max_params = 999`, the bind parameter limit a single row must fit in. Defaults to the
+    /// limit of the database.
+    pub(crate) max_params: Option<usize>,
+}
+
+impl syn::parse::Parse for InsertableAttr {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let mut db = None;
+        let mut table_name = None;
+        // The positional form starts with the database type and the table, the named one with
+        // any option, e.g. `db = ...`.
+        let named = input.peek2(syn::Token![=])
+            || input.fork().parse::<syn::Ident>().is_ok_and(|name| {
+                [
+                    "on_unique",
+                    "key_columns",
+                    "unique",
+                    "smoke_test",
+                    "builder",
+                    "as_row",
+                ]
+                .contains(&&*name.to_string())
+            });
+        if !named {
+            db = Some(input.parse()?);
+            input.parse::<syn::Token![,]>()?;
+            table_name = Some(input.parse::<syn::LitStr>()?.value());
+        }
+
+        let mut key = None;
+        let mut key_column = None;
+        let mut key_columns = Vec::new();
+        let mut unique = Vec::new();
+        let mut same_table_as = None;
+        let mut alphabetical = false;
+        let mut on_unique = Vec::new();
+        let mut smoke_test = false;
+        let mut extra = None;
+        let mut builder = false;
+        let mut as_row = false;
+        let mut max_params = None;
+        let mut first = named;
+        while !input.is_empty() {
+            if !first {
+                input.parse::<syn::Token![,]>()?;
+                if input.is_empty() {
+                    break;
+                }
+            }
+            first = false;
+            let name: syn::Ident = input.parse()?;
+            if name == "on_unique" {
+                let content;
+                syn::parenthesized!(content in input);
+                let constraint: syn::LitStr = content.parse()?;
+                content.parse::<syn::Token![,]>()?;
+                let variant: syn::LitStr = content.parse()?;
+                on_unique.push((constraint.value(), variant.parse()?));
+                continue;
+            }
+            if name == "key_columns" {
+                let content;
+                syn::parenthesized!(content in input);
+                let columns = content
+                    .parse_terminated::<syn::LitStr, syn::Token![,]>(syn::parse::Parse::parse)?;
+                key_columns.extend(columns.iter().map(syn::LitStr::value));
+                continue;
+            }
+            if name == "unique" {
+                let content;
+                syn::parenthesized!(content in input);
+                let columns = content
+                    .parse_terminated::<syn::LitStr, syn::Token![,]>(syn::parse::Parse::parse)?;
+                if columns.is_empty() {
+                    return Err(syn::Error::new(name.span(), "`unique` needs columns"));
+                }
+                unique.push(columns.iter().map(syn::LitStr::value).collect());
+                continue;
+            }
+            if name == "smoke_test" {
+                smoke_test = true;
+                continue;
+            }
+            if name == "builder" {
+                builder = true;
+                continue;
+            }
+            if name == "as_row" {
+                as_row = true;
+                continue;
+            }
+            input.parse::<syn::Token![=]>()?;
+            match name.to_string().as_str() {
+                "db" => db = Some(input.parse()?),
+                "table" => table_name = Some(input.parse::<syn::LitStr>()?.value()),
+                "key" => key = Some(input.parse()?),
+                "key_column" => key_column = Some(input.parse::<syn::LitStr>()?.value()),
+                "same_table_as" => same_table_as = Some(input.parse::<syn::LitStr>()?.parse()?),
+                "extra" => extra = Some(input.parse::<syn::LitStr>()?.value()),
+                "max_params" => max_params = Some(input.parse::<syn::LitInt>()?.base10_parse()?),
+                "column_order" => {
+                    let order: syn::LitStr = input.parse()?;
+                    alphabetical = match order.value().as_str() {
+                        "declaration" => false,
+                        "alphabetical" => true,
+                        _ => {
+                            return Err(syn::Error::new(
+                                order.span(),
+                                "expected \"declaration\" or \"alphabetical\"",
+                            ))
+                        }
+                    };
+                }
+                _ => return Err(syn::Error::new(
+                    name.span(),
+                    "expected `db`, `table`, `key`, `key_column`, `key_columns`, `same_table_as`, `column_order`, `extra`, `max_params`, `on_unique`, `unique`, `smoke_test`, `builder` or `as_row`",
+                )),
+            }
+        }
+
+        let db = db.ok_or_else(|| input.error("missing `db = <database type>`"))?;
+        let table_name = table_name.ok_or_else(|| input.error("missing `table = \"<name>\"`"))?;
+
+        Ok(InsertableAttr {
+            db,
+            table_name,
+            key,
+            key_column,
+            key_columns,
+            same_table_as,
+            alphabetical,
+            on_unique,
+            unique,
+            smoke_test,
+            extra,
+            builder,
+            as_row,
+            max_params,
+        })
+    }
+}
+
+/// Normalizers of `sqlx_plus::normalize` which can be named without a path.
+const BUILTIN_NORMALIZERS: &[&str] = &["nfc", "nfc_lowercase", "lowercase", "trim"];
+
+/// The bind parameter limit of the databases sqlx-plus knows, mirroring
+/// `sqlx_plus::Backend::MAX_BIND_PARAMS`.
+pub(crate) fn max_params_of(db: &syn::Path) -> Option<usize> {
+    match db.segments.last()?.ident.to_string().as_str() {
+        "Sqlite" => Some(32766),
+        "MySql" | "Postgres" => Some(65535),
+        "Mssql" => Some(2100),
+        _ => None,
+    }
+}
+
+/// Whether the database fills the period columns of system-versioned tables itself and rejects
+/// writing them: `GENERATED ALWAYS AS ROW START` on MSSQL. `MySql` is also MySQL, which has no
+/// system versioning, so MariaDB opts in per field with `period = "generated"`. Elsewhere they
+/// are maintained by triggers or the application and written like any column.
+pub(crate) fn generates_periods(db: &syn::Path) -> bool {
+    db.segments
+        .last()
+        .is_some_and(|segment| segment.ident == "Mssql")
+}
+
+/// The expression and the `sqlx_plus::geo` wrapper of `geo` fields, mirroring
+/// `sqlx_plus::geo::wkb_expression`: WKB, or WKT on MSSQL, which sqlx binds no bytes for.
+pub(crate) fn geo_binding_of(db: &syn::Path) -> Option<(&'static str, &'static str)> {
+    match db.segments.last()?.ident.to_string().as_str() {
+        "Sqlite" | "MySql" | "Postgres" => Some(("ST_GeomFromWKB(?)", "Wkb")),
+        "Mssql" => Some(("geometry::STGeomFromText(?, 0)", "Wkt")),
+        _ => None,
+    }
+}
+
+pub(crate) struct FieldAttr {
+    pub(crate) normalize: Option<syn::Path>,
+    pub(crate) key: bool,
+    pub(crate) skip_if: Option<syn::Path>,
+    /// `immutable`, leaving the column out of updates, e.g. `created_at` or `tenant_id`.
+    pub(crate) immutable: bool,
+    /// `period`, a period column of a system-versioned table, see [`generates_periods`].
+    pub(crate) period: bool,
+    /// `period = "generated"`, a period column the database generates whatever it is.
+    pub(crate) generated_period: bool,
+    /// `geo`, a `geo_types` geometry bound as WKB or WKT, see [`geo_binding_of`].
+    pub(crate) geo: bool,
+    /// `expr = "ST_GeomFromWKB(?, 4326)"`, the SQL expression around the placeholder.
+    pub(crate) expr: Option<String>,
+    /// `scale = 2`, rounding a decimal to the scale of its column.
+    pub(crate) scale: Option<u32>,
+    /// `anonymize = "email"`, the faker of a `sqlx_plus::Anonymizer` replacing the value.
+    pub(crate) anonymize: Option<String>,
+    /// `auto_now`, an `Option` timestamp filled with the time of the insert when `None`.
+    pub(crate) auto_now: bool,
+    /// `auto_uuid`, an `Option<Uuid>` filled with a new key when `None`.
+    pub(crate) auto_uuid: bool,
+    /// `default_if_none`, an `Option` left out of the insert when `None`.
+    pub(crate) default_if_none: bool,
+}
+
+impl syn::parse::Parse for FieldAttr {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let mut attr = FieldAttr {
+            normalize: None,
+            key: false,
+            skip_if: None,
+            immutable: false,
+            period: false,
+            generated_period: false,
+            geo: false,
+            expr: None,
+            scale: None,
+            anonymize: None,
+            auto_now: false,
+            auto_uuid: false,
+            default_if_none: false,
+        };
+
+        loop {
+            let name: syn::Ident = input.parse()?;
+            match name.to_string().as_str() {
+                "key" => attr.key = true,
+                "immutable" => attr.immutable = true,
+                "period" => {
+                    attr.period = true;
+                    if input.peek(syn::Token![=]) {
+                        input.parse::<syn::Token![=]>()?;
+                        let value: syn::LitStr = input.parse()?;
+                        if value.value() != "generated" {
+                            return Err(syn::Error::new(
+                                value.span(),
+                                "expected `period` or `period = \"generated\"`",
+                            ));
+                        }
+                        attr.generated_period = true;
+                    }
+                }
+                "geo" => attr.geo = true,
+                "auto_now" => attr.auto_now = true,
+                "auto_uuid" => attr.auto_uuid = true,
+                "default_if_none" => attr.default_if_none = true,
+                "expr" => {
+                    input.parse::<syn::Token![=]>()?;
+                    let value: syn::LitStr = input.parse()?;
+                    if value.value().matches('?').count() != 1 {
+                        return Err(syn::Error::new(
+                            value.span(),
+                            "`expr` needs exactly one `?` for the value",
+                        ));
+                    }
+                    attr.expr = Some(value.value());
+                }
+                "scale" => {
+                    input.parse::<syn::Token![=]>()?;
+                    attr.scale = Some(input.parse::<syn::LitInt>()?.base10_parse()?);
+                }
+                "anonymize" => {
+                    input.parse::<syn::Token![=]>()?;
+                    attr.anonymize = Some(input.parse::<syn::LitStr>()?.value());
+                }
+                "skip_if" => {
+                    input.parse::<syn::Token![=]>()?;
+                    attr.skip_if = Some(input.parse::<syn::LitStr>()?.parse()?);
+                }
+                "normalize" => {
+                    input.parse::<syn::Token![=]>()?;
+                    let value: syn::LitStr = input.parse()?;
+
+                    attr.normalize =
+                        Some(if BUILTIN_NORMALIZERS.contains(&value.value().as_str()) {
+                            let name = syn::Ident::new(&value.value(), value.span());
+                            syn::parse_quote!(sqlx_plus::normalize::#name)
+                        } else {
+                            value.parse()?
+                        });
+                }
+                _ => {
+                    return Err(syn::Error::new(
+                        name.span(),
+                        "expected `key`, `immutable`, `period`, `geo`, `auto_now`, `auto_uuid`, `default_if_none`, `expr`, `scale`, `normalize`, `anonymize` or `skip_if`",
+                    ))
+                }
+            }
+
+            if input.is_empty() {
+                break;
+            }
+            input.parse::<syn::Token![,]>()?;
+        }
+
+        Ok(attr)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(tokens: &str) -> syn::Result<InsertableAttr> {
+        syn::parse_str(tokens)
+    }
+
+    fn path(path: &syn::Path) -> String {
+        quote::quote!(#path).to_string().replace(' ', "")
+    }
+
+    #[test]
+    fn positional() {
+        let attr = parse(r#"sqlx::Postgres, "user", key = UserId, builder"#).unwrap();
+        assert_eq!(path(&attr.db), "sqlx::Postgres");
+        assert_eq!(attr.table_name, "user");
+        assert!(attr.key.is_some());
+        assert!(attr.builder);
+        assert!(!attr.smoke_test);
+        assert_eq!(attr.max_params, None);
+    }
+
+    #[test]
+    fn named() {
+        let attr = parse(r#"table = "user", db = sqlx::Sqlite"#).unwrap();
+        assert_eq!(path(&attr.db), "sqlx::Sqlite");
+        assert_eq!(attr.table_name, "user");
+
+        let attr = parse(
+            r#"smoke_test, db = sqlx::MySql, table = "membership", key_columns("tenant_id", "user_id"),"#,
+        )
+        .unwrap();
+        assert!(attr.smoke_test);
+        assert_eq!(attr.key_columns, ["tenant_id", "user_id"]);
+
+        let attr = parse(r#"unique("email"), db = sqlx::Postgres, table = "user""#).unwrap();
+        assert_eq!(attr.unique, [["email"]]);
+
+        let attr = parse(r#"as_row, db = sqlx::Postgres, table = "user""#).unwrap();
+        assert!(attr.as_row);
+        assert!(!attr.builder);
+    }
+
+    #[test]
+    fn options() {
+        let attr = parse(
+            r#"sqlx::Sqlite, "user", column_order = "alphabetical", on_unique("user_name_key", "NameTaken"), extra = "attrs", same_table_as = "User", max_params = 999"#,
+        )
+        .unwrap();
+        assert!(attr.alphabetical);
+        assert_eq!(attr.on_unique.len(), 1);
+        assert_eq!(attr.on_unique[0].0, "user_name_key");
+        assert_eq!(attr.on_unique[0].1, "NameTaken");
+        assert_eq!(attr.extra.as_deref(), Some("attrs"));
+        assert_eq!(path(attr.same_table_as.as_ref().unwrap()), "User");
+        assert_eq!(attr.max_params, Some(999));
+
+        let attr =
+            parse(r#"sqlx::Sqlite, "page", unique("id"), unique("tenant_id", "slug")"#).unwrap();
+        assert_eq!(
+            attr.unique,
+            [
+                vec!["id".to_owned()],
+                vec!["tenant_id".to_owned(), "slug".to_owned()]
+            ]
+        );
+    }
+
+    #[test]
+    fn max_params_of_known_databases() {
+        let max_params = |db| max_params_of(&syn::parse_str(db).unwrap());
+
+        assert_eq!(max_params("sqlx::Mssql"), Some(2100));
+        assert_eq!(max_params("Sqlite"), Some(32766));
+        assert_eq!(max_params("my_crate::CustomDatabase"), None);
+    }
+
+    #[test]
+    fn generates_periods_on_system_versioning_databases() {
+        let generates = |db| generates_periods(&syn::parse_str(db).unwrap());
+
+        assert!(generates("sqlx::Mssql"));
+        assert!(!generates("MySql"));
+        assert!(!generates("sqlx::Postgres"));
+        assert!(!generates("sqlx::Sqlite"));
+    }
+
+    #[test]
+    fn errors() {
+        let message = |tokens| parse(tokens).err().unwrap().to_string();
+
+        assert!(message(r#"db = sqlx::Sqlite"#).contains("missing `table"));
+        assert!(message(r#"table = "user""#).contains("missing `db"));
+        assert!(message(r#"sqlx::Sqlite, "user", unknown = 1"#).starts_with("expected `db`"));
+        assert!(message(r#"sqlx::Sqlite, "user", column_order = "random""#)
+            .contains("\"alphabetical\""));
+    }
+
+    #[test]
+    fn field() {
+        let attr: FieldAttr = syn::parse_str(r#"key, normalize = "trim""#).unwrap();
+        assert!(attr.key);
+        assert_eq!(
+            path(attr.normalize.as_ref().unwrap()),
+            "sqlx_plus::normalize::trim"
+        );
+
+        let attr: FieldAttr = syn::parse_str(r#"skip_if = "str::is_empty""#).unwrap();
+        assert_eq!(path(attr.skip_if.as_ref().unwrap()), "str::is_empty");
+        assert!(!attr.immutable);
+
+        let attr: FieldAttr = syn::parse_str("immutable").unwrap();
+        assert!(attr.immutable);
+        assert!(!attr.period);
+
+        let attr: FieldAttr = syn::parse_str("period").unwrap();
+        assert!(attr.period);
+        assert!(!attr.generated_period);
+
+        let attr: FieldAttr = syn::parse_str(r#"period = "generated""#).unwrap();
+        assert!(attr.period && attr.generated_period);
+        assert!(syn::parse_str::<FieldAttr>(r#"period = "always""#).is_err());
+
+        let attr: FieldAttr = syn::parse_str(r#"geo, expr = "ST_GeomFromWKB(?, 4326)""#).unwrap();
+        assert!(attr.geo);
+        assert_eq!(attr.expr.as_deref(), Some("ST_GeomFromWKB(?, 4326)"));
+        assert!(syn::parse_str::<FieldAttr>(r#"expr = "now()""#).is_err());
+
+        let attr: FieldAttr = syn::parse_str("scale = 2, skip_if = \"Option::is_none\"").unwrap();
+        assert_eq!(attr.scale, Some(2));
+        assert!(syn::parse_str::<FieldAttr>("scale = -1").is_err());
+        assert_eq!(
+            geo_binding_of(&syn::parse_str("sqlx::Mssql").unwrap()),
+            Some(("geometry::STGeomFromText(?, 0)", "Wkt"))
+        );
+
+        let attr: FieldAttr = syn::parse_str(r#"anonymize = "email", normalize = "trim""#).unwrap();
+        assert_eq!(attr.anonymize.as_deref(), Some("email"));
+        assert!(attr.normalize.is_some());
+        assert!(!attr.auto_now);
+
+        let attr: FieldAttr = syn::parse_str("auto_now").unwrap();
+        assert!(attr.auto_now);
+        assert!(!attr.auto_uuid);
+
+        let attr: FieldAttr = syn::parse_str("auto_uuid").unwrap();
+        assert!(attr.auto_uuid);
+        assert!(!attr.default_if_none);
+
+        let attr: FieldAttr = syn::parse_str("default_if_none, immutable").unwrap();
+        assert!(attr.default_if_none);
+        assert!(attr.immutable);
+
+        assert!(syn::parse_str::<FieldAttr>("unknown").is_err());
+    }
+}
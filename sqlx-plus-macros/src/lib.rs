@@ -1,6 +1,9 @@
+mod attr;
+
 use proc_macro::TokenStream;
 use quote::quote;
-use syn;
+
+use attr::{FieldAttr, InsertableAttr};
 
 #[proc_macro_derive(Insertable, attributes(insertable))]
 pub fn insertable_derive(input: TokenStream) -> TokenStream {
@@ -12,41 +15,646 @@ fn impl_insertable(ast: &syn::DeriveInput) -> TokenStream {
     let name = &ast.ident;
 
     let (impl_generics, ty_generics, where_clause) = ast.generics.split_for_impl();
-    let fields = get_struct_fields(&ast);
-    let attr = get_insertable_attribute(&ast);
-    let InsertableAttr { db, table_name } = attr.parse_args().unwrap();
+    let mut all_fields = get_struct_fields(ast);
+    let attr = get_insertable_attribute(ast);
+    let InsertableAttr {
+        db,
+        table_name,
+        key,
+        key_column,
+        key_columns,
+        same_table_as,
+        alphabetical,
+        on_unique,
+        unique,
+        smoke_test,
+        extra,
+        builder,
+        as_row,
+        max_params,
+    } = attr.parse_args().unwrap();
+    // Period columns the database generates can be read but never written.
+    let generates_periods = attr::generates_periods(&db);
+    for field in all_fields.iter_mut().filter(|field| field.period) {
+        field.generated_period |= generates_periods;
+        field.immutable |= field.generated_period;
+    }
+    for field in all_fields.iter().filter(|field| field.scale.is_some()) {
+        if field.normalize.is_some() || field.geo {
+            panic!("The `scale` field `{}` can not be normalized", field.ident);
+        }
+    }
+    // Filled in by the insert, not by an update.
+    for field in all_fields
+        .iter_mut()
+        .filter(|field| field.auto_now || field.auto_uuid)
+    {
+        let attr = if field.auto_now {
+            "auto_now"
+        } else {
+            "auto_uuid"
+        };
+        if field.auto_now && field.auto_uuid {
+            panic!(
+                "The field `{}` can not be both `auto_now` and `auto_uuid`",
+                field.ident
+            );
+        }
+        if field.normalize.is_some()
+            || field.skip_if.is_some()
+            || field.geo
+            || field.scale.is_some()
+        {
+            panic!(
+                "The `{}` field `{}` can not be converted",
+                attr, field.ident
+            );
+        }
+        if option_inner_type(&field.ty).is_none() {
+            panic!("The `{}` field `{}` must be an `Option`", attr, field.ident);
+        }
+        field.immutable = true;
+    }
+    for field in all_fields.iter().filter(|field| field.default_if_none) {
+        if field.auto_now || field.auto_uuid {
+            panic!(
+                "The `default_if_none` field `{}` is never `None` when inserted",
+                field.ident
+            );
+        }
+        if option_inner_type(&field.ty).is_none() {
+            panic!(
+                "The `default_if_none` field `{}` must be an `Option`",
+                field.ident
+            );
+        }
+    }
+    let geo_binding = attr::geo_binding_of(&db);
+    for field in all_fields.iter_mut().filter(|field| field.geo) {
+        if field.normalize.is_some() {
+            panic!("The `geo` field `{}` can not be normalized", field.ident);
+        }
+        if field.expr.is_none() {
+            let (expr, _) = geo_binding.unwrap_or_else(|| {
+                panic!(
+                    "`geo` field `{}` needs an `expr` for this database",
+                    field.ident
+                )
+            });
+            field.expr = Some(expr.to_owned());
+        }
+    }
+    // Every field gets a column token for `select()`, the key field included.
+    let column_tokens = all_fields
+        .iter()
+        .map(|field| {
+            let column = field.ident.to_string();
+            let column = column.trim_start_matches("r#").to_owned();
+            let token = quote::format_ident!("{}", column.to_uppercase());
+            let doc = format!("The `{}` column.", column);
+            let marker = if field.immutable {
+                quote! { sqlx_plus::Immutable }
+            } else {
+                quote! { sqlx_plus::Settable }
+            };
+            quote! {
+                #[doc = #doc]
+                pub const #token: sqlx_plus::Column<Self, #marker> = sqlx_plus::Column::new(#column);
+            }
+        })
+        .collect::<Vec<_>>();
+    // Every field can be anonymized, the key field included.
+    let anonymize = all_fields
+        .iter()
+        .any(|field| field.anonymize.is_some())
+        .then(|| {
+            let (idents, fakers): (Vec<_>, Vec<_>) = all_fields
+                .iter()
+                .filter_map(|field| Some((&field.ident, field.anonymize.as_ref()?)))
+                .unzip();
+            let columns = idents
+                .iter()
+                .map(|ident| ident.to_string().trim_start_matches("r#").to_owned());
+
+            quote! {
+                fn anonymized_columns() -> &'static [(&'static str, &'static str)] {
+                    &[ #( (#columns, #fakers) ),* ]
+                }
+
+                fn anonymize(
+                    &mut self,
+                    anonymizer: &sqlx_plus::Anonymizer,
+                ) -> sqlx_plus::__private::anyhow::Result<()> {
+                    #( sqlx_plus::anonymize::Fake::fake(&mut self.#idents, anonymizer, #fakers)?; )*
+                    Ok(())
+                }
+            }
+        });
+    let all_columns = all_fields
+        .iter()
+        .map(|field| field.ident.to_string())
+        .collect::<Vec<_>>();
+    let builder = builder.then(|| impl_builder(ast, &all_fields));
+    let (key_fields, mut fields): (Vec<_>, Vec<_>) =
+        all_fields.into_iter().partition(|field| field.key);
+    fields.retain(|field| !field.generated_period);
+    if let Some(extra) = extra {
+        let field = fields
+            .iter_mut()
+            .find(|field| field.ident == extra)
+            .unwrap_or_else(|| panic!("`extra` names no field `{}`", extra));
+        if field.normalize.is_some() {
+            panic!("The `extra` field `{}` can not be normalized", extra);
+        }
+        field.json = true;
+    }
+    // Columns and binds are both generated from `fields`, so sorting keeps them in step.
+    if alphabetical {
+        fields.sort_by_key(|field| field.ident.to_string());
+    }
+    let column_count = fields.len();
+    // Even one row of a wider struct can't be inserted, better to find out now than at runtime.
+    if let Some(max_params) = max_params.or_else(|| attr::max_params_of(&db)) {
+        if column_count > max_params {
+            panic!(
+                "{} has {} insert columns, but a statement for {} takes at most {} bind parameters; split the struct into narrower ones",
+                name,
+                column_count,
+                quote!(#db).to_string().replace(' ', ""),
+                max_params,
+            );
+        }
+    }
+    let columns = fields
+        .iter()
+        .map(|field| field.ident.to_string())
+        .collect::<Vec<_>>();
+    for columns in &unique {
+        if let Some(column) = columns.iter().find(|column| !all_columns.contains(column)) {
+            panic!("`unique` names no field `{}`", column);
+        }
+    }
+    let unique = unique.iter().map(|columns| quote! { &[ #( #columns ),* ] });
+    let insert_expressions = fields.iter().any(|field| field.expr.is_some()).then(|| {
+        let expressions = fields.iter().map(|field| match &field.expr {
+            Some(expr) => quote! { Some(#expr) },
+            None => quote! { None },
+        });
+        quote! {
+            fn insert_expressions() -> &'static [Option<&'static str>] {
+                &[ #( #expressions ),* ]
+            }
+        }
+    });
+    let default_if_none = fields.iter().any(|field| field.default_if_none).then(|| {
+        let (idents, columns): (Vec<_>, Vec<_>) = fields
+            .iter()
+            .filter(|field| field.default_if_none)
+            .map(|field| (&field.ident, field.ident.to_string()))
+            .unzip();
+
+        quote! {
+            fn default_if_none_columns() -> &'static [&'static str] {
+                &[ #( #columns ),* ]
+            }
+
+            fn defaulted_columns(&self) -> ::std::vec::Vec<&'static str> {
+                let mut columns = ::std::vec::Vec::new();
+                #( if self.#idents.is_none() { columns.push(#columns); } )*
+                columns
+            }
+        }
+    });
+    let bind_conversions = fields
+        .iter()
+        .any(|field| {
+            field.normalize.is_some()
+                || field.scale.is_some()
+                || field.skip_if.is_some()
+                || field.auto_now
+                || field.auto_uuid
+                || field.geo
+        })
+        .then(|| {
+            quote! {
+                fn has_bind_conversions() -> bool {
+                    true
+                }
+            }
+        });
+    let update_columns = fields
+        .iter()
+        .filter(|field| !field.immutable)
+        .map(|field| field.ident.to_string());
+    // The values as bound, also listed by `as_row`.
+    let values = fields
+        .iter()
+        .map(|field| {
+            let ident = &field.ident;
+            let value = match &field.normalize {
+                None if field.auto_now => quote! { sqlx_plus::timestamp::auto_now(&self.#ident) },
+                None if field.auto_uuid => quote! { sqlx_plus::clock::auto_uuid(&self.#ident) },
+                Some(normalize) => quote! { #normalize(&self.#ident) },
+                None if field.json => quote! { sqlx_plus::__private::Json(&self.#ident) },
+                None if field.geo => {
+                    // Without a known database the binding defaults to WKB.
+                    let wrapper = quote::format_ident!("{}", geo_binding.map_or("Wkb", |(_, x)| x));
+                    quote! { sqlx_plus::geo::#wrapper(&self.#ident) }
+                }
+                None => match field.scale {
+                    Some(scale) => {
+                        quote! { sqlx_plus::decimal::round_to_scale(&self.#ident, #scale) }
+                    }
+                    None => quote! { &self.#ident },
+                },
+            };
+            match &field.skip_if {
+                Some(skip_if) => quote! { (!#skip_if(&self.#ident)).then(|| #value) },
+                None => value,
+            }
+        })
+        .collect::<Vec<_>>();
+    let binds = values
+        .iter()
+        .map(|value| quote! { .bind(#value) })
+        .collect::<Vec<_>>();
+
+    // Constraints map to the variants of a generated `<Name>Violation` enum, so callers can match
+    // them exhaustively.
+    let violation_name = quote::format_ident!("{}Violation", name);
+    let mut violation_variants = Vec::new();
+    for (_, variant) in &on_unique {
+        if !violation_variants.contains(&variant) {
+            violation_variants.push(variant);
+        }
+    }
+    let map_error = (!on_unique.is_empty()).then(|| {
+        let constraints = on_unique.iter().map(|(constraint, _)| constraint);
+        let variants = on_unique.iter().map(|(_, variant)| variant);
+
+        quote! {
+            fn map_error(error: sqlx_plus::__private::Error) -> sqlx_plus::__private::anyhow::Error {
+                use sqlx_plus::ErrorExt;
+
+                let violation = if error.is_unique_violation() {
+                    match error.constraint_name() {
+                        #( Some(#constraints) => Some(#violation_name::#variants), )*
+                        _ => None,
+                    }
+                } else {
+                    None
+                };
+
+                match violation {
+                    Some(violation) => sqlx_plus::__private::anyhow::Error::new(error)
+                        .context(sqlx_plus::ConstraintViolation(violation)),
+                    None => error.into(),
+                }
+            }
+        }
+    });
+    let violation_enum = map_error.as_ref().map(|_| {
+        let vis = &ast.vis;
+        let doc = format!("The constraint violations of [`{}`].", name);
+
+        quote! {
+            #[doc = #doc]
+            #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+            #vis enum #violation_name {
+                #( #violation_variants, )*
+            }
+        }
+    });
 
     let gen = quote! {
         impl #impl_generics sqlx_plus::Insertable for #name #ty_generics #where_clause {
             type Database = #db;
 
+            const COLUMN_COUNT: usize = #column_count;
+
             fn table_name() -> &'static str {
                 #table_name
             }
 
-            fn insert_columns() -> Vec<&'static str> {
-                vec![ #( stringify!(#fields) ),* ]
+            fn insert_columns() -> &'static [&'static str] {
+                Self::__SQLX_PLUS_COLUMNS
+            }
+
+            fn update_columns() -> &'static [&'static str] {
+                &[ #( #update_columns ),* ]
+            }
+
+            fn unique_columns() -> &'static [&'static [&'static str]] {
+                &[ #( #unique ),* ]
             }
 
+            #insert_expressions
+
             fn bind_fields<'q, Q>(&'q self, q: Q) -> Q
             where
                 Q: sqlx_plus::QueryBindExt<'q, Self::Database>
             {
-                q #( .bind(&self.#fields) )*
+                q #( #binds )*
+            }
+
+            fn bind_field<'q, Q>(&'q self, column: &str, q: Q) -> Q
+            where
+                Q: sqlx_plus::QueryBindExt<'q, Self::Database>
+            {
+                match column {
+                    #( #columns => q #binds, )*
+                    _ => q,
+                }
+            }
+
+            #map_error
+
+            #anonymize
+
+            #default_if_none
+
+            #bind_conversions
+        }
+
+        impl #impl_generics #name #ty_generics #where_clause {
+            #[doc(hidden)]
+            pub const __SQLX_PLUS_TABLE_NAME: &'static str = #table_name;
+            #[doc(hidden)]
+            pub const __SQLX_PLUS_COLUMNS: &'static [&'static str] = &[ #( #columns ),* ];
+
+            #( #column_tokens )*
+
+            /// Start a typed `SELECT` on the table.
+            pub fn select() -> sqlx_plus::Select<Self> {
+                sqlx_plus::Select::new()
+            }
+        }
+    };
+
+    // Both sides are derived, so the checks can run on the constants at compile time.
+    let same_table_check = same_table_as.map(|canonical| {
+        let table_message = format!("{} does not insert into the table of {}", name, quote!(#canonical));
+        let columns_message = format!("{} has columns which {} does not have", name, quote!(#canonical));
+
+        quote! {
+            const _: () = {
+                assert!(
+                    sqlx_plus::__private::str_eq(#table_name, #canonical::__SQLX_PLUS_TABLE_NAME),
+                    #table_message
+                );
+                assert!(
+                    sqlx_plus::__private::is_subset(&[ #( #columns ),* ], #canonical::__SQLX_PLUS_COLUMNS),
+                    #columns_message
+                );
+            };
+        }
+    });
+
+    let key_field = match key_fields.len() {
+        0 => None,
+        1 if key.is_none() => key_fields.into_iter().next(),
+        1 => panic!("Declare the key either with `key = ...` or with a key field, not both"),
+        _ => panic!("Only one field can be the key"),
+    };
+
+    // A key field is generated by the database, so it is not inserted. `save` updates the row
+    // once it is set.
+    let entity = key_field.as_ref().map(|field| {
+        let ident = &field.ident;
+        let key = match option_inner_type(&field.ty) {
+            Some(_) => quote! { self.#ident.as_ref() },
+            None => quote! {
+                if self.#ident == ::std::default::Default::default() {
+                    None
+                } else {
+                    Some(&self.#ident)
+                }
+            },
+        };
+
+        quote! {
+            impl #impl_generics sqlx_plus::Entity for #name #ty_generics #where_clause {
+                fn key(&self) -> Option<&Self::Key> {
+                    #key
+                }
             }
         }
+    });
+
+    let key_columns = match (key_column, key_columns.is_empty()) {
+        (Some(_), false) => {
+            panic!("Declare the key columns either with `key_column` or with `key_columns`")
+        }
+        (Some(key_column), true) => vec![key_column],
+        (None, false) => key_columns,
+        (None, true) => vec![key_field
+            .as_ref()
+            .map(|field| field.ident.to_string())
+            .unwrap_or_else(|| "id".to_owned())],
     };
+    let key = key.or_else(|| {
+        key_field.map(|field| option_inner_type(&field.ty).cloned().unwrap_or(field.ty))
+    });
+
+    let keyed = key.map(|key| {
+        // A composite key is a tuple, bound element by element in the order of the columns.
+        let bind_key = match &key {
+            syn::Type::Tuple(tuple) if key_columns.len() > 1 => {
+                if tuple.elems.len() != key_columns.len() {
+                    panic!(
+                        "The key type has {} elements but there are {} key columns",
+                        tuple.elems.len(),
+                        key_columns.len()
+                    );
+                }
+                let index = (0..key_columns.len()).map(syn::Index::from);
+                quote! { q #( .bind(&key.#index) )* }
+            }
+            _ if key_columns.len() > 1 => panic!("A composite key needs a tuple key type"),
+            _ => quote! { q.bind(key) },
+        };
+
+        quote! {
+            impl #impl_generics sqlx_plus::Keyed for #name #ty_generics #where_clause {
+                type Key = #key;
+
+                fn key_columns() -> &'static [&'static str] {
+                    &[ #( #key_columns ),* ]
+                }
+
+                fn bind_key<'q, Q>(key: &'q Self::Key, q: Q) -> Q
+                where
+                    Q: sqlx_plus::QueryBindExt<'q, Self::Database>
+                {
+                    #bind_key
+                }
+            }
+        }
+    });
+
+    let as_row = as_row.then(|| {
+        quote! {
+            impl #impl_generics sqlx_plus::AsRow for #name #ty_generics #where_clause {
+                fn as_row(&self) -> ::std::vec::Vec<(&'static str, sqlx_plus::ValueRef<'_>)> {
+                    ::std::vec![ #( (#columns, sqlx_plus::ValueRef::new(#values)) ),* ]
+                }
+            }
+        }
+    });
 
-    gen.into()
+    // Only compiled into the tests of the deriving crate, where it runs against a real database.
+    let smoke_test = smoke_test.then(|| {
+        quote! {
+            #[cfg(test)]
+            impl #impl_generics #name #ty_generics #where_clause {
+                /// Insert `Self::default()` into a temporary copy of the table and read it back.
+                #[doc(hidden)]
+                pub async fn __sqlx_plus_check(
+                    conn: &mut <#db as sqlx_plus::__private::Database>::Connection,
+                ) -> sqlx_plus::__private::anyhow::Result<()> {
+                    sqlx_plus::check_insertable(conn, &<Self as ::std::default::Default>::default()).await
+                }
+            }
+        }
+    });
+
+    quote!(#gen #violation_enum #keyed #entity #same_table_check #smoke_test #builder #as_row)
+        .into()
 }
 
-fn get_struct_fields(ast: &syn::DeriveInput) -> Vec<syn::Ident> {
+/// `<Name>Builder` with a setter per field. `Option` fields and key fields may be left out,
+/// `build` fails on any other missing field. Each setter has the visibility of its field.
+fn impl_builder(ast: &syn::DeriveInput, fields: &[Field]) -> proc_macro2::TokenStream {
+    let name = &ast.ident;
+    let vis = &ast.vis;
+    let builder_name = quote::format_ident!("{}Builder", name);
+    let (impl_generics, ty_generics, where_clause) = ast.generics.split_for_impl();
+    let generics = &ast.generics;
+
+    let idents = fields.iter().map(|field| &field.ident).collect::<Vec<_>>();
+    let types = fields.iter().map(|field| &field.ty).collect::<Vec<_>>();
+    // A setter is as visible as its field, the builder must not open up a private field.
+    let setter_vis = fields.iter().map(|field| &field.vis);
+    let values = fields.iter().map(|field| {
+        let ident = &field.ident;
+        if field.key || option_inner_type(&field.ty).is_some() {
+            quote! { self.#ident.unwrap_or_default() }
+        } else {
+            let message = format!("missing field `{}`", ident);
+            quote! {
+                match self.#ident {
+                    Some(value) => value,
+                    None => sqlx_plus::__private::anyhow::bail!(#message),
+                }
+            }
+        }
+    });
+    let doc = format!("A builder for [`{}`].", name);
+
+    quote! {
+        #[doc = #doc]
+        #vis struct #builder_name #generics #where_clause {
+            #( #idents: ::std::option::Option<#types>, )*
+        }
+
+        impl #impl_generics ::std::default::Default for #builder_name #ty_generics #where_clause {
+            fn default() -> Self {
+                #builder_name {
+                    #( #idents: None, )*
+                }
+            }
+        }
+
+        impl #impl_generics #builder_name #ty_generics #where_clause {
+            #(
+                #setter_vis fn #idents(mut self, value: impl ::std::convert::Into<#types>) -> Self {
+                    self.#idents = Some(value.into());
+                    self
+                }
+            )*
+
+            pub fn build(self) -> sqlx_plus::__private::anyhow::Result<#name #ty_generics> {
+                Ok(#name {
+                    #( #idents: #values, )*
+                })
+            }
+        }
+
+        impl #impl_generics #name #ty_generics #where_clause {
+            pub fn builder() -> #builder_name #ty_generics {
+                ::std::default::Default::default()
+            }
+        }
+    }
+}
+
+struct Field {
+    ident: syn::Ident,
+    ty: syn::Type,
+    vis: syn::Visibility,
+    /// The function applied to the field before binding.
+    normalize: Option<syn::Path>,
+    /// Whether this is the key field of an entity.
+    key: bool,
+    /// Whether the field is bound as JSON, see `extra`.
+    json: bool,
+    /// A predicate on the field, binding `NULL` instead of the value when it holds.
+    skip_if: Option<syn::Path>,
+    /// Whether the column is only written by the insert, never by an update.
+    immutable: bool,
+    /// Whether this is a period column of a system-versioned table.
+    period: bool,
+    /// Whether the database generates the period column, which is then never written.
+    generated_period: bool,
+    /// Whether the field is a geometry, bound with `sqlx_plus::geo`.
+    geo: bool,
+    /// The SQL expression around the placeholder of the field.
+    expr: Option<String>,
+    /// The scale a decimal field is rounded to before binding.
+    scale: Option<u32>,
+    /// The faker replacing the field in anonymized copies.
+    anonymize: Option<String>,
+    /// Whether the field is filled with the timestamp of the insert.
+    auto_now: bool,
+    /// Whether the field is filled with a new UUID.
+    auto_uuid: bool,
+    /// Whether the column is left to its default when the field is `None`.
+    default_if_none: bool,
+}
+
+fn get_struct_fields(ast: &syn::DeriveInput) -> Vec<Field> {
     match ast.data {
         syn::Data::Struct(ref data_struct) => match data_struct.fields {
             syn::Fields::Named(ref fields_named) => fields_named
                 .named
                 .iter()
-                .map(|field| field.ident.clone().unwrap())
+                .map(|field| {
+                    let attr = field
+                        .attrs
+                        .iter()
+                        .find(|x| x.path.is_ident("insertable"))
+                        .map(|x| x.parse_args::<FieldAttr>().unwrap());
+
+                    Field {
+                        ident: field.ident.clone().unwrap(),
+                        ty: field.ty.clone(),
+                        vis: field.vis.clone(),
+                        normalize: attr.as_ref().and_then(|x| x.normalize.clone()),
+                        key: attr.as_ref().is_some_and(|x| x.key),
+                        immutable: attr.as_ref().is_some_and(|x| x.immutable),
+                        period: attr.as_ref().is_some_and(|x| x.period),
+                        generated_period: attr.as_ref().is_some_and(|x| x.generated_period),
+                        geo: attr.as_ref().is_some_and(|x| x.geo),
+                        expr: attr.as_ref().and_then(|x| x.expr.clone()),
+                        scale: attr.as_ref().and_then(|x| x.scale),
+                        anonymize: attr.as_ref().and_then(|x| x.anonymize.clone()),
+                        auto_now: attr.as_ref().is_some_and(|x| x.auto_now),
+                        auto_uuid: attr.as_ref().is_some_and(|x| x.auto_uuid),
+                        default_if_none: attr.as_ref().is_some_and(|x| x.default_if_none),
+                        skip_if: attr.and_then(|x| x.skip_if),
+                        json: false,
+                    }
+                })
                 .collect::<Vec<_>>(),
             syn::Fields::Unnamed(_) => panic!("Can not tuple structs derive Insertable trait"),
             syn::Fields::Unit => panic!("Can not unit structs derive Insertable trait"),
@@ -58,25 +666,24 @@ fn get_struct_fields(ast: &syn::DeriveInput) -> Vec<syn::Ident> {
 fn get_insertable_attribute(ast: &syn::DeriveInput) -> &syn::Attribute {
     ast.attrs
         .iter()
-        .filter(|x| x.path.is_ident("insertable"))
-        .next()
+        .find(|x| x.path.is_ident("insertable"))
         .expect("The insertable attribute is required for specifying DB type and table name")
 }
 
-struct InsertableAttr {
-    db: syn::Path,
-    table_name: String,
-}
-
-impl syn::parse::Parse for InsertableAttr {
-    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
-        let db: syn::Path = input.parse()?;
-        input.parse::<syn::Token![,]>()?;
-        let table: syn::LitStr = input.parse()?;
-
-        Ok(InsertableAttr {
-            db,
-            table_name: table.value(),
-        })
+/// `T` if `ty` is `Option<T>`.
+fn option_inner_type(ty: &syn::Type) -> Option<&syn::Type> {
+    let segment = match ty {
+        syn::Type::Path(path) => path.path.segments.last()?,
+        _ => return None,
+    };
+    if segment.ident != "Option" {
+        return None;
+    }
+    match &segment.arguments {
+        syn::PathArguments::AngleBracketed(args) => match args.args.first()? {
+            syn::GenericArgument::Type(ty) => Some(ty),
+            _ => None,
+        },
+        _ => None,
     }
 }
@@ -0,0 +1,171 @@
+//! Runs against the MySQL server of `SQLX_PLUS_MYSQL_URL`, and passes without one.
+
+use sqlx::Connection;
+
+async fn connect() -> anyhow::Result<Option<sqlx::MySqlConnection>> {
+    match std::env::var("SQLX_PLUS_MYSQL_URL") {
+        Ok(url) => Ok(Some(sqlx::MySqlConnection::connect(&url).await?)),
+        Err(_) => Ok(None),
+    }
+}
+
+#[tokio::test]
+async fn test_conformance() -> anyhow::Result<()> {
+    let mut conn = match connect().await? {
+        Some(conn) => conn,
+        None => return Ok(()),
+    };
+    sqlx_plus::conformance::run(&mut conn).await?;
+
+    Ok(())
+}
+
+#[derive(Debug, Clone, serde::Serialize, sqlx_plus::Insertable)]
+#[insertable(sqlx::MySql, "`load_note`")]
+struct LoadNote {
+    title: String,
+    body: Option<String>,
+    pinned: bool,
+}
+
+/// `skip_if` binds `NULL` for empty bodies, which a TSV of the serialized fields would skip.
+#[derive(Debug, Clone, serde::Serialize, sqlx_plus::Insertable)]
+#[insertable(sqlx::MySql, "`load_note`")]
+struct SkippedNote {
+    title: String,
+    #[insertable(skip_if = "String::is_empty")]
+    body: String,
+    pinned: bool,
+}
+
+/// Refuses local files like a server with `local_infile=0`, after recording the statement.
+#[derive(Default)]
+struct RefusingInfile(std::sync::Mutex<Vec<(String, Vec<u8>)>>);
+
+#[async_trait::async_trait]
+impl sqlx_plus::LocalInfile for RefusingInfile {
+    async fn load_data(&self, statement: &str, data: Vec<u8>) -> anyhow::Result<u64> {
+        self.0.lock().unwrap().push((statement.to_owned(), data));
+        Err(sqlx_plus::LocalInfileRefused.into())
+    }
+}
+
+#[tokio::test]
+async fn test_load_data() -> anyhow::Result<()> {
+    use sqlx_plus::{
+        load_data::{load_data_sql, to_tsv},
+        Insertable,
+    };
+
+    let notes = [
+        LoadNote {
+            title: "tab\there".to_owned(),
+            body: None,
+            pinned: true,
+        },
+        LoadNote {
+            title: "back\\slash".to_owned(),
+            body: Some("two\nlines".to_owned()),
+            pinned: false,
+        },
+    ];
+    assert_eq!(
+        load_data_sql::<LoadNote>("`load_note`"),
+        "LOAD DATA LOCAL INFILE 'sqlx_plus.tsv' INTO TABLE `load_note` CHARACTER SET utf8mb4 \
+         FIELDS TERMINATED BY '\\t' ESCAPED BY '\\\\' LINES TERMINATED BY '\\n' (title,body,pinned)"
+    );
+    assert_eq!(
+        String::from_utf8(to_tsv(&notes)?)?,
+        "tab\\there\t\\N\t1\nback\\\\slash\ttwo\\nlines\t0\n"
+    );
+    assert!(sqlx_plus::LocalInfileRefused::is_refusal(3948));
+    assert!(!LoadNote::has_bind_conversions());
+    assert!(SkippedNote::has_bind_conversions());
+
+    let mut conn = match connect().await? {
+        Some(conn) => conn,
+        None => return Ok(()),
+    };
+    sqlx::query("DROP TABLE IF EXISTS load_note")
+        .execute(&mut conn)
+        .await?;
+    sqlx::query(
+        "CREATE TABLE load_note (id BIGINT AUTO_INCREMENT PRIMARY KEY, title TEXT NOT NULL, body TEXT, pinned BOOLEAN NOT NULL)",
+    )
+    .execute(&mut conn)
+    .await?;
+
+    let infile = RefusingInfile::default();
+    let report = sqlx_plus::load_data(
+        &infile,
+        &mut conn,
+        &notes,
+        &sqlx_plus::BulkInsertOptions::default(),
+    )
+    .await?;
+    assert_eq!(report.rows_affected, 2);
+    assert_eq!(infile.0.lock().unwrap().len(), 1);
+    let titles: Vec<String> = sqlx::query_scalar("SELECT title FROM load_note ORDER BY id")
+        .fetch_all(&mut conn)
+        .await?;
+    assert_eq!(titles, ["tab\there", "back\\slash"]);
+
+    // Bind conversions go to the bulk insert without asking for a local file.
+    let notes = [SkippedNote {
+        title: "skipped".to_owned(),
+        body: String::new(),
+        pinned: false,
+    }];
+    let infile = RefusingInfile::default();
+    sqlx_plus::load_data(
+        &infile,
+        &mut conn,
+        &notes,
+        &sqlx_plus::BulkInsertOptions::default(),
+    )
+    .await?;
+    assert!(infile.0.lock().unwrap().is_empty());
+    let body: Option<String> =
+        sqlx::query_scalar("SELECT body FROM load_note WHERE title = 'skipped'")
+            .fetch_one(&mut conn)
+            .await?;
+    assert_eq!(body, None);
+
+    Ok(())
+}
+
+#[derive(Debug, Clone, sqlx_plus::Insertable)]
+#[insertable(sqlx::MySql, "`plain_note`")]
+struct PlainNote {
+    title: String,
+}
+
+#[tokio::test]
+async fn test_returning_ids_without_auto_increment() -> anyhow::Result<()> {
+    use sqlx_plus::Inserter;
+
+    let mut conn = match connect().await? {
+        Some(conn) => conn,
+        None => return Ok(()),
+    };
+    sqlx::query("DROP TABLE IF EXISTS plain_note")
+        .execute(&mut conn)
+        .await?;
+    sqlx::query("CREATE TABLE plain_note (title VARCHAR(64) PRIMARY KEY)")
+        .execute(&mut conn)
+        .await?;
+
+    let notes = [
+        PlainNote {
+            title: "first".to_owned(),
+        },
+        PlainNote {
+            title: "second".to_owned(),
+        },
+    ];
+    // `LAST_INSERT_ID()` stays 0, which must not pass for the ids 0 and 1.
+    let result = (&mut conn).bulk_insert_returning_ids(&notes).await;
+    assert!(result.is_err(), "{:?}", result);
+
+    Ok(())
+}
@@ -1,4 +1,4 @@
-use std::borrow::Cow;
+use std::{borrow::Cow, collections::HashMap};
 
 use async_trait::async_trait;
 use chrono::NaiveDateTime;
@@ -19,14 +19,14 @@ async fn test_main() -> anyhow::Result<()> {
         tx.bulk_insert(&[UserInsert {
             name: Cow::from("hoge1.5"),
             password: Cow::from("password4"),
-            created_at: chrono::NaiveDate::from_ymd(2022, 6, 20).and_hms(1, 2, 3),
+            created_at: created_at(),
         }])
         .await?;
 
         tx.insert(&UserInsert {
             name: Cow::from("hoge1.6"),
             password: Cow::from("password4"),
-            created_at: chrono::NaiveDate::from_ymd(2022, 6, 20).and_hms(1, 2, 3),
+            created_at: created_at(),
         })
         .await?;
 
@@ -38,50 +38,3720 @@ async fn test_main() -> anyhow::Result<()> {
         conn.insert(&UserInsert {
             name: Cow::from("hoge1.7"),
             password: Cow::from("password4"),
-            created_at: chrono::NaiveDate::from_ymd(2022, 6, 20).and_hms(1, 2, 3),
+            created_at: created_at(),
         })
         .await?;
 
         conn.bulk_insert(&[UserInsert {
             name: Cow::from("hoge1.8"),
             password: Cow::from("password4"),
-            created_at: chrono::NaiveDate::from_ymd(2022, 6, 20).and_hms(1, 2, 3),
+            created_at: created_at(),
         }])
         .await?;
     }
+    {
+        use sqlx_plus::InserterExt;
+
+        pool.insert_row(&UserInsert {
+            name: Cow::from("hoge1.9"),
+            password: Cow::from("password4"),
+            created_at: created_at(),
+        })
+        .await?;
+
+        let mut conn = pool.acquire().await?;
+        conn.insert_rows(&[UserInsert {
+            name: Cow::from("hoge1.10"),
+            password: Cow::from("password4"),
+            created_at: created_at(),
+        }])
+        .await?;
+    }
+
+    {
+        let mut conn = pool.acquire().await?;
+        let mut tx = conn.begin().await?;
+
+        assert_eq!(
+            tx.get_user_by_name_and_password("xxxSHINICHIxxx", "password3")
+                .await?,
+            Some(User {
+                id: 3,
+                name: "xxxSHINICHIxxx".into(),
+                password: "password3".into(),
+                created_at: created_at(),
+            })
+        );
+
+        assert_eq!(
+            tx.get_user_by_name_and_password("hoge", "password4")
+                .await?,
+            Some(User {
+                id: 4,
+                name: "hoge".into(),
+                password: "password4".into(),
+                created_at: created_at(),
+            })
+        );
+
+        tx.insert(&SortedUserInsert {
+            password: "password6".to_owned(),
+            name: "sorted".to_owned(),
+            created_at: created_at(),
+        })
+        .await?;
+        assert!(tx
+            .get_user_by_name_and_password("sorted", "password6")
+            .await?
+            .is_some());
+
+        tx.commit().await?;
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_bulk_insert_builder() -> anyhow::Result<()> {
+    let pool = sqlx::sqlite::SqlitePool::connect("sqlite://:memory:").await?;
+    let mut conn = pool.acquire().await?;
+    let mut tx = conn.begin().await?;
+
+    tx.setup_tables().await?;
+
+    let caps = sqlx_plus::Capabilities::detect(&mut tx).await?;
+    assert_eq!(caps.dialect, sqlx_plus::Dialect::Sqlite);
+    assert!(caps.server_version.is_some());
+
+    let users = ["a", "b", "c"]
+        .iter()
+        .map(|name| UserInsert {
+            name: Cow::from(*name),
+            password: Cow::from("password"),
+            created_at: created_at(),
+        })
+        .collect::<Vec<_>>();
+
+    let report = sqlx_plus::BulkInsert::new(&users)
+        .capabilities(&caps)
+        .chunk_size(2)
+        .execute(&mut tx)
+        .await?;
+    assert_eq!(report.chunks, 2);
+    assert_eq!(report.rows_affected, 3);
+    assert!(report.is_success());
+
+    // "c" violates the unique constraint on name, the chunk with "d" still goes in.
+    let users = ["c", "d"]
+        .iter()
+        .map(|name| UserInsert {
+            name: Cow::from(*name),
+            password: Cow::from("password"),
+            created_at: created_at(),
+        })
+        .collect::<Vec<_>>();
+
+    // A dry run reports the same outcome but leaves the table alone, which the count at the end
+    // checks.
+    let report = sqlx_plus::BulkInsert::new(&users)
+        .chunk_size(1)
+        .continue_on_error(true)
+        .execute_dry_run(&mut tx)
+        .await?;
+    assert_eq!((report.rows_affected, report.failed.len()), (1, 1));
+
+    let report = sqlx_plus::BulkInsert::new(&users)
+        .chunk_size(1)
+        .continue_on_error(true)
+        .execute(&mut tx)
+        .await?;
+    assert_eq!(report.chunks, 2);
+    assert_eq!(report.rows_affected, 1);
+    assert_eq!(report.failed.len(), 1);
+    assert_eq!(report.failed[0].rows, 0..1);
+
+    let more_users = ["e", "f"]
+        .iter()
+        .map(|name| UserInsert {
+            name: Cow::from(*name),
+            password: Cow::from("password"),
+            created_at: created_at(),
+        })
+        .collect::<Vec<_>>();
+
+    let mut config = sqlx_plus::Config::new();
+    config.set_chunk_size::<UserInsert>(1);
+    let report = sqlx_plus::BulkInsert::new(&more_users)
+        .config(config)
+        .execute(&mut tx)
+        .await?;
+    assert_eq!(report.chunks, 2);
+
+    let even_more_users = ["g", "h", "i"]
+        .iter()
+        .map(|name| UserInsert {
+            name: Cow::from(*name),
+            password: Cow::from("password"),
+            created_at: created_at(),
+        })
+        .collect::<Vec<_>>();
+    let mut config = sqlx_plus::Config::new();
+    config.set_offload_sql_params(1);
+    let report = sqlx_plus::BulkInsert::new(&even_more_users)
+        .config(config)
+        .chunk_size(2)
+        .execute(&mut tx)
+        .await?;
+    assert_eq!((report.chunks, report.rows_affected), (2, 3));
+
+    let error = sqlx_plus::BulkInsert::new(&users)
+        .chunk_size(20000)
+        .execute(&mut tx)
+        .await
+        .unwrap_err();
+    let error = error
+        .downcast_ref::<sqlx_plus::BindParamLimitExceeded>()
+        .unwrap();
+    assert_eq!(error.max_chunk_size(), 32766 / 3);
+
+    let (count,): (i64,) = sqlx::query_as("SELECT COUNT(*) FROM user")
+        .fetch_one(&mut tx)
+        .await?;
+    assert_eq!(count, 9);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_bulk_insert_returning_ids() -> anyhow::Result<()> {
+    let pool = sqlx::sqlite::SqlitePool::connect("sqlite://:memory:").await?;
+    let mut conn = pool.acquire().await?;
+    let mut tx = conn.begin().await?;
+
+    tx.setup_tables().await?;
+
+    let users = (0..5)
+        .map(|i| UserInsert {
+            name: Cow::from(format!("user{}", i)),
+            password: Cow::from("password"),
+            created_at: created_at(),
+        })
+        .collect::<Vec<_>>();
+
+    let ids = sqlx_plus::BulkInsert::new(&users[..3])
+        .chunk_size(2)
+        .execute_returning_ids(&mut tx)
+        .await?;
+    assert_eq!(ids, vec![1, 2, 3]);
+
+    // Emulates SQLite < 3.35 which has no RETURNING.
+    let mut caps = sqlx_plus::Capabilities::detect(&mut tx).await?;
+    caps.supports_returning = false;
+
+    let ids = sqlx_plus::BulkInsert::new(&users[3..])
+        .capabilities(&caps)
+        .execute_returning_ids(&mut tx)
+        .await?;
+    assert_eq!(ids, vec![4, 5]);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_default_chunk_size_fits_capabilities() -> anyhow::Result<()> {
+    use sqlx_plus::{BindParamLimitExceeded, BulkInsertOptions, Capabilities};
+
+    let pool = sqlx::sqlite::SqlitePool::connect("sqlite://:memory:").await?;
+    let mut conn = pool.acquire().await?;
+    let mut tx = conn.begin().await?;
+    tx.setup_tables().await?;
+
+    // The bind parameter limit of MSSQL.
+    let caps = Capabilities {
+        max_params: 2100,
+        ..Capabilities::assumed::<sqlx::Sqlite>()
+    };
+    let options = BulkInsertOptions {
+        capabilities: Some(caps.clone()),
+        ..Default::default()
+    };
+    assert!(options.chunk_size::<UserInsert>()? * 3 <= 2100);
+
+    let users = (0..1000)
+        .map(|i| UserInsert {
+            name: Cow::from(format!("user{}", i)),
+            password: Cow::from("password"),
+            created_at: created_at(),
+        })
+        .collect::<Vec<_>>();
+    let results = tx.bulk_insert_with_options(&users, &options).await?;
+    assert_eq!(results.len(), 2);
+
+    // A requested chunk size whose parameters overflow exceeds the limit.
+    let error = BulkInsertOptions {
+        chunk_size: Some(usize::MAX),
+        capabilities: Some(caps),
+        ..Default::default()
+    }
+    .chunk_size::<UserInsert>()
+    .unwrap_err();
+    assert!(error.downcast_ref::<BindParamLimitExceeded>().is_some());
+    assert!(!error.to_string().is_empty());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_capabilities_detected_per_pool() -> anyhow::Result<()> {
+    let pool = sqlx::sqlite::SqlitePool::connect("sqlite://:memory:").await?;
+    sqlx::query("CREATE TABLE tag (id INTEGER PRIMARY KEY, name TEXT NOT NULL UNIQUE)")
+        .execute(&pool)
+        .await?;
+
+    let config = std::sync::Arc::new(sqlx_plus::Config::new());
+    let tags = [TagInsert {
+        name: "a".to_owned(),
+    }];
+    assert_eq!(config.detected_capabilities(&pool), None);
+    sqlx_plus::BulkInsert::new(&tags)
+        .config(config.clone())
+        .execute(&pool)
+        .await?;
+    let caps = config.detected_capabilities(&pool).unwrap();
+    assert_eq!(caps, sqlx_plus::Capabilities::detect(&pool).await?);
+    assert!(caps.server_version.is_some());
+    assert_eq!(config.capabilities_of(&pool.clone()).await, caps);
+
+    // Another pool is detected on its own.
+    let other = sqlx::sqlite::SqlitePool::connect("sqlite://:memory:").await?;
+    assert_eq!(config.detected_capabilities(&other), None);
+
+    // A failed detection is assumed for the call, but not kept.
+    other.close().await;
+    let assumed = config.capabilities_of(&other).await;
+    assert_eq!(assumed, sqlx_plus::Capabilities::assumed::<sqlx::Sqlite>());
+    assert_eq!(config.detected_capabilities(&other), None);
+
+    config.forget_capabilities(&pool);
+    assert_eq!(config.detected_capabilities(&pool), None);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_error_ext() -> anyhow::Result<()> {
+    use sqlx_plus::ErrorExt;
+
+    let pool = sqlx::sqlite::SqlitePool::connect("sqlite://:memory:").await?;
+    let mut conn = pool.acquire().await?;
+    let mut tx = conn.begin().await?;
+
+    tx.setup_tables().await?;
+    sqlx::query(
+        "CREATE TABLE post (id INTEGER PRIMARY KEY, user_id INTEGER NOT NULL REFERENCES user (id))",
+    )
+    .execute(&mut tx)
+    .await?;
+
+    let user = UserInsert {
+        name: Cow::from("taken"),
+        password: Cow::from("password"),
+        created_at: created_at(),
+    };
+    tx.insert(&user).await?;
+
+    let err = tx.insert(&user).await.unwrap_err();
+    assert!(err.is_unique_violation());
+    assert!(!err.is_foreign_key_violation());
+    assert_eq!(err.constraint_name(), Some("user.name"));
+
+    let err = sqlx::query("INSERT INTO post (user_id) VALUES (42)")
+        .execute(&mut tx)
+        .await
+        .unwrap_err();
+    assert!(err.is_foreign_key_violation());
+    assert!(!err.is_unique_violation());
+
+    let err = tx
+        .insert(&MappedUserInsert {
+            name: "taken".to_owned(),
+            password: "password".to_owned(),
+        })
+        .await
+        .unwrap_err();
+    assert_eq!(
+        err.downcast_ref::<sqlx_plus::ConstraintViolation<MappedUserInsertViolation>>(),
+        Some(&sqlx_plus::ConstraintViolation(
+            MappedUserInsertViolation::NameTaken
+        ))
+    );
+    assert_eq!(err.constraint_name(), Some("user.name"));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_bulk_upsert() -> anyhow::Result<()> {
+    let pool = sqlx::sqlite::SqlitePool::connect("sqlite://:memory:").await?;
+    let mut conn = pool.acquire().await?;
+    let mut tx = conn.begin().await?;
+
+    tx.setup_tables().await?;
+
+    let users = |password: &'static str| {
+        ["a", "b"]
+            .iter()
+            .map(|name| UserInsert {
+                name: Cow::from(*name),
+                password: Cow::from(password),
+                created_at: created_at(),
+            })
+            .collect::<Vec<_>>()
+    };
+
+    sqlx_plus::BulkInsert::new(&users("old"))
+        .execute(&mut tx)
+        .await?;
+
+    let report = sqlx_plus::BulkInsert::new(&users("new"))
+        .on_conflict(sqlx_plus::OnConflict::columns(["name"]))
+        .execute(&mut tx)
+        .await?;
+    assert_eq!(report.rows_affected, 0);
+
+    let options = sqlx_plus::BulkInsert::new(&users("new"))
+        .on_conflict(sqlx_plus::OnConflict::columns(["name"]).do_update(["password"]))
+        .options()
+        .clone();
+    assert_eq!(
+        options.insert_sql::<UserInsert>(2)?,
+        "INSERT INTO user (name,password,created_at) VALUES (?,?,?),(?,?,?) ON CONFLICT (name) DO UPDATE SET password=excluded.password"
+    );
+
+    let report = sqlx_plus::BulkInsert::new(&users("new"))
+        .on_conflict(sqlx_plus::OnConflict::columns(["name"]).do_update(["password"]))
+        .execute(&mut tx)
+        .await?;
+    assert_eq!(report.rows_affected, 2);
+
+    let passwords: Vec<(String,)> = sqlx::query_as("SELECT password FROM user ORDER BY id")
+        .fetch_all(&mut tx)
+        .await?;
+    assert_eq!(passwords, vec![("new".to_owned(),), ("new".to_owned(),)]);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_normalize() -> anyhow::Result<()> {
+    let pool = sqlx::sqlite::SqlitePool::connect("sqlite://:memory:").await?;
+    let mut conn = pool.acquire().await?;
+    let mut tx = conn.begin().await?;
+
+    tx.setup_tables().await?;
+
+    // "e" followed by a combining acute accent, NFC composes it into "é".
+    tx.insert(&NormalizedUserInsert {
+        name: "Jose\u{301}".to_owned(),
+        password: "  password  ".to_owned(),
+    })
+    .await?;
+
+    let (name, password): (String, String) = sqlx::query_as("SELECT name, password FROM user")
+        .fetch_one(&mut tx)
+        .await?;
+    assert_eq!(name, "jos\u{e9}");
+    assert_eq!(password, "password");
+
+    Ok(())
+}
+
+#[test]
+fn test_as_row() {
+    use sqlx_plus::{AsRow, ValueRef};
+
+    fn describe<T: AsRow>(value: &T) -> Vec<String> {
+        value
+            .as_row()
+            .into_iter()
+            .map(|(column, value)| format!("{}={:?}", column, value))
+            .collect()
+    }
+
+    // The values are the bound ones, normalized.
+    let user = NormalizedUserInsert {
+        name: "Jose\u{301}".to_owned(),
+        password: "  password  ".to_owned(),
+    };
+    assert_eq!(
+        describe(&user),
+        ["name=\"jos\u{e9}\"", "password=\"password\""]
+    );
+
+    // `skip_if` binds `NULL`.
+    let imported = ImportedUserInsert {
+        name: "imported".to_owned(),
+        password: "password".to_owned(),
+        created_at: String::new(),
+    };
+    let row = imported.as_row();
+    assert_eq!(row[2].0, "created_at");
+    assert_eq!(format!("{:?}", row[2].1), "None");
+    assert_eq!(row[0].1, ValueRef::new("imported"));
+    assert!(row[1].1 != ValueRef::new("other"));
+}
+
+#[tokio::test]
+async fn test_archive_rows() -> anyhow::Result<()> {
+    let pool = sqlx::sqlite::SqlitePool::connect("sqlite://:memory:").await?;
+    let mut conn = pool.acquire().await?;
+    let mut tx = conn.begin().await?;
+
+    tx.setup_tables().await?;
+    tx.setup_user().await?;
+    sqlx::query("CREATE TABLE user_archive AS SELECT * FROM user WHERE 0")
+        .execute(&mut tx)
+        .await?;
+
+    let moved = sqlx_plus::archive_rows::<UserInsert>("password <> 'password5'")
+        .chunk_size(3)
+        .execute(&mut tx)
+        .await?;
+    assert_eq!(moved, 4);
+
+    let (count,): (i64,) = sqlx::query_as("SELECT COUNT(*) FROM user")
+        .fetch_one(&mut tx)
+        .await?;
+    assert_eq!(count, 1);
+
+    let names: Vec<(i64, String)> = sqlx::query_as("SELECT id, name FROM user_archive ORDER BY id")
+        .fetch_all(&mut tx)
+        .await?;
+    assert_eq!(names[0], (1, "aaabbb".to_owned()));
+    assert_eq!(names.len(), 4);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_find_and_delete_by_id() -> anyhow::Result<()> {
+    let pool = sqlx::sqlite::SqlitePool::connect("sqlite://:memory:").await?;
+    let mut conn = pool.acquire().await?;
+    let mut tx = conn.begin().await?;
+
+    tx.setup_tables().await?;
+    tx.setup_user().await?;
+
+    let user: Option<User> = sqlx_plus::find_by_id::<UserInsert, _, _>(&mut tx, &UserId(2)).await?;
+    assert_eq!(user.unwrap().name, UserName::from("heyheyhey"));
+
+    assert!(sqlx_plus::delete_by_id::<UserInsert, _>(&mut tx, &UserId(2)).await?);
+    assert!(!sqlx_plus::delete_by_id::<UserInsert, _>(&mut tx, &UserId(2)).await?);
+
+    let user: Option<User> = sqlx_plus::find_by_id::<UserInsert, _, _>(&mut tx, &UserId(2)).await?;
+    assert_eq!(user, None);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_get_or_create_many() -> anyhow::Result<()> {
+    let pool = sqlx::sqlite::SqlitePool::connect("sqlite://:memory:").await?;
+    let mut conn = pool.acquire().await?;
+    let mut tx = conn.begin().await?;
+
+    tx.setup_tables().await?;
+    tx.setup_user().await?;
+
+    let users = ["new", "heyheyhey", "new2"]
+        .iter()
+        .map(|name| UserInsert {
+            name: Cow::from(*name),
+            password: Cow::from("password"),
+            created_at: created_at(),
+        })
+        .collect::<Vec<_>>();
+
+    let rows: Vec<User> = sqlx_plus::BulkInsert::new(&users)
+        .on_conflict(sqlx_plus::OnConflict::columns(["name"]))
+        .get_or_create_many(&mut tx)
+        .await?;
+    assert_eq!(rows[1].id, 2);
+    let rows = rows
+        .into_iter()
+        .map(|user| (user.name.0, user.password))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        rows,
+        vec![
+            ("new".to_owned(), "password".to_owned()),
+            ("heyheyhey".to_owned(), "password2".to_owned()),
+            ("new2".to_owned(), "password".to_owned()),
+        ]
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_middleware() -> anyhow::Result<()> {
+    #[derive(Default)]
+    struct Recorder(std::sync::Mutex<Vec<(String, usize, u64)>>);
+
+    #[async_trait]
+    impl sqlx_plus::Middleware for Recorder {
+        async fn after(
+            &self,
+            statement: &sqlx_plus::Statement,
+            outcome: &sqlx_plus::StatementOutcome<'_>,
+        ) {
+            let rows = *outcome.result.as_ref().unwrap_or(&0);
+            self.0
+                .lock()
+                .unwrap()
+                .push((statement.sql.clone(), statement.rows, rows));
+        }
+    }
+
+    struct FailSingleRows;
+
+    #[async_trait]
+    impl sqlx_plus::Middleware for FailSingleRows {
+        async fn before(&self, statement: &mut sqlx_plus::Statement) -> anyhow::Result<()> {
+            statement.sql = format!("/* rows={} */ {}", statement.rows, statement.sql);
+            anyhow::ensure!(statement.rows > 1, "injected failure");
+            Ok(())
+        }
+    }
+
+    let pool = sqlx::sqlite::SqlitePool::connect("sqlite://:memory:").await?;
+    let mut conn = pool.acquire().await?;
+    let mut tx = conn.begin().await?;
+
+    tx.setup_tables().await?;
+
+    let users = ["a", "b", "c"]
+        .iter()
+        .map(|name| UserInsert {
+            name: Cow::from(*name),
+            password: Cow::from("password"),
+            created_at: created_at(),
+        })
+        .collect::<Vec<_>>();
+
+    let recorder = std::sync::Arc::new(Recorder::default());
+    let config = sqlx_plus::Config::new()
+        .middleware(FailSingleRows)
+        .middleware(recorder.clone());
+
+    let report = sqlx_plus::BulkInsert::new(&users)
+        .chunk_size(2)
+        .continue_on_error(true)
+        .config(config)
+        .execute(&mut tx)
+        .await?;
+    assert_eq!(report.rows_affected, 2);
+    assert_eq!(report.failed.len(), 1);
+    assert_eq!(report.failed[0].error.to_string(), "injected failure");
+
+    assert_eq!(
+        *recorder.0.lock().unwrap(),
+        vec![(
+            "/* rows=2 */ INSERT INTO user (name,password,created_at) VALUES (?,?,?),(?,?,?)"
+                .to_owned(),
+            2,
+            2
+        )]
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_statement_stats() -> anyhow::Result<()> {
+    use sqlx_plus::{StatementStats, TableStatementStats};
+
+    let pool = sqlx::sqlite::SqlitePool::connect("sqlite://:memory:").await?;
+    let mut conn = pool.acquire().await?;
+    sqlx::query("CREATE TABLE tag (id INTEGER PRIMARY KEY, name TEXT NOT NULL)")
+        .execute(&mut conn)
+        .await?;
+
+    let tags = (0..5)
+        .map(|i| TagInsert {
+            name: format!("tag{}", i),
+        })
+        .collect::<Vec<_>>();
+    let stats = std::sync::Arc::new(StatementStats::new());
+    let config = sqlx_plus::Config::new().middleware(stats.clone());
+    for _ in 0..2 {
+        sqlx_plus::BulkInsert::new(&tags)
+            .chunk_size(2)
+            .config(config.clone())
+            .execute(&mut conn)
+            .await?;
+    }
+
+    let tag = stats.table("tag");
+    assert_eq!(
+        tag,
+        TableStatementStats {
+            statements: 6,
+            reused: 4,
+            prepared: 2,
+        }
+    );
+    assert!((tag.reuse_rate() - 4.0 / 6.0).abs() < 1e-9);
+    assert_eq!(stats.table("user"), TableStatementStats::default());
+    assert_eq!(stats.snapshot().len(), 1);
+
+    stats.reset();
+    assert_eq!(stats.table("tag").statements, 0);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_inserter_receivers() -> anyhow::Result<()> {
+    use sqlx::Connection;
+    use sqlx_plus::Insertable;
+
+    /// Generic over the receiver, which needs `Inserter` to be `Send`.
+    async fn insert_tag<I: Inserter<sqlx::Sqlite>>(inserter: I, name: &str) -> anyhow::Result<u64> {
+        let report = inserter
+            .bulk_insert_report_with_options(
+                &[TagInsert {
+                    name: name.to_owned(),
+                }],
+                &Default::default(),
+            )
+            .await?;
+        Ok(report.rows_affected)
+    }
+
+    /// Generic over the database, bounded on its connection.
+    async fn insert_tags<DB, T>(
+        conn: &mut sqlx::pool::PoolConnection<DB>,
+        rows: &[T],
+    ) -> anyhow::Result<()>
+    where
+        DB: sqlx::Database,
+        T: Insertable<Database = DB> + Sync,
+        for<'c> &'c mut DB::Connection: Inserter<DB>,
+    {
+        conn.bulk_insert(rows).await?;
+        Ok(())
+    }
+
+    const CREATE_TAG: &str = "CREATE TABLE tag (id INTEGER PRIMARY KEY, name TEXT NOT NULL)";
+    // One connection, so that the pool and the connection taken from it share the database.
+    let pool = sqlx::sqlite::SqlitePoolOptions::new()
+        .max_connections(1)
+        .idle_timeout(None)
+        .max_lifetime(None)
+        .connect("sqlite://:memory:")
+        .await?;
+    sqlx::query(CREATE_TAG).execute(&pool).await?;
+
+    assert_eq!(insert_tag(&pool, "pool").await?, 1);
+    let mut conn = pool.acquire().await?;
+    assert_eq!(insert_tag(&mut conn, "pool connection").await?, 1);
+    insert_tags(
+        &mut conn,
+        &[TagInsert {
+            name: "generic".to_owned(),
+        }],
+    )
+    .await?;
+    let mut tx = conn.begin().await?;
+    assert_eq!(insert_tag(&mut tx, "transaction").await?, 1);
+    tx.commit().await?;
+    let names: Vec<String> = sqlx::query_scalar("SELECT name FROM tag ORDER BY id")
+        .fetch_all(&mut conn)
+        .await?;
+    assert_eq!(names, ["pool", "pool connection", "generic", "transaction"]);
+
+    let mut conn = sqlx::SqliteConnection::connect("sqlite://:memory:").await?;
+    sqlx::query(CREATE_TAG).execute(&mut conn).await?;
+    assert_eq!(insert_tag(&mut conn, "connection").await?, 1);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_conformance() -> anyhow::Result<()> {
+    use sqlx::Connection;
+
+    let mut conn = sqlx::SqliteConnection::connect("sqlite://:memory:").await?;
+    sqlx_plus::conformance::run(&mut conn).await?;
+
+    // The suite leaves nothing behind.
+    let tables: Vec<(String,)> = sqlx::query_as("SELECT name FROM sqlite_temp_master")
+        .fetch_all(&mut conn)
+        .await?;
+    assert_eq!(tables, vec![]);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_load_hooks() -> anyhow::Result<()> {
+    use sqlx::Connection;
+    use sqlx_plus::{BulkInsert, Dialect, LoadHooks};
+
+    let mut conn = sqlx::SqliteConnection::connect("sqlite://:memory:").await?;
+    sqlx::query("CREATE TABLE tag (id INTEGER PRIMARY KEY, name TEXT NOT NULL UNIQUE)")
+        .execute(&mut conn)
+        .await?;
+    sqlx::query("CREATE TABLE load_log (entry TEXT NOT NULL)")
+        .execute(&mut conn)
+        .await?;
+    async fn log(conn: &mut sqlx::SqliteConnection) -> anyhow::Result<Vec<String>> {
+        let entries: Vec<(String,)> = sqlx::query_as("SELECT entry FROM load_log ORDER BY rowid")
+            .fetch_all(&mut *conn)
+            .await?;
+        sqlx::query("DELETE FROM load_log")
+            .execute(&mut *conn)
+            .await?;
+        Ok(entries.into_iter().map(|(x,)| x).collect())
+    }
+
+    let hooks = LoadHooks::new()
+        .statement(
+            Dialect::Sqlite,
+            "INSERT INTO load_log VALUES ('before {table}')",
+            "INSERT INTO load_log VALUES ('after {table}')",
+        )
+        .statement(Dialect::MySql, "NOT SQLITE", "NOT SQLITE")
+        .defer_constraints();
+    let tags = |names: &[&str]| {
+        names
+            .iter()
+            .map(|name| TagInsert {
+                name: name.to_string(),
+            })
+            .collect::<Vec<_>>()
+    };
+
+    // Once around the whole insert, not per chunk.
+    let report = BulkInsert::new(&tags(&["a", "b", "c"]))
+        .chunk_size(1)
+        .load_hooks(hooks.clone())
+        .execute(&mut conn)
+        .await?;
+    assert_eq!(report.chunks, 3);
+    assert_eq!(log(&mut conn).await?, ["before tag", "after tag"]);
+
+    BulkInsert::new(&tags(&["d", "e"]))
+        .chunk_size(1)
+        .load_hooks(hooks.clone())
+        .try_for_each_chunk(&mut conn, |_, _, result| async move { result.map(drop) })
+        .await?;
+    assert_eq!(log(&mut conn).await?, ["before tag", "after tag"]);
+
+    // Restored after a failed insert, which is the error returned.
+    let error = BulkInsert::new(&tags(&["f", "a"]))
+        .load_hooks(hooks.clone())
+        .execute(&mut conn)
+        .await
+        .unwrap_err();
+    assert!(format!("{:#}", error).contains("UNIQUE"), "{:#}", error);
+    assert_eq!(log(&mut conn).await?, ["before tag", "after tag"]);
+
+    // A hook which fails to apply restores the ones before it and inserts nothing.
+    let error = BulkInsert::new(&tags(&["g"]))
+        .load_hooks(hooks.statement(Dialect::Sqlite, "INSERT INTO no_such_table VALUES (1)", ""))
+        .execute(&mut conn)
+        .await
+        .unwrap_err();
+    assert!(
+        format!("{:#}", error).contains("no_such_table"),
+        "{:#}",
+        error
+    );
+    assert_eq!(log(&mut conn).await?, ["before tag", "after tag"]);
+    let (count,): (i64,) = sqlx::query_as("SELECT COUNT(*) FROM tag")
+        .fetch_one(&mut conn)
+        .await?;
+    assert_eq!(count, 5);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_chunk_gate() -> anyhow::Result<()> {
+    use std::{
+        sync::{Arc, Mutex},
+        time::Duration,
+    };
+
+    use sqlx_plus::{BulkInsert, ChunkGate, ChunkPosition, Config, ReplicationLagGate};
+
+    #[derive(Default)]
+    struct Recorder(Mutex<Vec<usize>>);
+
+    #[async_trait]
+    impl ChunkGate for Recorder {
+        async fn wait(&self, chunk: &ChunkPosition) -> anyhow::Result<()> {
+            self.0.lock().unwrap().push(chunk.index);
+            Ok(())
+        }
+    }
+
+    let pool = sqlx::SqlitePool::connect("sqlite://:memory:").await?;
+    sqlx::query("CREATE TABLE tag (id INTEGER PRIMARY KEY, name TEXT NOT NULL)")
+        .execute(&pool)
+        .await?;
+    // The gate reads the lag on a pool of its own, one connection to keep the database.
+    let monitor = sqlx::sqlite::SqlitePoolOptions::new()
+        .max_connections(1)
+        .idle_timeout(None)
+        .max_lifetime(None)
+        .connect("sqlite://:memory:")
+        .await?;
+    sqlx::query("CREATE TABLE lag (seconds REAL NOT NULL)")
+        .execute(&monitor)
+        .await?;
+    sqlx::query("INSERT INTO lag VALUES (0.5)")
+        .execute(&monitor)
+        .await?;
+
+    let recorder = Arc::new(Recorder::default());
+    let lag_gate = ReplicationLagGate::new(
+        monitor.clone(),
+        "SELECT seconds FROM lag",
+        Duration::from_secs(1),
+    )
+    .check_interval(Duration::from_millis(10))
+    .max_pause(Duration::from_millis(50));
+    let config = Arc::new(
+        Config::new()
+            .chunk_gate(lag_gate)
+            .chunk_gate(recorder.clone()),
+    );
+    let tags: Vec<_> = (0..3)
+        .map(|i| TagInsert {
+            name: format!("tag{}", i),
+        })
+        .collect();
+
+    let report = BulkInsert::new(&tags)
+        .chunk_size(1)
+        .config(config.clone())
+        .execute(&pool)
+        .await?;
+    assert_eq!(report.rows_affected, 3);
+    assert_eq!(*recorder.0.lock().unwrap(), [0, 1, 2]);
+
+    // Lagging replicas pause the chunks until `max_pause` fails them.
+    sqlx::query("UPDATE lag SET seconds = 30")
+        .execute(&monitor)
+        .await?;
+    tokio::time::sleep(Duration::from_millis(20)).await;
+    let error = BulkInsert::new(&tags)
+        .config(config)
+        .execute(&pool)
+        .await
+        .unwrap_err();
+    assert!(
+        format!("{:#}", error).contains("replication lag"),
+        "{:#}",
+        error
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_dual_write() -> anyhow::Result<()> {
+    use sqlx_plus::{DualWrite, DualWritePolicy};
+
+    // One connection each, so that every use of a pool sees the same in-memory database.
+    async fn pool(with_table: bool) -> anyhow::Result<sqlx::SqlitePool> {
+        let pool = sqlx::sqlite::SqlitePoolOptions::new()
+            .max_connections(1)
+            .idle_timeout(None)
+            .max_lifetime(None)
+            .connect("sqlite://:memory:")
+            .await?;
+        if with_table {
+            sqlx::query("CREATE TABLE tag (id INTEGER PRIMARY KEY, name TEXT NOT NULL)")
+                .execute(&pool)
+                .await?;
+        }
+        Ok(pool)
+    }
+    async fn count(pool: &sqlx::SqlitePool) -> anyhow::Result<i64> {
+        Ok(sqlx::query_scalar("SELECT COUNT(*) FROM tag")
+            .fetch_one(pool)
+            .await?)
+    }
+
+    let tags = (0..3)
+        .map(|i| TagInsert {
+            name: format!("tag{}", i),
+        })
+        .collect::<Vec<_>>();
+
+    let (primary, secondary) = (pool(true).await?, pool(true).await?);
+    let report = DualWrite::new(primary.clone(), secondary.clone())
+        .execute(&tags)
+        .await?;
+    assert!(report.primary_committed && report.secondary_committed);
+    assert!(!report.diverged());
+    assert_eq!(report.secondary.as_ref().unwrap().rows_affected, 3);
+    assert_eq!((count(&primary).await?, count(&secondary).await?), (3, 3));
+
+    // The secondary lacks the table, so its insert fails.
+    let (primary, secondary) = (pool(true).await?, pool(false).await?);
+    let error = DualWrite::new(primary.clone(), secondary.clone())
+        .execute(&tags)
+        .await
+        .unwrap_err();
+    assert!(error.to_string().contains("neither side"));
+    assert_eq!(count(&primary).await?, 0);
+
+    let report = DualWrite::new(primary.clone(), secondary.clone())
+        .policy(DualWritePolicy::PrimaryWins)
+        .execute(&tags)
+        .await?;
+    assert!(report.primary_committed && !report.secondary_committed);
+    assert!(report.diverged());
+    assert!(report.secondary.is_err());
+    assert_eq!(count(&primary).await?, 3);
+
+    // The primary lacks it: only `Either` commits the secondary alone.
+    let (primary, secondary) = (secondary, primary);
+    assert!(DualWrite::new(primary.clone(), secondary.clone())
+        .policy(DualWritePolicy::PrimaryWins)
+        .execute(&tags)
+        .await
+        .is_err());
+    let report = DualWrite::new(primary, secondary.clone())
+        .policy(DualWritePolicy::Either)
+        .execute(&tags)
+        .await?;
+    assert!(!report.primary_committed && report.secondary_committed);
+    assert_eq!(count(&secondary).await?, 6);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_capture_changes() -> anyhow::Result<()> {
+    let pool = sqlx::sqlite::SqlitePool::connect("sqlite://:memory:").await?;
+    let mut conn = pool.acquire().await?;
+    let mut tx = conn.begin().await?;
+    tx.setup_tables().await?;
+    tx.setup_user().await?;
+
+    let mut changes = Vec::new();
+    let mut capture = sqlx_plus::capture_changes::<User, _, _>(&mut tx, |change| {
+        changes.push(change);
+    });
+    capture
+        .save(&UserEntity {
+            id: Some(UserId(1)),
+            name: "renamed".to_owned(),
+            password: "password1".to_owned(),
+        })
+        .await?;
+    assert!(capture.delete_by_id::<UserEntity>(&UserId(2)).await?);
+    assert!(!capture.delete_by_id::<UserEntity>(&UserId(2)).await?);
+
+    let names = changes
+        .iter()
+        .map(|change| {
+            (
+                change.before.as_ref().map(|x| x.name.0.as_str()),
+                change.after.as_ref().map(|x| x.name.0.as_str()),
+            )
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        names,
+        [(Some("aaabbb"), Some("renamed")), (Some("heyheyhey"), None)]
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_cache_transaction() -> anyhow::Result<()> {
+    #[derive(Default)]
+    struct Invalidated(std::sync::Mutex<Vec<(String, Vec<i64>)>>);
+
+    #[async_trait]
+    impl sqlx_plus::CacheInvalidator for Invalidated {
+        async fn invalidate(&self, table_name: &str, keys: &[&(dyn std::any::Any + Send + Sync)]) {
+            let keys = keys
+                .iter()
+                .filter_map(|key| key.downcast_ref::<UserId>())
+                .map(|key| key.0)
+                .collect();
+            self.0.lock().unwrap().push((table_name.to_owned(), keys));
+        }
+    }
+
+    let pool = sqlx::sqlite::SqlitePool::connect("sqlite://:memory:").await?;
+    let mut conn = pool.acquire().await?;
+    let invalidated = std::sync::Arc::new(Invalidated::default());
+    let config = sqlx_plus::Config::new().cache_invalidator(invalidated.clone());
+
+    let mut tx = sqlx_plus::CacheTransaction::begin(&mut conn)
+        .await?
+        .config(config.clone());
+    tx.setup_tables().await?;
+    tx.setup_user().await?;
+    tx.save(&UserEntity {
+        id: Some(UserId(1)),
+        name: "renamed".to_owned(),
+        password: "password1".to_owned(),
+    })
+    .await?;
+    tx.delete_by_ids::<UserEntity>(&[UserId(2), UserId(3)])
+        .await?;
+    assert!(!tx.delete_by_id::<UserEntity>(&UserId(2)).await?);
+    assert!(invalidated.0.lock().unwrap().is_empty());
+    tx.commit().await?;
+
+    assert_eq!(
+        *invalidated.0.lock().unwrap(),
+        [
+            ("user".to_owned(), vec![1]),
+            ("user".to_owned(), vec![2, 3])
+        ]
+    );
+
+    let mut tx = sqlx_plus::CacheTransaction::begin(&mut conn)
+        .await?
+        .config(config);
+    tx.delete_by_id::<UserEntity>(&UserId(4)).await?;
+    tx.rollback().await?;
+    assert_eq!(invalidated.0.lock().unwrap().len(), 2);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_context_deadline() -> anyhow::Result<()> {
+    let pool = sqlx::sqlite::SqlitePool::connect("sqlite://:memory:").await?;
+    let mut conn = pool.acquire().await?;
+    let mut tx = conn.begin().await?;
+    tx.setup_tables().await?;
+
+    let user = UserEntity {
+        id: None,
+        name: "late".to_owned(),
+        password: "password".to_owned(),
+    };
+    let error = sqlx_plus::Context::new()
+        .with_deadline(std::time::Instant::now())
+        .scope(tx.insert(&user))
+        .await
+        .unwrap_err();
+    assert!(error.is::<sqlx_plus::DeadlineExceeded>());
+    assert_eq!(sqlx_plus::Context::current(), None);
+
+    sqlx_plus::Context::new()
+        .with_timeout(std::time::Duration::from_secs(60))
+        .scope(tx.insert(&user))
+        .await?;
+
+    Ok(())
+}
+
+#[test]
+fn test_decimal_scale() {
+    use std::str::FromStr;
+
+    use sqlx::types::{BigDecimal, Decimal};
+    use sqlx_plus::decimal::round_to_scale;
+
+    let decimal = |x| Decimal::from_str(x).unwrap();
+    assert_eq!(round_to_scale(&decimal("10.005"), 2).to_string(), "10.01");
+    assert_eq!(round_to_scale(&decimal("-10.005"), 2).to_string(), "-10.01");
+    assert_eq!(round_to_scale(&decimal("10.004"), 2).to_string(), "10.00");
+    assert_eq!(round_to_scale(&decimal("10"), 2).to_string(), "10.00");
+    assert_eq!(round_to_scale(&None::<Decimal>, 2), None);
+
+    let big_decimal = |x| BigDecimal::from_str(x).unwrap();
+    assert_eq!(
+        round_to_scale(&big_decimal("10.005"), 2).to_string(),
+        "10.01"
+    );
+    assert_eq!(
+        round_to_scale(&big_decimal("-10.005"), 2).to_string(),
+        "-10.01"
+    );
+    assert_eq!(round_to_scale(&big_decimal("10"), 2).to_string(), "10.00");
+    assert_eq!(
+        round_to_scale(&Some(big_decimal("0.1234567")), 6),
+        Some(big_decimal("0.123457"))
+    );
+}
+
+#[tokio::test]
+async fn test_geo() -> anyhow::Result<()> {
+    use sqlx_plus::geo::{Wkb, Wkt};
+
+    assert_eq!(
+        sqlx_plus::bulk_insert_sql::<SpatialShop>("shop", 2)?,
+        "INSERT INTO shop (name,location) VALUES (?,ST_GeomFromWKB(?)),(?,ST_GeomFromWKB(?))"
+    );
+
+    let pool = sqlx::sqlite::SqlitePool::connect("sqlite://:memory:").await?;
+    let mut conn = pool.acquire().await?;
+    sqlx::query("CREATE TABLE shop (name TEXT NOT NULL, location TEXT)")
+        .execute(&mut conn)
+        .await?;
+    conn.bulk_insert(&[
+        Shop {
+            name: "station".to_owned(),
+            location: Some(geo_types::Point::new(1.0, 2.0)),
+        },
+        Shop {
+            name: "online".to_owned(),
+            location: None,
+        },
+    ])
+    .await?;
+    let rows: Vec<(String, Option<String>)> =
+        sqlx::query_as("SELECT name, location FROM shop ORDER BY name")
+            .fetch_all(&mut conn)
+            .await?;
+    assert_eq!(
+        rows,
+        [
+            ("online".to_owned(), None),
+            (
+                "station".to_owned(),
+                Some("0101000000000000000000F03F0000000000000040".to_owned())
+            ),
+        ]
+    );
+
+    let polygon = geo_types::Polygon::new(vec![(0.0, 0.0), (1.0, 0.0), (0.5, 1.5)].into(), vec![]);
+    assert_eq!(
+        Wkt(&polygon).to_text().unwrap(),
+        "POLYGON((0 0,1 0,0.5 1.5,0 0))"
+    );
+    let collection = geo_types::Geometry::GeometryCollection(geo_types::GeometryCollection(vec![
+        geo_types::Point::new(1.0, 2.0).into(),
+        geo_types::MultiPoint::new(vec![]).into(),
+    ]));
+    assert_eq!(
+        Wkt(&collection).to_text().unwrap(),
+        "GEOMETRYCOLLECTION(POINT(1 2),MULTIPOINT EMPTY)"
+    );
+    let text: String = sqlx::query_scalar("SELECT ?")
+        .bind(Wkt(geo_types::Point::new(-0.5, 3.0)))
+        .fetch_one(&mut conn)
+        .await?;
+    assert_eq!(text, "POINT(-0.5 3)");
+    assert_eq!(
+        Wkb(&polygon).to_bytes().unwrap().len(),
+        1 + 4 + 4 + 4 + 4 * 16
+    );
+    assert_eq!(Wkb(None::<geo_types::Point>).to_bytes(), None);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_zero_length_input() -> anyhow::Result<()> {
+    use sqlx_plus::BulkInsertOptions;
+
+    let pool = sqlx::sqlite::SqlitePool::connect("sqlite://:memory:").await?;
+    let mut conn = pool.acquire().await?;
+    sqlx::query("CREATE TABLE tag (id INTEGER PRIMARY KEY, name TEXT NOT NULL UNIQUE)")
+        .execute(&mut conn)
+        .await?;
+    sqlx::query("CREATE TABLE visit (id INTEGER PRIMARY KEY, at TEXT NOT NULL DEFAULT 'now')")
+        .execute(&mut conn)
+        .await?;
+
+    let no_tags: &[TagInsert] = &[];
+    assert!(conn.bulk_insert(no_tags).await?.is_empty());
+    assert_eq!(conn.insert_all(no_tags).await?.rows_affected(), 0);
+    let report = conn
+        .bulk_insert_report_with_options(no_tags, &BulkInsertOptions::default())
+        .await?;
+    assert_eq!((report.chunks, report.rows_affected), (0, 0));
+    assert!(conn.bulk_insert_returning_ids(no_tags).await?.is_empty());
+    assert_eq!(
+        conn.bulk_insert_missing(no_tags, &["name"])
+            .await?
+            .rows_affected,
+        0
+    );
+
+    assert_eq!(
+        sqlx_plus::bulk_insert_sql::<Visit>("visit", 1)?,
+        "INSERT INTO visit DEFAULT VALUES"
+    );
+    assert!(sqlx_plus::bulk_insert_sql::<Visit>("visit", 2).is_err());
+    assert_eq!(
+        sqlx_plus::estimate_bulk_insert::<Visit>(3, 100).statements,
+        3
+    );
+    assert_eq!(BulkInsertOptions::default().chunk_size::<Visit>()?, 1);
+    let visits = vec![Visit::default(); 3];
+    assert_eq!(conn.bulk_insert(&visits).await?.len(), 3);
+    assert_eq!(conn.bulk_insert_returning_ids(&visits).await?, [4, 5, 6]);
+    assert!(conn.insert_all(&visits).await.is_err());
+    conn.insert_all(&visits[..1]).await?;
+    let (count,): (i64,) = sqlx::query_as("SELECT COUNT(*) FROM visit WHERE at = 'now'")
+        .fetch_one(&mut conn)
+        .await?;
+    assert_eq!(count, 7);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_period_columns() -> anyhow::Result<()> {
+    use sqlx_plus::Insertable;
+
+    let pool = sqlx::sqlite::SqlitePool::connect("sqlite://:memory:").await?;
+    let mut conn = pool.acquire().await?;
+    sqlx::query(
+        "CREATE TABLE price (item TEXT NOT NULL, amount INTEGER NOT NULL, valid_from DATETIME NOT NULL, valid_to DATETIME NOT NULL)",
+    )
+    .execute(&mut conn)
+    .await?;
+
+    assert_eq!(
+        Price::insert_columns(),
+        ["item", "amount", "valid_from", "valid_to"]
+    );
+    assert_eq!(Price::update_columns(), Price::insert_columns());
+    assert_eq!(GeneratedPrice::insert_columns(), ["item", "amount"]);
+    assert_eq!(GeneratedPrice::update_columns(), ["item", "amount"]);
+
+    conn.insert(&Price {
+        item: "apple".to_owned(),
+        amount: 120,
+        valid_from: created_at(),
+        valid_to: created_at(),
+    })
+    .await?;
+    let (valid_from,): (NaiveDateTime,) = sqlx::query_as("SELECT valid_from FROM price")
+        .fetch_one(&mut conn)
+        .await?;
+    assert_eq!(valid_from, created_at());
+    let price: GeneratedPrice = sqlx::query_as("SELECT item, amount, valid_from FROM price")
+        .fetch_one(&mut conn)
+        .await?;
+    assert_eq!(price.valid_from, created_at());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_immutable_field() -> anyhow::Result<()> {
+    let pool = sqlx::sqlite::SqlitePool::connect("sqlite://:memory:").await?;
+    let mut conn = pool.acquire().await?;
+    let mut tx = conn.begin().await?;
+    tx.setup_tables().await?;
+    tx.setup_user().await?;
+
+    assert_eq!(
+        <UserProfile as sqlx_plus::Insertable>::update_columns(),
+        ["name"]
+    );
+
+    let mut user = UserProfile {
+        id: Some(UserId(1)),
+        name: "renamed".to_owned(),
+        password: "changed".to_owned(),
+    };
+    tx.save(&user).await?;
+    let row: (String, String) = sqlx::query_as("SELECT name, password FROM user WHERE id = 1")
+        .fetch_one(&mut tx)
+        .await?;
+    assert_eq!(row, ("renamed".to_owned(), "password1".to_owned()));
+
+    user.name = "renamed again".to_owned();
+    sqlx_plus::update_fields(&mut tx, &user, &[UserProfile::NAME]).await?;
+    let row: (String, String) = sqlx::query_as("SELECT name, password FROM user WHERE id = 1")
+        .fetch_one(&mut tx)
+        .await?;
+    assert_eq!(row, ("renamed again".to_owned(), "password1".to_owned()));
+
+    user.id = None;
+    assert!(
+        sqlx_plus::update_fields(&mut tx, &user, &[UserProfile::NAME])
+            .await
+            .is_err()
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_diff_update() -> anyhow::Result<()> {
+    let pool = sqlx::sqlite::SqlitePool::connect("sqlite://:memory:").await?;
+    let mut conn = pool.acquire().await?;
+    let mut tx = conn.begin().await?;
+    tx.setup_tables().await?;
+    tx.setup_user().await?;
+
+    let old = UserEntity {
+        id: Some(UserId(1)),
+        name: "aaabbb".to_owned(),
+        password: "password1".to_owned(),
+    };
+    let mut new = old.clone();
+    assert!(sqlx_plus::diff_update(&mut tx, &old, &new).await?.is_none());
+
+    // Only the changed name is written, the password changed meanwhile stays.
+    sqlx::query("UPDATE user SET password = 'changed elsewhere' WHERE id = 1")
+        .execute(&mut tx)
+        .await?;
+    new.name = "renamed".to_owned();
+    let result = sqlx_plus::diff_update(&mut tx, &old, &new).await?.unwrap();
+    assert_eq!(result.rows_affected(), 1);
+    let row: (String, String) = sqlx::query_as("SELECT name, password FROM user WHERE id = 1")
+        .fetch_one(&mut tx)
+        .await?;
+    assert_eq!(row, ("renamed".to_owned(), "changed elsewhere".to_owned()));
+
+    new.id = None;
+    assert!(sqlx_plus::diff_update(&mut tx, &old, &new).await.is_err());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_declared_unique() -> anyhow::Result<()> {
+    let pool = sqlx::sqlite::SqlitePool::connect("sqlite://:memory:").await?;
+    let mut conn = pool.acquire().await?;
+    sqlx::query("CREATE TABLE tag (id INTEGER PRIMARY KEY, name TEXT NOT NULL UNIQUE)")
+        .execute(&mut conn)
+        .await?;
+
+    let tags = ["a", "b"].map(|name| UniqueTag {
+        name: name.to_owned(),
+    });
+    sqlx_plus::BulkInsert::new(&tags)
+        .on_conflict_default()
+        .execute(&mut conn)
+        .await?;
+    let report = sqlx_plus::BulkInsert::new(&tags)
+        .on_conflict_default()
+        .execute(&mut conn)
+        .await?;
+    assert_eq!(report.rows_affected, 0);
+
+    let tags = ["b", "c"].map(|name| UniqueTag {
+        name: name.to_owned(),
+    });
+    let report = conn.bulk_insert_missing(&tags, &[]).await?;
+    assert_eq!(report.rows_affected, 1);
+
+    assert!(conn
+        .bulk_insert_missing(&[TagInsert { name: "d".into() }], &[])
+        .await
+        .is_err());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_chaos_executor() -> anyhow::Result<()> {
+    use sqlx_plus::{ChaosExecutor, ChaosPolicy};
+
+    async fn insert_with_retries(
+        chaos: &mut ChaosExecutor<'_, sqlx::SqliteConnection>,
+    ) -> anyhow::Result<Vec<bool>> {
+        let mut attempts = Vec::new();
+        for name in ["a", "b", "c", "d", "e"] {
+            loop {
+                let tag = TagInsert {
+                    name: name.to_owned(),
+                };
+                let ok = chaos.insert(&tag).await.is_ok();
+                attempts.push(ok);
+                if ok {
+                    break;
+                }
+            }
+        }
+        Ok(attempts)
+    }
+
+    let pool = sqlx::sqlite::SqlitePool::connect("sqlite://:memory:").await?;
+    let policy = ChaosPolicy::new(7)
+        .error_rate(0.5)
+        .delay(0.5, std::time::Duration::from_millis(5));
+
+    let mut runs = Vec::new();
+    for _ in 0..2 {
+        let mut conn = pool.acquire().await?;
+        sqlx::query("DROP TABLE IF EXISTS tag; CREATE TABLE tag (id INTEGER PRIMARY KEY, name TEXT NOT NULL)")
+            .execute(&mut conn)
+            .await?;
+        let mut chaos = ChaosExecutor::new(&mut *conn, policy.clone());
+        let attempts = insert_with_retries(&mut chaos).await?;
+        let stats = chaos.stats();
+        assert_eq!(stats.statements, attempts.len() as u64);
+        assert_eq!(
+            stats.errors,
+            attempts.iter().filter(|ok| !**ok).count() as u64
+        );
+
+        let (count,): (i64,) = sqlx::query_as("SELECT COUNT(*) FROM tag")
+            .fetch_one(chaos.get_mut())
+            .await?;
+        assert_eq!(count, 5);
+        runs.push(attempts);
+    }
+    // The same seed injects the same faults.
+    assert_eq!(runs[0], runs[1]);
+    assert!(runs[0].contains(&false));
+
+    let (mut conn, mut fresh) = (pool.acquire().await?, pool.acquire().await?);
+    let mut chaos = ChaosExecutor::new(&mut *conn, ChaosPolicy::new(1).drop_rate(1.0));
+    assert!(sqlx::query("SELECT 1").execute(&mut chaos).await.is_err());
+    assert!(chaos.is_dropped());
+    chaos.reconnect(&mut *fresh);
+    assert!(!chaos.is_dropped());
+    assert_eq!(chaos.stats().drops, 1);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_verify_rows_affected() -> anyhow::Result<()> {
+    let pool = sqlx::sqlite::SqlitePool::connect("sqlite://:memory:").await?;
+    let mut conn = pool.acquire().await?;
+    sqlx::query(
+        "CREATE TABLE tag (id INTEGER PRIMARY KEY, name TEXT NOT NULL);
+         CREATE TRIGGER drop_hidden BEFORE INSERT ON tag WHEN NEW.name LIKE '.%'
+         BEGIN SELECT RAISE(IGNORE); END",
+    )
+    .execute(&mut conn)
+    .await?;
+
+    let tags = ["a", "b", ".c", "d"].map(|name| TagInsert {
+        name: name.to_owned(),
+    });
+    let report = sqlx_plus::BulkInsert::new(&tags)
+        .chunk_size(2)
+        .execute(&mut conn)
+        .await?;
+    assert_eq!(report.rows_affected, 3);
+
+    let report = sqlx_plus::BulkInsert::new(&tags)
+        .chunk_size(2)
+        .verify_rows_affected(true)
+        .continue_on_error(true)
+        .execute(&mut conn)
+        .await?;
+    assert_eq!(report.rows_affected, 2);
+    assert_eq!(report.failed.len(), 1);
+    let error = report.failed[0]
+        .error
+        .downcast_ref::<sqlx_plus::PartialWrite>()
+        .unwrap();
+    assert_eq!(
+        *error,
+        sqlx_plus::PartialWrite {
+            chunk: 1,
+            expected: 2,
+            rows_affected: 1,
+        }
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_type_map() {
+    use sqlx_plus::{Dialect, TypeMap};
+
+    let types = TypeMap::new(Dialect::Postgres);
+    assert_eq!(types.sql_type("i64"), Some("BIGINT"));
+    assert_eq!(
+        types.sql_type("Option<chrono::DateTime<chrono::Utc>>"),
+        Some("TIMESTAMPTZ")
+    );
+    assert_eq!(types.sql_type("UserId"), None);
+    assert_eq!(types.rust_type("bigint"), Some("i64"));
+    assert_eq!(
+        TypeMap::new(Dialect::Sqlite).rust_type("INTEGER"),
+        Some("i64")
+    );
+    assert_eq!(TypeMap::new(Dialect::Sqlite).sql_type("u64"), None);
+    assert_eq!(
+        TypeMap::new(Dialect::Mssql).sql_type("std::string::String"),
+        Some("NVARCHAR(MAX)")
+    );
+    // The types of sqlx's other type features map alike, chrono's win the reverse lookup.
+    assert_eq!(types.sql_type("time::OffsetDateTime"), Some("TIMESTAMPTZ"));
+    assert_eq!(
+        TypeMap::new(Dialect::MySql).sql_type("time::PrimitiveDateTime"),
+        Some("DATETIME(6)")
+    );
+    assert_eq!(
+        TypeMap::new(Dialect::MySql).rust_type("DATE"),
+        Some("NaiveDate")
+    );
+    assert_eq!(
+        types.sql_type("sqlx::types::ipnetwork::IpNetwork"),
+        Some("INET")
+    );
+    assert_eq!(types.rust_type("inet"), Some("IpNetwork"));
+    assert_eq!(TypeMap::new(Dialect::Sqlite).sql_type("IpNetwork"), None);
+
+    let types = types
+        .override_type("String", "VARCHAR(255)")
+        .override_type("crate::UserId", "BIGINT");
+    assert_eq!(types.sql_type("Option<String>"), Some("VARCHAR(255)"));
+    assert_eq!(types.sql_type("UserId"), Some("BIGINT"));
+    assert_eq!(types.rust_type("varchar(255)"), Some("String"));
+    assert_eq!(types.rust_type("BIGINT"), Some("UserId"));
+}
+
+#[tokio::test]
+async fn test_arguments_ext() -> anyhow::Result<()> {
+    use sqlx_plus::ArgumentsExt;
+
+    let pool = sqlx::sqlite::SqlitePool::connect("sqlite://:memory:").await?;
+    let mut conn = pool.acquire().await?;
+    sqlx::query("CREATE TABLE tag (id INTEGER PRIMARY KEY, name TEXT NOT NULL)")
+        .execute(&mut conn)
+        .await?;
+
+    let tags = ["a", "b"].map(|name| TagInsert {
+        name: name.to_owned(),
+    });
+    let mut args = sqlx::sqlite::SqliteArguments::default();
+    args.add_multi_fields(&tags);
+    args.add_fields(&tags[0]);
+    let sql = sqlx_plus::BulkInsertOptions::default().insert_sql::<TagInsert>(3)?;
+    sqlx::query_with(&sql, args).execute(&mut conn).await?;
+
+    let mut args = sqlx::sqlite::SqliteArguments::default();
+    args.add_multi(["a", "b"]);
+    let (count,): (i64,) =
+        sqlx::query_as_with("SELECT COUNT(*) FROM tag WHERE name IN (?, ?)", args)
+            .fetch_one(&mut conn)
+            .await?;
+    assert_eq!(count, 3);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_sqlcommenter() -> anyhow::Result<()> {
+    use std::sync::{Arc, Mutex};
+    use tracing::{span, Instrument};
+
+    /// Just enough of a subscriber for `Span::current()`.
+    #[derive(Default)]
+    struct Spans {
+        metadata: Mutex<Vec<&'static tracing::Metadata<'static>>>,
+        entered: Mutex<Vec<span::Id>>,
+    }
+
+    impl tracing::Subscriber for Spans {
+        fn enabled(&self, _: &tracing::Metadata<'_>) -> bool {
+            true
+        }
+        fn new_span(&self, span: &span::Attributes<'_>) -> span::Id {
+            let mut metadata = self.metadata.lock().unwrap();
+            metadata.push(span.metadata());
+            span::Id::from_u64(metadata.len() as u64)
+        }
+        fn record(&self, _: &span::Id, _: &span::Record<'_>) {}
+        fn record_follows_from(&self, _: &span::Id, _: &span::Id) {}
+        fn event(&self, _: &tracing::Event<'_>) {}
+        fn enter(&self, span: &span::Id) {
+            self.entered.lock().unwrap().push(span.clone());
+        }
+        fn exit(&self, _: &span::Id) {
+            self.entered.lock().unwrap().pop();
+        }
+        fn current_span(&self) -> tracing_core::span::Current {
+            match self.entered.lock().unwrap().last() {
+                Some(id) => {
+                    let metadata = self.metadata.lock().unwrap()[id.into_u64() as usize - 1];
+                    tracing_core::span::Current::new(id.clone(), metadata)
+                }
+                None => tracing_core::span::Current::none(),
+            }
+        }
+    }
+
+    struct Recorder(Mutex<Vec<String>>);
+
+    #[async_trait]
+    impl sqlx_plus::Middleware for Recorder {
+        async fn before(&self, statement: &mut sqlx_plus::Statement) -> anyhow::Result<()> {
+            // Unique per chunk, so not worth caching.
+            assert!(!statement.persistent);
+            self.0.lock().unwrap().push(statement.sql.clone());
+            Ok(())
+        }
+    }
+
+    let _subscriber = tracing::subscriber::set_default(Spans::default());
+    let recorder = Arc::new(Recorder(Mutex::new(Vec::new())));
+    let config = sqlx_plus::Config::new()
+        .middleware(sqlx_plus::SqlCommenter::new().tag("application", "it's a test"))
+        .middleware(recorder.clone());
+
+    let pool = sqlx::sqlite::SqlitePool::connect("sqlite://:memory:").await?;
+    let mut conn = pool.acquire().await?;
+    sqlx::query("CREATE TABLE tag (id INTEGER PRIMARY KEY, name TEXT NOT NULL)")
+        .execute(&mut conn)
+        .await?;
+
+    let tags = ["a", "b", "c"].map(|name| TagInsert {
+        name: name.to_owned(),
+    });
+    sqlx_plus::BulkInsert::new(&tags)
+        .chunk_size(2)
+        .batch_id(7)
+        .config(config)
+        .execute(&mut conn)
+        .instrument(tracing::info_span!("import"))
+        .await?;
+
+    let sql = recorder.0.lock().unwrap().clone();
+    assert_eq!(
+        sql,
+        [
+            "INSERT INTO tag (name) VALUES (?),(?) /*application='it%27s%20a%20test',batch_id='7',chunk_index='0',chunk_total='2',local_span_id='1',span_name='import'*/",
+            "INSERT INTO tag (name) VALUES (?) /*application='it%27s%20a%20test',batch_id='7',chunk_index='1',chunk_total='2',local_span_id='1',span_name='import'*/",
+        ]
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_sql_format_is_for_logs() -> anyhow::Result<()> {
+    use std::sync::{Arc, Mutex};
+
+    struct Recorder(Mutex<Vec<(String, String)>>);
+
+    #[async_trait]
+    impl sqlx_plus::Middleware for Recorder {
+        async fn before(&self, statement: &mut sqlx_plus::Statement) -> anyhow::Result<()> {
+            let formatted = statement.formatted_sql().into_owned();
+            self.0
+                .lock()
+                .unwrap()
+                .push((statement.sql.clone(), formatted));
+            Ok(())
+        }
+    }
+
+    let recorder = Arc::new(Recorder(Mutex::new(Vec::new())));
+    let mut config = sqlx_plus::Config::new().middleware(recorder.clone());
+    config.set_sql_format(sqlx_plus::SqlFormat::Pretty);
+
+    let pool = sqlx::sqlite::SqlitePool::connect("sqlite://:memory:").await?;
+    let mut conn = pool.acquire().await?;
+    sqlx::query("CREATE TABLE tag (id INTEGER PRIMARY KEY, name TEXT NOT NULL)")
+        .execute(&mut conn)
+        .await?;
+    let tags = ["a", "b"].map(|name| TagInsert {
+        name: name.to_owned(),
+    });
+    sqlx_plus::BulkInsert::new(&tags)
+        .config(config)
+        .execute(&mut conn)
+        .await?;
+
+    assert_eq!(
+        recorder.0.lock().unwrap().clone(),
+        [(
+            "INSERT INTO tag (name) VALUES (?),(?)".to_owned(),
+            "INSERT INTO tag (name)\nVALUES\n  (?),\n  (?)".to_owned()
+        )]
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_try_for_each_chunk() -> anyhow::Result<()> {
+    use sqlx_plus::ErrorExt;
+
+    let pool = sqlx::sqlite::SqlitePool::connect("sqlite://:memory:").await?;
+    let mut conn = pool.acquire().await?;
+    sqlx::query("CREATE TABLE tag (id INTEGER PRIMARY KEY, name TEXT NOT NULL UNIQUE)")
+        .execute(&mut *conn)
+        .await?;
+
+    let tags = ["a", "b", "c", "a", "d", "e", "f"].map(|name| TagInsert {
+        name: name.to_owned(),
+    });
+    let acked = std::sync::Mutex::new(Vec::new());
+    sqlx_plus::BulkInsert::new(&tags)
+        .chunk_size(2)
+        .try_for_each_chunk(&mut *conn, |index, rows, result| {
+            let acked = &acked;
+            async move {
+                match result {
+                    Ok(rows_affected) => acked.lock().unwrap().push((index, rows, rows_affected)),
+                    // The duplicate `a` fails the second chunk, skip it.
+                    Err(error) if index == 1 => assert!(error.is_unique_violation()),
+                    Err(error) => return Err(error),
+                }
+                anyhow::ensure!(index < 2, "stop after the third chunk");
+                Ok(())
+            }
+        })
+        .await
+        .unwrap_err();
+    assert_eq!(acked.into_inner().unwrap(), [(0, 0..2, 2), (2, 4..6, 2)]);
+
+    let (count,): (i64,) = sqlx::query_as("SELECT COUNT(*) FROM tag")
+        .fetch_one(&mut *conn)
+        .await?;
+    assert_eq!(count, 4);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_batch() -> anyhow::Result<()> {
+    let pool = sqlx::sqlite::SqlitePool::connect("sqlite://:memory:").await?;
+    let mut conn = pool.acquire().await?;
+    let mut tx = conn.begin().await?;
+    tx.setup_tables().await?;
+    tx.setup_user().await?;
+    sqlx::query("CREATE TABLE tag (id INTEGER PRIMARY KEY, name TEXT NOT NULL)")
+        .execute(&mut tx)
+        .await?;
+
+    let report = sqlx_plus::Batch::new()
+        .insert(UserEntity {
+            id: None,
+            name: "batched".to_owned(),
+            password: "password".to_owned(),
+        })
+        .bulk_insert(vec![
+            TagInsert {
+                name: "a".to_owned(),
+            },
+            TagInsert {
+                name: "b".to_owned(),
+            },
+        ])
+        .save(UserEntity {
+            id: Some(UserId(1)),
+            name: "renamed".to_owned(),
+            password: "password1".to_owned(),
+        })
+        .sql("DELETE FROM user WHERE name = ? OR name = ?", |q| {
+            q.bind("heyheyhey").bind("hoge")
+        })
+        .execute(&mut tx)
+        .await?;
+    assert_eq!(report.rows_affected, [1, 2, 1, 2]);
+    assert_eq!(report.total_rows_affected(), 6);
+
+    let error = sqlx_plus::Batch::new()
+        .bulk_insert(vec![TagInsert {
+            name: "c".to_owned(),
+        }])
+        .sql("DELETE FROM missing_table", |q| q)
+        .execute(&mut tx)
+        .await
+        .unwrap_err();
+    assert!(error.to_string().starts_with("batch operation 1 ("));
+    let (tags,): (i64,) = sqlx::query_as("SELECT COUNT(*) FROM tag")
+        .fetch_one(&mut tx)
+        .await?;
+    assert_eq!(tags, 2);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_prepare_insert() -> anyhow::Result<()> {
+    let pool = sqlx::sqlite::SqlitePool::connect("sqlite://:memory:").await?;
+    let mut conn = pool.acquire().await?;
+    sqlx::query("CREATE TABLE tag (id INTEGER PRIMARY KEY, name TEXT NOT NULL)")
+        .execute(&mut conn)
+        .await?;
+
+    let cached = conn.cached_statements_size();
+    sqlx_plus::prepare_insert::<TagInsert, _>(&mut conn, 100).await?;
+    assert_eq!(conn.cached_statements_size(), cached + 2);
+
+    // The prepared statement is the one the bulk insert uses.
+    let tags = vec![TagInsert::default(); 100];
+    conn.bulk_insert_with_chunk_size(100, &tags).await?;
+    assert_eq!(conn.cached_statements_size(), cached + 2);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_fetch_stream_chunked() -> anyhow::Result<()> {
+    use futures_util::TryStreamExt;
+
+    let pool = sqlx::sqlite::SqlitePool::connect("sqlite://:memory:").await?;
+    let mut conn = pool.acquire().await?;
+    sqlx::query("CREATE TABLE tag (id INTEGER PRIMARY KEY, name TEXT NOT NULL)")
+        .execute(&mut conn)
+        .await?;
+    let tags = ["a", "b", "c", "d", "e"].map(|name| TagInsert {
+        name: name.to_owned(),
+    });
+    conn.bulk_insert(&tags).await?;
+
+    let chunks = sqlx_plus::fetch_stream_chunked::<TagInsert, (i64, String)>(2)
+        .stream(&mut conn)
+        .try_collect::<Vec<_>>()
+        .await?;
+    let ids = chunks
+        .iter()
+        .map(|chunk| chunk.iter().map(|(id, _)| *id).collect::<Vec<_>>())
+        .collect::<Vec<_>>();
+    assert_eq!(ids, [vec![1, 2], vec![3, 4], vec![5]]);
+
+    let chunks = sqlx_plus::fetch_stream_chunked::<TagInsert, (i64, String)>(2)
+        .after(3)
+        .stream(&mut conn)
+        .try_collect::<Vec<_>>()
+        .await?;
+    assert_eq!(chunks.concat().len(), 2);
+
+    let chunks = sqlx_plus::chunked(
+        sqlx::query_as::<_, (i64, String)>("SELECT * FROM tag").fetch(&mut conn),
+        4,
+    )
+    .try_collect::<Vec<_>>()
+    .await?;
+    assert_eq!(chunks.iter().map(Vec::len).collect::<Vec<_>>(), [4, 1]);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_spill() -> anyhow::Result<()> {
+    let pool = sqlx::sqlite::SqlitePool::connect("sqlite://:memory:").await?;
+    let mut conn = pool.acquire().await?;
+    sqlx::query("CREATE TABLE tag (id INTEGER PRIMARY KEY, name TEXT NOT NULL)")
+        .execute(&mut conn)
+        .await?;
+
+    let path = std::env::temp_dir().join(format!("sqlx-plus-spill-{}", std::process::id()));
+    let tags = ["a", "b", "c"].map(|name| TagInsert {
+        name: name.to_owned(),
+    });
+    {
+        let mut spill = sqlx_plus::Spill::<TagInsert>::open(&path)?;
+        spill.append(&tags[..2])?;
+        spill.append(&tags[2..])?;
+    }
+
+    // A new process picks up what the last one spilled.
+    let mut spill = sqlx_plus::Spill::<TagInsert>::open(&path)?.chunk_size(2);
+    assert!(!spill.is_empty()?);
+    assert_eq!(spill.replay(&mut conn).await?, 3);
+    assert!(spill.is_empty()?);
+    assert_eq!(spill.replay(&mut conn).await?, 0);
+
+    let names: Vec<(String,)> = sqlx::query_as("SELECT name FROM tag ORDER BY id")
+        .fetch_all(&mut conn)
+        .await?;
+    assert_eq!(names, [("a".into(),), ("b".into(),), ("c".into(),)]);
+
+    std::fs::remove_file(&path)?;
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_composite_key() -> anyhow::Result<()> {
+    let pool = sqlx::sqlite::SqlitePool::connect("sqlite://:memory:").await?;
+    let mut conn = pool.acquire().await?;
+    let mut tx = conn.begin().await?;
+
+    sqlx::query(
+        "CREATE TABLE membership (tenant_id INTEGER NOT NULL, user_id INTEGER NOT NULL, \
+         role TEXT NOT NULL, PRIMARY KEY (tenant_id, user_id))",
+    )
+    .execute(&mut tx)
+    .await?;
+    let memberships =
+        [(1, 1, "owner"), (1, 2, "member"), (2, 1, "member")].map(|(tenant_id, user_id, role)| {
+            Membership {
+                tenant_id,
+                user_id,
+                role: role.to_owned(),
+            }
+        });
+    tx.bulk_insert(&memberships).await?;
+
+    let found: Option<Membership> =
+        sqlx_plus::find_by_id::<Membership, _, _>(&mut tx, &(1, 2)).await?;
+    assert_eq!(found.as_ref(), Some(&memberships[1]));
+
+    let mut found: Vec<Membership> =
+        sqlx_plus::find_by_ids::<Membership, _, _>(&mut tx, &[(2, 1), (1, 1), (3, 3)]).await?;
+    found.sort_by_key(|x| (x.tenant_id, x.user_id));
+    assert_eq!(found, [memberships[0].clone(), memberships[2].clone()]);
+
+    let deleted = sqlx_plus::delete_by_ids::<Membership, _>(&mut tx, &[(1, 1), (1, 2)]).await?;
+    assert_eq!(deleted, 2);
+    assert!(!sqlx_plus::delete_by_id::<Membership, _>(&mut tx, &(1, 1)).await?);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_save() -> anyhow::Result<()> {
+    let pool = sqlx::sqlite::SqlitePool::connect("sqlite://:memory:").await?;
+    let mut conn = pool.acquire().await?;
+    let mut tx = conn.begin().await?;
+
+    tx.setup_tables().await?;
+    tx.setup_user().await?;
+
+    // The key is left out, so the builder sets it to `None`.
+    let mut user = UserEntity::builder()
+        .name("saved")
+        .password("password")
+        .build()?;
+    assert_eq!(user.id, None);
+    assert!(UserEntity::builder().name("saved").build().is_err());
+    tx.save(&user).await?;
+
+    let (id,): (i64,) = sqlx::query_as("SELECT id FROM user WHERE name = 'saved'")
+        .fetch_one(&mut tx)
+        .await?;
+    user.id = Some(UserId(id));
+    user.password = "changed".to_owned();
+    let result = tx.save(&user).await?;
+    assert_eq!(result.rows_affected(), 1);
+
+    let (password,): (String,) = sqlx::query_as("SELECT password FROM user WHERE id = ?")
+        .bind(id)
+        .fetch_one(&mut tx)
+        .await?;
+    assert_eq!(password, "changed");
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_private_fields() -> anyhow::Result<()> {
+    use account::Account;
+
+    let pool = sqlx::sqlite::SqlitePool::connect("sqlite://:memory:").await?;
+    let mut conn = pool.acquire().await?;
+    let mut tx = conn.begin().await?;
+    tx.setup_tables().await?;
+
+    // Fields private to `account` are bound all the same.
+    tx.bulk_insert(&[Account::new("alice", "terces")]).await?;
+    tx.save(&Account::new("bob", "drowssap")).await?;
+    let rows: Vec<(String, String)> = sqlx::query_as("SELECT name, password FROM user ORDER BY id")
+        .fetch_all(&mut tx)
+        .await?;
+    assert_eq!(
+        rows,
+        [
+            ("alice".to_owned(), "secret".to_owned()),
+            ("bob".to_owned(), "password".to_owned()),
+        ]
+    );
+    let error = tx.insert(&Account::new("alice", "x")).await.unwrap_err();
+    assert_eq!(
+        error.downcast_ref::<sqlx_plus::ConstraintViolation<account::AccountViolation>>(),
+        Some(&sqlx_plus::ConstraintViolation(
+            account::AccountViolation::NameTaken
+        ))
+    );
+
+    // Only the setter of `name` is visible here, the private `password` can't be built around.
+    assert!(Account::builder().name("carol").build().is_err());
+    assert_eq!(Account::NAME.name(), "name");
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_impl_insertable_manually() -> anyhow::Result<()> {
+    use sqlx_plus::QueryBindExt;
+
+    /// Stands in for a type generated from a schema, which can't derive.
+    struct GeneratedTag {
+        name: String,
+        description: Option<String>,
+    }
+
+    sqlx_plus::impl_insertable_manually!(GeneratedTag, sqlx::Sqlite, "tag", |tag| {
+        "name" => tag.name.trim(),
+        "description" => tag.description.as_deref(),
+    });
+
+    /// Binds one value less than it has columns.
+    struct Miscounted;
+
+    impl sqlx_plus::Insertable for Miscounted {
+        type Database = sqlx::Sqlite;
+
+        const COLUMN_COUNT: usize = 2;
+
+        fn table_name() -> &'static str {
+            "tag"
+        }
+
+        fn insert_columns() -> &'static [&'static str] {
+            &["name", "description"]
+        }
+
+        fn bind_fields<'q, Q>(&'q self, q: Q) -> Q
+        where
+            Q: sqlx_plus::QueryBindExt<'q, Self::Database>,
+        {
+            q.bind("name")
+        }
+        fn bind_field<'q, Q>(&'q self, column: &str, q: Q) -> Q
+        where
+            Q: sqlx_plus::QueryBindExt<'q, Self::Database>,
+        {
+            match column {
+                "name" => q.bind("name"),
+                _ => q,
+            }
+        }
+    }
+
+    /// Written by hand, for a table which doesn't have `label` yet.
+    struct LabeledTag {
+        name: &'static str,
+        label: Option<&'static str>,
+    }
+
+    impl sqlx_plus::Insertable for LabeledTag {
+        type Database = sqlx::Sqlite;
+
+        const COLUMN_COUNT: usize = 2;
+
+        fn table_name() -> &'static str {
+            "tag"
+        }
+
+        fn insert_columns() -> &'static [&'static str] {
+            &["name", "label"]
+        }
+
+        fn bind_fields<'q, Q>(&'q self, q: Q) -> Q
+        where
+            Q: sqlx_plus::QueryBindExt<'q, Self::Database>,
+        {
+            q.bind(self.name).bind(self.label)
+        }
+
+        fn bind_field<'q, Q>(&'q self, column: &str, q: Q) -> Q
+        where
+            Q: sqlx_plus::QueryBindExt<'q, Self::Database>,
+        {
+            match column {
+                "name" => q.bind(self.name),
+                "label" => q.bind(self.label),
+                _ => q,
+            }
+        }
+    }
+
+    let pool = sqlx::sqlite::SqlitePool::connect("sqlite://:memory:").await?;
+    let mut conn = pool.acquire().await?;
+    sqlx::query("CREATE TABLE tag (id INTEGER PRIMARY KEY, name TEXT NOT NULL, description TEXT)")
+        .execute(&mut conn)
+        .await?;
+
+    let tags = [
+        GeneratedTag {
+            name: " rust ".to_owned(),
+            description: Some("a language".to_owned()),
+        },
+        GeneratedTag {
+            name: "sql".to_owned(),
+            description: None,
+        },
+    ];
+    assert_eq!(<GeneratedTag as sqlx_plus::Insertable>::COLUMN_COUNT, 2);
+    sqlx_plus::check_binds(&tags[0])?;
+    conn.bulk_insert(&tags).await?;
+    sqlx::query("UPDATE tag SET description = ? WHERE name = 'sql'")
+        .bind_fields_of(&tags[0], &["description"])?
+        .execute(&mut conn)
+        .await?;
+    let rows: Vec<(String, Option<String>)> =
+        sqlx::query_as("SELECT name, description FROM tag ORDER BY id")
+            .fetch_all(&mut conn)
+            .await?;
+    assert_eq!(
+        rows,
+        [
+            ("rust".to_owned(), Some("a language".to_owned())),
+            ("sql".to_owned(), Some("a language".to_owned())),
+        ]
+    );
+
+    sqlx_plus::BulkInsert::new(&[LabeledTag {
+        name: "hand",
+        label: Some("written"),
+    }])
+    .skip_missing_columns(sqlx_plus::ColumnCache::new())
+    .execute(&mut conn)
+    .await?;
+    let name: String = sqlx::query_scalar("SELECT name FROM tag WHERE id = 3")
+        .fetch_one(&mut conn)
+        .await?;
+    assert_eq!(name, "hand");
+
+    let error = sqlx_plus::check_binds(&Miscounted).unwrap_err();
+    assert!(error.to_string().ends_with("binds 1 values for 2 columns"));
+
+    Ok(())
+}
+
+// The repeated pattern is the point.
+#[allow(unreachable_patterns)]
+#[test]
+fn test_impl_insertable_manually_checks_columns() {
+    use sqlx_plus::QueryBindExt;
+
+    /// Names `name` twice, which debug builds refuse to bind.
+    struct TwiceNamed;
+
+    sqlx_plus::impl_insertable_manually!(TwiceNamed, sqlx::Sqlite, "tag", |tag| {
+        "name" => "first",
+        "name" => "second",
+    });
+
+    let error = sqlx_plus::check_binds(&TwiceNamed).unwrap_err();
+    assert!(error.to_string().contains("names the column name twice"));
+    if cfg!(debug_assertions) {
+        assert!(std::panic::catch_unwind(|| {
+            sqlx::query::<sqlx::Sqlite>("INSERT INTO tag (name, name) VALUES (?, ?)")
+                .bind_fields(&TwiceNamed)
+        })
+        .is_err());
+    }
+}
+
+#[tokio::test]
+async fn test_bind_fields_of() -> anyhow::Result<()> {
+    use sqlx_plus::QueryBindExt;
+
+    let pool = sqlx::sqlite::SqlitePool::connect("sqlite://:memory:").await?;
+    let mut conn = pool.acquire().await?;
+    let mut tx = conn.begin().await?;
+
+    tx.setup_tables().await?;
+
+    let user = UserInsert {
+        name: Cow::from("partial"),
+        password: Cow::from("password"),
+        created_at: created_at(),
+    };
+    sqlx::query("INSERT INTO user (password, name) VALUES (?, ?)")
+        .bind_fields_of(&user, &["password", "name"])?
+        .execute(&mut tx)
+        .await?;
+
+    let (name, password): (String, String) = sqlx::query_as("SELECT name, password FROM user")
+        .fetch_one(&mut tx)
+        .await?;
+    assert_eq!((name.as_str(), password.as_str()), ("partial", "password"));
+
+    assert!(sqlx::query("SELECT 1")
+        .bind_fields_of(&user, &["email"])
+        .is_err());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_dialect_literals() -> anyhow::Result<()> {
+    let pool = sqlx::sqlite::SqlitePool::connect("sqlite://:memory:").await?;
+
+    let sql = format!(
+        "SELECT {}, {}",
+        sqlx_plus::sql_bool(sqlx_plus::Dialect::Sqlite, true),
+        sqlx_plus::sql_now(sqlx_plus::Dialect::Sqlite)
+    );
+    let (flag, now): (bool, NaiveDateTime) = sqlx::query_as(&sql).fetch_one(&pool).await?;
+    assert!(flag);
+    assert!(now > created_at());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_demux() -> anyhow::Result<()> {
+    enum Event {
+        UserCreated(UserInsert<'static>),
+        Tagged(TagInsert),
+    }
+
+    let pool = sqlx::sqlite::SqlitePool::connect("sqlite://:memory:").await?;
+    let mut conn = pool.acquire().await?;
+    let mut tx = conn.begin().await?;
+
+    tx.setup_tables().await?;
+    sqlx::query("CREATE TABLE tag (id INTEGER PRIMARY KEY, name TEXT NOT NULL)")
+        .execute(&mut tx)
+        .await?;
+
+    let mut demux = sqlx_plus::Demux::<Event, Database>::new()
+        .buffer_size(2)
+        .route(|event| match event {
+            Event::UserCreated(user) => Ok(user),
+            event => Err(event),
+        })
+        .route(|event| match event {
+            Event::Tagged(tag) => Ok(tag),
+            event => Err(event),
+        });
+
+    let mut inserted = 0;
+    for i in 0..3 {
+        inserted += demux
+            .push(
+                &mut tx,
+                Event::UserCreated(UserInsert {
+                    name: Cow::from(format!("user{}", i)),
+                    password: Cow::from("password"),
+                    created_at: created_at(),
+                }),
+            )
+            .await?;
+        inserted += demux
+            .push(
+                &mut tx,
+                Event::Tagged(TagInsert {
+                    name: format!("tag{}", i),
+                }),
+            )
+            .await?;
+    }
+    assert_eq!(inserted, 4);
+    assert_eq!(demux.flush(&mut tx).await?, 2);
+
+    let (users, tags): (i64, i64) =
+        sqlx::query_as("SELECT (SELECT COUNT(*) FROM user), (SELECT COUNT(*) FROM tag)")
+            .fetch_one(&mut tx)
+            .await?;
+    assert_eq!((users, tags), (3, 3));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_bulk_insert_missing() -> anyhow::Result<()> {
+    let pool = sqlx::sqlite::SqlitePool::connect("sqlite://:memory:").await?;
+    let mut conn = pool.acquire().await?;
+    let mut tx = conn.begin().await?;
+
+    sqlx::query("CREATE TABLE tag (id INTEGER PRIMARY KEY, name TEXT NOT NULL)")
+        .execute(&mut tx)
+        .await?;
+    let tags = |names: &[&str]| {
+        names
+            .iter()
+            .map(|name| TagInsert {
+                name: name.to_string(),
+            })
+            .collect::<Vec<_>>()
+    };
+    tx.bulk_insert(&tags(&["a", "c"])).await?;
+
+    let options = sqlx_plus::BulkInsertOptions {
+        chunk_size: Some(2),
+        ..Default::default()
+    };
+    let report = tx
+        .bulk_insert_missing_with_options(&tags(&["a", "b", "c", "d", "e"]), &["name"], &options)
+        .await?;
+    assert_eq!(report.rows_affected, 3);
+
+    // Loading again inserts nothing.
+    let report = tx
+        .bulk_insert_missing(&tags(&["a", "b", "c", "d", "e"]), &["name"])
+        .await?;
+    assert_eq!(report.rows_affected, 0);
+    assert!(tx
+        .bulk_insert_missing(&tags(&["a"]), &["nothing"])
+        .await
+        .is_err());
+
+    let names: Vec<String> = sqlx::query_scalar("SELECT name FROM tag ORDER BY id")
+        .fetch_all(&mut tx)
+        .await?;
+    assert_eq!(names, ["a", "c", "b", "d", "e"]);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_group_by_table() -> anyhow::Result<()> {
+    let pool = sqlx::sqlite::SqlitePool::connect("sqlite://:memory:").await?;
+    let mut conn = pool.acquire().await?;
+
+    for table in ["tag_2024_01", "tag_2024_02"] {
+        sqlx::query(&format!(
+            "CREATE TABLE {} (id INTEGER PRIMARY KEY, name TEXT NOT NULL)",
+            table
+        ))
+        .execute(&mut conn)
+        .await?;
+    }
+
+    let rows = ["01", "02", "01", "01"]
+        .into_iter()
+        .enumerate()
+        .map(|(i, month)| {
+            (
+                format!("tag_2024_{}", month),
+                TagInsert {
+                    name: format!("tag{}", i),
+                },
+            )
+        })
+        .collect();
+    let reports = sqlx_plus::group_by_table(rows)
+        .options(sqlx_plus::BulkInsertOptions {
+            chunk_size: Some(2),
+            ..Default::default()
+        })
+        .execute(&mut conn)
+        .await?;
+    assert_eq!(
+        reports
+            .iter()
+            .map(|(table, report)| (table.as_str(), report.rows_affected, report.chunks))
+            .collect::<Vec<_>>(),
+        [("tag_2024_01", 3, 2), ("tag_2024_02", 1, 1)]
+    );
+
+    let names: Vec<String> = sqlx::query_scalar("SELECT name FROM tag_2024_01 ORDER BY id")
+        .fetch_all(&mut conn)
+        .await?;
+    assert_eq!(names, ["tag0", "tag2", "tag3"]);
+
+    // An invalid table name fails before anything is inserted.
+    let rows = vec![("tag; --".to_owned(), TagInsert::default())];
+    assert!(sqlx_plus::group_by_table(rows)
+        .execute(&mut conn)
+        .await
+        .is_err());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_demux_memory_budget() -> anyhow::Result<()> {
+    let pool = sqlx::sqlite::SqlitePool::connect("sqlite://:memory:").await?;
+    let mut conn = pool.acquire().await?;
+    let mut tx = conn.begin().await?;
+
+    sqlx::query("CREATE TABLE tag (id INTEGER PRIMARY KEY, name TEXT NOT NULL)")
+        .execute(&mut tx)
+        .await?;
+
+    let tag = |i: usize| TagInsert {
+        name: format!("tag{}", i),
+    };
+    let size = sqlx_plus::SizeEstimate::estimated_size(&tag(0));
+    let budget = sqlx_plus::MemoryBudget::new(size * 2);
+
+    let mut shedding = sqlx_plus::Demux::<TagInsert, Database>::new()
+        .memory_budget(budget.clone())
+        .budget_policy(sqlx_plus::BudgetPolicy::Shed)
+        .route_estimated(Ok);
+    assert_eq!(shedding.push(&mut tx, tag(0)).await?, 0);
+    assert_eq!(shedding.push(&mut tx, tag(1)).await?, 0);
+    let err = shedding.push(&mut tx, tag(2)).await.unwrap_err();
+    assert_eq!(
+        err.downcast_ref::<sqlx_plus::MemoryBudgetExceeded>(),
+        Some(&sqlx_plus::MemoryBudgetExceeded {
+            requested: size,
+            used: size * 2,
+            limit: size * 2,
+        })
+    );
+    assert_eq!(shedding.flush(&mut tx).await?, 2);
+    assert_eq!(budget.used(), 0);
+
+    let mut backpressure = sqlx_plus::Demux::<TagInsert, Database>::new()
+        .memory_budget(budget.clone())
+        .route_estimated(Ok);
+    assert_eq!(backpressure.push(&mut tx, tag(3)).await?, 0);
+    assert_eq!(backpressure.push(&mut tx, tag(4)).await?, 0);
+    assert_eq!(backpressure.push(&mut tx, tag(5)).await?, 3);
+    assert_eq!(budget.used(), 0);
+
+    let tags: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM tag")
+        .fetch_one(&mut tx)
+        .await?;
+    assert_eq!(tags, 5);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_bulk_insert_smart_pointers() -> anyhow::Result<()> {
+    let pool = sqlx::sqlite::SqlitePool::connect("sqlite://:memory:").await?;
+    let mut conn = pool.acquire().await?;
+    let mut tx = conn.begin().await?;
+
+    tx.setup_tables().await?;
+
+    let users = ["a", "b"]
+        .iter()
+        .map(|name| {
+            std::sync::Arc::new(UserInsert {
+                name: Cow::from(*name),
+                password: Cow::from("password"),
+                created_at: created_at(),
+            })
+        })
+        .collect::<Vec<_>>();
+
+    tx.bulk_insert(&users).await?;
+    tx.bulk_insert(users.iter().filter(|user| user.name == "a").map(|user| {
+        std::sync::Arc::new(UserInsert {
+            name: Cow::from("a2"),
+            ..UserInsert::clone(user)
+        })
+    }))
+    .await?;
+    let result = tx
+        .insert_all(&[
+            UserInsert {
+                name: Cow::from("d"),
+                password: Cow::from("password"),
+                created_at: created_at(),
+            },
+            UserInsert {
+                name: Cow::from("e"),
+                password: Cow::from("password"),
+                created_at: created_at(),
+            },
+        ])
+        .await?;
+    assert_eq!(result.rows_affected(), 2);
+    tx.insert(&Box::new(UserInsert {
+        name: Cow::from("c"),
+        password: Cow::from("password"),
+        created_at: created_at(),
+    }))
+    .await?;
+
+    let (count,): (i64,) = sqlx::query_as("SELECT COUNT(*) FROM user")
+        .fetch_one(&mut tx)
+        .await?;
+    assert_eq!(count, 6);
+
+    Ok(())
+}
+
+#[test]
+fn test_blocking() -> anyhow::Result<()> {
+    use sqlx_plus::blocking;
+
+    let pool = blocking::connect::<sqlx::Sqlite>("sqlite://:memory:")?;
+    blocking::block_on(async {
+        let mut tx = pool.begin().await?;
+        tx.setup_tables().await?;
+        Ok::<_, anyhow::Error>(tx.commit().await?)
+    })?;
+
+    blocking::bulk_insert(
+        &pool,
+        &[UserInsert {
+            name: Cow::from("a"),
+            password: Cow::from("password"),
+            created_at: created_at(),
+        }],
+    )?;
+
+    let (count,): (i64,) =
+        blocking::block_on(sqlx::query_as("SELECT COUNT(*) FROM user").fetch_one(&pool))?;
+    assert_eq!(count, 1);
+
+    Ok(())
+}
+
+#[test]
+fn test_postgres_placeholder_numbering() {
+    for start_num in [1, 2, 9, 10, 99, 65000, 65535] {
+        for num in [0, 1, 2, 10, 535] {
+            let within_limit = start_num + num <= 65536;
+            let placeholders =
+                std::panic::catch_unwind(|| sqlx_plus::placeholders_postgres(num, Some(start_num)));
+            assert_eq!(
+                placeholders.is_ok(),
+                within_limit,
+                "{} from {}",
+                num,
+                start_num
+            );
+            if let Ok(placeholders) = placeholders {
+                let numbers = placeholders
+                    .split(',')
+                    .filter(|p| !p.is_empty())
+                    .map(|p| p.strip_prefix('$').unwrap().parse().unwrap())
+                    .collect::<Vec<usize>>();
+                assert_eq!(numbers, (start_num..start_num + num).collect::<Vec<_>>());
+            }
+        }
+    }
+    assert!(
+        std::panic::catch_unwind(|| sqlx_plus::placeholders_postgres(2, Some(usize::MAX))).is_err()
+    );
+
+    struct WideRow;
+
+    impl sqlx_plus::Insertable for WideRow {
+        type Database = sqlx::Sqlite;
+
+        const COLUMN_COUNT: usize = 40000;
+
+        fn table_name() -> &'static str {
+            "wide"
+        }
+
+        fn insert_columns() -> &'static [&'static str] {
+            &[]
+        }
+
+        fn bind_fields<'q, Q>(&'q self, q: Q) -> Q
+        where
+            Q: sqlx_plus::QueryBindExt<'q, Self::Database>,
+        {
+            q
+        }
+        fn bind_field<'q, Q>(&'q self, _column: &str, q: Q) -> Q
+        where
+            Q: sqlx_plus::QueryBindExt<'q, Self::Database>,
+        {
+            q
+        }
+    }
+
+    let error = sqlx_plus::BulkInsertOptions::default()
+        .chunk_size::<WideRow>()
+        .unwrap_err();
+    let error = error
+        .downcast_ref::<sqlx_plus::BindParamLimitExceeded>()
+        .unwrap();
+    assert_eq!(error.max_chunk_size(), 0);
+}
+
+#[test]
+fn test_generated_sql() {
+    assert_eq!(
+        sqlx_plus::bulk_insert_sql::<UserInsert>("user", 2).unwrap(),
+        "INSERT INTO user (name,password,created_at) VALUES (?,?,?),(?,?,?)"
+    );
+    assert_eq!(
+        sqlx_plus::bulk_insert_sql::<SortedUserInsert>("user", 1).unwrap(),
+        "INSERT INTO user (created_at,name,password) VALUES (?,?,?)"
+    );
+    assert_eq!(sqlx_plus::placeholders_postgres(3, Some(9)), "$9,$10,$11");
+    {
+        use sqlx_plus::{dialect, Dialect};
+
+        assert_eq!(dialect::placeholders(Dialect::MySql, 3), "?,?,?");
+        assert_eq!(
+            dialect::placeholders_for_values(Dialect::Postgres, 2, 2),
+            "($1,$2),($3,$4)"
+        );
+        assert_eq!(
+            dialect::placeholders_for_values(Dialect::Sqlite, 2, 1),
+            "(?),(?)"
+        );
+    }
+    assert_eq!(
+        sqlx_plus::estimate_bulk_insert::<TagInsert>(5, 2),
+        sqlx_plus::BulkInsertEstimate {
+            chunks: 3,
+            statements: 3,
+            placeholders_per_statement: 2,
+            payload_bytes: 2 * "INSERT INTO tag (name) VALUES (?),(?)".len()
+                + "INSERT INTO tag (name) VALUES (?)".len(),
+        }
+    );
+
+    sqlx_plus::SqlSnapshot::new()
+        .insertable::<UserInsert>("UserInsert", &Default::default())
+        .insertable::<TagInsert>(
+            "TagInsert upsert",
+            &sqlx_plus::BulkInsertOptions {
+                on_conflict: Some(sqlx_plus::OnConflict::columns(["name"])),
+                ..Default::default()
+            },
+        )
+        .add(
+            "User by name",
+            User::select()
+                .filter(User::NAME.eq(UserName::from("name")))
+                .order_by(User::CREATED_AT.desc())
+                .sql(),
+        )
+        .assert_matches("tests/snapshots/sql.snap");
+
+    let sql = sqlx_plus::BulkInsertOptions {
+        on_conflict: Some(sqlx_plus::OnConflict::columns(["name"])),
+        ..Default::default()
+    }
+    .insert_sql::<TagInsert>(2)
+    .unwrap();
+    assert_eq!(
+        sqlx_plus::SqlFormat::Pretty.apply(&sql),
+        "INSERT INTO tag (name)\nVALUES\n  (?),\n  (?)\nON CONFLICT (name) DO NOTHING"
+    );
+    let sql = "SELECT $$ a  b\n  c $$ AS body,\n    'it''s' FROM t";
+    assert_eq!(sqlx_plus::SqlFormat::Compact.apply(sql), sql);
+    assert!(matches!(
+        sqlx_plus::SqlFormat::Compact.apply(sql),
+        std::borrow::Cow::Borrowed(_)
+    ));
+
+    // Chunks shrink to the longest statement SQLite accepts.
+    let mut options = sqlx_plus::BulkInsertOptions {
+        chunk_size: Some(10),
+        capabilities: Some(sqlx_plus::Capabilities {
+            max_sql_length: Some("INSERT INTO tag (name) VALUES (?),(?),(?)".len()),
+            ..sqlx_plus::Capabilities::assumed::<Database>()
+        }),
+        ..Default::default()
+    };
+    assert_eq!(options.chunk_size::<TagInsert>().unwrap(), 3);
+    options.capabilities.as_mut().unwrap().max_sql_length = Some(20);
+    let error = options.chunk_size::<TagInsert>().unwrap_err();
+    assert!(error.is::<sqlx_plus::SqlLengthExceeded>());
+    assert_eq!(
+        sqlx_plus::values_table::<TagInsert>(2),
+        "(SELECT column1 AS name FROM (VALUES (?),(?))) AS v"
+    );
+
+    let mut options = sqlx_plus::BulkInsertOptions {
+        on_conflict: Some(
+            sqlx_plus::OnConflict::constraint("uniq_active_name").do_update(["password"]),
+        ),
+        ..Default::default()
+    };
+    assert!(options.insert_sql::<UserInsert>(1).is_err());
+    options.capabilities = Some(sqlx_plus::Capabilities {
+        dialect: sqlx_plus::Dialect::Postgres,
+        ..sqlx_plus::Capabilities::assumed::<Database>()
+    });
+    assert_eq!(
+        options.insert_sql::<UserInsert>(1).unwrap(),
+        "INSERT INTO user (name,password,created_at) VALUES (?,?,?) \
+         ON CONFLICT ON CONSTRAINT uniq_active_name DO UPDATE SET password=excluded.password"
+    );
+
+    let hints = sqlx_plus::Hints::new()
+        .mysql("LOW_PRIORITY")
+        .mssql("TABLOCK")
+        .postgres("Set(enable_seqscan off)");
+    let with_dialect = |dialect| sqlx_plus::BulkInsertOptions {
+        hints: Some(hints.clone()),
+        capabilities: Some(sqlx_plus::Capabilities {
+            dialect,
+            ..sqlx_plus::Capabilities::assumed::<Database>()
+        }),
+        ..Default::default()
+    };
+    assert_eq!(
+        with_dialect(sqlx_plus::Dialect::Sqlite)
+            .insert_sql::<TagInsert>(1)
+            .unwrap(),
+        "INSERT INTO tag (name) VALUES (?)"
+    );
+    assert_eq!(
+        with_dialect(sqlx_plus::Dialect::MySql)
+            .insert_sql::<TagInsert>(1)
+            .unwrap(),
+        "INSERT LOW_PRIORITY INTO tag (name) VALUES (?)"
+    );
+    assert_eq!(
+        with_dialect(sqlx_plus::Dialect::Mssql)
+            .insert_sql::<TagInsert>(1)
+            .unwrap(),
+        "INSERT INTO tag WITH (TABLOCK) (name) VALUES (?)"
+    );
+    assert_eq!(
+        with_dialect(sqlx_plus::Dialect::Postgres)
+            .insert_sql::<TagInsert>(1)
+            .unwrap(),
+        "/*+ Set(enable_seqscan off) */ INSERT INTO tag (name) VALUES (?)"
+    );
+
+    // MySQL keeps a conflicting row with a no-op assignment, which needs a column.
+    let with_conflict = |on_conflict| sqlx_plus::BulkInsertOptions {
+        on_conflict: Some(on_conflict),
+        capabilities: Some(sqlx_plus::Capabilities {
+            dialect: sqlx_plus::Dialect::MySql,
+            ..sqlx_plus::Capabilities::assumed::<Database>()
+        }),
+        ..Default::default()
+    };
+    assert_eq!(
+        with_conflict(sqlx_plus::OnConflict::any())
+            .insert_sql::<TagInsert>(1)
+            .unwrap(),
+        "INSERT INTO tag (name) VALUES (?) ON DUPLICATE KEY UPDATE name=name"
+    );
+    assert!(with_conflict(sqlx_plus::OnConflict::columns(["id"]))
+        .insert_sql::<Visit>(1)
+        .unwrap()
+        .ends_with(" ON DUPLICATE KEY UPDATE id=id"));
+    assert!(with_conflict(sqlx_plus::OnConflict::any())
+        .insert_sql::<Visit>(1)
+        .is_err());
+
+    // MSSQL outputs the ids between the columns and the values, after any table hints.
+    assert_eq!(
+        with_dialect(sqlx_plus::Dialect::Mssql)
+            .returning_sql::<UserInsert>(2)
+            .unwrap(),
+        "INSERT INTO user WITH (TABLOCK) (name,password,created_at) OUTPUT INSERTED.id \
+         VALUES (?,?,?),(?,?,?)"
+    );
+    assert_eq!(
+        with_dialect(sqlx_plus::Dialect::Mssql)
+            .returning_sql::<Visit>(1)
+            .unwrap(),
+        "INSERT INTO visit WITH (TABLOCK) OUTPUT INSERTED.id DEFAULT VALUES"
+    );
+    let options = sqlx_plus::BulkInsertOptions {
+        id_column: Some("tag_id".into()),
+        ..with_dialect(sqlx_plus::Dialect::Sqlite)
+    };
+    assert_eq!(
+        options.returning_sql::<TagInsert>(2).unwrap(),
+        "INSERT INTO tag (name) VALUES (?),(?) RETURNING tag_id"
+    );
+}
+
+#[tokio::test]
+async fn test_values_table() -> anyhow::Result<()> {
+    use sqlx_plus::QueryBindExt;
+
+    let pool = sqlx::sqlite::SqlitePool::connect("sqlite://:memory:").await?;
+    let mut conn = pool.acquire().await?;
+    let mut tx = conn.begin().await?;
+
+    tx.setup_tables().await?;
+    tx.setup_user().await?;
+
+    let users = ["aaabbb", "missing"]
+        .iter()
+        .map(|name| UserInsert {
+            name: Cow::from(*name),
+            password: Cow::from("password"),
+            created_at: created_at(),
+        })
+        .collect::<Vec<_>>();
+
+    let sql = format!(
+        "SELECT v.name FROM {} LEFT JOIN user ON user.name = v.name WHERE user.id IS NULL",
+        sqlx_plus::values_table::<UserInsert>(users.len())
+    );
+    let missing: Vec<String> = sqlx::query_scalar(&sql)
+        .bind_multi_fields(&users)
+        .fetch_all(&mut tx)
+        .await?;
+    assert_eq!(missing, ["missing"]);
+
+    let (count,): (i64,) = sqlx::query_as(
+        "SELECT COUNT(*) FROM user WHERE (name = ? AND password = ?) OR id IN (?, ?)",
+    )
+    .bind_tuple(("aaabbb", "password1"))
+    .bind_tuple([2_i64, 3])
+    .fetch_one(&mut tx)
+    .await?;
+    assert_eq!(count, 3);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_extra_json_column() -> anyhow::Result<()> {
+    let pool = sqlx::sqlite::SqlitePool::connect("sqlite://:memory:").await?;
+    let mut conn = pool.acquire().await?;
+    let mut tx = conn.begin().await?;
+
+    sqlx::query("CREATE TABLE event (name TEXT NOT NULL, attrs TEXT NOT NULL)")
+        .execute(&mut tx)
+        .await?;
+
+    let event = EventInsert {
+        name: "signup".to_owned(),
+        attrs: [
+            ("plan".to_owned(), serde_json::json!("pro")),
+            ("seats".to_owned(), serde_json::json!(3)),
+        ]
+        .into_iter()
+        .collect(),
+    };
+    tx.insert(&event).await?;
+
+    let attrs: String = sqlx::query_scalar("SELECT attrs FROM event WHERE name = 'signup'")
+        .fetch_one(&mut tx)
+        .await?;
+    assert_eq!(
+        serde_json::from_str::<HashMap<String, serde_json::Value>>(&attrs)?,
+        event.attrs
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_skip_if() -> anyhow::Result<()> {
+    let pool = sqlx::sqlite::SqlitePool::connect("sqlite://:memory:").await?;
+    let mut conn = pool.acquire().await?;
+    let mut tx = conn.begin().await?;
+    tx.setup_tables().await?;
+
+    let users = [("dated", "2022-06-20 01:02:03"), ("undated", "")].map(|(name, created_at)| {
+        ImportedUserInsert {
+            name: name.to_owned(),
+            password: "password".to_owned(),
+            created_at: created_at.to_owned(),
+        }
+    });
+    tx.bulk_insert(&users).await?;
+
+    let rows: Vec<(String, Option<String>)> =
+        sqlx::query_as("SELECT name, created_at FROM user ORDER BY id")
+            .fetch_all(&mut tx)
+            .await?;
+    assert_eq!(
+        rows,
+        [
+            ("dated".to_owned(), Some("2022-06-20 01:02:03".to_owned())),
+            ("undated".to_owned(), None),
+        ]
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_seed() -> anyhow::Result<()> {
+    let pool = sqlx::sqlite::SqlitePool::connect("sqlite://:memory:").await?;
+    let mut conn = pool.acquire().await?;
+
+    {
+        let mut tx = conn.begin().await?;
+        tx.setup_tables().await?;
+        tx.commit().await?;
+    }
+    sqlx::query(
+        "CREATE TABLE post (id INTEGER PRIMARY KEY, user_id INTEGER NOT NULL REFERENCES user (id), title TEXT NOT NULL)",
+    )
+    .execute(&mut conn)
+    .await?;
+
+    let user = |name: &'static str| UserInsert {
+        name: Cow::from(name),
+        password: Cow::from("password"),
+        created_at: created_at(),
+    };
+    // Posts are declared first, but inserted after the users they reference.
+    let rows = sqlx_plus::seed![
+        [
+            PostInsert { user_id: 1, title: "hello".to_owned() },
+            PostInsert { user_id: 2, title: "world".to_owned() },
+        ] references "user";
+        [user("alice"), user("bob")];
+    ]
+    .execute(&mut conn)
+    .await?;
+    assert_eq!(rows, 4);
+
+    let cyclic = sqlx_plus::Seed::new()
+        .table(vec![PostInsert {
+            user_id: 1,
+            title: "cycle".to_owned(),
+        }])
+        .references(["user"])
+        .table(vec![user("carol")])
+        .references(["post"])
+        .execute(&mut conn)
+        .await;
+    assert!(cyclic.is_err());
+
+    let titles: Vec<String> = sqlx::query_scalar(
+        "SELECT title FROM post JOIN user ON user.id = post.user_id ORDER BY user.name",
+    )
+    .fetch_all(&mut conn)
+    .await?;
+    assert_eq!(titles, ["hello", "world"]);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_copy_table() -> anyhow::Result<()> {
+    let source = sqlx::sqlite::SqlitePool::connect("sqlite://:memory:").await?;
+    let destination = sqlx::sqlite::SqlitePool::connect("sqlite://:memory:").await?;
+    let mut source = source.acquire().await?;
+    let mut destination = destination.acquire().await?;
+
+    for conn in [&mut source, &mut destination] {
+        let mut tx = conn.begin().await?;
+        tx.setup_tables().await?;
+        tx.commit().await?;
+    }
+    let mut tx = source.begin().await?;
+    tx.setup_user().await?;
+    tx.commit().await?;
+
+    let options = sqlx_plus::CopyOptions {
+        batch_size: 2,
+        ..Default::default()
+    };
+    let mut pages = Vec::new();
+    let progress = sqlx_plus::copy_table::<CopiedUser, _, _, _>(
+        &mut *source,
+        &mut *destination,
+        &options,
+        |progress| pages.push(*progress),
+    )
+    .await?;
+    assert_eq!(progress.rows, 5);
+    assert_eq!(pages.iter().map(|x| x.rows).collect::<Vec<_>>(), [2, 4, 5]);
+
+    // Resuming after the last key finds nothing left to copy.
+    let options = sqlx_plus::CopyOptions {
+        resume_after: progress.last_key,
+        ..options
+    };
+    let resumed = sqlx_plus::copy_table::<CopiedUser, _, _, _>(
+        &mut *source,
+        &mut *destination,
+        &options,
+        |_| {},
+    )
+    .await?;
+    assert_eq!(resumed.rows, 0);
+
+    let names: Vec<String> = sqlx::query_scalar("SELECT name FROM user ORDER BY id")
+        .fetch_all(&mut *destination)
+        .await?;
+    assert_eq!(
+        names,
+        ["aaabbb", "heyheyhey", "xxxSHINICHIxxx", "hoge", "fuga"]
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_copy_table_anonymized() -> anyhow::Result<()> {
+    let source = sqlx::sqlite::SqlitePool::connect("sqlite://:memory:").await?;
+    let destination = sqlx::sqlite::SqlitePool::connect("sqlite://:memory:").await?;
+    let mut source = source.acquire().await?;
+    let mut destination = destination.acquire().await?;
+
+    for conn in [&mut source, &mut destination] {
+        let mut tx = conn.begin().await?;
+        tx.setup_tables().await?;
+        tx.commit().await?;
+    }
+    let mut tx = source.begin().await?;
+    tx.setup_user().await?;
+    tx.commit().await?;
+
+    // An unregistered faker fails before anything is copied.
+    let options = sqlx_plus::CopyOptions {
+        anonymizer: Some(sqlx_plus::Anonymizer::new()),
+        ..Default::default()
+    };
+    let missing = sqlx_plus::copy_table::<AnonymizedUser, _, _, _>(
+        &mut *source,
+        &mut *destination,
+        &options,
+        |_| {},
+    )
+    .await;
+    assert!(missing.unwrap_err().to_string().contains("\"password\""));
+
+    let anonymizer = sqlx_plus::Anonymizer::new()
+        .salt("staging")
+        .faker("password", |_| "secret".to_owned());
+    let options = sqlx_plus::CopyOptions {
+        anonymizer: Some(anonymizer.clone()),
+        ..Default::default()
+    };
+    let progress = sqlx_plus::copy_table::<AnonymizedUser, _, _, _>(
+        &mut *source,
+        &mut *destination,
+        &options,
+        |_| {},
+    )
+    .await?;
+    assert_eq!(progress.rows, 5);
+
+    let rows: Vec<(String, String)> = sqlx::query_as("SELECT name, password FROM user ORDER BY id")
+        .fetch_all(&mut *destination)
+        .await?;
+    assert_eq!(rows[0].0, anonymizer.fake("name", "aaabbb")?);
+    assert!(rows[0].0.starts_with("Person "));
+    assert!(rows.iter().all(|(_, password)| password == "secret"));
+    // Distinct values stay distinct, for the unique index on `name`.
+    let mut names = rows.iter().map(|(name, _)| name).collect::<Vec<_>>();
+    names.dedup();
+    assert_eq!(names.len(), 5);
+    assert_ne!(
+        anonymizer.fake("email", "a@example.com")?,
+        sqlx_plus::Anonymizer::new().fake("email", "a@example.com")?
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_json_predicates() -> anyhow::Result<()> {
+    use sqlx_plus::{json, Dialect, QueryBindExt};
+
+    let pool = sqlx::sqlite::SqlitePool::connect("sqlite://:memory:").await?;
+    let mut conn = pool.acquire().await?;
+    sqlx::query("CREATE TABLE event (id INTEGER PRIMARY KEY, payload TEXT NOT NULL)")
+        .execute(&mut conn)
+        .await?;
+    sqlx::query("INSERT INTO event (id, payload) VALUES (1, ?), (2, ?), (3, ?)")
+        .bind(r#"{"user": {"country": "JP", "it's": 1}, "tags": ["beta", "ops"]}"#)
+        .bind(r#"{"user": {"country": "JP"}, "tags": ["ops"]}"#)
+        .bind(r#"{"user": {"country": "FR"}, "tags": "beta"}"#)
+        .execute(&mut conn)
+        .await?;
+
+    let sql = format!(
+        "SELECT id FROM event WHERE {} = ? ORDER BY id",
+        json::extract(Dialect::Sqlite, "payload", 1)
+    );
+    let country = json::Path::new().key("user").key("country");
+    let ids: Vec<i64> = sqlx::query_scalar(&sql)
+        .bind_json_path(&country)
+        .bind("JP")
+        .fetch_all(&mut conn)
+        .await?;
+    assert_eq!(ids, [1, 2]);
+
+    // Keys are quoted, not spliced into the path.
+    let quoted = json::Path::new().key("user").key("it's");
+    assert_eq!(quoted.to_string(), r#"$."user"."it's""#);
+    let ids: Vec<i64> = sqlx::query_scalar(&sql)
+        .bind_json_path(&quoted)
+        .bind(1)
+        .fetch_all(&mut conn)
+        .await?;
+    assert_eq!(ids, [1]);
 
-    {
-        let mut conn = pool.acquire().await?;
-        let mut tx = conn.begin().await?;
+    let sql = format!(
+        "SELECT id FROM event WHERE {} ORDER BY id",
+        json::array_contains(Dialect::Sqlite, "payload", 1)
+    );
+    let ids: Vec<i64> = sqlx::query_scalar(&sql)
+        .bind_json_path(&json::Path::new().key("tags"))
+        .bind("beta")
+        .fetch_all(&mut conn)
+        .await?;
+    assert_eq!(ids, [1]);
 
-        assert_eq!(
-            tx.get_user_by_name_and_password("xxxSHINICHIxxx", "password3")
-                .await?,
-            Some(User {
-                id: 3,
-                name: "xxxSHINICHIxxx".into(),
-                password: "password3".into(),
-                created_at: chrono::NaiveDate::from_ymd(2022, 6, 20).and_hms(1, 2, 3),
-            })
-        );
+    let first_tag: Option<String> = sqlx::query_scalar(&format!(
+        "SELECT {} FROM event WHERE id = 2",
+        json::extract(Dialect::Sqlite, "payload", 1)
+    ))
+    .bind_json_path(&json::Path::new().key("tags").index(0))
+    .fetch_one(&mut conn)
+    .await?;
+    assert_eq!(first_tag.as_deref(), Some("ops"));
 
-        assert_eq!(
-            tx.get_user_by_name_and_password("hoge", "password4")
-                .await?,
-            Some(User {
-                id: 4,
-                name: "hoge".into(),
-                password: "password4".into(),
-                created_at: chrono::NaiveDate::from_ymd(2022, 6, 20).and_hms(1, 2, 3),
+    assert_eq!(
+        json::array_contains(Dialect::Postgres, "payload", 2),
+        "(jsonb_path_query_first(CAST(payload AS jsonb), CAST($2 AS jsonpath)) @> jsonb_build_array(CAST($3 AS text)))"
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_auto_now() -> anyhow::Result<()> {
+    let pool = sqlx::sqlite::SqlitePool::connect("sqlite://:memory:").await?;
+    let mut conn = pool.acquire().await?;
+    let mut tx = conn.begin().await?;
+    tx.setup_tables().await?;
+
+    let users = (0..5)
+        .map(|i| StampedUser {
+            name: format!("user{}", i),
+            password: "secret".to_owned(),
+            created_at: None,
+        })
+        .collect::<Vec<_>>();
+    // One timestamp for the whole batch, however many statements it takes.
+    sqlx_plus::BulkInsert::new(&users)
+        .chunk_size(2)
+        .execute(&mut tx)
+        .await?;
+    let stamps: Vec<chrono::NaiveDateTime> =
+        sqlx::query_scalar("SELECT created_at FROM user ORDER BY id")
+            .fetch_all(&mut tx)
+            .await?;
+    assert_eq!(stamps.len(), 5);
+    assert!(stamps.iter().all(|x| *x == stamps[0]));
+
+    let frozen = std::time::UNIX_EPOCH + std::time::Duration::from_secs(1_700_000_000);
+    let set = chrono::NaiveDate::from_ymd_opt(2020, 1, 1)
+        .unwrap()
+        .and_hms_opt(0, 0, 0)
+        .unwrap();
+    let users = [
+        StampedUser {
+            name: "frozen".to_owned(),
+            password: "secret".to_owned(),
+            created_at: None,
+        },
+        StampedUser {
+            name: "set".to_owned(),
+            password: "secret".to_owned(),
+            created_at: Some(set),
+        },
+    ];
+    sqlx_plus::BulkInsert::new(&users)
+        .now(frozen)
+        .execute(&mut tx)
+        .await?;
+    let stamps: Vec<chrono::NaiveDateTime> =
+        sqlx::query_scalar("SELECT created_at FROM user WHERE id > 5 ORDER BY id")
+            .fetch_all(&mut tx)
+            .await?;
+    assert_eq!(
+        stamps,
+        [
+            chrono::DateTime::<chrono::Utc>::from(frozen).naive_utc(),
+            set
+        ]
+    );
+    assert_eq!(
+        <StampedUser as sqlx_plus::Insertable>::update_columns(),
+        ["name", "password"]
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_clock_and_ids() -> anyhow::Result<()> {
+    use sqlx_plus::clock::{FixedClock, IdGenerator, SequentialIds};
+
+    let pool = sqlx::sqlite::SqlitePool::connect("sqlite://:memory:").await?;
+    let mut conn = pool.acquire().await?;
+    sqlx::query(
+        "CREATE TABLE token (id BLOB PRIMARY KEY, name TEXT NOT NULL, created_at DATETIME)",
+    )
+    .execute(&mut conn)
+    .await?;
+
+    let frozen = std::time::UNIX_EPOCH + std::time::Duration::from_secs(1_700_000_000);
+    let clock = std::sync::Arc::new(FixedClock::new(frozen));
+    let mut config = sqlx_plus::Config::new();
+    config
+        .set_clock(clock.clone())
+        .set_id_generator(SequentialIds::new(1));
+    let config = std::sync::Arc::new(config);
+
+    let tokens = |names: &[&str]| {
+        names
+            .iter()
+            .map(|name| Token {
+                id: None,
+                name: name.to_string(),
+                created_at: None,
             })
-        );
+            .collect::<Vec<_>>()
+    };
+    sqlx_plus::BulkInsert::new(&tokens(&["a", "b", "c"]))
+        .chunk_size(1)
+        .config(config.clone())
+        .execute(&mut conn)
+        .await?;
+    clock.advance(std::time::Duration::from_secs(60));
+    sqlx_plus::BulkInsert::new(&tokens(&["d"]))
+        .config(config.clone())
+        .execute(&mut conn)
+        .await?;
 
-        tx.commit().await?;
+    let rows: Vec<(sqlx::types::Uuid, chrono::NaiveDateTime)> =
+        sqlx::query_as("SELECT id, created_at FROM token ORDER BY name")
+            .fetch_all(&mut conn)
+            .await?;
+    let ids = rows.iter().map(|(id, _)| id.as_u128()).collect::<Vec<_>>();
+    assert_eq!(ids, [1, 2, 3, 4]);
+    let frozen = chrono::DateTime::<chrono::Utc>::from(frozen).naive_utc();
+    assert_eq!(
+        rows.iter().map(|(_, x)| *x).collect::<Vec<_>>(),
+        [
+            frozen,
+            frozen,
+            frozen,
+            frozen + chrono::Duration::seconds(60)
+        ]
+    );
+
+    // The default generator makes UUIDv7s.
+    let id = sqlx::types::Uuid::from_u128(sqlx_plus::clock::UuidV7.next_id());
+    assert_eq!(id.get_version_num(), 7);
+    assert_ne!(
+        sqlx_plus::clock::UuidV7.next_id(),
+        sqlx_plus::clock::UuidV7.next_id()
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_skip_missing_columns() -> anyhow::Result<()> {
+    let pool = sqlx::sqlite::SqlitePool::connect("sqlite://:memory:").await?;
+    let mut conn = pool.acquire().await?;
+    sqlx::query("CREATE TABLE account (id INTEGER PRIMARY KEY, name TEXT NOT NULL)")
+        .execute(&mut conn)
+        .await?;
+
+    let accounts = [
+        Account {
+            name: "a".to_owned(),
+            nickname: Some("aa".to_owned()),
+        },
+        Account {
+            name: "b".to_owned(),
+            nickname: None,
+        },
+    ];
+    // The migration adding `nickname` hasn't run yet.
+    assert!(sqlx_plus::BulkInsert::new(&accounts)
+        .execute(&mut conn)
+        .await
+        .is_err());
+    let cache = std::sync::Arc::new(sqlx_plus::ColumnCache::new());
+    sqlx_plus::BulkInsert::new(&accounts)
+        .skip_missing_columns(cache.clone())
+        .execute(&mut conn)
+        .await?;
+
+    sqlx::query("ALTER TABLE account ADD COLUMN nickname TEXT")
+        .execute(&mut conn)
+        .await?;
+    cache.invalidate("account");
+    sqlx_plus::BulkInsert::new(&accounts[..1])
+        .skip_missing_columns(cache.clone())
+        .execute(&mut conn)
+        .await?;
+
+    let rows: Vec<(String, Option<String>)> =
+        sqlx::query_as("SELECT name, nickname FROM account ORDER BY id")
+            .fetch_all(&mut conn)
+            .await?;
+    assert_eq!(
+        rows,
+        [
+            ("a".to_owned(), None),
+            ("b".to_owned(), None),
+            ("a".to_owned(), Some("aa".to_owned())),
+        ]
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_borrowed_fields() -> anyhow::Result<()> {
+    use sqlx_plus::{AsRow, Insertable};
+
+    let pool = sqlx::sqlite::SqlitePool::connect("sqlite://:memory:").await?;
+    let mut conn = pool.acquire().await?;
+    sqlx::query("CREATE TABLE account (id INTEGER PRIMARY KEY, name TEXT NOT NULL, nickname TEXT)")
+        .execute(&mut conn)
+        .await?;
+
+    let names = ["a".to_owned(), "b".to_owned()];
+    let borrowed = names
+        .iter()
+        .map(|name| BorrowedAccount {
+            name,
+            nickname: Some(name),
+        })
+        .collect::<Vec<_>>();
+    conn.bulk_insert(&borrowed).await?;
+    conn.insert(&BorrowedAccount {
+        name: "c",
+        nickname: None,
+    })
+    .await?;
+    assert_eq!(
+        borrowed[0].as_row(),
+        [
+            ("name", sqlx_plus::ValueRef::new("a")),
+            ("nickname", sqlx_plus::ValueRef::new(Some("a")))
+        ]
+    );
+
+    // Literals and owned strings, without a lifetime parameter.
+    let mut account = CowAccount {
+        name: Cow::Borrowed("d"),
+        nickname: Some(Cow::Owned(format!("{}{}", "d", "d"))),
+    };
+    account.anonymize(&sqlx_plus::Anonymizer::new().faker("upper", |x| x.to_uppercase()))?;
+    conn.insert(&account).await?;
+
+    let rows: Vec<(String, Option<String>)> =
+        sqlx::query_as("SELECT name, nickname FROM account ORDER BY id")
+            .fetch_all(&mut conn)
+            .await?;
+    assert_eq!(
+        rows,
+        [
+            ("a".to_owned(), Some("a".to_owned())),
+            ("b".to_owned(), Some("b".to_owned())),
+            ("c".to_owned(), None),
+            ("D".to_owned(), Some("DD".to_owned())),
+        ]
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_chunks_for() -> anyhow::Result<()> {
+    use sqlx_plus::QueryBindExt;
+
+    let pool = sqlx::sqlite::SqlitePool::connect("sqlite://:memory:").await?;
+    let mut conn = pool.acquire().await?;
+    sqlx::query("CREATE TABLE account (id INTEGER PRIMARY KEY, name TEXT NOT NULL, nickname TEXT)")
+        .execute(&mut conn)
+        .await?;
+
+    let accounts = (0..5)
+        .map(|i| Account {
+            name: i.to_string(),
+            nickname: None,
+        })
+        .collect::<Vec<_>>();
+    let options = sqlx_plus::BulkInsertOptions {
+        chunk_size: Some(2),
+        ..Default::default()
+    };
+    let chunks = options.chunks(&accounts)?;
+    assert_eq!(chunks.len(), 3);
+    for (chunk, sql) in chunks {
+        assert_eq!(sql, options.insert_sql::<Account>(chunk.len())?);
+        sqlx::query(&sql)
+            .bind_multi_fields(chunk)
+            .execute(&mut conn)
+            .await?;
     }
 
+    let chunks = sqlx_plus::chunks_for::<sqlx::Sqlite, _>(&accounts)?.collect::<Vec<_>>();
+    assert_eq!(chunks.len(), 1);
+    assert_eq!(
+        chunks[0].1,
+        "INSERT INTO account (name,nickname) VALUES (?,?),(?,?),(?,?),(?,?),(?,?)"
+    );
+    assert_eq!(
+        sqlx_plus::chunks_for::<sqlx::Sqlite, Account>(&[])?.count(),
+        0
+    );
+
+    let names: Vec<String> = sqlx::query_scalar("SELECT name FROM account ORDER BY id")
+        .fetch_all(&mut conn)
+        .await?;
+    assert_eq!(names, ["0", "1", "2", "3", "4"]);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_default_if_none() -> anyhow::Result<()> {
+    let pool = sqlx::sqlite::SqlitePool::connect("sqlite://:memory:").await?;
+    let mut conn = pool.acquire().await?;
+    sqlx::query(
+        "CREATE TABLE ticket (id INTEGER PRIMARY KEY, name TEXT NOT NULL, status TEXT NOT NULL DEFAULT 'new')",
+    )
+    .execute(&mut conn)
+    .await?;
+
+    let ticket = |name: &str, status: Option<&str>| Ticket {
+        name: name.to_owned(),
+        status: status.map(str::to_owned),
+    };
+    let report = sqlx_plus::BulkInsert::new(&[
+        ticket("a", None),
+        ticket("b", Some("open")),
+        ticket("c", None),
+        ticket("d", Some("closed")),
+        ticket("e", None),
+    ])
+    .chunk_size(2)
+    .execute(&mut conn)
+    .await?;
+    // `a, c`, `e`, then `b, d`.
+    assert_eq!(report.chunks, 3);
+    assert_eq!(report.rows_affected, 5);
+    conn.insert(&ticket("f", None)).await?;
+    assert!(conn
+        .insert_all(&[ticket("g", None), ticket("h", Some("open"))])
+        .await
+        .is_err());
+
+    let rows: Vec<(String, String)> = sqlx::query_as("SELECT name, status FROM ticket ORDER BY id")
+        .fetch_all(&mut conn)
+        .await?;
+    assert_eq!(
+        rows,
+        [
+            ("a", "new"),
+            ("c", "new"),
+            ("e", "new"),
+            ("b", "open"),
+            ("d", "closed"),
+            ("f", "new"),
+        ]
+        .map(|(name, status)| (name.to_owned(), status.to_owned()))
+    );
+
+    // Rows without any column go one `DEFAULT VALUES` statement each.
+    sqlx::query("CREATE TABLE draft (id INTEGER PRIMARY KEY, status TEXT NOT NULL DEFAULT 'new')")
+        .execute(&mut conn)
+        .await?;
+    let drafts = [
+        Draft { status: None },
+        Draft {
+            status: Some("open".to_owned()),
+        },
+        Draft { status: None },
+        Draft { status: None },
+    ];
+    let report = sqlx_plus::BulkInsert::new(&drafts)
+        .execute(&mut conn)
+        .await?;
+    // `None` one by one, then `open`.
+    assert_eq!(report.chunks, 4);
+    assert_eq!(report.rows_affected, 4);
+    assert!(conn.insert_all(&drafts[2..]).await.is_err());
+
+    let statuses: Vec<String> = sqlx::query_scalar("SELECT status FROM draft ORDER BY id")
+        .fetch_all(&mut conn)
+        .await?;
+    assert_eq!(statuses, ["new", "new", "new", "open"]);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_aggregates() -> anyhow::Result<()> {
+    let pool = sqlx::sqlite::SqlitePool::connect("sqlite://:memory:").await?;
+    let mut conn = pool.acquire().await?;
+    sqlx::query(
+        "CREATE TABLE ticket (id INTEGER PRIMARY KEY, name TEXT NOT NULL, status TEXT NOT NULL DEFAULT 'new')",
+    )
+    .execute(&mut conn)
+    .await?;
+
+    assert_eq!(conn.max_of::<Ticket, i64>("id").await?, None);
+    conn.bulk_insert(&["b", "c", "a"].map(|name| Ticket {
+        name: name.to_owned(),
+        status: None,
+    }))
+    .await?;
+
+    assert_eq!(conn.max_of::<Ticket, i64>("id").await?, Some(3));
+    assert_eq!(conn.sum_of::<Ticket, i64>("id").await?, Some(6));
+    assert_eq!(
+        conn.min_of::<Ticket, String>("name").await?,
+        Some("a".to_owned())
+    );
+    assert!(conn
+        .max_of::<Ticket, i64>("id) FROM ticket; --")
+        .await
+        .is_err());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_select() -> anyhow::Result<()> {
+    let pool = sqlx::sqlite::SqlitePool::connect("sqlite://:memory:").await?;
+    let mut conn = pool.acquire().await?;
+    let mut tx = conn.begin().await?;
+    tx.setup_tables().await?;
+    tx.setup_user().await?;
+
+    let select = User::select()
+        .filter(User::NAME.ne(UserName::from("hoge")))
+        .filter(User::ID.gt(1_i64))
+        .order_by(User::NAME.desc())
+        .limit(2);
+    assert_eq!(
+        select.sql(),
+        "SELECT * FROM user WHERE name <> ? AND id > ? ORDER BY name DESC LIMIT 2"
+    );
+    let users = select.fetch_all(&mut *tx).await?;
+    assert_eq!(
+        users.iter().map(|x| x.name.0.as_str()).collect::<Vec<_>>(),
+        ["xxxSHINICHIxxx", "heyheyhey"]
+    );
+
+    let user = User::select()
+        .filter(User::NAME.eq(UserName::from("hoge")))
+        .fetch_one(&mut *tx)
+        .await?;
+    assert_eq!(user.id, 4);
+    assert!(User::select()
+        .filter(User::NAME.like("nobody%".to_owned()))
+        .fetch_optional(&mut *tx)
+        .await?
+        .is_none());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_table_name() -> anyhow::Result<()> {
+    use sqlx_plus::{Dialect, TableName};
+
+    assert!(TableName::new("user; DROP TABLE user").is_err());
+    assert!(TableName::new("1user").is_err());
+    assert!(TableName::new("main.").is_err());
+    assert_eq!(TableName::new("main.user")?.as_str(), "main.user");
+    assert_eq!(
+        TableName::quoted(Dialect::Postgres, "my \"user\"").as_str(),
+        "\"my \"\"user\"\"\""
+    );
+    assert_eq!(
+        TableName::quoted(Dialect::Mssql, "dbo.user]").as_str(),
+        "[dbo].[user]]]"
+    );
+
+    let pool = sqlx::sqlite::SqlitePool::connect("sqlite://:memory:").await?;
+    let mut conn = pool.acquire().await?;
+    let mut tx = conn.begin().await?;
+
+    tx.setup_tables().await?;
+    tx.bulk_insert_with_table_name(
+        &TableName::quoted(Dialect::Sqlite, "user"),
+        &[UserInsert {
+            name: Cow::from("quoted"),
+            password: Cow::from("password"),
+            created_at: created_at(),
+        }],
+    )
+    .await?;
+
+    let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM user WHERE name = 'quoted'")
+        .fetch_one(&mut tx)
+        .await?;
+    assert_eq!(count, 1);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_check_insertable() -> anyhow::Result<()> {
+    let pool = sqlx::sqlite::SqlitePool::connect("sqlite://:memory:").await?;
+    let mut conn = pool.acquire().await?;
+    let mut tx = conn.begin().await?;
+
+    tx.setup_tables().await?;
+    sqlx::query("CREATE TABLE tag (id INTEGER PRIMARY KEY, name TEXT NOT NULL)")
+        .execute(&mut tx)
+        .await?;
+
+    sqlx_plus::assert_insertable!(
+        &mut tx,
+        UserInsert {
+            name: Cow::from("sample"),
+            password: Cow::from("password"),
+            created_at: created_at(),
+        }
+    );
+    TagInsert::__sqlx_plus_check(&mut tx).await?;
+
+    sqlx_plus::ensure_writable::<UserInsert, _>(&mut tx).await?;
+
+    // The table lost the password column.
+    sqlx::query("CREATE TABLE drifted (name TEXT NOT NULL)")
+        .execute(&mut tx)
+        .await?;
+    assert!(
+        sqlx_plus::check_insertable(&mut tx, &DriftedInsert::default())
+            .await
+            .is_err()
+    );
+    let err = sqlx_plus::ensure_writable::<DriftedInsert, _>(&mut tx)
+        .await
+        .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "cannot insert into drifted (name,password)"
+    );
+
+    let sample = UserInsert {
+        name: Cow::from("roundtrip"),
+        password: Cow::from("password"),
+        created_at: created_at(),
+    };
+    let user: User = sqlx_plus::roundtrip_check(&mut tx, &sample, |written, read: &User| {
+        read.name.0 == written.name && read.created_at == written.created_at
+    })
+    .await?;
+    assert_eq!(user.password, "password");
+    let err = sqlx_plus::roundtrip_check(&mut tx, &sample, |_, _: &User| false)
+        .await
+        .unwrap_err();
+    assert!(err
+        .to_string()
+        .starts_with("user does not read back as written"));
+
+    let users: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM user")
+        .fetch_one(&mut tx)
+        .await?;
+    assert_eq!(users, 0);
+
     Ok(())
 }
 
+#[test]
+fn test_server_version_parse() {
+    let version = sqlx_plus::ServerVersion::parse("10.6.12-MariaDB-1:10.6.12+maria~ubu2004");
+    assert_eq!((version.major, version.minor, version.patch), (10, 6, 12));
+    assert!(version.is_mariadb());
+
+    let version = sqlx_plus::ServerVersion::parse("14.5 (Debian 14.5-1.pgdg110+1)");
+    assert_eq!((version.major, version.minor, version.patch), (14, 5, 0));
+    assert!(version.at_least(9, 5, 0));
+}
+
+fn created_at() -> NaiveDateTime {
+    chrono::NaiveDate::from_ymd_opt(2022, 6, 20)
+        .and_then(|date| date.and_hms_opt(1, 2, 3))
+        .unwrap()
+}
+
 type Database = sqlx::Sqlite;
 
 #[async_trait]
@@ -123,7 +3793,7 @@ where
     async fn setup_user(&mut self) -> anyhow::Result<()> {
         use sqlx_plus::QueryBindExt;
 
-        let now = chrono::NaiveDate::from_ymd(2022, 6, 20).and_hms(1, 2, 3);
+        let now = created_at();
 
         self.bulk_insert(&[
             UserInsert {
@@ -144,15 +3814,17 @@ where
         ])
         .await?;
 
-        self.insert(&UserInsert {
-            name: Cow::from("hoge"),
-            password: Cow::from("password4"),
-            created_at: now,
-        })
+        self.insert(
+            &UserInsert::builder()
+                .name("hoge")
+                .password("password4")
+                .created_at(now)
+                .build()?,
+        )
         .await?;
 
         sqlx::query(r#"INSERT INTO user (name, password) VALUES (?, ?)"#)
-            .bind_multi(&["fuga", "password5"])
+            .bind_multi(["fuga", "password5"])
             .execute(self)
             .await?;
 
@@ -182,7 +3854,7 @@ where
         use sqlx_plus::QueryBindExt;
 
         sqlx::query_as("SELECT * FROM user WHERE name = ? AND password = ?")
-            .bind_multi(&[name, password])
+            .bind_multi([name, password])
             .fetch_optional(self)
             .await
             .map_err(From::from)
@@ -199,7 +3871,13 @@ impl From<&str> for UserName {
     }
 }
 
-#[derive(Debug, Clone, Eq, PartialEq, Hash, sqlx::FromRow)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, sqlx::Type)]
+#[sqlx(transparent)]
+struct UserId(i64);
+
+/// The canonical struct of the user table.
+#[derive(Debug, Clone, Eq, PartialEq, Hash, sqlx::FromRow, sqlx_plus::Insertable)]
+#[insertable(sqlx::Sqlite, "user")]
 struct User {
     id: i64,
     name: UserName,
@@ -208,9 +3886,345 @@ struct User {
 }
 
 #[derive(Debug, Clone, Eq, PartialEq, Hash, sqlx_plus::Insertable)]
-#[insertable(sqlx::Sqlite, "user")]
+#[insertable(sqlx::Sqlite, "user", key = UserId, same_table_as = "User", builder)]
 struct UserInsert<'a> {
     name: Cow<'a, str>,
     password: Cow<'a, str>,
     created_at: NaiveDateTime,
 }
+
+#[derive(Debug, Clone, Eq, PartialEq, Hash, sqlx_plus::Insertable)]
+#[insertable(sqlx::Sqlite, "user", same_table_as = "User", as_row)]
+struct NormalizedUserInsert {
+    #[insertable(normalize = "nfc_lowercase")]
+    name: String,
+    #[insertable(normalize = "sqlx_plus::normalize::trim")]
+    password: String,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Hash, sqlx::FromRow, sqlx_plus::Insertable)]
+#[insertable(sqlx::Sqlite, "membership", key = (i64, i64), key_columns("tenant_id", "user_id"))]
+struct Membership {
+    tenant_id: i64,
+    user_id: i64,
+    role: String,
+}
+
+/// External data with empty strings for missing timestamps.
+#[derive(Debug, Clone, Eq, PartialEq, Hash, sqlx_plus::Insertable)]
+#[insertable(sqlx::Sqlite, "user", same_table_as = "User", as_row)]
+struct ImportedUserInsert {
+    name: String,
+    password: String,
+    #[insertable(skip_if = "String::is_empty")]
+    created_at: String,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Hash, sqlx_plus::Insertable)]
+#[insertable(sqlx::Sqlite, "user", column_order = "alphabetical")]
+struct SortedUserInsert {
+    password: String,
+    name: String,
+    created_at: NaiveDateTime,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Hash, sqlx_plus::Insertable)]
+#[insertable(sqlx::Sqlite, "user", same_table_as = "User", builder, as_row)]
+struct UserEntity {
+    #[insertable(key)]
+    id: Option<UserId>,
+    name: String,
+    password: String,
+}
+
+#[derive(Debug, Clone, sqlx_plus::Insertable)]
+#[insertable(sqlx::Sqlite, "tag", unique("name"))]
+struct UniqueTag {
+    name: String,
+}
+
+/// `UserEntity` whose password can only be set by the insert.
+#[derive(Debug, Clone, sqlx_plus::Insertable)]
+#[insertable(sqlx::Sqlite, "user")]
+struct UserProfile {
+    #[insertable(key)]
+    id: Option<UserId>,
+    name: String,
+    #[insertable(immutable)]
+    password: String,
+}
+
+/// A shop with its location stored as hex WKB, SQLite has no spatial functions without
+/// SpatiaLite.
+#[derive(Debug, Clone, sqlx_plus::Insertable)]
+#[insertable(sqlx::Sqlite, "shop")]
+struct Shop {
+    name: String,
+    #[insertable(geo, expr = "nullif(hex(?), '')")]
+    location: Option<geo_types::Point>,
+}
+
+#[derive(Debug, Clone, sqlx_plus::Insertable)]
+#[insertable(sqlx::Sqlite, "shop")]
+struct SpatialShop {
+    name: String,
+    #[insertable(geo)]
+    location: geo_types::Point,
+}
+
+/// A row filled by the defaults of the table alone.
+#[derive(Debug, Clone, Default, sqlx_plus::Insertable)]
+#[insertable(sqlx::Sqlite, "visit")]
+struct Visit {
+    #[insertable(key)]
+    id: Option<i64>,
+}
+
+/// A row of a history table. SQLite has no system versioning, so the periods are written.
+#[derive(Debug, Clone, sqlx_plus::Insertable)]
+#[insertable(sqlx::Sqlite, "price")]
+struct Price {
+    item: String,
+    amount: i64,
+    #[insertable(period)]
+    valid_from: NaiveDateTime,
+    #[insertable(period)]
+    valid_to: NaiveDateTime,
+}
+
+/// A row whose periods the database is told to generate, like MariaDB's `ROW START`.
+#[derive(Debug, Clone, sqlx::FromRow, sqlx_plus::Insertable)]
+#[insertable(sqlx::Sqlite, "price")]
+struct GeneratedPrice {
+    item: String,
+    amount: i64,
+    #[insertable(period = "generated")]
+    valid_from: NaiveDateTime,
+}
+
+#[derive(Debug, Clone, sqlx::FromRow, sqlx_plus::Insertable)]
+#[insertable(db = sqlx::Sqlite, table = "user")]
+struct CopiedUser {
+    name: String,
+    password: String,
+}
+
+#[derive(Debug, Clone, sqlx_plus::Insertable)]
+#[insertable(sqlx::Sqlite, "account")]
+struct Account {
+    name: String,
+    nickname: Option<String>,
+}
+
+#[derive(Debug, Clone, sqlx_plus::Insertable)]
+#[insertable(sqlx::Sqlite, "account", as_row)]
+struct BorrowedAccount<'a> {
+    name: &'a str,
+    nickname: Option<&'a str>,
+}
+
+#[derive(Debug, Clone, sqlx_plus::Insertable)]
+#[insertable(sqlx::Sqlite, "account")]
+struct CowAccount {
+    #[insertable(anonymize = "upper")]
+    name: Cow<'static, str>,
+    #[insertable(anonymize = "upper")]
+    nickname: Option<Cow<'static, str>>,
+}
+
+#[derive(Debug, Clone, sqlx_plus::Insertable)]
+#[insertable(sqlx::Sqlite, "ticket")]
+struct Ticket {
+    name: String,
+    #[insertable(default_if_none)]
+    status: Option<String>,
+}
+
+/// Every column defaulted when `None`.
+#[derive(Debug, Clone, sqlx_plus::Insertable)]
+#[insertable(sqlx::Sqlite, "draft")]
+struct Draft {
+    #[insertable(default_if_none)]
+    status: Option<String>,
+}
+
+#[derive(Debug, Clone, sqlx_plus::Insertable)]
+#[insertable(sqlx::Sqlite, "token")]
+struct Token {
+    #[insertable(auto_uuid)]
+    id: Option<sqlx::types::Uuid>,
+    name: String,
+    #[insertable(auto_now)]
+    created_at: Option<chrono::NaiveDateTime>,
+}
+
+#[derive(Debug, Clone, sqlx_plus::Insertable)]
+#[insertable(sqlx::Sqlite, "user")]
+struct StampedUser {
+    name: String,
+    password: String,
+    #[insertable(auto_now)]
+    created_at: Option<chrono::NaiveDateTime>,
+}
+
+#[derive(Debug, Clone, sqlx::FromRow, sqlx_plus::Insertable)]
+#[insertable(sqlx::Sqlite, "user")]
+struct AnonymizedUser {
+    #[insertable(anonymize = "name")]
+    name: String,
+    #[insertable(anonymize = "password")]
+    password: String,
+}
+
+#[derive(Debug, Clone, sqlx_plus::Insertable)]
+#[insertable(sqlx::Sqlite, "post")]
+struct PostInsert {
+    user_id: i64,
+    title: String,
+}
+
+#[derive(Debug, Clone, sqlx_plus::Insertable)]
+#[insertable(sqlx::Sqlite, "event", extra = "attrs")]
+struct EventInsert {
+    name: String,
+    attrs: HashMap<String, serde_json::Value>,
+}
+
+#[derive(Debug, Clone, Default, Eq, PartialEq, Hash, sqlx_plus::Insertable)]
+#[insertable(sqlx::Sqlite, "drifted")]
+struct DriftedInsert {
+    name: String,
+    password: String,
+}
+
+#[derive(Debug, Clone, Default, Eq, PartialEq, Hash, sqlx_plus::Insertable)]
+#[insertable(sqlx::Sqlite, "user", on_unique("user.name", "NameTaken"))]
+struct MappedUserInsert {
+    name: String,
+    password: String,
+}
+
+#[derive(
+    Debug,
+    Clone,
+    Default,
+    Eq,
+    PartialEq,
+    Hash,
+    serde::Serialize,
+    serde::Deserialize,
+    sqlx_plus::Insertable,
+)]
+#[insertable(sqlx::Sqlite, "tag", smoke_test)]
+struct TagInsert {
+    name: String,
+}
+
+impl sqlx_plus::SizeEstimate for TagInsert {
+    fn estimated_size(&self) -> usize {
+        self.name.estimated_size()
+    }
+}
+
+#[tokio::test]
+async fn test_sanitize() -> anyhow::Result<()> {
+    use sqlx_plus::{
+        sanitize::{self, LIKE_ESCAPE},
+        Dialect,
+    };
+
+    const PAYLOADS: &[&str] = &[
+        "user",
+        "user\"; DROP TABLE user; --",
+        "a\"\"b",
+        "`]'\"",
+        "x' OR '1'='1",
+        "%_\\[",
+        "",
+    ];
+    for dialect in [
+        Dialect::Sqlite,
+        Dialect::MySql,
+        Dialect::Postgres,
+        Dialect::Mssql,
+    ] {
+        for payload in PAYLOADS {
+            sanitize::check_identifier(dialect, payload);
+            sanitize::check_like(dialect, payload);
+        }
+        for (rows, columns) in [(0, 3), (1, 1), (3, 2), (70000, 1), (1, 70000)] {
+            sanitize::check_placeholders(dialect, rows, columns);
+        }
+    }
+
+    let pool = sqlx::sqlite::SqlitePool::connect("sqlite://:memory:").await?;
+    let mut conn = pool.acquire().await?;
+
+    // Every payload is a single column name, not a way out of the statement.
+    let columns: Vec<_> = PAYLOADS[..6]
+        .iter()
+        .map(|x| format!("{} TEXT", sanitize::quote_identifier(Dialect::Sqlite, x)))
+        .collect();
+    sqlx::query(&format!("CREATE TABLE t ({})", columns.join(",")))
+        .execute(&mut *conn)
+        .await?;
+    let names: Vec<String> = sqlx::query_scalar("SELECT name FROM pragma_table_info('t')")
+        .fetch_all(&mut *conn)
+        .await?;
+    assert_eq!(names, &PAYLOADS[..6]);
+
+    let column = sanitize::quote_identifier(Dialect::Sqlite, PAYLOADS[0]);
+    for value in ["100%", "100x", "a_c", "abc", "\\", "x' OR '1'='1"] {
+        sqlx::query(&format!("INSERT INTO t ({}) VALUES (?)", column))
+            .bind(value)
+            .execute(&mut *conn)
+            .await?;
+    }
+    for (pattern, expected) in [
+        ("100%", vec!["100%"]),
+        ("a_c", vec!["a_c"]),
+        ("\\", vec!["\\"]),
+    ] {
+        let found: Vec<String> = sqlx::query_scalar(&format!(
+            "SELECT {0} FROM t WHERE {0} LIKE ?{1} ORDER BY 1",
+            column, LIKE_ESCAPE
+        ))
+        .bind(sanitize::escape_like(Dialect::Sqlite, pattern))
+        .fetch_all(&mut *conn)
+        .await?;
+        assert_eq!(found, expected);
+    }
+
+    Ok(())
+}
+
+/// A domain type which keeps its fields to itself.
+mod account {
+    use super::UserId;
+
+    #[derive(Debug, Clone, Default, PartialEq, Eq, sqlx_plus::Insertable)]
+    #[insertable(
+        sqlx::Sqlite,
+        "user",
+        same_table_as = "super::User",
+        on_unique("user.name", "NameTaken"),
+        builder
+    )]
+    #[non_exhaustive]
+    pub(crate) struct Account {
+        #[insertable(key)]
+        id: Option<UserId>,
+        pub(crate) name: String,
+        password: String,
+    }
+
+    impl Account {
+        pub(crate) fn new(name: &str, password: &str) -> Self {
+            Account {
+                id: None,
+                name: name.to_owned(),
+                password: password.chars().rev().collect(),
+            }
+        }
+    }
+}
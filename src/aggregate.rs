@@ -0,0 +1,40 @@
+use sqlx::{Executor, FromRow, IntoArguments};
+
+use crate::{compat::ArgumentsOf, Backend, Config, Insertable};
+
+/// `function` of `column` over the table of `T`, `None` on an empty table. Behind
+/// [`Inserter::max_of`](crate::Inserter::max_of) and its siblings.
+pub(crate) async fn aggregate<T, V, E, DB>(
+    executor: &mut E,
+    function: &str,
+    column: &str,
+) -> anyhow::Result<Option<V>>
+where
+    DB: Backend,
+    T: Insertable<Database = DB>,
+    V: Send + Sync + Unpin,
+    for<'e> &'e mut E: Executor<'e, Database = DB>,
+    for<'q> ArgumentsOf<'q, DB>: IntoArguments<'q, DB>,
+    (Option<V>,): for<'r> FromRow<'r, DB::Row>,
+{
+    // The key column is usually not an insert column, any plain identifier goes.
+    anyhow::ensure!(
+        T::insert_columns().contains(&column)
+            || (column.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+                && column
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '_')),
+        "invalid column name {:?}",
+        column
+    );
+
+    let config = Config::global();
+    let sql = format!("SELECT {}({}) FROM {}", function, column, T::table_name());
+    let statement = config.before(sql, T::table_name(), 0).await?;
+    let value = statement
+        .run(sqlx::query_scalar::<_, Option<V>>(statement.sql()).fetch_one(executor))
+        .await;
+    statement.after(&value, |_| 1).await;
+
+    Ok(value?)
+}
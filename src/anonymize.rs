@@ -0,0 +1,155 @@
+//! Masking of personal data when copying production tables into staging, see [`Anonymizer`].
+//!
+//! Fields marked `#[insertable(anonymize = "...")]` name the faker replacing them. The rows go
+//! through the same [`copy_table`](crate::copy_table) as a plain copy, only with
+//! [`CopyOptions::anonymizer`](crate::CopyOptions::anonymizer) set:
+//!
+//! ```ignore
+//! #[derive(sqlx::FromRow, sqlx_plus::Insertable)]
+//! #[insertable(sqlx::Postgres, "users")]
+//! struct User {
+//!     id: i64,
+//!     #[insertable(anonymize = "email")]
+//!     email: String,
+//!     #[insertable(anonymize = "name")]
+//!     display_name: Option<String>,
+//!     #[insertable(anonymize = "iban")]
+//!     iban: String,
+//! }
+//!
+//! let options = CopyOptions {
+//!     anonymizer: Some(Anonymizer::new().salt(salt).faker("iban", fake_iban)),
+//!     ..Default::default()
+//! };
+//! copy_table::<User, _, _, _>(&mut production, &mut staging, &options, |_| {}).await?;
+//! ```
+
+use std::{borrow::Cow, collections::BTreeMap, fmt, sync::Arc};
+
+use crate::Insertable;
+
+type Faker = dyn Fn(&str) -> String + Send + Sync;
+
+/// The built-in fakers, see [`Anonymizer`].
+const BUILTIN: &[&str] = &["email", "name", "phone", "redact"];
+
+/// The fakers applied to `#[insertable(anonymize = "...")]` fields, by name.
+///
+/// The built-in ones derive the replacement from a hash of the salted value, so a value maps to
+/// the same fake everywhere it occurs and unique columns stay unique:
+///
+/// - `email`: `user-<hash>@example.invalid`
+/// - `name`: `Person <hash>`
+/// - `phone`: `+1555` and seven digits
+/// - `redact`: `[redacted]`
+///
+/// Without a secret [`salt`](Self::salt) the hashes of guessable values like email addresses can
+/// be reversed by hashing candidates.
+#[derive(Clone, Default)]
+pub struct Anonymizer {
+    salt: String,
+    fakers: BTreeMap<String, Arc<Faker>>,
+}
+
+impl Anonymizer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Mix `salt` into the hashes of the built-in fakers.
+    pub fn salt(mut self, salt: impl Into<String>) -> Self {
+        self.salt = salt.into();
+        self
+    }
+
+    /// Register `faker` under `name`, replacing a built-in one of the same name.
+    pub fn faker(
+        mut self,
+        name: impl Into<String>,
+        faker: impl Fn(&str) -> String + Send + Sync + 'static,
+    ) -> Self {
+        self.fakers.insert(name.into(), Arc::new(faker));
+        self
+    }
+
+    /// Whether a faker is registered under `name`.
+    pub fn has_faker(&self, name: &str) -> bool {
+        self.fakers.contains_key(name) || BUILTIN.contains(&name)
+    }
+
+    /// The replacement of `value` by the faker `name`.
+    pub fn fake(&self, name: &str, value: &str) -> anyhow::Result<String> {
+        if let Some(faker) = self.fakers.get(name) {
+            return Ok(faker(value));
+        }
+        let hash = || fnv1a(self.salt.as_bytes().iter().chain(value.as_bytes()));
+        Ok(match name {
+            "email" => format!("user-{:016x}@example.invalid", hash()),
+            "name" => format!("Person {:08x}", hash() as u32),
+            "phone" => format!("+1555{:07}", hash() % 10_000_000),
+            "redact" => "[redacted]".to_owned(),
+            _ => anyhow::bail!("no faker named {:?} is registered", name),
+        })
+    }
+
+    /// Fail unless every faker `T` names is registered, before any row is copied.
+    pub(crate) fn check<T: Insertable>(&self) -> anyhow::Result<()> {
+        for (column, faker) in T::anonymized_columns() {
+            anyhow::ensure!(
+                self.has_faker(faker),
+                "no faker named {:?} is registered, {}.{} needs it",
+                faker,
+                T::table_name(),
+                column
+            );
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Debug for Anonymizer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // The salt is a secret.
+        f.debug_struct("Anonymizer")
+            .field("fakers", &self.fakers.keys().collect::<Vec<_>>())
+            .finish_non_exhaustive()
+    }
+}
+
+/// 64-bit FNV-1a, stable across Rust versions and platforms unlike `DefaultHasher`, so the fakes
+/// of separate runs match.
+fn fnv1a<'a>(bytes: impl Iterator<Item = &'a u8>) -> u64 {
+    bytes.fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ u64::from(*byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+/// A field type `#[insertable(anonymize = "...")]` can replace.
+pub trait Fake {
+    fn fake(&mut self, anonymizer: &Anonymizer, faker: &str) -> anyhow::Result<()>;
+}
+
+impl Fake for String {
+    fn fake(&mut self, anonymizer: &Anonymizer, faker: &str) -> anyhow::Result<()> {
+        *self = anonymizer.fake(faker, self)?;
+        Ok(())
+    }
+}
+
+/// A borrowed value is replaced by an owned one.
+impl Fake for Cow<'_, str> {
+    fn fake(&mut self, anonymizer: &Anonymizer, faker: &str) -> anyhow::Result<()> {
+        *self = Cow::Owned(anonymizer.fake(faker, self)?);
+        Ok(())
+    }
+}
+
+/// `NULL` stays `NULL`.
+impl<T: Fake> Fake for Option<T> {
+    fn fake(&mut self, anonymizer: &Anonymizer, faker: &str) -> anyhow::Result<()> {
+        match self {
+            Some(value) => value.fake(anonymizer, faker),
+            None => Ok(()),
+        }
+    }
+}
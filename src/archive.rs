@@ -0,0 +1,172 @@
+use std::marker::PhantomData;
+
+use sqlx::{Acquire, Executor, FromRow, IntoArguments};
+
+use crate::{
+    compat::ArgumentsOf, Backend, Config, Dialect, Insertable, PlaceHolders, QueryBindExt,
+    TableName,
+};
+
+/// Move the rows of `T`'s table matching `filter` to an archive table.
+///
+/// ```ignore
+/// let moved = sqlx_plus::archive_rows::<Order>("created_at < now() - interval '1 year'")
+///     .archive_table(TableName::new("orders_archive")?)
+///     .execute(&pool)
+///     .await?;
+/// ```
+///
+/// `filter` is inserted into the SQL as is, so it must not contain untrusted input.
+pub fn archive_rows<T: Insertable>(filter: impl Into<String>) -> ArchiveRows<T> {
+    ArchiveRows {
+        filter: filter.into(),
+        archive_table: None,
+        key_column: None,
+        chunk_size: 1000,
+        _marker: PhantomData,
+    }
+}
+
+/// A builder for [`archive_rows`].
+///
+/// The rows are copied with `INSERT INTO {archive_table} SELECT ...` and then deleted, one chunk
+/// of keys at a time, all in a single transaction. The archive table needs the key column and
+/// the insert columns of `T`.
+#[derive(Debug, Clone)]
+pub struct ArchiveRows<T> {
+    filter: String,
+    archive_table: Option<TableName>,
+    key_column: Option<String>,
+    chunk_size: usize,
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<T> ArchiveRows<T>
+where
+    T: Insertable,
+    T::Database: Backend,
+{
+    /// Defaults to `{table_name}_archive`.
+    pub fn archive_table(mut self, archive_table: TableName) -> Self {
+        self.archive_table = Some(archive_table);
+        self
+    }
+
+    /// The integer key the rows are chunked by. Defaults to `id`.
+    pub fn key_column(mut self, key_column: impl Into<String>) -> Self {
+        self.key_column = Some(key_column.into());
+        self
+    }
+
+    pub fn chunk_size(mut self, chunk_size: usize) -> Self {
+        self.chunk_size = chunk_size;
+        self
+    }
+
+    /// Archive the rows and return how many were moved.
+    pub async fn execute<'c, A>(self, acquire: A) -> anyhow::Result<u64>
+    where
+        A: Acquire<'c, Database = T::Database>,
+        for<'e> &'e mut <T::Database as sqlx::Database>::Connection:
+            Executor<'e, Database = T::Database>,
+        for<'q> ArgumentsOf<'q, T::Database>: IntoArguments<'q, T::Database>,
+        (i64,): for<'r> FromRow<'r, <T::Database as sqlx::Database>::Row>,
+        i64: for<'q> sqlx::Encode<'q, T::Database> + sqlx::Type<T::Database>,
+    {
+        let table_name = T::table_name();
+        let key_column = self.key_column.as_deref().unwrap_or("id");
+        let archive_table = match self.archive_table {
+            Some(archive_table) => archive_table,
+            None => TableName::trusted(format!("{}_archive", table_name)),
+        };
+        let chunk_size = self.chunk_size.clamp(1, T::Database::MAX_BIND_PARAMS);
+
+        let mut columns = key_column.to_owned();
+        for column in T::insert_columns() {
+            if *column != key_column {
+                columns.push(',');
+                columns.push_str(column);
+            }
+        }
+
+        let select_keys = match T::Database::DIALECT {
+            Dialect::Mssql => format!(
+                "SELECT TOP {} {key} FROM {} WHERE {} ORDER BY {key}",
+                chunk_size,
+                table_name,
+                self.filter,
+                key = key_column,
+            ),
+            _ => format!(
+                "SELECT {key} FROM {} WHERE {} ORDER BY {key} LIMIT {}",
+                table_name,
+                self.filter,
+                chunk_size,
+                key = key_column,
+            ),
+        };
+
+        let config = Config::global();
+        let mut tx = acquire.begin().await?;
+        let mut moved = 0;
+
+        loop {
+            let statement = config.before(select_keys.clone(), table_name, 0).await?;
+            let keys: Result<Vec<(i64,)>, _> = statement
+                .run(sqlx::query_as(statement.sql()).fetch_all(&mut *tx))
+                .await;
+            statement.after(&keys, |keys| keys.len() as u64).await;
+            let keys = keys?;
+            if keys.is_empty() {
+                break;
+            }
+
+            let mut placeholders = String::new();
+            T::Database::write_placeholders(&mut placeholders, keys.len(), 1);
+
+            let sql = format!(
+                "INSERT INTO {} ({columns}) SELECT {columns} FROM {} WHERE {} IN ({})",
+                archive_table,
+                table_name,
+                key_column,
+                placeholders,
+                columns = columns,
+            );
+            let statement = config
+                .before(sql, archive_table.as_str(), keys.len())
+                .await?;
+            let result = statement
+                .run(
+                    sqlx::query(statement.sql())
+                        .bind_multi(keys.iter().map(|(key,)| *key))
+                        .execute(&mut *tx),
+                )
+                .await;
+            statement.after(&result, T::Database::rows_affected).await;
+            result?;
+
+            let sql = format!(
+                "DELETE FROM {} WHERE {} IN ({})",
+                table_name, key_column, placeholders
+            );
+            let statement = config.before(sql, table_name, keys.len()).await?;
+            let result = statement
+                .run(
+                    sqlx::query(statement.sql())
+                        .bind_multi(keys.iter().map(|(key,)| *key))
+                        .execute(&mut *tx),
+                )
+                .await;
+            statement.after(&result, T::Database::rows_affected).await;
+            moved += T::Database::rows_affected(&result?);
+
+            if keys.len() < chunk_size {
+                break;
+            }
+        }
+
+        tx.commit().await?;
+
+        Ok(moved)
+    }
+}
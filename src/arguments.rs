@@ -0,0 +1,53 @@
+use sqlx::Arguments;
+
+use crate::{Insertable, QueryBindExt};
+
+/// Binding like [`QueryBindExt`], but into an arguments object, for `sqlx::query_with`,
+/// `sqlx::query_as_with` and statements prepared outside of sqlx-plus:
+///
+/// ```ignore
+/// let mut args = SqliteArguments::default();
+/// args.add_multi_fields(&users);
+/// sqlx::query_with(&sql, args).execute(&mut conn).await?;
+/// ```
+pub trait ArgumentsExt<'q>: Arguments<'q> {
+    /// Add one value per column of `value`, in the order of `insert_columns()`.
+    fn add_fields<T: Insertable<Database = Self::Database>>(&mut self, value: &'q T) {
+        bind_into(self, |binder| binder.bind_fields(value));
+    }
+
+    fn add_multi_fields<T: Insertable<Database = Self::Database> + 'q>(
+        &mut self,
+        values: impl IntoIterator<Item = &'q T>,
+    ) {
+        bind_into(self, |binder| binder.bind_multi_fields(values));
+    }
+
+    fn add_multi<T>(&mut self, values: impl IntoIterator<Item = T>)
+    where
+        T: 'q + Send + sqlx::Encode<'q, Self::Database> + sqlx::Type<Self::Database>,
+    {
+        for value in values {
+            self.add(value);
+        }
+    }
+}
+
+impl<'q, A: Arguments<'q>> ArgumentsExt<'q> for A {}
+
+/// Arguments as a [`QueryBindExt`], so the generated binding code fills them like a query.
+struct Binder<A>(A);
+
+fn bind_into<A: Default>(args: &mut A, bind: impl FnOnce(Binder<A>) -> Binder<A>) {
+    *args = bind(Binder(std::mem::take(args))).0;
+}
+
+impl<'q, A: Arguments<'q>> QueryBindExt<'q, A::Database> for Binder<A> {
+    fn bind<T>(mut self, value: T) -> Self
+    where
+        T: 'q + Send + sqlx::Encode<'q, A::Database> + sqlx::Type<A::Database>,
+    {
+        self.0.add(value);
+        self
+    }
+}
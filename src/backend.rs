@@ -0,0 +1,110 @@
+use std::ops::Range;
+
+use crate::{compat::QueryOf, Dialect, PlaceHolders};
+
+/// Database specific knowledge used by sqlx-plus.
+pub trait Backend: sqlx::Database + PlaceHolders {
+    const DIALECT: Dialect;
+
+    /// The maximum number of bind parameters a single statement can have on a current server.
+    const MAX_BIND_PARAMS: usize;
+
+    /// The maximum length of a statement in bytes, for backends which limit it.
+    const MAX_SQL_LENGTH: Option<usize> = None;
+
+    /// A query which returns the server version as a single text column.
+    const SERVER_VERSION_QUERY: &'static str;
+
+    fn rows_affected(result: &Self::QueryResult) -> u64;
+
+    /// `Query::persistent` for backends which have a prepared statement cache, a no-op otherwise.
+    #[allow(unused_variables)]
+    fn set_persistent(query: QueryOf<'_, Self>, persistent: bool) -> QueryOf<'_, Self> {
+        query
+    }
+
+    /// The auto-generated ids of a multi-row insert, for backends which report them in the result.
+    /// `None` when the insert generated no ids although it inserted rows.
+    #[allow(unused_variables)]
+    fn generated_ids(result: &Self::QueryResult) -> Option<Range<i64>> {
+        None
+    }
+}
+
+#[cfg(feature = "sqlite")]
+impl Backend for sqlx::Sqlite {
+    const DIALECT: Dialect = Dialect::Sqlite;
+    const MAX_BIND_PARAMS: usize = 32766;
+    /// `SQLITE_MAX_SQL_LENGTH` of the default build.
+    const MAX_SQL_LENGTH: Option<usize> = Some(1_000_000_000);
+    const SERVER_VERSION_QUERY: &'static str = "SELECT sqlite_version()";
+
+    fn rows_affected(result: &Self::QueryResult) -> u64 {
+        result.rows_affected()
+    }
+
+    fn set_persistent(query: QueryOf<'_, Self>, persistent: bool) -> QueryOf<'_, Self> {
+        query.persistent(persistent)
+    }
+
+    /// `last_insert_rowid()` is the id of the last row, rows before it got the preceding rowids.
+    fn generated_ids(result: &Self::QueryResult) -> Option<Range<i64>> {
+        let last = result.last_insert_rowid();
+        Some(last + 1 - result.rows_affected() as i64..last + 1)
+    }
+}
+
+// Bulk inserts use multi-row VALUES, sqlx 0.6 can't drive `LOAD DATA LOCAL INFILE`. See the
+// `load_data` module for running it through another driver.
+#[cfg(feature = "mysql")]
+impl Backend for sqlx::MySql {
+    const DIALECT: Dialect = Dialect::MySql;
+    const MAX_BIND_PARAMS: usize = 65535;
+    const SERVER_VERSION_QUERY: &'static str = "SELECT VERSION()";
+
+    fn rows_affected(result: &Self::QueryResult) -> u64 {
+        result.rows_affected()
+    }
+
+    fn set_persistent(query: QueryOf<'_, Self>, persistent: bool) -> QueryOf<'_, Self> {
+        query.persistent(persistent)
+    }
+
+    /// `LAST_INSERT_ID()` is the id of the first row, `ROW_COUNT()` tells how many followed. It
+    /// stays `0` when the table has no `AUTO_INCREMENT` column.
+    fn generated_ids(result: &Self::QueryResult) -> Option<Range<i64>> {
+        let first = result.last_insert_id() as i64;
+        let rows = result.rows_affected() as i64;
+        if first == 0 && rows > 0 {
+            return None;
+        }
+        Some(first..first + rows)
+    }
+}
+
+#[cfg(feature = "postgres")]
+impl Backend for sqlx::Postgres {
+    const DIALECT: Dialect = Dialect::Postgres;
+    const MAX_BIND_PARAMS: usize = 65535;
+    const SERVER_VERSION_QUERY: &'static str = "SELECT current_setting('server_version')";
+
+    fn rows_affected(result: &Self::QueryResult) -> u64 {
+        result.rows_affected()
+    }
+
+    fn set_persistent(query: QueryOf<'_, Self>, persistent: bool) -> QueryOf<'_, Self> {
+        query.persistent(persistent)
+    }
+}
+
+#[cfg(feature = "mssql")]
+impl Backend for sqlx::Mssql {
+    const DIALECT: Dialect = Dialect::Mssql;
+    const MAX_BIND_PARAMS: usize = 2100;
+    const SERVER_VERSION_QUERY: &'static str =
+        "SELECT CAST(SERVERPROPERTY('ProductVersion') AS NVARCHAR(128))";
+
+    fn rows_affected(result: &Self::QueryResult) -> u64 {
+        result.rows_affected()
+    }
+}
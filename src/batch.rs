@@ -0,0 +1,239 @@
+use std::time::{Duration, Instant};
+
+use anyhow::Context as _;
+use async_trait::async_trait;
+use sqlx::{Acquire, Executor, IntoArguments};
+
+use crate::{
+    compat::{ArgumentsOf, QueryOf},
+    Backend, BulkInsertOptions, Config, Entity, Insertable, Inserter,
+};
+
+/// Writes of any kind queued up and executed one after the other in one transaction, so a
+/// request handler awaits once for all of them:
+///
+/// ```ignore
+/// let report = Batch::new()
+///     .insert(NewOrder { ... })
+///     .bulk_insert(order_lines)
+///     .save(customer)
+///     .sql("UPDATE stock SET count = count - ? WHERE item_id = ?", move |q| {
+///         q.bind(quantity).bind(item_id)
+///     })
+///     .execute(&pool)
+///     .await?;
+/// ```
+///
+/// The first failing operation rolls the transaction back and is returned as the error.
+pub struct Batch<DB> {
+    operations: Vec<Box<dyn Operation<DB>>>,
+}
+
+impl<DB> Batch<DB>
+where
+    DB: Backend,
+    for<'c> &'c mut DB::Connection: Inserter<DB> + Executor<'c, Database = DB>,
+    for<'q> ArgumentsOf<'q, DB>: IntoArguments<'q, DB>,
+{
+    pub fn new() -> Self {
+        Batch {
+            operations: Vec::new(),
+        }
+    }
+
+    pub fn insert<T>(mut self, value: T) -> Self
+    where
+        T: Insertable<Database = DB> + Send + Sync + 'static,
+    {
+        self.operations.push(Box::new(Insert(value)));
+        self
+    }
+
+    pub fn bulk_insert<T>(mut self, values: Vec<T>) -> Self
+    where
+        T: Insertable<Database = DB> + Send + Sync + 'static,
+    {
+        self.operations.push(Box::new(values));
+        self
+    }
+
+    /// Insert the entity if it has no key yet, otherwise update its row.
+    pub fn save<T>(mut self, value: T) -> Self
+    where
+        T: Entity<Database = DB> + Send + Sync + 'static,
+    {
+        self.operations.push(Box::new(Save(value)));
+        self
+    }
+
+    /// A statement of its own, with its values bound by `bind`.
+    pub fn sql<F>(mut self, sql: impl Into<String>, bind: F) -> Self
+    where
+        F: for<'q> FnOnce(QueryOf<'q, DB>) -> QueryOf<'q, DB> + Send + 'static,
+    {
+        self.operations.push(Box::new(Sql {
+            sql: sql.into(),
+            bind: Some(Box::new(bind)),
+        }));
+        self
+    }
+
+    pub fn len(&self) -> usize {
+        self.operations.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.operations.is_empty()
+    }
+
+    /// Execute the operations in order in one transaction.
+    pub async fn execute<'c, A>(self, acquire: A) -> anyhow::Result<BatchReport>
+    where
+        A: Acquire<'c, Database = DB>,
+    {
+        let started = Instant::now();
+        let mut tx = acquire.begin().await?;
+        let mut rows_affected = Vec::with_capacity(self.operations.len());
+
+        for (index, mut operation) in self.operations.into_iter().enumerate() {
+            let rows = operation.execute(&mut tx).await.with_context(|| {
+                format!(
+                    "batch operation {} ({}) failed",
+                    index,
+                    operation.describe()
+                )
+            })?;
+            rows_affected.push(rows);
+        }
+
+        tx.commit().await?;
+
+        Ok(BatchReport {
+            rows_affected,
+            duration: started.elapsed(),
+        })
+    }
+}
+
+impl<DB> Default for Batch<DB>
+where
+    DB: Backend,
+    for<'c> &'c mut DB::Connection: Inserter<DB> + Executor<'c, Database = DB>,
+    for<'q> ArgumentsOf<'q, DB>: IntoArguments<'q, DB>,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The outcome of a [`Batch`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BatchReport {
+    /// The rows affected by each operation, in the order they were queued.
+    pub rows_affected: Vec<u64>,
+    pub duration: Duration,
+}
+
+impl BatchReport {
+    pub fn total_rows_affected(&self) -> u64 {
+        self.rows_affected.iter().sum()
+    }
+}
+
+#[async_trait]
+trait Operation<DB: Backend>: Send {
+    /// Run the operation once, it may give up its values.
+    async fn execute(&mut self, conn: &mut DB::Connection) -> anyhow::Result<u64>;
+
+    fn describe(&self) -> String;
+}
+
+struct Insert<T>(T);
+
+#[async_trait]
+impl<DB, T> Operation<DB> for Insert<T>
+where
+    DB: Backend,
+    T: Insertable<Database = DB> + Send + Sync,
+    for<'c> &'c mut DB::Connection: Inserter<DB>,
+{
+    async fn execute(&mut self, conn: &mut DB::Connection) -> anyhow::Result<u64> {
+        Ok(DB::rows_affected(&conn.insert(&self.0).await?))
+    }
+
+    fn describe(&self) -> String {
+        format!("insert into {}", T::table_name())
+    }
+}
+
+#[async_trait]
+impl<DB, T> Operation<DB> for Vec<T>
+where
+    DB: Backend,
+    T: Insertable<Database = DB> + Send + Sync,
+    for<'c> &'c mut DB::Connection: Inserter<DB>,
+{
+    async fn execute(&mut self, conn: &mut DB::Connection) -> anyhow::Result<u64> {
+        let report = conn
+            .bulk_insert_report_with_options(self, &BulkInsertOptions::default())
+            .await?;
+        Ok(report.rows_affected)
+    }
+
+    fn describe(&self) -> String {
+        format!(
+            "bulk insert of {} rows into {}",
+            self.len(),
+            T::table_name()
+        )
+    }
+}
+
+struct Save<T>(T);
+
+#[async_trait]
+impl<DB, T> Operation<DB> for Save<T>
+where
+    DB: Backend,
+    T: Entity<Database = DB> + Send + Sync,
+    for<'c> &'c mut DB::Connection: Inserter<DB>,
+{
+    async fn execute(&mut self, conn: &mut DB::Connection) -> anyhow::Result<u64> {
+        Ok(DB::rows_affected(&conn.save(&self.0).await?))
+    }
+
+    fn describe(&self) -> String {
+        format!("save into {}", T::table_name())
+    }
+}
+
+type Bind<DB> = Box<dyn for<'q> FnOnce(QueryOf<'q, DB>) -> QueryOf<'q, DB> + Send>;
+
+struct Sql<DB: Backend> {
+    sql: String,
+    bind: Option<Bind<DB>>,
+}
+
+#[async_trait]
+impl<DB> Operation<DB> for Sql<DB>
+where
+    DB: Backend,
+    for<'c> &'c mut DB::Connection: Executor<'c, Database = DB>,
+    for<'q> ArgumentsOf<'q, DB>: IntoArguments<'q, DB>,
+{
+    async fn execute(&mut self, conn: &mut DB::Connection) -> anyhow::Result<u64> {
+        let bind = self.bind.take().expect("a batch operation runs only once");
+        let config = Config::global();
+        let statement = config.before(self.sql.clone(), "", 0).await?;
+        let result = statement
+            .run(bind(sqlx::query(statement.sql())).execute(&mut *conn))
+            .await;
+        statement.after(&result, DB::rows_affected).await;
+
+        Ok(DB::rows_affected(&result?))
+    }
+
+    fn describe(&self) -> String {
+        format!("`{}`", self.sql)
+    }
+}
@@ -0,0 +1,76 @@
+//! Synchronous wrappers for scripts and CLIs which don't want to write async plumbing.
+//!
+//! ```ignore
+//! let pool = sqlx_plus::blocking::connect::<sqlx::Postgres>("postgres://...")?;
+//! sqlx_plus::blocking::bulk_insert(&pool, &users)?;
+//! let rows: Vec<User> = sqlx_plus::blocking::block_on(sqlx::query_as("SELECT ...").fetch_all(&pool))?;
+//! ```
+//!
+//! With a tokio runtime feature everything runs on a lazily created current-thread runtime,
+//! so create the pool through [`connect`] as sqlx pools are tied to the runtime they were
+//! created on. These functions must not be called from async code.
+
+use std::future::Future;
+
+use crate::{Backend, BulkInsertOptions, BulkInsertReport, Insertable, Inserter};
+
+/// Run `future` to completion on the runtime sqlx is built with.
+#[cfg(any(feature = "runtime-tokio-rustls", feature = "runtime-tokio-native-tls"))]
+pub fn block_on<F: Future>(future: F) -> F::Output {
+    static RUNTIME: std::sync::OnceLock<tokio::runtime::Runtime> = std::sync::OnceLock::new();
+
+    RUNTIME
+        .get_or_init(|| {
+            tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()
+                .expect("failed to build the runtime for sqlx_plus::blocking")
+        })
+        .block_on(future)
+}
+
+/// Run `future` to completion on the runtime sqlx is built with.
+#[cfg(not(any(feature = "runtime-tokio-rustls", feature = "runtime-tokio-native-tls")))]
+pub fn block_on<F: Future>(future: F) -> F::Output {
+    async_std::task::block_on(future)
+}
+
+pub fn connect<DB: sqlx::Database>(url: &str) -> anyhow::Result<sqlx::Pool<DB>> {
+    Ok(block_on(sqlx::Pool::connect(url))?)
+}
+
+pub fn insert<'p, DB, T>(pool: &'p sqlx::Pool<DB>, value: &T) -> anyhow::Result<DB::QueryResult>
+where
+    DB: Backend,
+    T: Insertable<Database = DB> + Sync,
+    &'p sqlx::Pool<DB>: Inserter<DB>,
+{
+    block_on(pool.insert(value))
+}
+
+pub fn bulk_insert<'p, DB, I>(
+    pool: &'p sqlx::Pool<DB>,
+    values: I,
+) -> anyhow::Result<Vec<DB::QueryResult>>
+where
+    DB: Backend,
+    I: IntoIterator + Send,
+    I::IntoIter: Send,
+    I::Item: Insertable<Database = DB> + Send + Sync,
+    &'p sqlx::Pool<DB>: Inserter<DB>,
+{
+    block_on(pool.bulk_insert(values))
+}
+
+pub fn bulk_insert_with_options<'p, DB, T>(
+    pool: &'p sqlx::Pool<DB>,
+    values: &[T],
+    options: &BulkInsertOptions,
+) -> anyhow::Result<BulkInsertReport>
+where
+    DB: Backend,
+    T: Insertable<Database = DB> + Sync,
+    &'p sqlx::Pool<DB>: Inserter<DB>,
+{
+    block_on(pool.bulk_insert_report_with_options(values, options))
+}
@@ -0,0 +1,153 @@
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
+
+/// A memory limit shared by everything that buffers values before inserting them.
+///
+/// Buffers acquire the estimated size of each value and release it once the values are
+/// inserted. Clones share the same budget, set one on the [`Config`](crate::Config) to cap the
+/// whole process.
+#[derive(Debug, Clone)]
+pub struct MemoryBudget {
+    inner: Arc<Inner>,
+}
+
+#[derive(Debug)]
+struct Inner {
+    limit: usize,
+    used: AtomicUsize,
+}
+
+impl MemoryBudget {
+    pub fn new(limit_bytes: usize) -> Self {
+        MemoryBudget {
+            inner: Arc::new(Inner {
+                limit: limit_bytes,
+                used: AtomicUsize::new(0),
+            }),
+        }
+    }
+
+    pub fn limit(&self) -> usize {
+        self.inner.limit
+    }
+
+    pub fn used(&self) -> usize {
+        self.inner.used.load(Ordering::Acquire)
+    }
+
+    /// Acquire `bytes` if they fit into the budget.
+    pub fn try_acquire(&self, bytes: usize) -> bool {
+        self.inner
+            .used
+            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |used| {
+                used.checked_add(bytes)
+                    .filter(|total| *total <= self.inner.limit)
+            })
+            .is_ok()
+    }
+
+    pub fn release(&self, bytes: usize) {
+        self.inner.used.fetch_sub(bytes, Ordering::AcqRel);
+    }
+}
+
+/// What a buffer does with a value which doesn't fit into the memory budget.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum BudgetPolicy {
+    /// Insert what is buffered right away, which frees the memory and makes the producer wait
+    /// for the database.
+    #[default]
+    Backpressure,
+    /// Reject the value with [`MemoryBudgetExceeded`](crate::MemoryBudgetExceeded).
+    Shed,
+}
+
+/// An estimate of the memory a value occupies, including its heap allocations.
+pub trait SizeEstimate {
+    fn estimated_size(&self) -> usize;
+}
+
+macro_rules! impl_size_estimate_for_plain {
+    ( $($ty:ty),* ) => {
+        $(
+            impl SizeEstimate for $ty {
+                fn estimated_size(&self) -> usize {
+                    std::mem::size_of::<$ty>()
+                }
+            }
+        )*
+    };
+}
+
+impl_size_estimate_for_plain!(
+    bool, char, i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize, f32, f64
+);
+
+#[cfg(feature = "chrono")]
+impl_size_estimate_for_plain!(
+    sqlx::types::chrono::NaiveDate,
+    sqlx::types::chrono::NaiveTime,
+    sqlx::types::chrono::NaiveDateTime,
+    sqlx::types::chrono::DateTime<sqlx::types::chrono::Utc>,
+    sqlx::types::chrono::DateTime<sqlx::types::chrono::FixedOffset>,
+    sqlx::types::chrono::DateTime<sqlx::types::chrono::Local>
+);
+
+#[cfg(feature = "time")]
+impl_size_estimate_for_plain!(
+    sqlx::types::time::Date,
+    sqlx::types::time::Time,
+    sqlx::types::time::PrimitiveDateTime,
+    sqlx::types::time::OffsetDateTime
+);
+
+#[cfg(feature = "uuid")]
+impl_size_estimate_for_plain!(sqlx::types::Uuid);
+
+#[cfg(feature = "ipnetwork")]
+impl_size_estimate_for_plain!(sqlx::types::ipnetwork::IpNetwork);
+
+#[cfg(feature = "decimal")]
+impl_size_estimate_for_plain!(sqlx::types::Decimal);
+
+impl SizeEstimate for String {
+    fn estimated_size(&self) -> usize {
+        std::mem::size_of::<String>() + self.capacity()
+    }
+}
+
+impl SizeEstimate for std::borrow::Cow<'_, str> {
+    fn estimated_size(&self) -> usize {
+        match self {
+            std::borrow::Cow::Borrowed(_) => std::mem::size_of::<Self>(),
+            std::borrow::Cow::Owned(s) => std::mem::size_of::<Self>() + s.capacity(),
+        }
+    }
+}
+
+impl<T: SizeEstimate> SizeEstimate for Option<T> {
+    fn estimated_size(&self) -> usize {
+        match self {
+            Some(value) => {
+                std::mem::size_of::<Self>() - std::mem::size_of::<T>() + value.estimated_size()
+            }
+            None => std::mem::size_of::<Self>(),
+        }
+    }
+}
+
+impl<T: SizeEstimate> SizeEstimate for Vec<T> {
+    fn estimated_size(&self) -> usize {
+        std::mem::size_of::<Self>()
+            + (self.capacity() - self.len()) * std::mem::size_of::<T>()
+            + self.iter().map(SizeEstimate::estimated_size).sum::<usize>()
+    }
+}
+
+impl<T: SizeEstimate + ?Sized> SizeEstimate for Box<T> {
+    fn estimated_size(&self) -> usize {
+        std::mem::size_of::<Self>() + (**self).estimated_size()
+    }
+}
@@ -0,0 +1,617 @@
+use std::{borrow::Cow, future::Future, ops::Range, sync::Arc, time::SystemTime};
+
+use sqlx::{Acquire, Executor, IntoArguments};
+
+use crate::{
+    clock::{self, Batch},
+    compat::ArgumentsOf,
+    next_batch_id, runtime, write_insert_into, write_insert_into_columns, Backend,
+    BindParamLimitExceeded, BulkInsertReport, Capabilities, ChunkPosition, ColumnCache, Config,
+    Dialect, Hints, Insertable, Inserter, LoadHooks, OnConflict, SqlLengthExceeded, TableName,
+    UpsertOutcome,
+};
+
+/// Options for a bulk insert. Usually built through [`BulkInsert`].
+#[derive(Debug, Clone)]
+pub struct BulkInsertOptions {
+    /// Overrides `Insertable::table_name()`.
+    pub table_name: Option<TableName>,
+    /// Rows per statement. Falls back to the chunk size registered in the config for the table,
+    /// then to the largest one the capabilities allow.
+    pub chunk_size: Option<usize>,
+    pub capabilities: Option<Capabilities>,
+    /// The auto-generated key column returned by `execute_returning_ids`. Defaults to `id`.
+    pub id_column: Option<String>,
+    /// Keep inserting the remaining chunks when one fails and report the failure instead.
+    /// PostgreSQL aborts the surrounding transaction on a failed statement, so this is only
+    /// useful outside of transactions there.
+    pub continue_on_error: bool,
+    /// Whether the generated statements are kept in the connection's prepared statement cache.
+    /// Bulk inserts of varying sizes produce many distinct statements, disable this to keep them
+    /// from crowding out the cache.
+    pub persistent: bool,
+    /// Turns the insert into an upsert.
+    pub on_conflict: Option<OnConflict>,
+    /// Overrides the global config.
+    pub config: Option<Arc<Config>>,
+    pub hints: Option<Hints>,
+    /// Fail a chunk with [`PartialWrite`](crate::PartialWrite) unless it affected exactly one row per value. Upserts
+    /// are not checked, skipped and updated rows count differently on each database.
+    pub verify_rows_affected: bool,
+    /// Identifies the chunks of the insert in [`Statement::chunk`](crate::Statement::chunk), e.g.
+    /// the id of an ingestion job. Defaults to a new [`next_batch_id`](crate::next_batch_id)
+    /// per insert.
+    pub batch_id: Option<u64>,
+    /// Statements around the whole insert, see [`LoadHooks`].
+    pub load_hooks: Option<LoadHooks>,
+    /// The timestamp of the `#[insertable(auto_now)]` fields of every row. Defaults to the time
+    /// of the config's [`Clock`](crate::clock::Clock) when the insert starts, see
+    /// [`timestamp`](crate::timestamp).
+    pub now: Option<SystemTime>,
+    /// Leave out the columns of `T` which the table doesn't have (yet), see [`ColumnCache`].
+    pub skip_missing_columns: Option<Arc<ColumnCache>>,
+}
+
+impl Default for BulkInsertOptions {
+    fn default() -> Self {
+        BulkInsertOptions {
+            table_name: None,
+            chunk_size: None,
+            capabilities: None,
+            id_column: None,
+            continue_on_error: false,
+            persistent: true,
+            on_conflict: None,
+            config: None,
+            hints: None,
+            verify_rows_affected: false,
+            batch_id: None,
+            load_hooks: None,
+            now: None,
+            skip_missing_columns: None,
+        }
+    }
+}
+
+impl BulkInsertOptions {
+    pub fn table_name<T: Insertable>(&self) -> &str {
+        self.table_name
+            .as_ref()
+            .map_or(T::table_name(), TableName::as_str)
+    }
+
+    pub fn id_column(&self) -> &str {
+        self.id_column.as_deref().unwrap_or("id")
+    }
+
+    pub fn config(&self) -> Arc<Config> {
+        self.config.clone().unwrap_or_else(Config::global)
+    }
+
+    /// Apply the [`LoadHooks`], if any, before the first statement of an insert of `T`.
+    pub(crate) async fn before_load<T, E>(&self, executor: &mut E) -> anyhow::Result<()>
+    where
+        T: Insertable,
+        T::Database: Backend,
+        for<'e> &'e mut E: Executor<'e, Database = T::Database>,
+        for<'q> ArgumentsOf<'q, T::Database>: IntoArguments<'q, T::Database>,
+    {
+        match &self.load_hooks {
+            Some(hooks) => {
+                hooks
+                    .before::<E, T::Database>(executor, self.table_name::<T>())
+                    .await
+            }
+            None => Ok(()),
+        }
+    }
+
+    /// Restore the [`LoadHooks`], if any, after an insert of `T` which ended with `result`.
+    pub(crate) async fn after_load<T, E, R>(
+        &self,
+        executor: &mut E,
+        result: anyhow::Result<R>,
+    ) -> anyhow::Result<R>
+    where
+        T: Insertable,
+        T::Database: Backend,
+        for<'e> &'e mut E: Executor<'e, Database = T::Database>,
+        for<'q> ArgumentsOf<'q, T::Database>: IntoArguments<'q, T::Database>,
+    {
+        match &self.load_hooks {
+            Some(hooks) => {
+                hooks
+                    .after::<E, T::Database, R>(executor, self.table_name::<T>(), result)
+                    .await
+            }
+            None => result,
+        }
+    }
+
+    /// The timestamp of the `auto_now` fields, read once per insert. A nested insert shares the
+    /// one of the enclosing insert.
+    pub(crate) fn batch_now(&self) -> SystemTime {
+        self.now
+            .or_else(clock::scoped_now)
+            .unwrap_or_else(|| self.config().clock().now())
+    }
+
+    /// The timestamp and the key source of an insert.
+    pub(crate) fn batch(&self) -> Batch {
+        Batch {
+            now: self.batch_now(),
+            ids: self.config().id_generator(),
+        }
+    }
+
+    pub(crate) fn verifies_rows_affected(&self) -> bool {
+        self.verify_rows_affected && self.on_conflict.is_none()
+    }
+
+    /// The position of each of `total` chunks, all in one batch.
+    pub(crate) fn chunk_positions(&self, total: usize) -> impl Iterator<Item = ChunkPosition> {
+        let batch_id = self.batch_id.unwrap_or_else(next_batch_id);
+        (0..total).map(move |index| ChunkPosition {
+            batch_id,
+            index,
+            total,
+        })
+    }
+
+    /// The capabilities set, or the ones of a current server. Through a pool they are detected
+    /// instead, see [`Config::capabilities_of`].
+    pub fn capabilities<DB: Backend>(&self) -> Capabilities {
+        self.capabilities
+            .clone()
+            .unwrap_or_else(Capabilities::assumed::<DB>)
+    }
+
+    /// These options with the capabilities of `pool`'s server, unless some are set.
+    pub(crate) async fn with_capabilities_of<DB>(&self, pool: &sqlx::Pool<DB>) -> Cow<'_, Self>
+    where
+        DB: Backend,
+        for<'c> &'c mut DB::Connection: Executor<'c, Database = DB>,
+        (String,): for<'r> sqlx::FromRow<'r, DB::Row>,
+        for<'q> ArgumentsOf<'q, DB>: IntoArguments<'q, DB>,
+    {
+        if self.capabilities.is_some() {
+            return Cow::Borrowed(self);
+        }
+        let mut options = self.clone();
+        options.capabilities = Some(self.config().capabilities_of(pool).await);
+        Cow::Owned(options)
+    }
+
+    /// The statement inserting `rows` rows, including the upsert clause and hints.
+    pub fn insert_sql<T: Insertable>(&self, rows: usize) -> anyhow::Result<String>
+    where
+        T::Database: Backend,
+    {
+        self.insert_sql_with::<T>(rows, None)
+    }
+
+    /// Like [`insert_sql`](Self::insert_sql), returning the [`id_column`](Self::id_column) of
+    /// the inserted rows with `RETURNING`, or `OUTPUT` on MSSQL. See [`crate::returning`].
+    pub fn returning_sql<T: Insertable>(&self, rows: usize) -> anyhow::Result<String>
+    where
+        T::Database: Backend,
+    {
+        match self.capabilities::<T::Database>().dialect {
+            Dialect::Mssql => self.statement_sql::<T>(rows, None, Some(self.id_column())),
+            _ => Ok(format!(
+                "{} RETURNING {}",
+                self.insert_sql::<T>(rows)?,
+                self.id_column()
+            )),
+        }
+    }
+
+    /// Like [`insert_sql`](Self::insert_sql), inserting only `columns` of `T`'s columns.
+    pub(crate) fn insert_sql_with<T: Insertable>(
+        &self,
+        rows: usize,
+        columns: Option<&[&str]>,
+    ) -> anyhow::Result<String>
+    where
+        T::Database: Backend,
+    {
+        self.statement_sql::<T>(rows, columns, None)
+    }
+
+    /// The statement of `rows` rows of `columns`, all if `None`, with MSSQL's `OUTPUT` of the
+    /// `output` column.
+    fn statement_sql<T: Insertable>(
+        &self,
+        rows: usize,
+        columns: Option<&[&str]>,
+        output: Option<&str>,
+    ) -> anyhow::Result<String>
+    where
+        T::Database: Backend,
+    {
+        let write_insert_into = |buf: &mut String, table_name: &str| match columns {
+            None => write_insert_into::<T>(buf, table_name, rows, output),
+            Some(columns) => write_insert_into_columns::<T>(buf, table_name, columns, rows, output),
+        };
+        let statement = |table_name: &str| match &self.on_conflict {
+            None => {
+                let mut buf = String::from("INSERT ");
+                write_insert_into(&mut buf, table_name)?;
+                Ok(buf)
+            }
+            Some(on_conflict) => {
+                let mut buf = String::new();
+                on_conflict.write_sql(
+                    &mut buf,
+                    &self.capabilities::<T::Database>(),
+                    columns.unwrap_or(T::insert_columns()),
+                    |buf| write_insert_into(buf, table_name),
+                )?;
+                Ok(buf)
+            }
+        };
+
+        match &self.hints {
+            None => statement(self.table_name::<T>()),
+            Some(hints) => hints.apply(
+                self.capabilities::<T::Database>().dialect,
+                self.table_name::<T>(),
+                statement,
+            ),
+        }
+    }
+
+    /// Like [`insert_sql`](Self::insert_sql), on a blocking thread for statements with at least
+    /// [`Config::offload_sql_params`] bind parameters.
+    pub(crate) async fn insert_sql_offloaded<T: Insertable>(
+        &self,
+        rows: usize,
+    ) -> anyhow::Result<String>
+    where
+        T::Database: Backend,
+    {
+        match self.config().offload_sql_params() {
+            Some(params) if rows * T::COLUMN_COUNT >= params => {
+                // A fn pointer doesn't carry `T`, so `T` needn't be `'static`.
+                let insert_sql: fn(&Self, usize) -> anyhow::Result<String> = Self::insert_sql::<T>;
+                let options = self.clone();
+                runtime::spawn_blocking(move || insert_sql(&options, rows)).await
+            }
+            _ => self.insert_sql::<T>(rows),
+        }
+    }
+
+    /// The rows per statement, checked against the bind parameter limit of the database.
+    pub fn chunk_size<T: Insertable>(&self) -> anyhow::Result<usize>
+    where
+        T::Database: Backend,
+    {
+        let capabilities = self.capabilities::<T::Database>();
+        let columns = T::COLUMN_COUNT;
+
+        if columns > capabilities.max_params {
+            return Err(BindParamLimitExceeded {
+                chunk_size: 1,
+                columns,
+                max_params: capabilities.max_params,
+            }
+            .into());
+        }
+
+        // Only MySQL inserts several rows without columns in one statement.
+        if columns == 0 && capabilities.dialect != Dialect::MySql {
+            return Ok(1);
+        }
+
+        let chunk_size = match self
+            .chunk_size
+            .or_else(|| self.config().chunk_size(self.table_name::<T>()))
+        {
+            Some(chunk_size) => chunk_size,
+            None => {
+                let chunk_size = capabilities.max_rows_per_statement(columns);
+                return self.fit_sql_length::<T>(chunk_size, &capabilities);
+            }
+        };
+
+        anyhow::ensure!(chunk_size > 0, "chunk size must be greater than 0");
+
+        if chunk_size
+            .checked_mul(columns)
+            .is_none_or(|params| params > capabilities.max_params)
+        {
+            return Err(BindParamLimitExceeded {
+                chunk_size,
+                columns,
+                max_params: capabilities.max_params,
+            }
+            .into());
+        }
+
+        self.fit_sql_length::<T>(chunk_size, &capabilities)
+    }
+
+    /// Shrink `chunk_size` until the rendered statement fits into the maximum SQL length.
+    fn fit_sql_length<T: Insertable>(
+        &self,
+        mut chunk_size: usize,
+        capabilities: &Capabilities,
+    ) -> anyhow::Result<usize>
+    where
+        T::Database: Backend,
+    {
+        let max_length = match capabilities.max_sql_length {
+            Some(max_length) => max_length,
+            None => return Ok(chunk_size),
+        };
+
+        // Rendering a full chunk is costly, so skip it when a linear estimate leaves plenty of
+        // room. Numbered placeholders grow at most twice as long per row.
+        let first = self.insert_sql::<T>(1)?.len();
+        let per_row = self.insert_sql::<T>(2)?.len() - first;
+        if (first + (chunk_size - 1) * per_row) * 2 <= max_length {
+            return Ok(chunk_size);
+        }
+
+        loop {
+            let length = self.insert_sql::<T>(chunk_size)?.len();
+            if length <= max_length {
+                return Ok(chunk_size);
+            }
+            if chunk_size == 1 {
+                return Err(SqlLengthExceeded { length, max_length }.into());
+            }
+            chunk_size = (chunk_size * max_length / length).clamp(1, chunk_size - 1);
+        }
+    }
+}
+
+/// A builder for bulk inserts.
+///
+/// ```ignore
+/// let report = BulkInsert::new(&users)
+///     .capabilities(&caps)
+///     .execute(&mut tx)
+///     .await?;
+/// ```
+#[derive(Debug, Clone)]
+pub struct BulkInsert<'v, T> {
+    values: &'v [T],
+    options: BulkInsertOptions,
+}
+
+impl<'v, T> BulkInsert<'v, T>
+where
+    T: Insertable + Sync,
+    T::Database: Backend,
+{
+    pub fn new(values: &'v [T]) -> Self {
+        BulkInsert {
+            values,
+            options: BulkInsertOptions::default(),
+        }
+    }
+
+    pub fn table_name(mut self, table_name: TableName) -> Self {
+        self.options.table_name = Some(table_name);
+        self
+    }
+
+    pub fn chunk_size(mut self, chunk_size: usize) -> Self {
+        self.options.chunk_size = Some(chunk_size);
+        self
+    }
+
+    pub fn capabilities(mut self, capabilities: &Capabilities) -> Self {
+        self.options.capabilities = Some(capabilities.clone());
+        self
+    }
+
+    pub fn id_column(mut self, id_column: impl Into<String>) -> Self {
+        self.options.id_column = Some(id_column.into());
+        self
+    }
+
+    pub fn continue_on_error(mut self, continue_on_error: bool) -> Self {
+        self.options.continue_on_error = continue_on_error;
+        self
+    }
+
+    pub fn persistent(mut self, persistent: bool) -> Self {
+        self.options.persistent = persistent;
+        self
+    }
+
+    pub fn on_conflict(mut self, on_conflict: OnConflict) -> Self {
+        self.options.on_conflict = Some(on_conflict);
+        self
+    }
+
+    pub fn verify_rows_affected(mut self, verify_rows_affected: bool) -> Self {
+        self.options.verify_rows_affected = verify_rows_affected;
+        self
+    }
+
+    pub fn batch_id(mut self, batch_id: u64) -> Self {
+        self.options.batch_id = Some(batch_id);
+        self
+    }
+
+    pub fn hints(mut self, hints: Hints) -> Self {
+        self.options.hints = Some(hints);
+        self
+    }
+
+    pub fn load_hooks(mut self, load_hooks: LoadHooks) -> Self {
+        self.options.load_hooks = Some(load_hooks);
+        self
+    }
+
+    /// Leave out the columns of `T` which the table doesn't have, looked up in `cache`.
+    pub fn skip_missing_columns(mut self, cache: impl Into<Arc<ColumnCache>>) -> Self {
+        self.options.skip_missing_columns = Some(cache.into());
+        self
+    }
+
+    /// Fill the `#[insertable(auto_now)]` fields with `now` instead of the time the insert starts.
+    pub fn now(mut self, now: SystemTime) -> Self {
+        self.options.now = Some(now);
+        self
+    }
+
+    /// Skip rows conflicting on the first unique constraint declared on the derive, or on any
+    /// constraint if none is declared.
+    pub fn on_conflict_default(self) -> Self {
+        self.on_conflict(OnConflict::unique_of::<T>())
+    }
+
+    /// Skip rows violating the named constraint, see [`OnConflict::constraint`].
+    pub fn on_conflict_constraint(self, name: impl Into<String>) -> Self {
+        self.on_conflict(OnConflict::constraint(name))
+    }
+
+    pub fn config(mut self, config: impl Into<Arc<Config>>) -> Self {
+        self.options.config = Some(config.into());
+        self
+    }
+
+    pub fn options(&self) -> &BulkInsertOptions {
+        &self.options
+    }
+
+    pub async fn execute<I>(self, inserter: I) -> anyhow::Result<BulkInsertReport>
+    where
+        I: Inserter<T::Database>,
+    {
+        inserter
+            .bulk_insert_report_with_options(self.values, &self.options)
+            .await
+    }
+
+    /// Insert chunk by chunk and hand each chunk's row range and rows affected, or its error, to
+    /// `on_chunk` before the next one starts, e.g. to acknowledge the rows to a queue as they
+    /// land:
+    ///
+    /// ```ignore
+    /// BulkInsert::new(&messages)
+    ///     .try_for_each_chunk(&mut conn, |index, rows, result| async move {
+    ///         result?;
+    ///         queue.ack(&deliveries[rows]).await
+    ///     })
+    ///     .await?;
+    /// ```
+    ///
+    /// An error returned by `on_chunk` stops the insert and is returned, the chunks before it
+    /// stay inserted. Returning `Ok` for a failed chunk moves on to the next one.
+    pub async fn try_for_each_chunk<E, F, Fut>(
+        self,
+        executor: &mut E,
+        mut on_chunk: F,
+    ) -> anyhow::Result<()>
+    where
+        for<'e> &'e mut E: Inserter<T::Database> + Executor<'e, Database = T::Database>,
+        for<'q> ArgumentsOf<'q, T::Database>: IntoArguments<'q, T::Database>,
+        F: FnMut(usize, Range<usize>, anyhow::Result<u64>) -> Fut,
+        Fut: Future<Output = anyhow::Result<()>>,
+    {
+        // Each chunk is an insert of its own, they share the batch id, the timestamp and the
+        // load hooks.
+        let options = BulkInsertOptions {
+            chunk_size: Some(self.options.chunk_size::<T>()?),
+            continue_on_error: false,
+            batch_id: Some(self.options.batch_id.unwrap_or_else(next_batch_id)),
+            load_hooks: None,
+            now: Some(self.options.batch_now()),
+            ..self.options.clone()
+        };
+        let chunk_size = options.chunk_size.unwrap_or(1);
+
+        if self.values.is_empty() {
+            return Ok(());
+        }
+        self.options.before_load::<T, E>(&mut *executor).await?;
+        let result = async {
+            for (index, chunk) in self.values.chunks(chunk_size).enumerate() {
+                let result = executor
+                    .bulk_insert_report_with_options(chunk, &options)
+                    .await
+                    .map(|report| report.rows_affected);
+                let offset = index * chunk_size;
+                on_chunk(index, offset..offset + chunk.len(), result).await?;
+            }
+            Ok(())
+        }
+        .await;
+        self.options.after_load::<T, E, _>(executor, result).await
+    }
+
+    /// Run the insert in a transaction which is always rolled back, e.g. to validate an import
+    /// against production data. The report holds the row counts the insert would have had.
+    ///
+    /// With [`continue_on_error`](Self::continue_on_error) constraint errors are collected in
+    /// the report instead of ending the run, except on PostgreSQL, where the first failure
+    /// aborts the transaction. On a transaction, `acquire` runs the insert in a savepoint.
+    pub async fn execute_dry_run<'c, A>(self, acquire: A) -> anyhow::Result<BulkInsertReport>
+    where
+        A: Acquire<'c, Database = T::Database>,
+        for<'e> &'e mut <T::Database as sqlx::Database>::Connection: Inserter<T::Database>,
+    {
+        let mut tx = acquire.begin().await?;
+        let report = (&mut *tx)
+            .bulk_insert_report_with_options(self.values, &self.options)
+            .await;
+        tx.rollback().await?;
+
+        report
+    }
+
+    /// Insert the values and return their auto-generated ids in input order.
+    ///
+    /// See [`crate::returning`] for how each backend obtains them.
+    pub async fn execute_returning_ids<I>(self, inserter: I) -> anyhow::Result<Vec<i64>>
+    where
+        I: Inserter<T::Database>,
+    {
+        inserter
+            .bulk_insert_returning_ids_with_options(self.values, &self.options)
+            .await
+    }
+
+    /// Upsert the values and return what happened to each of them, in input order.
+    /// PostgreSQL only, see [`crate::upsert`].
+    pub async fn execute_upsert_returning<I>(
+        self,
+        inserter: I,
+    ) -> anyhow::Result<Vec<UpsertOutcome>>
+    where
+        I: Inserter<T::Database>,
+    {
+        inserter
+            .bulk_upsert_returning_with_options(self.values, &self.options)
+            .await
+    }
+
+    /// Insert the values which don't exist yet and return the rows of all of them as `O`, in
+    /// input order.
+    ///
+    /// The conflict target of [`on_conflict`](Self::on_conflict) or
+    /// [`on_conflict_default`](Self::on_conflict_default) defines row identity. With
+    /// `do_nothing` existing rows are returned unchanged, with `do_update` they are refreshed
+    /// first. MSSQL is not supported.
+    ///
+    /// ```ignore
+    /// let tags: Vec<Tag> = BulkInsert::new(&new_tags)
+    ///     .on_conflict(OnConflict::columns(["name"]))
+    ///     .get_or_create_many(&mut tx)
+    ///     .await?;
+    /// ```
+    pub async fn get_or_create_many<O, I>(self, inserter: I) -> anyhow::Result<Vec<O>>
+    where
+        I: Inserter<T::Database>,
+        O: for<'r> sqlx::FromRow<'r, <T::Database as sqlx::Database>::Row> + Send + Unpin,
+    {
+        inserter
+            .get_or_create_many_with_options(self.values, &self.options)
+            .await
+    }
+}
@@ -0,0 +1,124 @@
+use sqlx::{Executor, IntoArguments};
+
+use crate::{compat::ArgumentsOf, Backend, Dialect};
+
+/// A parsed server version like `8.0.33` or `10.6.12-MariaDB`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ServerVersion {
+    pub major: u32,
+    pub minor: u32,
+    pub patch: u32,
+    /// The version string as reported by the server.
+    pub raw: String,
+}
+
+impl ServerVersion {
+    /// Parse the leading `major.minor.patch` part of a version string. Missing parts are `0`.
+    pub fn parse(raw: &str) -> Self {
+        let mut parts = raw
+            .trim()
+            .split(|c: char| !c.is_ascii_digit())
+            .take_while(|s| !s.is_empty())
+            .map(|s| s.parse().unwrap_or(0));
+
+        ServerVersion {
+            major: parts.next().unwrap_or(0),
+            minor: parts.next().unwrap_or(0),
+            patch: parts.next().unwrap_or(0),
+            raw: raw.to_owned(),
+        }
+    }
+
+    pub fn at_least(&self, major: u32, minor: u32, patch: u32) -> bool {
+        (self.major, self.minor, self.patch) >= (major, minor, patch)
+    }
+
+    pub fn is_mariadb(&self) -> bool {
+        self.raw.contains("MariaDB")
+    }
+}
+
+/// What the connected server supports.
+///
+/// Inserts through a pool detect them on the first use of the pool and keep them on the
+/// [`Config`](crate::Config). Connections and transactions can't tell their pool, so builders
+/// used with them assume a current server unless handed capabilities, e.g. the ones of
+/// [`Config::capabilities_of`](crate::Config::capabilities_of) or of [`Capabilities::detect`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Capabilities {
+    pub dialect: Dialect,
+    pub server_version: Option<ServerVersion>,
+    pub supports_returning: bool,
+    pub supports_on_conflict: bool,
+    pub max_params: usize,
+    /// The maximum statement length in bytes. Chunks are split to stay below it. SQLite builds
+    /// and connections can lower it, set it accordingly.
+    pub max_sql_length: Option<usize>,
+}
+
+impl Capabilities {
+    /// Query the server version through `executor` and derive capabilities from it.
+    pub async fn detect<'e, DB, E>(executor: E) -> anyhow::Result<Self>
+    where
+        DB: Backend,
+        E: Executor<'e, Database = DB>,
+        (String,): for<'r> sqlx::FromRow<'r, DB::Row>,
+        for<'q> ArgumentsOf<'q, DB>: IntoArguments<'q, DB>,
+    {
+        let (version,): (String,) = sqlx::query_as(DB::SERVER_VERSION_QUERY)
+            .fetch_one(executor)
+            .await?;
+
+        Ok(Self::from_server_version::<DB>(ServerVersion::parse(
+            &version,
+        )))
+    }
+
+    /// Capabilities of a current server, used when nothing was detected.
+    pub fn assumed<DB: Backend>() -> Self {
+        Capabilities {
+            dialect: DB::DIALECT,
+            server_version: None,
+            supports_returning: !matches!(DB::DIALECT, Dialect::MySql | Dialect::Mssql),
+            supports_on_conflict: !matches!(DB::DIALECT, Dialect::Mssql),
+            max_params: DB::MAX_BIND_PARAMS,
+            max_sql_length: DB::MAX_SQL_LENGTH,
+        }
+    }
+
+    pub fn from_server_version<DB: Backend>(version: ServerVersion) -> Self {
+        let (supports_returning, supports_on_conflict, max_params) = match DB::DIALECT {
+            Dialect::Sqlite => (
+                version.at_least(3, 35, 0),
+                version.at_least(3, 24, 0),
+                if version.at_least(3, 32, 0) {
+                    DB::MAX_BIND_PARAMS
+                } else {
+                    999
+                },
+            ),
+            // `ON DUPLICATE KEY UPDATE` is available on every supported MySQL/MariaDB.
+            Dialect::MySql => (
+                version.is_mariadb() && version.at_least(10, 5, 0),
+                true,
+                DB::MAX_BIND_PARAMS,
+            ),
+            Dialect::Postgres => (true, version.at_least(9, 5, 0), DB::MAX_BIND_PARAMS),
+            Dialect::Mssql => (false, false, DB::MAX_BIND_PARAMS),
+        };
+
+        Capabilities {
+            dialect: DB::DIALECT,
+            server_version: Some(version),
+            supports_returning,
+            supports_on_conflict,
+            max_params,
+            max_sql_length: DB::MAX_SQL_LENGTH,
+        }
+    }
+
+    /// The largest number of rows of `columns` columns that fit in a single statement.
+    pub fn max_rows_per_statement(&self, columns: usize) -> usize {
+        (self.max_params / columns.max(1)).max(1)
+    }
+}
@@ -0,0 +1,132 @@
+use std::marker::PhantomData;
+
+use sqlx::{Executor, FromRow, IntoArguments};
+
+use crate::{
+    compat::ArgumentsOf, delete_by_id, delete_by_ids, find_by_id, find_by_ids, keyed, Backend,
+    Entity, Keyed,
+};
+
+/// A row before and after a write, see [`capture_changes`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Change<O> {
+    pub table_name: &'static str,
+    /// `None` when the write created the row.
+    pub before: Option<O>,
+    /// `None` when the write deleted the row.
+    pub after: Option<O>,
+}
+
+/// Run updates and deletes on `executor` and hand the affected rows, read as `O` before and
+/// after each write, to `on_change`:
+///
+/// ```ignore
+/// let mut tx = pool.begin().await?;
+/// let (sender, receiver) = std::sync::mpsc::channel();
+/// let mut capture = sqlx_plus::capture_changes::<User, _, _>(&mut tx, |change| {
+///     sender.send(change).unwrap();
+/// });
+/// capture.save(&user).await?;
+/// capture.delete_by_id::<UserEntity>(&UserId(1)).await?;
+/// tx.commit().await?;
+/// ```
+///
+/// The rows are read with the same executor, so inside a transaction the images are consistent
+/// with the write. `on_change` runs right after each write, before the transaction commits.
+/// Inserts of new entities are not captured.
+pub fn capture_changes<O, E, F>(executor: &mut E, on_change: F) -> ChangeCapture<'_, O, E, F>
+where
+    F: FnMut(Change<O>),
+{
+    ChangeCapture {
+        executor,
+        on_change,
+        _marker: PhantomData,
+    }
+}
+
+/// See [`capture_changes`].
+pub struct ChangeCapture<'a, O, E, F> {
+    executor: &'a mut E,
+    on_change: F,
+    _marker: PhantomData<fn() -> O>,
+}
+
+impl<O, E, F> ChangeCapture<'_, O, E, F>
+where
+    F: FnMut(Change<O>),
+{
+    /// Save `value` like `Inserter::save`, capturing the update of an existing row.
+    pub async fn save<T>(
+        &mut self,
+        value: &T,
+    ) -> anyhow::Result<<T::Database as sqlx::Database>::QueryResult>
+    where
+        T: Entity + Sync,
+        T::Key: Sync,
+        T::Database: Backend,
+        O: for<'r> FromRow<'r, <T::Database as sqlx::Database>::Row> + Send + Unpin,
+        for<'e> &'e mut E: Executor<'e, Database = T::Database>,
+        for<'q> ArgumentsOf<'q, T::Database>: IntoArguments<'q, T::Database>,
+    {
+        let key = match value.key() {
+            Some(key) => key,
+            None => return keyed::save(self.executor, value).await,
+        };
+
+        let before = find_by_id::<T, O, E>(self.executor, key).await?;
+        let result = keyed::save(self.executor, value).await?;
+        let after = find_by_id::<T, O, E>(self.executor, key).await?;
+        (self.on_change)(Change {
+            table_name: T::table_name(),
+            before,
+            after,
+        });
+
+        Ok(result)
+    }
+
+    /// Delete like [`delete_by_id`], capturing the deleted row.
+    pub async fn delete_by_id<T>(&mut self, key: &T::Key) -> anyhow::Result<bool>
+    where
+        T: Keyed,
+        T::Database: Backend,
+        O: for<'r> FromRow<'r, <T::Database as sqlx::Database>::Row> + Send + Unpin,
+        for<'e> &'e mut E: Executor<'e, Database = T::Database>,
+        for<'q> ArgumentsOf<'q, T::Database>: IntoArguments<'q, T::Database>,
+    {
+        let before = find_by_id::<T, O, E>(self.executor, key).await?;
+        let deleted = delete_by_id::<T, E>(self.executor, key).await?;
+        if deleted {
+            (self.on_change)(Change {
+                table_name: T::table_name(),
+                before,
+                after: None,
+            });
+        }
+
+        Ok(deleted)
+    }
+
+    /// Delete like [`delete_by_ids`], capturing each deleted row.
+    pub async fn delete_by_ids<T>(&mut self, keys: &[T::Key]) -> anyhow::Result<u64>
+    where
+        T: Keyed,
+        T::Database: Backend,
+        O: for<'r> FromRow<'r, <T::Database as sqlx::Database>::Row> + Send + Unpin,
+        for<'e> &'e mut E: Executor<'e, Database = T::Database>,
+        for<'q> ArgumentsOf<'q, T::Database>: IntoArguments<'q, T::Database>,
+    {
+        let before = find_by_ids::<T, O, E>(self.executor, keys).await?;
+        let deleted = delete_by_ids::<T, E>(self.executor, keys).await?;
+        for row in before {
+            (self.on_change)(Change {
+                table_name: T::table_name(),
+                before: Some(row),
+                after: None,
+            });
+        }
+
+        Ok(deleted)
+    }
+}
@@ -0,0 +1,279 @@
+//! Fault injection for testing retry and resume logic against a real database.
+//!
+//! [`ChaosExecutor`] wraps a connection or transaction and, before each statement, draws from a
+//! seeded generator whether to fail it, delay it or drop the connection. The same seed and the
+//! same statements give the same faults, so a failing run can be replayed:
+//!
+//! ```ignore
+//! let policy = ChaosPolicy::new(42)
+//!     .error_rate(0.1)
+//!     .delay(0.2, Duration::from_millis(50))
+//!     .drop_rate(0.01);
+//! let mut chaos = ChaosExecutor::new(&mut *conn, policy);
+//! import_with_retries(&mut chaos, &rows).await?;
+//! println!("{:?}", chaos.stats());
+//! ```
+//!
+//! Injected errors are `sqlx::Error::Io` with `TimedOut`, dropped connections fail every
+//! statement with `ConnectionReset` until [`ChaosExecutor::reconnect`].
+
+use std::{
+    io::{self, ErrorKind},
+    time::Duration,
+};
+
+use futures_core::{future::BoxFuture, stream::BoxStream};
+use futures_util::{stream, StreamExt};
+use sqlx::{Database, Describe, Either, Execute, Executor};
+
+use crate::compat::StatementOf;
+
+/// How often a [`ChaosExecutor`] injects which fault. Rates are probabilities per statement.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChaosPolicy {
+    seed: u64,
+    error_rate: f64,
+    delay_rate: f64,
+    max_delay: Duration,
+    drop_rate: f64,
+}
+
+impl ChaosPolicy {
+    /// A policy injecting nothing yet.
+    pub fn new(seed: u64) -> Self {
+        ChaosPolicy {
+            seed,
+            error_rate: 0.0,
+            delay_rate: 0.0,
+            max_delay: Duration::ZERO,
+            drop_rate: 0.0,
+        }
+    }
+
+    /// Fail statements with a transient error.
+    pub fn error_rate(mut self, rate: f64) -> Self {
+        self.error_rate = rate;
+        self
+    }
+
+    /// Delay statements by up to `max_delay` before running them.
+    pub fn delay(mut self, rate: f64, max_delay: Duration) -> Self {
+        self.delay_rate = rate;
+        self.max_delay = max_delay;
+        self
+    }
+
+    /// Break the connection, failing this and every later statement.
+    pub fn drop_rate(mut self, rate: f64) -> Self {
+        self.drop_rate = rate;
+        self
+    }
+}
+
+/// The faults a [`ChaosExecutor`] injected so far.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct ChaosStats {
+    pub statements: u64,
+    pub errors: u64,
+    pub delays: u64,
+    pub drops: u64,
+}
+
+/// An executor injecting the faults of a [`ChaosPolicy`], see the [module](self) docs.
+///
+/// It borrows a connection, e.g. `&mut *tx` of a transaction. `&mut ChaosExecutor` is an
+/// `Executor` and thus an [`Inserter`](crate::Inserter).
+#[derive(Debug)]
+pub struct ChaosExecutor<'a, C> {
+    inner: &'a mut C,
+    policy: ChaosPolicy,
+    rng: fastrand::Rng,
+    dropped: bool,
+    stats: ChaosStats,
+}
+
+enum Fault {
+    Delay(Duration),
+    Error(sqlx::Error),
+}
+
+impl<'a, C> ChaosExecutor<'a, C> {
+    pub fn new(inner: &'a mut C, policy: ChaosPolicy) -> Self {
+        ChaosExecutor {
+            inner,
+            rng: fastrand::Rng::with_seed(policy.seed),
+            policy,
+            dropped: false,
+            stats: ChaosStats::default(),
+        }
+    }
+
+    pub fn stats(&self) -> ChaosStats {
+        self.stats
+    }
+
+    /// Whether an injected drop broke the connection.
+    pub fn is_dropped(&self) -> bool {
+        self.dropped
+    }
+
+    /// Replace the broken connection, like a pool would after a drop.
+    pub fn reconnect(&mut self, inner: &'a mut C) {
+        self.inner = inner;
+        self.dropped = false;
+    }
+
+    pub fn get_mut(&mut self) -> &mut C {
+        self.inner
+    }
+
+    pub fn into_inner(self) -> &'a mut C {
+        self.inner
+    }
+
+    /// Draw the fault of the next statement. Every statement draws the same numbers, so the
+    /// faults only depend on the seed and the number of statements.
+    fn next_fault(&mut self) -> Option<Fault> {
+        let (drop, error, delay, fraction) = (
+            self.rng.f64(),
+            self.rng.f64(),
+            self.rng.f64(),
+            self.rng.f64(),
+        );
+        self.stats.statements += 1;
+
+        if self.dropped {
+            return Some(Fault::Error(connection_reset()));
+        }
+        if drop < self.policy.drop_rate {
+            self.dropped = true;
+            self.stats.drops += 1;
+            return Some(Fault::Error(connection_reset()));
+        }
+        if error < self.policy.error_rate {
+            self.stats.errors += 1;
+            return Some(Fault::Error(sqlx::Error::Io(io::Error::new(
+                ErrorKind::TimedOut,
+                "injected transient error",
+            ))));
+        }
+        if delay < self.policy.delay_rate {
+            self.stats.delays += 1;
+            return Some(Fault::Delay(self.policy.max_delay.mul_f64(fraction)));
+        }
+
+        None
+    }
+}
+
+fn connection_reset() -> sqlx::Error {
+    sqlx::Error::Io(io::Error::new(
+        ErrorKind::ConnectionReset,
+        "injected connection drop",
+    ))
+}
+
+async fn inject(fault: Option<Fault>) -> Result<(), sqlx::Error> {
+    match fault {
+        None => Ok(()),
+        Some(Fault::Delay(delay)) => {
+            crate::runtime::sleep(delay).await;
+            Ok(())
+        }
+        Some(Fault::Error(error)) => Err(error),
+    }
+}
+
+macro_rules! impl_chaos_executor {
+    ( $db:ty, $conn:ty ) => {
+        impl<'c> Executor<'c> for &'c mut ChaosExecutor<'_, $conn> {
+            type Database = $db;
+
+            fn fetch_many<'e, 'q: 'e, Q>(
+                self,
+                query: Q,
+            ) -> BoxStream<
+                'e,
+                Result<
+                    Either<
+                        <Self::Database as Database>::QueryResult,
+                        <Self::Database as Database>::Row,
+                    >,
+                    sqlx::Error,
+                >,
+            >
+            where
+                'c: 'e,
+                Q: 'q + Execute<'q, Self::Database>,
+            {
+                let fault = self.next_fault();
+                let inner = self.inner.fetch_many(query);
+                match fault {
+                    None => inner,
+                    Some(Fault::Error(error)) => Box::pin(stream::once(async { Err(error) })),
+                    fault => Box::pin(
+                        stream::once(async move {
+                            match inject(fault).await {
+                                Ok(()) => inner,
+                                Err(error) => Box::pin(stream::once(async { Err(error) })),
+                            }
+                        })
+                        .flatten(),
+                    ),
+                }
+            }
+
+            fn fetch_optional<'e, 'q: 'e, Q>(
+                self,
+                query: Q,
+            ) -> BoxFuture<'e, Result<Option<<Self::Database as Database>::Row>, sqlx::Error>>
+            where
+                'c: 'e,
+                Q: 'q + Execute<'q, Self::Database>,
+            {
+                let fault = self.next_fault();
+                let inner = self.inner.fetch_optional(query);
+                Box::pin(async move {
+                    inject(fault).await?;
+                    inner.await
+                })
+            }
+
+            fn prepare_with<'e, 'q: 'e>(
+                self,
+                sql: &'q str,
+                parameters: &'e [<Self::Database as Database>::TypeInfo],
+            ) -> BoxFuture<'e, Result<StatementOf<'q, Self::Database>, sqlx::Error>>
+            where
+                'c: 'e,
+            {
+                let fault = self.next_fault();
+                let inner = self.inner.prepare_with(sql, parameters);
+                Box::pin(async move {
+                    inject(fault).await?;
+                    inner.await
+                })
+            }
+
+            #[doc(hidden)]
+            fn describe<'e, 'q: 'e>(
+                self,
+                sql: &'q str,
+            ) -> BoxFuture<'e, Result<Describe<Self::Database>, sqlx::Error>>
+            where
+                'c: 'e,
+            {
+                self.inner.describe(sql)
+            }
+        }
+    };
+}
+
+#[cfg(feature = "sqlite")]
+impl_chaos_executor!(sqlx::Sqlite, sqlx::SqliteConnection);
+#[cfg(feature = "mysql")]
+impl_chaos_executor!(sqlx::MySql, sqlx::MySqlConnection);
+#[cfg(feature = "postgres")]
+impl_chaos_executor!(sqlx::Postgres, sqlx::PgConnection);
+#[cfg(feature = "mssql")]
+impl_chaos_executor!(sqlx::Mssql, sqlx::MssqlConnection);
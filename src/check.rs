@@ -0,0 +1,207 @@
+use std::fmt;
+
+use anyhow::Context;
+use sqlx::{Acquire, Executor, FromRow, IntoArguments};
+
+use crate::{
+    bulk_insert_with_options, compat::ArgumentsOf, returning, Backend, BulkInsertOptions, Dialect,
+    Insertable, PlaceHolders, TableName,
+};
+
+/// Insert `sample` into a temporary copy of `T`'s table and read it back, which fails when the
+/// columns or types of `T` drifted from the table.
+///
+/// The copy is created from the live table, so the check sees its current schema without
+/// touching its rows. Usually called through [`assert_insertable!`](crate::assert_insertable) or
+/// the `__sqlx_plus_check` function generated by `#[insertable(..., smoke_test)]`.
+pub async fn check_insertable<T, E>(executor: &mut E, sample: &T) -> anyhow::Result<()>
+where
+    T: Insertable + Sync,
+    T::Database: Backend,
+    for<'e> &'e mut E: Executor<'e, Database = T::Database>,
+    for<'q> ArgumentsOf<'q, T::Database>: IntoArguments<'q, T::Database>,
+{
+    let table_name = T::table_name();
+    let check_table = format!(
+        "{}sqlx_plus_check_{}",
+        if T::Database::DIALECT == Dialect::Mssql {
+            "#"
+        } else {
+            ""
+        },
+        table_name.replace('.', "_")
+    );
+
+    let create = match T::Database::DIALECT {
+        Dialect::Sqlite => format!(
+            "CREATE TEMP TABLE {} AS SELECT * FROM {} WHERE 0",
+            check_table, table_name
+        ),
+        Dialect::MySql => format!("CREATE TEMPORARY TABLE {} LIKE {}", check_table, table_name),
+        Dialect::Postgres => format!(
+            "CREATE TEMP TABLE {} (LIKE {} INCLUDING DEFAULTS)",
+            check_table, table_name
+        ),
+        Dialect::Mssql => format!(
+            "SELECT * INTO {} FROM {} WHERE 1 = 0",
+            check_table, table_name
+        ),
+    };
+    sqlx::query(&create).execute(&mut *executor).await?;
+
+    let options = BulkInsertOptions {
+        table_name: Some(TableName::trusted(check_table.as_str())),
+        ..Default::default()
+    };
+    let checked = async {
+        bulk_insert_with_options(&mut *executor, std::slice::from_ref(sample), &options).await?;
+
+        let select = format!(
+            "SELECT {} FROM {}",
+            T::insert_columns().join(","),
+            check_table
+        );
+        let rows = sqlx::query(&select).fetch_all(&mut *executor).await?;
+        anyhow::ensure!(
+            rows.len() == 1,
+            "expected the sample row of {} back, got {} rows",
+            table_name,
+            rows.len()
+        );
+        Ok(())
+    }
+    .await;
+
+    // After a failure PostgreSQL rejects the drop as well, the failure is what matters.
+    let dropped = sqlx::query(&format!("DROP TABLE {}", check_table))
+        .execute(&mut *executor)
+        .await;
+    checked?;
+    dropped?;
+
+    Ok(())
+}
+
+/// Check at startup that `T` can be inserted, with an error naming the failed step.
+///
+/// Connects, then runs `INSERT INTO t (...) SELECT ... FROM t WHERE 1 = 0` in a transaction which
+/// is rolled back. It inserts nothing, but fails like a real insert when the table or one of the
+/// columns is missing or the user lacks the privileges.
+///
+/// ```ignore
+/// sqlx_plus::ensure_writable::<UserInsert, _>(&pool).await?;
+/// ```
+pub async fn ensure_writable<'c, T, A>(acquire: A) -> anyhow::Result<()>
+where
+    T: Insertable,
+    A: Acquire<'c, Database = T::Database>,
+    for<'e> &'e mut <T::Database as sqlx::Database>::Connection:
+        Executor<'e, Database = T::Database>,
+    for<'q> ArgumentsOf<'q, T::Database>: IntoArguments<'q, T::Database>,
+{
+    let table_name = T::table_name();
+    let columns = T::insert_columns().join(",");
+
+    let mut tx = acquire
+        .begin()
+        .await
+        .context("cannot connect to the database")?;
+
+    let sql = format!(
+        "INSERT INTO {table} ({columns}) SELECT {columns} FROM {table} WHERE 1 = 0",
+        table = table_name,
+        columns = columns,
+    );
+    sqlx::query(&sql)
+        .execute(&mut *tx)
+        .await
+        .with_context(|| format!("cannot insert into {} ({})", table_name, columns))?;
+
+    tx.rollback().await?;
+
+    Ok(())
+}
+
+/// Insert `sample` in a transaction which is rolled back, read the row back as `R` and compare
+/// both with `equivalent`, which fails when a column does not survive the round trip (e.g. a
+/// truncated string or a time zone shift).
+///
+/// ```ignore
+/// let user: User = sqlx_plus::roundtrip_check(&pool, &sample, |written: &UserInsert, read: &User| {
+///     written.name == read.name && written.created_at == read.created_at
+/// })
+/// .await?;
+/// ```
+///
+/// The row is found by the `id` column generated for it, see [`crate::returning`] for the
+/// supported backends. The row read back is returned for further checks.
+pub async fn roundtrip_check<'c, T, R, A>(
+    acquire: A,
+    sample: &T,
+    equivalent: impl FnOnce(&T, &R) -> bool,
+) -> anyhow::Result<R>
+where
+    T: Insertable + Sync + fmt::Debug,
+    T::Database: Backend,
+    R: for<'r> FromRow<'r, <T::Database as sqlx::Database>::Row> + Send + Unpin + fmt::Debug,
+    A: Acquire<'c, Database = T::Database>,
+    for<'e> &'e mut <T::Database as sqlx::Database>::Connection:
+        Executor<'e, Database = T::Database>,
+    for<'q> ArgumentsOf<'q, T::Database>: IntoArguments<'q, T::Database>,
+    (i64,): for<'r> FromRow<'r, <T::Database as sqlx::Database>::Row>,
+    i64: for<'q> sqlx::Encode<'q, T::Database> + sqlx::Type<T::Database>,
+{
+    let options = BulkInsertOptions::default();
+    let mut tx = acquire.begin().await?;
+
+    let ids =
+        returning::bulk_insert_returning_ids(&mut *tx, std::slice::from_ref(sample), &options)
+            .await?;
+    let id = match ids.as_slice() {
+        [id] => *id,
+        _ => anyhow::bail!("the sample row of {} was not inserted", T::table_name()),
+    };
+
+    let mut sql = format!(
+        "SELECT * FROM {} WHERE {} = ",
+        T::table_name(),
+        options.id_column()
+    );
+    T::Database::write_placeholders(&mut sql, 1, 1);
+    let read: R = sqlx::query_as(&sql).bind(id).fetch_one(&mut *tx).await?;
+
+    tx.rollback().await?;
+
+    anyhow::ensure!(
+        equivalent(sample, &read),
+        "{} does not read back as written: wrote {:?}, read {:?}",
+        T::table_name(),
+        sample,
+        read
+    );
+
+    Ok(read)
+}
+
+/// Check that a sample value can be inserted into its table, see [`check_insertable`].
+///
+/// ```ignore
+/// #[tokio::test]
+/// async fn user_insert_matches_schema() {
+///     let mut conn = connect().await;
+///     sqlx_plus::assert_insertable!(&mut conn, UserInsert::default());
+/// }
+/// ```
+#[macro_export]
+macro_rules! assert_insertable {
+    ($executor:expr, $sample:expr $(,)?) => {{
+        let sample = $sample;
+        if let Err(error) = $crate::check_insertable($executor, &sample).await {
+            panic!(
+                "{} does not insert into its table: {:#}",
+                stringify!($sample),
+                error
+            );
+        }
+    }};
+}
@@ -0,0 +1,80 @@
+use std::slice;
+
+use crate::{Backend, BulkInsertOptions, Insertable};
+
+/// The chunks a bulk insert of `values` with the default options runs, each with its statement,
+/// for driving the execution yourself, e.g. with a retry policy of your own:
+///
+/// ```ignore
+/// for (chunk, sql) in sqlx_plus::chunks_for::<Postgres, _>(&rows)? {
+///     retry(|| sqlx::query(&sql).bind_multi_fields(chunk).execute(&pool)).await?;
+/// }
+/// ```
+///
+/// The chunk size and the SQL are the ones of [`BulkInsert`](crate::BulkInsert), see
+/// [`BulkInsertOptions::chunks`] for other options. The rows are not grouped by their
+/// [`defaulted_columns`](Insertable::defaulted_columns), `None`s bind `NULL`.
+pub fn chunks_for<DB, T>(values: &[T]) -> anyhow::Result<Chunks<'_, T>>
+where
+    DB: Backend,
+    T: Insertable<Database = DB>,
+{
+    BulkInsertOptions::default().chunks(values)
+}
+
+/// An iterator over `(chunk, sql)` pairs, see [`chunks_for`].
+#[derive(Debug, Clone)]
+pub struct Chunks<'v, T> {
+    chunks: slice::Chunks<'v, T>,
+    chunk_size: usize,
+    sql: String,
+    /// The statement of a shorter last chunk.
+    last_sql: Option<String>,
+}
+
+impl BulkInsertOptions {
+    /// The chunks a bulk insert of `values` with these options runs, see [`chunks_for`].
+    ///
+    /// A statement is built per chunk size, that is at most two of them, so errors surface
+    /// here rather than during the iteration.
+    pub fn chunks<'v, T>(&self, values: &'v [T]) -> anyhow::Result<Chunks<'v, T>>
+    where
+        T: Insertable,
+        T::Database: Backend,
+    {
+        let chunk_size = self.chunk_size::<T>()?;
+        let sql = match chunk_size.min(values.len()) {
+            0 => String::new(),
+            rows => self.insert_sql::<T>(rows)?,
+        };
+        let last_sql = match values.len() % chunk_size {
+            rows if rows > 0 && values.len() > chunk_size => Some(self.insert_sql::<T>(rows)?),
+            _ => None,
+        };
+        Ok(Chunks {
+            chunks: values.chunks(chunk_size),
+            chunk_size,
+            sql,
+            last_sql,
+        })
+    }
+}
+
+impl<'v, T> Iterator for Chunks<'v, T> {
+    type Item = (&'v [T], String);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let chunk = self.chunks.next()?;
+        let sql = match &self.last_sql {
+            Some(sql) if chunk.len() < self.chunk_size => sql.clone(),
+            _ => self.sql.clone(),
+        };
+        Some((chunk, sql))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.chunks.size_hint()
+    }
+}
+
+impl<T> ExactSizeIterator for Chunks<'_, T> {}
@@ -0,0 +1,249 @@
+//! The sources of `#[insertable(auto_now)]` timestamps and `#[insertable(auto_uuid)]` keys.
+//!
+//! Both are read from the [`Config`](crate::Config), so integration tests can freeze time and
+//! get predictable keys while going through the real insert path:
+//!
+//! ```ignore
+//! let clock = Arc::new(FixedClock::new(UNIX_EPOCH + Duration::from_secs(1_700_000_000)));
+//! let mut config = Config::new();
+//! config.set_clock(clock.clone()).set_id_generator(SequentialIds::new(1));
+//! BulkInsert::new(&orders).config(config).execute(&pool).await?;
+//! clock.advance(Duration::from_secs(60));
+//! ```
+
+use std::{
+    cell::RefCell,
+    collections::hash_map::RandomState,
+    future::Future,
+    hash::{BuildHasher, Hasher},
+    pin::Pin,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+    task::{self, Poll},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use crate::Config;
+
+/// The current time.
+pub trait Clock: Send + Sync {
+    fn now(&self) -> SystemTime;
+}
+
+impl<C: Clock + ?Sized> Clock for Arc<C> {
+    fn now(&self) -> SystemTime {
+        (**self).now()
+    }
+}
+
+/// The system time, the default.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> SystemTime {
+        SystemTime::now()
+    }
+}
+
+/// A clock which only moves when told to.
+#[derive(Debug)]
+pub struct FixedClock {
+    now: Mutex<SystemTime>,
+}
+
+impl FixedClock {
+    pub fn new(now: SystemTime) -> Self {
+        FixedClock {
+            now: Mutex::new(now),
+        }
+    }
+
+    pub fn set(&self, now: SystemTime) {
+        *self.now.lock().unwrap() = now;
+    }
+
+    pub fn advance(&self, duration: Duration) {
+        *self.now.lock().unwrap() += duration;
+    }
+}
+
+impl Clock for FixedClock {
+    fn now(&self) -> SystemTime {
+        *self.now.lock().unwrap()
+    }
+}
+
+/// The keys of `auto_uuid` fields, as the 128 bits of a UUID.
+pub trait IdGenerator: Send + Sync {
+    fn next_id(&self) -> u128;
+}
+
+impl<G: IdGenerator + ?Sized> IdGenerator for Arc<G> {
+    fn next_id(&self) -> u128 {
+        (**self).next_id()
+    }
+}
+
+/// UUIDv7, the default: the milliseconds of the [`Clock`] followed by random bits, so keys
+/// inserted together are close together in an index.
+///
+/// The random bits come from the standard library's randomly seeded hasher. They are unique
+/// enough for keys but not meant to be unguessable.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct UuidV7;
+
+impl IdGenerator for UuidV7 {
+    fn next_id(&self) -> u128 {
+        let millis = now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis()
+            & ((1 << 48) - 1);
+        let random = (u128::from(random_u64()) << 64) | u128::from(random_u64());
+
+        (millis << 80)
+            | (0x7 << 76)
+            | (random & (0xfff << 64))
+            | (0b10 << 62)
+            | (random & ((1 << 62) - 1))
+    }
+}
+
+fn random_u64() -> u64 {
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+
+    let mut hasher = RandomState::new().build_hasher();
+    hasher.write_u64(COUNTER.fetch_add(1, Ordering::Relaxed));
+    hasher.finish()
+}
+
+/// `start`, `start + 1` and so on, e.g. `00000000-0000-0000-0000-000000000001` from 1.
+#[derive(Debug)]
+pub struct SequentialIds {
+    next: AtomicU64,
+}
+
+impl SequentialIds {
+    pub fn new(start: u64) -> Self {
+        SequentialIds {
+            next: AtomicU64::new(start),
+        }
+    }
+}
+
+impl IdGenerator for SequentialIds {
+    fn next_id(&self) -> u128 {
+        u128::from(self.next.fetch_add(1, Ordering::Relaxed))
+    }
+}
+
+/// The timestamp and the key source of a bulk insert, see [`scope`].
+#[derive(Clone)]
+pub(crate) struct Batch {
+    pub(crate) now: SystemTime,
+    pub(crate) ids: Arc<dyn IdGenerator>,
+}
+
+thread_local! {
+    static BATCH: RefCell<Option<Batch>> = const { RefCell::new(None) };
+}
+
+/// The time of the bulk insert the caller runs in, or of the global config's clock outside of
+/// one.
+pub fn now() -> SystemTime {
+    scoped_now().unwrap_or_else(|| Config::global().clock().now())
+}
+
+/// The next key of the bulk insert the caller runs in, or of the global config's generator
+/// outside of one.
+pub fn next_id() -> u128 {
+    let ids = BATCH.with(|batch| batch.borrow().as_ref().map(|batch| batch.ids.clone()));
+    match ids {
+        Some(ids) => ids.next_id(),
+        None => Config::global().id_generator().next_id(),
+    }
+}
+
+pub(crate) fn scoped_now() -> Option<SystemTime> {
+    BATCH.with(|batch| batch.borrow().as_ref().map(|batch| batch.now))
+}
+
+/// Run `future` with the timestamp and the key source of `batch`.
+pub(crate) async fn scope<F: Future>(batch: Batch, future: F) -> F::Output {
+    Scoped {
+        batch,
+        future: Box::pin(future),
+    }
+    .await
+}
+
+struct Scoped<F> {
+    batch: Batch,
+    future: Pin<Box<F>>,
+}
+
+impl<F: Future> Future for Scoped<F> {
+    type Output = F::Output;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut task::Context<'_>) -> Poll<Self::Output> {
+        struct Restore(Option<Batch>);
+
+        impl Drop for Restore {
+            fn drop(&mut self) {
+                BATCH.with(|batch| *batch.borrow_mut() = self.0.take());
+            }
+        }
+
+        let batch = self.batch.clone();
+        let _restore = Restore(BATCH.with(|current| current.borrow_mut().replace(batch)));
+        self.future.as_mut().poll(cx)
+    }
+}
+
+/// The value bound for an `auto_uuid` field: its own value, or the [`next_id`] if it is `None`.
+#[cfg(feature = "uuid")]
+#[doc(hidden)]
+pub fn auto_uuid(value: &Option<sqlx::types::Uuid>) -> AutoUuid<'_> {
+    AutoUuid(value)
+}
+
+/// Draws the key when it is encoded rather than when it is bound, so binds which are only
+/// counted, like the ones of [`check_binds`](crate::check_binds), use up no keys.
+#[cfg(feature = "uuid")]
+#[doc(hidden)]
+#[derive(Debug)]
+pub struct AutoUuid<'a>(&'a Option<sqlx::types::Uuid>);
+
+#[cfg(feature = "uuid")]
+impl<'q, DB> sqlx::Encode<'q, DB> for AutoUuid<'_>
+where
+    DB: sqlx::Database,
+    sqlx::types::Uuid: sqlx::Encode<'q, DB>,
+{
+    fn encode_by_ref(
+        &self,
+        buf: &mut crate::compat::ArgumentBufferOf<'q, DB>,
+    ) -> sqlx::encode::IsNull {
+        self.0
+            .unwrap_or_else(|| sqlx::types::Uuid::from_u128(next_id()))
+            .encode_by_ref(buf)
+    }
+}
+
+#[cfg(feature = "uuid")]
+impl<DB> sqlx::Type<DB> for AutoUuid<'_>
+where
+    DB: sqlx::Database,
+    sqlx::types::Uuid: sqlx::Type<DB>,
+{
+    fn type_info() -> DB::TypeInfo {
+        <sqlx::types::Uuid as sqlx::Type<DB>>::type_info()
+    }
+
+    fn compatible(ty: &DB::TypeInfo) -> bool {
+        <sqlx::types::Uuid as sqlx::Type<DB>>::compatible(ty)
+    }
+}
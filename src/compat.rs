@@ -0,0 +1,21 @@
+//! The sqlx items whose names change between sqlx versions. The rest of the crate only refers
+//! to these aliases, so following a new sqlx release which e.g. removes `HasArguments` only
+//! touches this module.
+
+use sqlx::{
+    database::HasArguments,
+    query::{Query, QueryAs},
+};
+
+/// The bind arguments of a query for `DB`.
+pub(crate) type ArgumentsOf<'q, DB> = <DB as HasArguments<'q>>::Arguments;
+
+/// The buffer `Encode` writes a bind argument for `DB` to.
+pub(crate) type ArgumentBufferOf<'q, DB> = <DB as HasArguments<'q>>::ArgumentBuffer;
+
+#[cfg(feature = "chaos")]
+pub(crate) type StatementOf<'q, DB> = <DB as sqlx::database::HasStatement<'q>>::Statement;
+
+pub(crate) type QueryOf<'q, DB> = Query<'q, DB, ArgumentsOf<'q, DB>>;
+
+pub(crate) type QueryAsOf<'q, DB, O> = QueryAs<'q, DB, O, ArgumentsOf<'q, DB>>;
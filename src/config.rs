@@ -0,0 +1,227 @@
+use std::{
+    collections::HashMap,
+    fmt,
+    sync::{Arc, Mutex, RwLock},
+};
+
+use sqlx::{Executor, FromRow, IntoArguments};
+
+use crate::{
+    clock::{Clock, IdGenerator, SystemClock, UuidV7},
+    compat::ArgumentsOf,
+    middleware::Middleware,
+    Backend, CacheInvalidator, Capabilities, ChunkGate, Insertable, MemoryBudget, SqlFormat,
+};
+
+static GLOBAL: RwLock<Option<Arc<Config>>> = RwLock::new(None);
+
+/// Settings which apply to every call instead of being passed to each of them.
+///
+/// Install one process-wide with [`Config::set_global`], or hand one to a single builder with
+/// `BulkInsert::config`.
+#[derive(Clone, Default)]
+pub struct Config {
+    middlewares: Vec<Arc<dyn Middleware>>,
+    chunk_gates: Vec<Arc<dyn ChunkGate>>,
+    cache_invalidators: Vec<Arc<dyn CacheInvalidator>>,
+    /// Default chunk sizes by table name.
+    chunk_sizes: HashMap<String, usize>,
+    memory_budget: Option<MemoryBudget>,
+    sql_format: SqlFormat,
+    offload_sql_params: Option<usize>,
+    clock: Option<Arc<dyn Clock>>,
+    id_generator: Option<Arc<dyn IdGenerator>>,
+    /// Capabilities detected per pool, keyed by [`pool_key`]. Shared by clones.
+    capabilities: Arc<Mutex<HashMap<usize, Capabilities>>>,
+}
+
+impl Config {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The global config, or the default one when none was set.
+    pub fn global() -> Arc<Config> {
+        if let Some(config) = GLOBAL.read().unwrap().clone() {
+            return config;
+        }
+        // Kept, so what it caches survives until a config is set.
+        GLOBAL
+            .write()
+            .unwrap()
+            .get_or_insert_with(Default::default)
+            .clone()
+    }
+
+    pub fn set_global(self) {
+        *GLOBAL.write().unwrap() = Some(Arc::new(self));
+    }
+
+    /// Add a middleware, it runs after the ones added before it.
+    pub fn middleware(mut self, middleware: impl Middleware + 'static) -> Self {
+        self.middlewares.push(Arc::new(middleware));
+        self
+    }
+
+    pub fn middlewares(&self) -> &[Arc<dyn Middleware>] {
+        &self.middlewares
+    }
+
+    /// Add a gate every chunk of a bulk operation waits for, after the ones added before it.
+    pub fn chunk_gate(mut self, gate: impl ChunkGate + 'static) -> Self {
+        self.chunk_gates.push(Arc::new(gate));
+        self
+    }
+
+    pub fn chunk_gates(&self) -> &[Arc<dyn ChunkGate>] {
+        &self.chunk_gates
+    }
+
+    pub fn cache_invalidator(mut self, invalidator: impl CacheInvalidator + 'static) -> Self {
+        self.cache_invalidators.push(Arc::new(invalidator));
+        self
+    }
+
+    pub fn cache_invalidators(&self) -> &[Arc<dyn CacheInvalidator>] {
+        &self.cache_invalidators
+    }
+
+    /// Cap the memory of everything that buffers values, like [`Demux`](crate::Demux).
+    pub fn set_memory_budget(&mut self, budget: MemoryBudget) -> &mut Self {
+        self.memory_budget = Some(budget);
+        self
+    }
+
+    pub fn memory_budget(&self) -> Option<&MemoryBudget> {
+        self.memory_budget.as_ref()
+    }
+
+    /// The layout of statements in logs, see [`Statement::formatted_sql`](crate::Statement::formatted_sql).
+    /// The executed SQL is never reformatted.
+    pub fn set_sql_format(&mut self, sql_format: SqlFormat) -> &mut Self {
+        self.sql_format = sql_format;
+        self
+    }
+
+    pub fn sql_format(&self) -> SqlFormat {
+        self.sql_format
+    }
+
+    /// Build bulk insert statements with at least `params` bind parameters on a blocking thread,
+    /// so assembling multi-megabyte SQL doesn't stall the async runtime. Off by default.
+    pub fn set_offload_sql_params(&mut self, params: usize) -> &mut Self {
+        self.offload_sql_params = Some(params);
+        self
+    }
+
+    pub fn offload_sql_params(&self) -> Option<usize> {
+        self.offload_sql_params
+    }
+
+    /// The time of `#[insertable(auto_now)]` fields, the system time by default.
+    pub fn set_clock(&mut self, clock: impl Clock + 'static) -> &mut Self {
+        self.clock = Some(Arc::new(clock));
+        self
+    }
+
+    pub fn clock(&self) -> &dyn Clock {
+        self.clock.as_deref().unwrap_or(&SystemClock)
+    }
+
+    /// The keys of `#[insertable(auto_uuid)]` fields, [`UuidV7`] by default.
+    pub fn set_id_generator(&mut self, id_generator: impl IdGenerator + 'static) -> &mut Self {
+        self.id_generator = Some(Arc::new(id_generator));
+        self
+    }
+
+    pub fn id_generator(&self) -> Arc<dyn IdGenerator> {
+        self.id_generator
+            .clone()
+            .unwrap_or_else(|| Arc::new(UuidV7))
+    }
+
+    /// The chunk size for bulk inserts into `T`'s table which don't set one.
+    pub fn set_chunk_size<T: Insertable>(&mut self, chunk_size: usize) -> &mut Self {
+        self.set_table_chunk_size(T::table_name(), chunk_size)
+    }
+
+    /// Like [`set_chunk_size`](Self::set_chunk_size), for inserts with an overridden table name.
+    pub fn set_table_chunk_size(
+        &mut self,
+        table_name: impl Into<String>,
+        chunk_size: usize,
+    ) -> &mut Self {
+        self.chunk_sizes.insert(table_name.into(), chunk_size);
+        self
+    }
+
+    pub fn chunk_size(&self, table_name: &str) -> Option<usize> {
+        self.chunk_sizes.get(table_name).copied()
+    }
+
+    /// The capabilities of the server behind `pool`, detected on its first use and kept for the
+    /// pool. While detection fails they are [assumed](Capabilities::assumed) and detected again
+    /// on the next call.
+    pub async fn capabilities_of<DB>(&self, pool: &sqlx::Pool<DB>) -> Capabilities
+    where
+        DB: Backend,
+        for<'c> &'c mut DB::Connection: Executor<'c, Database = DB>,
+        (String,): for<'r> FromRow<'r, DB::Row>,
+        for<'q> ArgumentsOf<'q, DB>: IntoArguments<'q, DB>,
+    {
+        if let Some(capabilities) = self.detected_capabilities(pool) {
+            return capabilities;
+        }
+        match Capabilities::detect(pool).await {
+            Ok(capabilities) => {
+                self.capabilities
+                    .lock()
+                    .unwrap()
+                    .insert(pool_key(pool), capabilities.clone());
+                capabilities
+            }
+            Err(_) => Capabilities::assumed::<DB>(),
+        }
+    }
+
+    /// The capabilities detected for `pool` so far, see [`capabilities_of`](Self::capabilities_of).
+    pub fn detected_capabilities<DB: sqlx::Database>(
+        &self,
+        pool: &sqlx::Pool<DB>,
+    ) -> Option<Capabilities> {
+        self.capabilities
+            .lock()
+            .unwrap()
+            .get(&pool_key(pool))
+            .cloned()
+    }
+
+    /// Detect the capabilities of `pool` again on its next use, e.g. after a server upgrade.
+    pub fn forget_capabilities<DB: sqlx::Database>(&self, pool: &sqlx::Pool<DB>) {
+        self.capabilities.lock().unwrap().remove(&pool_key(pool));
+    }
+}
+
+/// The identity of a pool: the address of its shared state, which all its clones point to.
+/// A pool created after another one was dropped may reuse the address, forget the capabilities
+/// of a dropped pool if the next one may connect elsewhere.
+fn pool_key<DB: sqlx::Database>(pool: &sqlx::Pool<DB>) -> usize {
+    pool.connect_options() as *const _ as usize
+}
+
+impl fmt::Debug for Config {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Config")
+            .field("middlewares", &self.middlewares.len())
+            .field("chunk_gates", &self.chunk_gates.len())
+            .field("cache_invalidators", &self.cache_invalidators.len())
+            .field("chunk_sizes", &self.chunk_sizes)
+            .field("memory_budget", &self.memory_budget)
+            .field("sql_format", &self.sql_format)
+            .field("offload_sql_params", &self.offload_sql_params)
+            .field("clock", &self.clock.is_some())
+            .field("id_generator", &self.id_generator.is_some())
+            .field("capabilities", &self.capabilities.lock().unwrap().len())
+            .finish()
+    }
+}
@@ -0,0 +1,287 @@
+//! A conformance suite for a [`Backend`], run against a live database to validate a backend
+//! implementation, e.g. a custom one or a new server version, before relying on it.
+//!
+//! ```ignore
+//! #[tokio::test]
+//! async fn conformance() {
+//!     let mut conn = PgConnection::connect(&std::env::var("DATABASE_URL").unwrap()).await.unwrap();
+//!     sqlx_plus::conformance::run(&mut conn).await.unwrap();
+//! }
+//! ```
+//!
+//! It works in a temporary table, so it needs a single connection rather than a pool.
+
+use std::marker::PhantomData;
+
+use anyhow::Context;
+use sqlx::{Executor, FromRow, IntoArguments};
+
+use crate::{
+    bulk_insert_report_with_options, compat::ArgumentsOf, dialect, insert, insert_all, Backend,
+    BulkInsertOptions, Dialect, Insertable, QueryBindExt,
+};
+
+/// Texts which break naive quoting or placeholder handling.
+const TEXTS: &[&str] = &["plain", "it's", "back\\slash", "? and $1", "ünïcödé", ""];
+
+/// A row of the conformance table, with a column which is a reserved word.
+struct Probe<DB> {
+    n: i64,
+    text: String,
+    database: PhantomData<fn() -> DB>,
+}
+
+impl<DB> Probe<DB> {
+    fn new(n: usize) -> Self {
+        Probe {
+            n: n as i64,
+            text: TEXTS[n % TEXTS.len()].to_string(),
+            database: PhantomData,
+        }
+    }
+}
+
+impl<DB> Insertable for Probe<DB>
+where
+    DB: Backend,
+    i64: for<'q> sqlx::Encode<'q, DB> + sqlx::Type<DB>,
+    String: for<'q> sqlx::Encode<'q, DB> + sqlx::Type<DB>,
+{
+    type Database = DB;
+
+    const COLUMN_COUNT: usize = 2;
+
+    fn table_name() -> &'static str {
+        match DB::DIALECT {
+            Dialect::Mssql => "#sqlx_plus_conformance",
+            Dialect::Sqlite | Dialect::MySql | Dialect::Postgres => "sqlx_plus_conformance",
+        }
+    }
+
+    fn insert_columns() -> &'static [&'static str] {
+        match DB::DIALECT {
+            Dialect::MySql => &["n", "`order`"],
+            Dialect::Mssql => &["n", "[order]"],
+            Dialect::Sqlite | Dialect::Postgres => &["n", "\"order\""],
+        }
+    }
+
+    fn bind_fields<'q, Q>(&'q self, q: Q) -> Q
+    where
+        Q: QueryBindExt<'q, Self::Database>,
+    {
+        q.bind(self.n).bind(&self.text)
+    }
+
+    fn bind_field<'q, Q>(&'q self, column: &str, q: Q) -> Q
+    where
+        Q: QueryBindExt<'q, Self::Database>,
+    {
+        match Self::insert_columns().iter().position(|x| *x == column) {
+            Some(0) => q.bind(self.n),
+            Some(_) => q.bind(&self.text),
+            None => q,
+        }
+    }
+}
+
+/// Run the whole suite: [`check_placeholders`], then single, multi-row and bulk inserts at the
+/// chunk size boundaries into a temporary table, reading every row back. Fails with the first
+/// check which does not hold.
+pub async fn run<DB, E>(executor: &mut E) -> anyhow::Result<()>
+where
+    DB: Backend,
+    for<'e> &'e mut E: Executor<'e, Database = DB>,
+    for<'q> ArgumentsOf<'q, DB>: IntoArguments<'q, DB>,
+    i64: for<'q> sqlx::Encode<'q, DB> + sqlx::Type<DB>,
+    String: for<'q> sqlx::Encode<'q, DB> + sqlx::Type<DB>,
+    (i64, String): for<'r> FromRow<'r, DB::Row>,
+{
+    check_placeholders::<DB>()?;
+
+    let table = Probe::<DB>::table_name();
+    let order = Probe::<DB>::insert_columns()[1];
+    let create = match DB::DIALECT {
+        Dialect::Sqlite | Dialect::Postgres => format!(
+            "CREATE TEMP TABLE {} (n BIGINT NOT NULL, {} VARCHAR(255) NOT NULL)",
+            table, order
+        ),
+        Dialect::MySql => format!(
+            "CREATE TEMPORARY TABLE {} (n BIGINT NOT NULL, {} VARCHAR(255) NOT NULL)",
+            table, order
+        ),
+        Dialect::Mssql => format!(
+            "CREATE TABLE {} (n BIGINT NOT NULL, {} NVARCHAR(255) NOT NULL)",
+            table, order
+        ),
+    };
+    sqlx::query(&create)
+        .execute(&mut *executor)
+        .await
+        .context("cannot create the conformance table")?;
+
+    let checked = async {
+        check_inserts(&mut *executor)
+            .await
+            .context("single and multi-row inserts")?;
+        check_bulk_inserts(&mut *executor)
+            .await
+            .context("bulk inserts")
+    }
+    .await;
+
+    let dropped = sqlx::query(&format!("DROP TABLE {}", table))
+        .execute(&mut *executor)
+        .await;
+    checked?;
+    dropped?;
+
+    Ok(())
+}
+
+/// Check that the placeholders of `DB` are the ones of its [`Dialect`]: numbered on from
+/// `start_num` on PostgreSQL, `?` elsewhere, one group per row.
+pub fn check_placeholders<DB: Backend>() -> anyhow::Result<()> {
+    for (num, start_num) in [(1, 1), (3, 1), (3, 4)] {
+        let mut expected = String::new();
+        dialect::write_placeholders(&mut expected, DB::DIALECT, num, start_num);
+        let actual = DB::placeholders(num, Some(start_num));
+        anyhow::ensure!(
+            actual == expected,
+            "{} placeholders from {} are {:?}, expected {:?}",
+            num,
+            start_num,
+            actual,
+            expected
+        );
+    }
+
+    for (rows, columns) in [(1, 1), (1, 3), (3, 1), (3, 2)] {
+        let expected = dialect::placeholders_for_values(DB::DIALECT, rows, columns);
+        let mut actual = String::new();
+        DB::write_placeholders_for_values(&mut actual, rows, columns, 1);
+        anyhow::ensure!(
+            actual == expected,
+            "placeholders for {} rows of {} columns are {:?}, expected {:?}",
+            rows,
+            columns,
+            actual,
+            expected
+        );
+    }
+
+    Ok(())
+}
+
+async fn check_inserts<DB, E>(executor: &mut E) -> anyhow::Result<()>
+where
+    DB: Backend,
+    for<'e> &'e mut E: Executor<'e, Database = DB>,
+    for<'q> ArgumentsOf<'q, DB>: IntoArguments<'q, DB>,
+    i64: for<'q> sqlx::Encode<'q, DB> + sqlx::Type<DB>,
+    String: for<'q> sqlx::Encode<'q, DB> + sqlx::Type<DB>,
+    (i64, String): for<'r> FromRow<'r, DB::Row>,
+{
+    let result = insert(&mut *executor, &Probe::<DB>::new(0)).await?;
+    anyhow::ensure!(
+        DB::rows_affected(&result) == 1,
+        "a single insert affected {} rows",
+        DB::rows_affected(&result)
+    );
+
+    let values: Vec<_> = (1..TEXTS.len()).map(Probe::<DB>::new).collect();
+    let result = insert_all(&mut *executor, &values).await?;
+    anyhow::ensure!(
+        DB::rows_affected(&result) == values.len() as u64,
+        "a multi-row insert of {} rows affected {} rows",
+        values.len(),
+        DB::rows_affected(&result)
+    );
+
+    check_rows(&mut *executor, TEXTS.len()).await
+}
+
+async fn check_bulk_inserts<DB, E>(executor: &mut E) -> anyhow::Result<()>
+where
+    DB: Backend,
+    for<'e> &'e mut E: Executor<'e, Database = DB>,
+    for<'q> ArgumentsOf<'q, DB>: IntoArguments<'q, DB>,
+    i64: for<'q> sqlx::Encode<'q, DB> + sqlx::Type<DB>,
+    String: for<'q> sqlx::Encode<'q, DB> + sqlx::Type<DB>,
+    (i64, String): for<'r> FromRow<'r, DB::Row>,
+{
+    let largest = BulkInsertOptions::default().chunk_size::<Probe<DB>>()?;
+
+    for chunk_size in [1, 2, largest] {
+        let options = BulkInsertOptions {
+            chunk_size: Some(chunk_size),
+            verify_rows_affected: true,
+            ..Default::default()
+        };
+
+        for rows in [chunk_size - 1, chunk_size, chunk_size + 1] {
+            if rows == 0 {
+                continue;
+            }
+            let values: Vec<_> = (0..rows).map(Probe::<DB>::new).collect();
+            let report = bulk_insert_report_with_options(&mut *executor, &values, &options)
+                .await
+                .with_context(|| format!("{} rows in chunks of {}", rows, chunk_size))?;
+            anyhow::ensure!(
+                report.chunks == rows.div_ceil(chunk_size),
+                "{} rows in chunks of {} took {} statements",
+                rows,
+                chunk_size,
+                report.chunks
+            );
+            check_rows(&mut *executor, rows)
+                .await
+                .with_context(|| format!("{} rows in chunks of {}", rows, chunk_size))?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Check that the table holds `Probe::new(0..rows)`, then empty it.
+async fn check_rows<DB, E>(executor: &mut E, rows: usize) -> anyhow::Result<()>
+where
+    DB: Backend,
+    for<'e> &'e mut E: Executor<'e, Database = DB>,
+    for<'q> ArgumentsOf<'q, DB>: IntoArguments<'q, DB>,
+    i64: for<'q> sqlx::Encode<'q, DB> + sqlx::Type<DB>,
+    String: for<'q> sqlx::Encode<'q, DB> + sqlx::Type<DB>,
+    (i64, String): for<'r> FromRow<'r, DB::Row>,
+{
+    let table = Probe::<DB>::table_name();
+    let select = format!(
+        "SELECT {} FROM {} ORDER BY n",
+        Probe::<DB>::insert_columns().join(","),
+        table
+    );
+    let read: Vec<(i64, String)> = sqlx::query_as(&select).fetch_all(&mut *executor).await?;
+
+    anyhow::ensure!(
+        read.len() == rows,
+        "expected {} rows back, got {}",
+        rows,
+        read.len()
+    );
+    for (i, (n, text)) in read.into_iter().enumerate() {
+        let expected = Probe::<DB>::new(i);
+        anyhow::ensure!(
+            n == expected.n && text == expected.text,
+            "expected row ({}, {:?}), got ({}, {:?})",
+            expected.n,
+            expected.text,
+            n,
+            text
+        );
+    }
+
+    sqlx::query(&format!("DELETE FROM {}", table))
+        .execute(&mut *executor)
+        .await?;
+
+    Ok(())
+}
@@ -0,0 +1,143 @@
+//! A request-scoped [`Context`] every statement of a task inherits.
+//!
+//! The web framework layer scopes each request's future with the request deadline, and every
+//! statement sqlx-plus executes inside it fails once the deadline passed, without passing
+//! timeouts to each call:
+//!
+//! ```ignore
+//! async fn handle(request: Request) -> Response {
+//!     Context::new()
+//!         .with_timeout(Duration::from_secs(2))
+//!         .scope(async move { ... pool.bulk_insert(&rows).await ... })
+//!         .await
+//! }
+//! ```
+//!
+//! A statement started after the deadline fails with [`DeadlineExceeded`]. A statement still
+//! running at the deadline is dropped and fails with an I/O error of kind `TimedOut`; the
+//! connection may be unusable afterwards, like after any cancelled query.
+//!
+//! The context follows the scoped future, not the task: futures spawned from it don't inherit
+//! it.
+
+use std::{
+    cell::Cell,
+    fmt,
+    future::Future,
+    pin::Pin,
+    task::{self, Poll},
+    time::{Duration, Instant},
+};
+
+use crate::runtime;
+
+thread_local! {
+    static CURRENT: Cell<Option<Context>> = const { Cell::new(None) };
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Context {
+    deadline: Option<Instant>,
+}
+
+impl Context {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The context of the scope the caller runs in, if any.
+    pub fn current() -> Option<Context> {
+        CURRENT.with(Cell::get)
+    }
+
+    pub fn with_deadline(mut self, deadline: Instant) -> Self {
+        self.deadline = Some(deadline);
+        self
+    }
+
+    pub fn with_timeout(self, timeout: Duration) -> Self {
+        self.with_deadline(Instant::now() + timeout)
+    }
+
+    pub fn deadline(&self) -> Option<Instant> {
+        self.deadline
+    }
+
+    /// The time left until the deadline, zero once it passed.
+    pub fn remaining(&self) -> Option<Duration> {
+        self.deadline
+            .map(|deadline| deadline.saturating_duration_since(Instant::now()))
+    }
+
+    /// Run `future` with this context. It replaces the context of an enclosing scope.
+    pub async fn scope<F: Future>(self, future: F) -> F::Output {
+        Scoped {
+            context: self,
+            future: Box::pin(future),
+        }
+        .await
+    }
+
+    /// Fail with [`DeadlineExceeded`] if the deadline passed.
+    pub(crate) fn check(&self) -> Result<(), DeadlineExceeded> {
+        match self.deadline {
+            Some(deadline) if Instant::now() >= deadline => Err(DeadlineExceeded { deadline }),
+            _ => Ok(()),
+        }
+    }
+}
+
+struct Scoped<F> {
+    context: Context,
+    future: Pin<Box<F>>,
+}
+
+impl<F: Future> Future for Scoped<F> {
+    type Output = F::Output;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut task::Context<'_>) -> Poll<Self::Output> {
+        struct Restore(Option<Context>);
+
+        impl Drop for Restore {
+            fn drop(&mut self) {
+                CURRENT.with(|current| current.set(self.0));
+            }
+        }
+
+        let _restore = Restore(CURRENT.with(|current| current.replace(Some(self.context))));
+        self.future.as_mut().poll(cx)
+    }
+}
+
+/// A statement was about to start after the deadline of the current [`Context`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeadlineExceeded {
+    pub deadline: Instant,
+}
+
+impl fmt::Display for DeadlineExceeded {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "the deadline passed {:?} ago", self.deadline.elapsed())
+    }
+}
+
+impl std::error::Error for DeadlineExceeded {}
+
+/// Run `future`, failing it when the deadline of the current context passes first.
+pub(crate) async fn with_deadline<R, F>(future: F) -> Result<R, sqlx::Error>
+where
+    F: Future<Output = Result<R, sqlx::Error>>,
+{
+    let remaining = match Context::current().and_then(|context| context.remaining()) {
+        Some(remaining) => remaining,
+        None => return future.await,
+    };
+
+    match runtime::timeout(remaining, future).await {
+        Some(result) => result,
+        None => Err(sqlx::Error::Io(std::io::Error::new(
+            std::io::ErrorKind::TimedOut,
+            "the deadline of the sqlx_plus::Context passed",
+        ))),
+    }
+}
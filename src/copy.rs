@@ -0,0 +1,152 @@
+use sqlx::{Executor, FromRow, IntoArguments, Row};
+
+use crate::{
+    bulk_insert_report_with_options, compat::ArgumentsOf, Anonymizer, Backend, BulkInsertOptions,
+    Dialect, Insertable, TableName,
+};
+
+/// Options for [`copy_table`].
+#[derive(Debug, Clone)]
+pub struct CopyOptions {
+    /// The table to read from. Defaults to `T`'s table.
+    pub source_table: Option<TableName>,
+    /// The integer key the rows are paginated by. Defaults to `id`.
+    pub key_column: Option<String>,
+    /// Rows read per page. Defaults to 1000.
+    pub batch_size: usize,
+    /// Only copy rows with a greater key, to resume from the `last_key` of an earlier run.
+    pub resume_after: Option<i64>,
+    /// Options of the bulk inserts into the destination.
+    pub insert: BulkInsertOptions,
+    /// Replace the `#[insertable(anonymize = "...")]` fields by fakes, for masked staging data.
+    pub anonymizer: Option<Anonymizer>,
+}
+
+impl Default for CopyOptions {
+    fn default() -> Self {
+        CopyOptions {
+            source_table: None,
+            key_column: None,
+            batch_size: 1000,
+            resume_after: None,
+            insert: BulkInsertOptions::default(),
+            anonymizer: None,
+        }
+    }
+}
+
+/// How far a [`copy_table`] got.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct CopyProgress {
+    /// Rows inserted into the destination.
+    pub rows: u64,
+    /// The key of the last copied row, pass it as `resume_after` to continue after a failure.
+    pub last_key: Option<i64>,
+}
+
+/// Copy the rows of `T`'s table from `source` to `destination`, which may be another kind of
+/// database.
+///
+/// Pages of `batch_size` rows are read in key order with keyset pagination and bulk inserted,
+/// `on_progress` is called after each page. The pages are not copied in a single transaction,
+/// on failure resume from the last reported `last_key`. With an `anonymizer` the rows are
+/// masked before they are inserted, see [`crate::anonymize`].
+///
+/// ```ignore
+/// let progress = copy_table::<User, _, _, _>(&mut mysql, &mut pg, &CopyOptions::default(), |p| {
+///     log::info!("copied {} rows up to id {:?}", p.rows, p.last_key)
+/// })
+/// .await?;
+/// ```
+pub async fn copy_table<T, S, D, SDB>(
+    source: &mut S,
+    destination: &mut D,
+    options: &CopyOptions,
+    mut on_progress: impl FnMut(&CopyProgress) + Send,
+) -> anyhow::Result<CopyProgress>
+where
+    T: Insertable + for<'r> FromRow<'r, SDB::Row> + Send + Sync + Unpin,
+    T::Database: Backend,
+    SDB: Backend,
+    for<'e> &'e mut S: Executor<'e, Database = SDB>,
+    for<'q> ArgumentsOf<'q, SDB>: IntoArguments<'q, SDB>,
+    i64: for<'q> sqlx::Encode<'q, SDB> + for<'r> sqlx::Decode<'r, SDB> + sqlx::Type<SDB>,
+    usize: sqlx::ColumnIndex<SDB::Row>,
+    for<'e> &'e mut D: Executor<'e, Database = T::Database>,
+    for<'q> ArgumentsOf<'q, T::Database>: IntoArguments<'q, T::Database>,
+{
+    let source_table = options
+        .source_table
+        .as_ref()
+        .map_or(T::table_name(), TableName::as_str);
+    let key_column = options.key_column.as_deref().unwrap_or("id");
+    let batch_size = options.batch_size.max(1);
+    if let Some(anonymizer) = &options.anonymizer {
+        anonymizer.check::<T>()?;
+    }
+
+    let mut columns = key_column.to_owned();
+    for column in T::insert_columns() {
+        if *column != key_column {
+            columns.push(',');
+            columns.push_str(column);
+        }
+    }
+
+    let mut placeholder = String::new();
+    SDB::write_placeholders(&mut placeholder, 1, 1);
+    let sql = match SDB::DIALECT {
+        Dialect::Mssql => format!(
+            "SELECT TOP {} {} FROM {} WHERE {key} > {} ORDER BY {key}",
+            batch_size,
+            columns,
+            source_table,
+            placeholder,
+            key = key_column,
+        ),
+        _ => format!(
+            "SELECT {} FROM {} WHERE {key} > {} ORDER BY {key} LIMIT {}",
+            columns,
+            source_table,
+            placeholder,
+            batch_size,
+            key = key_column,
+        ),
+    };
+
+    let mut progress = CopyProgress {
+        rows: 0,
+        last_key: options.resume_after,
+    };
+
+    loop {
+        let rows = sqlx::query(&sql)
+            .bind(progress.last_key.unwrap_or(i64::MIN))
+            .fetch_all(&mut *source)
+            .await?;
+        let last_key = match rows.last() {
+            Some(row) => row.try_get::<i64, _>(0)?,
+            None => break,
+        };
+        let mut values = rows
+            .iter()
+            .map(|row| T::from_row(row))
+            .collect::<Result<Vec<_>, _>>()?;
+        if let Some(anonymizer) = &options.anonymizer {
+            for value in &mut values {
+                value.anonymize(anonymizer)?;
+            }
+        }
+
+        let report = bulk_insert_report_with_options(destination, &values, &options.insert).await?;
+        progress.rows += report.rows_affected;
+        progress.last_key = Some(last_key);
+        on_progress(&progress);
+
+        if rows.len() < batch_size {
+            break;
+        }
+    }
+
+    Ok(progress)
+}
@@ -0,0 +1,66 @@
+//! Decimal columns: `rust_decimal::Decimal` with the `decimal` feature, `bigdecimal::BigDecimal`
+//! with the `bigdecimal` one.
+//!
+//! Both bind like any other field. `#[insertable(scale = 2)]` rounds the value to the scale of
+//! the column before binding, half away from zero, so `NUMERIC(12, 2)` gets `10.01` for `10.005`
+//! on every database instead of whatever the server's rounding or truncation does:
+//!
+//! ```ignore
+//! #[derive(sqlx_plus::Insertable)]
+//! #[insertable(sqlx::Postgres, "payment")]
+//! struct Payment {
+//!     invoice_id: i64,
+//!     #[insertable(scale = 2)]
+//!     amount: Decimal,
+//!     #[insertable(scale = 6)]
+//!     exchange_rate: Option<Decimal>,
+//! }
+//!
+//! tx.bulk_insert(&payments).await?;
+//! ```
+//!
+//! Rounded values also carry exactly `scale` digits, `10` is bound as `10.00`.
+
+/// A decimal which `#[insertable(scale = ...)]` can round.
+pub trait DecimalScale {
+    type Output;
+
+    /// The value rounded half away from zero to `scale` fractional digits.
+    fn round_to_scale(&self, scale: u32) -> Self::Output;
+}
+
+/// The normalizer generated for `#[insertable(scale = ...)]`.
+pub fn round_to_scale<T: DecimalScale + ?Sized>(value: &T, scale: u32) -> T::Output {
+    value.round_to_scale(scale)
+}
+
+#[cfg(feature = "decimal")]
+impl DecimalScale for rust_decimal::Decimal {
+    type Output = rust_decimal::Decimal;
+
+    /// Scales above 28, the most a `Decimal` holds, are clamped.
+    fn round_to_scale(&self, scale: u32) -> Self::Output {
+        let mut value = self
+            .round_dp_with_strategy(scale, rust_decimal::RoundingStrategy::MidpointAwayFromZero);
+        value.rescale(scale);
+        value
+    }
+}
+
+#[cfg(feature = "bigdecimal")]
+impl DecimalScale for bigdecimal::BigDecimal {
+    type Output = bigdecimal::BigDecimal;
+
+    fn round_to_scale(&self, scale: u32) -> Self::Output {
+        let scale = i64::from(scale);
+        self.round(scale).with_scale(scale)
+    }
+}
+
+impl<T: DecimalScale> DecimalScale for Option<T> {
+    type Output = Option<T::Output>;
+
+    fn round_to_scale(&self, scale: u32) -> Self::Output {
+        self.as_ref().map(|x| x.round_to_scale(scale))
+    }
+}
@@ -0,0 +1,258 @@
+use async_trait::async_trait;
+
+use crate::{
+    Backend, BudgetPolicy, BulkInsertOptions, Config, Insertable, Inserter, MemoryBudget,
+    MemoryBudgetExceeded, SizeEstimate,
+};
+
+/// Bulk inserts a stream of mixed values into one table per kind of value.
+///
+/// Each route extracts the values of one table from the stream items, typically the variants of
+/// an enum replayed from an event log. Values are buffered per table, and a table's buffer is
+/// inserted once it holds `buffer_size` values. The rest is inserted by [`flush`](Self::flush),
+/// table by table in the order the routes were added, so add referenced tables first.
+///
+/// Buffered values count against the [`MemoryBudget`] of the global config, or the one set with
+/// [`memory_budget`](Self::memory_budget). When a value doesn't fit, the [`BudgetPolicy`] either
+/// inserts all buffers early or rejects the value.
+///
+/// ```ignore
+/// let mut demux = Demux::<Event, Postgres>::new()
+///     .route(|event| match event {
+///         Event::UserCreated(user) => Ok(user),
+///         event => Err(event),
+///     })
+///     .route(|event| match event {
+///         Event::OrderPlaced(order) => Ok(order),
+///         event => Err(event),
+///     });
+///
+/// for event in events {
+///     demux.push(&mut tx, event).await?;
+/// }
+/// demux.flush(&mut tx).await?;
+/// ```
+pub struct Demux<V, DB> {
+    routes: Vec<Box<dyn Route<V, DB>>>,
+    buffer_size: usize,
+    budget: Option<MemoryBudget>,
+    policy: BudgetPolicy,
+    /// Bytes acquired from the budget, per route.
+    reserved: Vec<usize>,
+}
+
+impl<V, DB> Demux<V, DB>
+where
+    V: Send + 'static,
+    DB: Backend,
+    for<'c> &'c mut DB::Connection: Inserter<DB>,
+{
+    pub fn new() -> Self {
+        Demux {
+            routes: Vec::new(),
+            buffer_size: 10_000,
+            budget: Config::global().memory_budget().cloned(),
+            policy: BudgetPolicy::default(),
+            reserved: Vec::new(),
+        }
+    }
+
+    /// Values buffered per table before they are inserted. Defaults to 10000.
+    pub fn buffer_size(mut self, buffer_size: usize) -> Self {
+        self.buffer_size = buffer_size.max(1);
+        self
+    }
+
+    /// Overrides the memory budget of the global config.
+    pub fn memory_budget(mut self, budget: MemoryBudget) -> Self {
+        self.budget = Some(budget);
+        self
+    }
+
+    pub fn budget_policy(mut self, policy: BudgetPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// Add a table. `extract` returns the value to insert, or hands the item back when it
+    /// belongs to another table.
+    ///
+    /// Values are charged their shallow size against the memory budget, use
+    /// [`route_estimated`](Self::route_estimated) for values owning heap data.
+    pub fn route<T, F>(self, extract: F) -> Self
+    where
+        T: Insertable<Database = DB> + Send + Sync + 'static,
+        F: Fn(V) -> Result<T, V> + Send + Sync + 'static,
+    {
+        self.add_route(extract, |_: &T| std::mem::size_of::<T>())
+    }
+
+    /// Like [`route`](Self::route), charging values their [`SizeEstimate`].
+    pub fn route_estimated<T, F>(self, extract: F) -> Self
+    where
+        T: Insertable<Database = DB> + SizeEstimate + Send + Sync + 'static,
+        F: Fn(V) -> Result<T, V> + Send + Sync + 'static,
+    {
+        self.add_route(extract, T::estimated_size)
+    }
+
+    fn add_route<T, F>(mut self, extract: F, estimate: fn(&T) -> usize) -> Self
+    where
+        T: Insertable<Database = DB> + Send + Sync + 'static,
+        F: Fn(V) -> Result<T, V> + Send + Sync + 'static,
+    {
+        self.routes.push(Box::new(TableRoute {
+            extract,
+            estimate,
+            buffer: Vec::new(),
+        }));
+        self.reserved.push(0);
+        self
+    }
+
+    /// Buffer `value` for its table and insert the table's buffer if it is full. Returns the
+    /// number of inserted rows.
+    ///
+    /// Fails with [`MemoryBudgetExceeded`] when the value is shed.
+    pub async fn push(&mut self, conn: &mut DB::Connection, value: V) -> anyhow::Result<u64> {
+        let mut value = value;
+        let mut accepted = None;
+
+        for (index, route) in self.routes.iter_mut().enumerate() {
+            match route.offer(value) {
+                Ok(offered) => {
+                    accepted = Some((index, offered));
+                    break;
+                }
+                Err(rejected) => value = rejected,
+            }
+        }
+
+        let (index, (buffered, size)) = match accepted {
+            Some(accepted) => accepted,
+            None => anyhow::bail!("no route accepts the value"),
+        };
+
+        if let Some(budget) = &self.budget {
+            if budget.try_acquire(size) {
+                self.reserved[index] += size;
+            } else {
+                match self.policy {
+                    // The value is inserted along with the rest, so it is never charged.
+                    BudgetPolicy::Backpressure => return self.flush(conn).await,
+                    BudgetPolicy::Shed => {
+                        self.routes[index].discard_last();
+                        return Err(MemoryBudgetExceeded {
+                            requested: size,
+                            used: budget.used(),
+                            limit: budget.limit(),
+                        }
+                        .into());
+                    }
+                }
+            }
+        }
+
+        if buffered >= self.buffer_size {
+            return self.flush_route(index, conn).await;
+        }
+        Ok(0)
+    }
+
+    /// Insert everything buffered. Returns the number of inserted rows.
+    pub async fn flush(&mut self, conn: &mut DB::Connection) -> anyhow::Result<u64> {
+        let mut rows = 0;
+        for index in 0..self.routes.len() {
+            rows += self.flush_route(index, conn).await?;
+        }
+        Ok(rows)
+    }
+
+    async fn flush_route(
+        &mut self,
+        index: usize,
+        conn: &mut DB::Connection,
+    ) -> anyhow::Result<u64> {
+        let rows = self.routes[index].flush(conn).await?;
+        self.release(index);
+        Ok(rows)
+    }
+}
+
+impl<V, DB> Demux<V, DB> {
+    fn release(&mut self, index: usize) {
+        let reserved = std::mem::take(&mut self.reserved[index]);
+        if let Some(budget) = &self.budget {
+            budget.release(reserved);
+        }
+    }
+}
+
+impl<V, DB> Drop for Demux<V, DB> {
+    fn drop(&mut self) {
+        for index in 0..self.reserved.len() {
+            self.release(index);
+        }
+    }
+}
+
+impl<V, DB> Default for Demux<V, DB>
+where
+    V: Send + 'static,
+    DB: Backend,
+    for<'c> &'c mut DB::Connection: Inserter<DB>,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+trait Route<V, DB: Backend>: Send + Sync {
+    /// Buffer the value if it belongs to this route and return the number of buffered values
+    /// and the estimated size of the value.
+    fn offer(&mut self, value: V) -> Result<(usize, usize), V>;
+
+    fn discard_last(&mut self);
+
+    async fn flush(&mut self, conn: &mut DB::Connection) -> anyhow::Result<u64>;
+}
+
+struct TableRoute<T, F> {
+    extract: F,
+    estimate: fn(&T) -> usize,
+    buffer: Vec<T>,
+}
+
+#[async_trait]
+impl<V, DB, T, F> Route<V, DB> for TableRoute<T, F>
+where
+    DB: Backend,
+    T: Insertable<Database = DB> + Send + Sync,
+    F: Fn(V) -> Result<T, V> + Send + Sync,
+    for<'c> &'c mut DB::Connection: Inserter<DB>,
+{
+    fn offer(&mut self, value: V) -> Result<(usize, usize), V> {
+        let value = (self.extract)(value)?;
+        let size = (self.estimate)(&value);
+        self.buffer.push(value);
+        Ok((self.buffer.len(), size))
+    }
+
+    fn discard_last(&mut self) {
+        self.buffer.pop();
+    }
+
+    async fn flush(&mut self, conn: &mut DB::Connection) -> anyhow::Result<u64> {
+        if self.buffer.is_empty() {
+            return Ok(0);
+        }
+
+        let report = conn
+            .bulk_insert_report_with_options(&self.buffer, &BulkInsertOptions::default())
+            .await?;
+        self.buffer.clear();
+
+        Ok(report.rows_affected)
+    }
+}
@@ -0,0 +1,67 @@
+//! SQL dialects, for building SQL strings outside of the `sqlx::Database` types, e.g. for logs
+//! or generated SQL files.
+
+use crate::write_placeholders_postgres;
+
+/// SQL dialects supported by sqlx-plus.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Dialect {
+    Sqlite,
+    MySql,
+    Postgres,
+    Mssql,
+}
+
+/// The literal for `value`. MSSQL has no `TRUE`/`FALSE` and SQLite only since 3.23, both use
+/// `1`/`0`.
+pub fn sql_bool(dialect: Dialect, value: bool) -> &'static str {
+    match (dialect, value) {
+        (Dialect::Sqlite | Dialect::Mssql, true) => "1",
+        (Dialect::Sqlite | Dialect::Mssql, false) => "0",
+        (Dialect::MySql | Dialect::Postgres, true) => "TRUE",
+        (Dialect::MySql | Dialect::Postgres, false) => "FALSE",
+    }
+}
+
+/// An expression for the current timestamp.
+///
+/// SQLite's `CURRENT_TIMESTAMP` drops the fractional seconds, so it is formatted explicitly
+/// (in UTC, like `CURRENT_TIMESTAMP`). MySQL keeps microseconds with `CURRENT_TIMESTAMP(6)`.
+pub fn sql_now(dialect: Dialect) -> &'static str {
+    match dialect {
+        Dialect::Sqlite => "STRFTIME('%Y-%m-%d %H:%M:%f', 'now')",
+        Dialect::MySql => "CURRENT_TIMESTAMP(6)",
+        Dialect::Postgres => "CURRENT_TIMESTAMP",
+        Dialect::Mssql => "SYSDATETIME()",
+    }
+}
+
+/// Placeholders like `?,?,?`, or `$1,$2,$3` on PostgreSQL.
+pub fn placeholders(dialect: Dialect, num: usize) -> String {
+    let mut buf = String::with_capacity(num * 3);
+    write_placeholders(&mut buf, dialect, num, 1);
+    buf
+}
+
+/// Append `num` placeholders to `buf`. `start_num` is the number of the first placeholder on
+/// PostgreSQL and ignored elsewhere, like in [`crate::PlaceHolders`].
+pub fn write_placeholders(buf: &mut String, dialect: Dialect, num: usize, start_num: usize) {
+    match dialect {
+        Dialect::Postgres => write_placeholders_postgres(buf, num, start_num),
+        Dialect::Sqlite | Dialect::MySql | Dialect::Mssql => crate::write_placeholders(buf, num),
+    }
+}
+
+/// `rows` groups of `columns` placeholders like `(?,?),(?,?)`, or `($1,$2),($3,$4)` on PostgreSQL.
+pub fn placeholders_for_values(dialect: Dialect, rows: usize, columns: usize) -> String {
+    let mut buf = String::with_capacity(rows * (columns * 3 + 3));
+    for row in 0..rows {
+        if row > 0 {
+            buf.push(',');
+        }
+        buf.push('(');
+        write_placeholders(&mut buf, dialect, columns, 1 + row * columns);
+        buf.push(')');
+    }
+    buf
+}
@@ -0,0 +1,142 @@
+use futures_util::future;
+use sqlx::{Executor, IntoArguments, Pool};
+
+use crate::{
+    bulk_insert_report_with_options, compat::ArgumentsOf, Backend, BulkInsertOptions,
+    BulkInsertReport, Insertable,
+};
+
+/// Which sides of a [`DualWrite`] commit when a side fails.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum DualWritePolicy {
+    /// Commit only if both sides succeed.
+    #[default]
+    Both,
+    /// Commit the primary when it succeeds, the secondary only along with it. For migrations
+    /// where the secondary is catching up.
+    PrimaryWins,
+    /// Commit every side which succeeds. For migrations where either side may lag.
+    Either,
+}
+
+/// Insert the same values into two databases of the same kind, e.g. the old and the new
+/// PostgreSQL instance during a migration.
+///
+/// Both inserts run in a transaction of their own, then the [`DualWritePolicy`] decides which to
+/// commit. This is a best-effort two-phase commit: if the second commit fails after the first
+/// succeeded, the sides diverge and the report says so.
+///
+/// ```ignore
+/// let report = DualWrite::new(old_pool.clone(), new_pool.clone())
+///     .policy(DualWritePolicy::PrimaryWins)
+///     .execute(&users)
+///     .await?;
+/// if report.diverged() {
+///     reconcile_later(&users, report.secondary.unwrap_err());
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct DualWrite<DB: sqlx::Database> {
+    primary: Pool<DB>,
+    secondary: Pool<DB>,
+    policy: DualWritePolicy,
+    options: BulkInsertOptions,
+}
+
+/// What a [`DualWrite`] did on each side.
+#[derive(Debug)]
+pub struct DualWriteReport {
+    /// The insert into the primary, or why it or its commit failed.
+    pub primary: anyhow::Result<BulkInsertReport>,
+    pub secondary: anyhow::Result<BulkInsertReport>,
+    pub primary_committed: bool,
+    pub secondary_committed: bool,
+}
+
+impl DualWriteReport {
+    /// Whether the values were committed on one side only, which needs reconciliation.
+    pub fn diverged(&self) -> bool {
+        self.primary_committed != self.secondary_committed
+    }
+}
+
+impl<DB: Backend> DualWrite<DB> {
+    pub fn new(primary: Pool<DB>, secondary: Pool<DB>) -> Self {
+        DualWrite {
+            primary,
+            secondary,
+            policy: DualWritePolicy::default(),
+            options: BulkInsertOptions::default(),
+        }
+    }
+
+    pub fn policy(mut self, policy: DualWritePolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// The options of the bulk inserts on both sides.
+    pub fn options(mut self, options: BulkInsertOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Insert `values` into both sides. Fails when no side committed, with the error of the
+    /// primary if it failed and of the secondary otherwise.
+    pub async fn execute<T>(&self, values: &[T]) -> anyhow::Result<DualWriteReport>
+    where
+        T: Insertable<Database = DB> + Sync,
+        for<'e> &'e mut DB::Connection: Executor<'e, Database = DB>,
+        for<'q> ArgumentsOf<'q, DB>: IntoArguments<'q, DB>,
+    {
+        let (mut primary_tx, mut secondary_tx) =
+            future::try_join(self.primary.begin(), self.secondary.begin()).await?;
+        let (mut primary, mut secondary) = future::join(
+            bulk_insert_report_with_options(&mut *primary_tx, values, &self.options),
+            bulk_insert_report_with_options(&mut *secondary_tx, values, &self.options),
+        )
+        .await;
+
+        let (commit_primary, commit_secondary) = match self.policy {
+            DualWritePolicy::Both => {
+                let both = primary.is_ok() && secondary.is_ok();
+                (both, both)
+            }
+            DualWritePolicy::PrimaryWins => (primary.is_ok(), primary.is_ok() && secondary.is_ok()),
+            DualWritePolicy::Either => (primary.is_ok(), secondary.is_ok()),
+        };
+
+        // Transactions which don't commit roll back when dropped.
+        let mut primary_committed = false;
+        if commit_primary {
+            match primary_tx.commit().await {
+                Ok(()) => primary_committed = true,
+                Err(error) => primary = Err(error.into()),
+            }
+        }
+        // The secondary must not commit alone when the policy ties it to the primary.
+        let mut secondary_committed = false;
+        if commit_secondary && (primary_committed || self.policy == DualWritePolicy::Either) {
+            match secondary_tx.commit().await {
+                Ok(()) => secondary_committed = true,
+                Err(error) => secondary = Err(error.into()),
+            }
+        }
+
+        if !primary_committed && !secondary_committed {
+            return match (primary, secondary) {
+                (Err(error), _) | (_, Err(error)) => {
+                    Err(error.context("dual write committed on neither side"))
+                }
+                (Ok(_), Ok(_)) => unreachable!("a side succeeded but did not commit"),
+            };
+        }
+
+        Ok(DualWriteReport {
+            primary,
+            secondary,
+            primary_committed,
+            secondary_committed,
+        })
+    }
+}
@@ -0,0 +1,326 @@
+use std::fmt;
+
+use sqlx::error::DatabaseError;
+
+use crate::ChunkError;
+
+/// A bulk insert chunk would need more bind parameters than the database accepts.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BindParamLimitExceeded {
+    pub chunk_size: usize,
+    pub columns: usize,
+    pub max_params: usize,
+}
+
+impl BindParamLimitExceeded {
+    /// The largest chunk size which stays within the limit.
+    pub fn max_chunk_size(&self) -> usize {
+        self.max_params / self.columns.max(1)
+    }
+}
+
+impl fmt::Display for BindParamLimitExceeded {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.max_chunk_size() == 0 {
+            return write!(
+                f,
+                "a single row with {} columns needs more bind parameters than the database accepts ({})",
+                self.columns, self.max_params,
+            );
+        }
+
+        write!(
+            f,
+            "chunk size {} with {} columns needs {} bind parameters, but the database accepts at most {}; use a chunk size of at most {}",
+            self.chunk_size,
+            self.columns,
+            self.chunk_size.saturating_mul(self.columns),
+            self.max_params,
+            self.max_chunk_size(),
+        )
+    }
+}
+
+impl std::error::Error for BindParamLimitExceeded {}
+
+/// Even a single row renders a statement longer than the database accepts, see
+/// [`Capabilities::max_sql_length`](crate::Capabilities::max_sql_length).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SqlLengthExceeded {
+    pub length: usize,
+    pub max_length: usize,
+}
+
+impl fmt::Display for SqlLengthExceeded {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "a single row needs a statement of {} bytes, but the database accepts at most {}",
+            self.length, self.max_length,
+        )
+    }
+}
+
+impl std::error::Error for SqlLengthExceeded {}
+
+/// A value did not fit into the [`MemoryBudget`](crate::MemoryBudget) and was shed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MemoryBudgetExceeded {
+    pub requested: usize,
+    pub used: usize,
+    pub limit: usize,
+}
+
+impl fmt::Display for MemoryBudgetExceeded {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "cannot buffer {} more bytes, {} of the memory budget of {} bytes are in use",
+            self.requested, self.used, self.limit,
+        )
+    }
+}
+
+impl std::error::Error for MemoryBudgetExceeded {}
+
+/// A chunk inserted fewer or more rows than it had, e.g. because a trigger or rule dropped or
+/// redirected some of them. Raised with
+/// [`BulkInsertOptions::verify_rows_affected`](crate::BulkInsertOptions::verify_rows_affected).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PartialWrite {
+    pub chunk: usize,
+    pub expected: u64,
+    pub rows_affected: u64,
+}
+
+impl fmt::Display for PartialWrite {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "chunk {} of {} rows affected {} rows",
+            self.chunk, self.expected, self.rows_affected,
+        )
+    }
+}
+
+impl std::error::Error for PartialWrite {}
+
+/// Classification of constraint violations, decoded from the error codes of each backend.
+///
+/// ```ignore
+/// match tx.insert(&user).await {
+///     Err(e) if e.is_unique_violation() => bail!("{} is taken", user.name),
+///     result => result?,
+/// };
+/// ```
+pub trait ErrorExt {
+    fn is_unique_violation(&self) -> bool;
+
+    fn is_foreign_key_violation(&self) -> bool;
+
+    /// The name of the violated constraint. SQLite doesn't name constraints in errors, for a
+    /// unique violation it reports the columns instead, like `user.name`.
+    fn constraint_name(&self) -> Option<&str>;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Violation {
+    Unique,
+    ForeignKey,
+}
+
+fn violation(error: &dyn DatabaseError) -> Option<Violation> {
+    #[cfg(feature = "sqlite")]
+    if let Some(error) = error.try_downcast_ref::<sqlx::sqlite::SqliteError>() {
+        // SQLITE_CONSTRAINT_UNIQUE, SQLITE_CONSTRAINT_PRIMARYKEY, SQLITE_CONSTRAINT_FOREIGNKEY
+        return match error.code()?.as_ref() {
+            "2067" | "1555" => Some(Violation::Unique),
+            "787" => Some(Violation::ForeignKey),
+            _ => None,
+        };
+    }
+    #[cfg(feature = "mysql")]
+    if let Some(error) = error.try_downcast_ref::<sqlx::mysql::MySqlDatabaseError>() {
+        // ER_DUP_ENTRY, ER_DUP_ENTRY_WITH_KEY_NAME, ER_NO_REFERENCED_ROW(_2),
+        // ER_ROW_IS_REFERENCED(_2)
+        return match error.number() {
+            1062 | 1586 => Some(Violation::Unique),
+            1216 | 1217 | 1451 | 1452 => Some(Violation::ForeignKey),
+            _ => None,
+        };
+    }
+    #[cfg(feature = "postgres")]
+    if let Some(error) = error.try_downcast_ref::<sqlx::postgres::PgDatabaseError>() {
+        return match error.code() {
+            "23505" => Some(Violation::Unique),
+            "23503" => Some(Violation::ForeignKey),
+            _ => None,
+        };
+    }
+    // sqlx doesn't expose the error number of MSSQL, so the messages of errors 2627, 2601 and
+    // 547 are matched instead.
+    #[cfg(feature = "mssql")]
+    if let Some(error) = error.try_downcast_ref::<sqlx::mssql::MssqlDatabaseError>() {
+        let message = error.message();
+        return if message.starts_with("Violation of PRIMARY KEY constraint")
+            || message.starts_with("Violation of UNIQUE KEY constraint")
+            || message.starts_with("Cannot insert duplicate key row")
+        {
+            Some(Violation::Unique)
+        } else if message.contains("conflicted with the FOREIGN KEY constraint")
+            || message.contains("conflicted with the REFERENCE constraint")
+        {
+            Some(Violation::ForeignKey)
+        } else {
+            None
+        };
+    }
+
+    None
+}
+
+fn constraint_name(error: &dyn DatabaseError) -> Option<&str> {
+    if let Some(constraint) = error.constraint() {
+        return Some(constraint);
+    }
+
+    #[cfg(feature = "sqlite")]
+    if error
+        .try_downcast_ref::<sqlx::sqlite::SqliteError>()
+        .is_some()
+    {
+        // UNIQUE constraint failed: user.name
+        return match violation(error)? {
+            Violation::Unique => error
+                .message()
+                .split_once("constraint failed: ")
+                .map(|(_, columns)| columns),
+            Violation::ForeignKey => None,
+        };
+    }
+    #[cfg(feature = "mysql")]
+    if error
+        .try_downcast_ref::<sqlx::mysql::MySqlDatabaseError>()
+        .is_some()
+    {
+        let message = error.message();
+        return match violation(error)? {
+            // Duplicate entry 'x' for key 'user.name'
+            Violation::Unique => message
+                .rsplit_once(" for key '")
+                .and_then(|(_, key)| key.strip_suffix('\'')),
+            // ... a foreign key constraint fails (`db`.`t`, CONSTRAINT `fk` FOREIGN KEY ...)
+            Violation::ForeignKey => message
+                .split_once("CONSTRAINT `")
+                .and_then(|(_, rest)| rest.split_once('`'))
+                .map(|(name, _)| name),
+        };
+    }
+    #[cfg(feature = "mssql")]
+    if error
+        .try_downcast_ref::<sqlx::mssql::MssqlDatabaseError>()
+        .is_some()
+    {
+        // Violation of UNIQUE KEY constraint 'UQ_user'. ...
+        // Cannot insert duplicate key row in object 'dbo.user' with unique index 'IX_name'. ...
+        // The INSERT statement conflicted with the FOREIGN KEY constraint "FK_user". ...
+        let message = error.message();
+        return match violation(error)? {
+            Violation::Unique if message.starts_with("Cannot insert duplicate key row") => {
+                message.split('\'').nth(3)
+            }
+            Violation::Unique => message.split('\'').nth(1),
+            Violation::ForeignKey => message.split('"').nth(1),
+        };
+    }
+
+    None
+}
+
+impl ErrorExt for dyn DatabaseError {
+    fn is_unique_violation(&self) -> bool {
+        violation(self) == Some(Violation::Unique)
+    }
+
+    fn is_foreign_key_violation(&self) -> bool {
+        violation(self) == Some(Violation::ForeignKey)
+    }
+
+    fn constraint_name(&self) -> Option<&str> {
+        constraint_name(self)
+    }
+}
+
+impl ErrorExt for sqlx::Error {
+    fn is_unique_violation(&self) -> bool {
+        self.as_database_error()
+            .is_some_and(|x| x.is_unique_violation())
+    }
+
+    fn is_foreign_key_violation(&self) -> bool {
+        self.as_database_error()
+            .is_some_and(|x| x.is_foreign_key_violation())
+    }
+
+    fn constraint_name(&self) -> Option<&str> {
+        self.as_database_error()?.constraint_name()
+    }
+}
+
+/// Looks for the `sqlx::Error` in the chain of causes.
+impl ErrorExt for anyhow::Error {
+    fn is_unique_violation(&self) -> bool {
+        sqlx_error(self).is_some_and(|x| x.is_unique_violation())
+    }
+
+    fn is_foreign_key_violation(&self) -> bool {
+        sqlx_error(self).is_some_and(|x| x.is_foreign_key_violation())
+    }
+
+    fn constraint_name(&self) -> Option<&str> {
+        sqlx_error(self)?.constraint_name()
+    }
+}
+
+fn sqlx_error(error: &anyhow::Error) -> Option<&sqlx::Error> {
+    error.chain().find_map(|x| x.downcast_ref::<sqlx::Error>())
+}
+
+impl ErrorExt for ChunkError {
+    fn is_unique_violation(&self) -> bool {
+        self.error.is_unique_violation()
+    }
+
+    fn is_foreign_key_violation(&self) -> bool {
+        self.error.is_foreign_key_violation()
+    }
+
+    fn constraint_name(&self) -> Option<&str> {
+        self.error.constraint_name()
+    }
+}
+
+/// A constraint violation mapped to a domain error with `#[insertable(on_unique(...))]`.
+///
+/// It is attached as context to the database error, which stays reachable through
+/// [`ErrorExt`].
+///
+/// ```ignore
+/// match tx.insert(&user).await {
+///     Err(e) => match e.downcast_ref::<ConstraintViolation<UserInsertViolation>>() {
+///         Some(ConstraintViolation(UserInsertViolation::UserNameTaken)) => ...,
+///         None => return Err(e),
+///     },
+///     Ok(_) => ...,
+/// }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ConstraintViolation<V>(pub V);
+
+impl<V: fmt::Debug> fmt::Display for ConstraintViolation<V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "constraint violation: {:?}", self.0)
+    }
+}
+
+impl<V: fmt::Debug> std::error::Error for ConstraintViolation<V> {}
@@ -0,0 +1,52 @@
+use crate::{bulk_insert_sql, Backend, Dialect, Insertable};
+
+/// What a bulk insert would send to the database, see [`estimate_bulk_insert`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct BulkInsertEstimate {
+    pub chunks: usize,
+    /// Statements executed, one per chunk.
+    pub statements: usize,
+    /// Bind parameters of the largest statement.
+    pub placeholders_per_statement: usize,
+    /// Bytes of SQL text over all statements, without the bound values.
+    pub payload_bytes: usize,
+}
+
+/// Estimate a bulk insert of `rows` rows of `T` in chunks of `chunk_size` rows without touching
+/// the database, e.g. to reject jobs which would exceed limits.
+pub fn estimate_bulk_insert<T>(rows: usize, chunk_size: usize) -> BulkInsertEstimate
+where
+    T: Insertable,
+    T::Database: Backend,
+{
+    if rows == 0 {
+        return BulkInsertEstimate::default();
+    }
+
+    // Like the bulk insert, rows without columns go one statement each but on MySQL.
+    let chunk_size = if T::COLUMN_COUNT == 0 && T::Database::DIALECT != Dialect::MySql {
+        1
+    } else {
+        chunk_size.clamp(1, rows)
+    };
+    let (full_chunks, remainder) = (rows / chunk_size, rows % chunk_size);
+    let chunks = full_chunks + (remainder > 0) as usize;
+
+    // Can't fail with the chunk size above.
+    let statement_bytes = |rows| {
+        bulk_insert_sql::<T>(T::table_name(), rows)
+            .map(|sql| sql.len())
+            .unwrap_or_default()
+    };
+    let mut payload_bytes = full_chunks * statement_bytes(chunk_size);
+    if remainder > 0 {
+        payload_bytes += statement_bytes(remainder);
+    }
+
+    BulkInsertEstimate {
+        chunks,
+        statements: chunks,
+        placeholders_per_statement: chunk_size * T::COLUMN_COUNT,
+        payload_bytes,
+    }
+}
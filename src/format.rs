@@ -0,0 +1,112 @@
+use std::borrow::Cow;
+
+/// How statements are laid out in logs, see [`crate::Config::set_sql_format`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum SqlFormat {
+    /// As executed. Generated statements are a single line already.
+    #[default]
+    Compact,
+    /// One clause per line and one `VALUES` row per line, for reading statements in logs.
+    Pretty,
+}
+
+/// Clauses which start a new line in pretty statements, when outside of parentheses.
+const CLAUSES: &[&str] = &[
+    "VALUES",
+    "SELECT",
+    "FROM",
+    "WHERE",
+    "GROUP BY",
+    "ORDER BY",
+    "ON CONFLICT",
+    "ON DUPLICATE KEY UPDATE",
+    "OUTPUT",
+    "RETURNING",
+    "UNION ALL",
+    "LIMIT",
+    "OFFSET",
+];
+
+impl SqlFormat {
+    /// Lay out `sql`. Quoted strings and identifiers are kept as they are.
+    pub fn apply(self, sql: &str) -> Cow<'_, str> {
+        if self == SqlFormat::Compact {
+            return Cow::Borrowed(sql);
+        }
+
+        let mut buf = String::with_capacity(sql.len());
+        let mut quote = None;
+        let mut comment = false;
+        let mut depth = 0usize;
+        let mut in_values = false;
+        let mut rest = sql.trim();
+
+        while let Some(c) = rest.chars().next() {
+            let len = c.len_utf8();
+
+            if comment {
+                // A line comment ends with its line, which must not be joined with the next.
+                buf.push(c);
+                comment = c != '\n';
+                rest = &rest[len..];
+                continue;
+            }
+            if let Some(end) = quote {
+                buf.push(c);
+                if c == end {
+                    quote = None;
+                }
+                rest = &rest[len..];
+                continue;
+            }
+
+            match c {
+                '\'' | '"' | '`' => quote = Some(c),
+                '[' => quote = Some(']'),
+                '-' if rest.starts_with("--") => comment = true,
+                '(' => depth += 1,
+                ')' => depth = depth.saturating_sub(1),
+                _ => {}
+            }
+
+            if c.is_whitespace() {
+                let trimmed = rest.trim_start();
+                rest = trimmed;
+                if buf.ends_with('\n') {
+                    continue;
+                }
+                let clause = (depth == 0)
+                    .then(|| {
+                        CLAUSES.iter().find(|clause| {
+                            trimmed.starts_with(*clause)
+                                && trimmed[clause.len()..]
+                                    .starts_with(|c: char| c.is_whitespace() || c == '(')
+                        })
+                    })
+                    .flatten();
+                match clause {
+                    Some(&"VALUES") => {
+                        buf.push_str("\nVALUES\n  ");
+                        rest = rest["VALUES".len()..].trim_start();
+                        in_values = true;
+                    }
+                    Some(_) => {
+                        buf.push('\n');
+                        in_values = false;
+                    }
+                    None => buf.push(' '),
+                }
+                continue;
+            }
+
+            buf.push(c);
+            rest = &rest[len..];
+            if c == ',' && in_values && depth == 0 {
+                buf.push_str("\n  ");
+                rest = rest.trim_start();
+            }
+        }
+
+        Cow::Owned(buf)
+    }
+}
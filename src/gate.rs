@@ -0,0 +1,126 @@
+use std::{
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use async_trait::async_trait;
+use sqlx::{Executor, FromRow, IntoArguments, Pool};
+
+use crate::{compat::ArgumentsOf, runtime, ChunkPosition};
+
+/// Decides when the next chunk of a bulk operation may start, e.g. to pace a backfill by the
+/// load it puts on the database. Registered with [`Config::chunk_gate`](crate::Config::chunk_gate)
+/// and awaited before every chunk, ahead of the middlewares.
+#[async_trait]
+pub trait ChunkGate: Send + Sync {
+    /// Return once `chunk` may run. An error fails the chunk like a database error would.
+    async fn wait(&self, chunk: &ChunkPosition) -> anyhow::Result<()>;
+}
+
+#[async_trait]
+impl<G: ChunkGate + ?Sized> ChunkGate for Arc<G> {
+    async fn wait(&self, chunk: &ChunkPosition) -> anyhow::Result<()> {
+        (**self).wait(chunk).await
+    }
+}
+
+/// Pauses chunks while the replicas lag behind, so a massive backfill doesn't leave them
+/// serving stale reads.
+///
+/// The lag is read with a query returning it in seconds as a single float, on a pool of its own
+/// so the insert's transaction doesn't hide it. It is checked at most once per
+/// `check_interval` while below `max_lag`, and every `check_interval` while pausing.
+///
+/// ```ignore
+/// let gate = ReplicationLagGate::postgres(pool.clone(), Duration::from_secs(5))
+///     .max_pause(Duration::from_secs(600));
+/// let config = Config::new().chunk_gate(gate);
+/// BulkInsert::new(&rows).config(config).execute(&pool).await?;
+/// ```
+#[derive(Debug)]
+pub struct ReplicationLagGate<DB: sqlx::Database> {
+    pool: Pool<DB>,
+    query: String,
+    max_lag: Duration,
+    check_interval: Duration,
+    max_pause: Option<Duration>,
+    /// When the lag was last found below `max_lag`.
+    last_passed: Mutex<Option<Instant>>,
+}
+
+impl<DB: sqlx::Database> ReplicationLagGate<DB> {
+    /// `query` returns the lag in seconds as a single float.
+    pub fn new(pool: Pool<DB>, query: impl Into<String>, max_lag: Duration) -> Self {
+        ReplicationLagGate {
+            pool,
+            query: query.into(),
+            max_lag,
+            check_interval: Duration::from_secs(1),
+            max_pause: None,
+            last_passed: Mutex::new(None),
+        }
+    }
+
+    /// How often the lag is checked, 1 second by default.
+    pub fn check_interval(mut self, check_interval: Duration) -> Self {
+        self.check_interval = check_interval;
+        self
+    }
+
+    /// Fail the chunk instead of pausing longer than `max_pause`. Pauses indefinitely by default.
+    pub fn max_pause(mut self, max_pause: Duration) -> Self {
+        self.max_pause = Some(max_pause);
+        self
+    }
+}
+
+#[cfg(feature = "postgres")]
+impl ReplicationLagGate<sqlx::Postgres> {
+    /// The replay lag of the slowest replica in `pg_stat_replication`, as seen by the primary.
+    /// Without replicas there is no lag.
+    pub fn postgres(pool: Pool<sqlx::Postgres>, max_lag: Duration) -> Self {
+        Self::new(
+            pool,
+            "SELECT COALESCE(EXTRACT(EPOCH FROM MAX(replay_lag)), 0)::float8 FROM pg_stat_replication",
+            max_lag,
+        )
+    }
+}
+
+#[async_trait]
+impl<DB> ChunkGate for ReplicationLagGate<DB>
+where
+    DB: sqlx::Database,
+    for<'e> &'e Pool<DB>: Executor<'e, Database = DB>,
+    for<'q> ArgumentsOf<'q, DB>: IntoArguments<'q, DB>,
+    (f64,): for<'r> FromRow<'r, DB::Row>,
+{
+    async fn wait(&self, _chunk: &ChunkPosition) -> anyhow::Result<()> {
+        let started = Instant::now();
+        loop {
+            let passed = *self.last_passed.lock().unwrap();
+            if passed.is_some_and(|x| x.elapsed() < self.check_interval) {
+                return Ok(());
+            }
+
+            let (lag,): (f64,) = sqlx::query_as(&self.query).fetch_one(&self.pool).await?;
+            let lag = Duration::from_secs_f64(lag.max(0.0));
+            if lag <= self.max_lag {
+                *self.last_passed.lock().unwrap() = Some(Instant::now());
+                return Ok(());
+            }
+
+            if let Some(max_pause) = self.max_pause {
+                anyhow::ensure!(
+                    started.elapsed() < max_pause,
+                    "replication lag of {:?} stayed above {:?} for {:?}",
+                    lag,
+                    self.max_lag,
+                    max_pause
+                );
+            }
+            *self.last_passed.lock().unwrap() = None;
+            runtime::sleep(self.check_interval).await;
+        }
+    }
+}
@@ -0,0 +1,439 @@
+//! Binding [`geo_types`] geometries to spatial columns.
+//!
+//! A geometry is bound as WKB with [`Wkb`] or as WKT with [`Wkt`], and turned into the column's
+//! type by a function of the database around the placeholder. Fields marked
+//! `#[insertable(geo)]` do both: they are bound as WKB wrapped in `ST_GeomFromWKB(?)`, or on
+//! MSSQL, where sqlx can't bind bytes, as WKT wrapped in `geometry::STGeomFromText(?, 0)`. Give
+//! the expression yourself for a specific SRID:
+//!
+//! ```ignore
+//! #[derive(Insertable)]
+//! #[insertable(sqlx::Postgres, "shop")]
+//! struct Shop {
+//!     name: String,
+//!     #[insertable(geo, expr = "ST_GeomFromWKB(?, 4326)")]
+//!     location: geo_types::Point,
+//! }
+//! ```
+//!
+//! Geometries are written in two dimensions, `Option`s of geometries bind `NULL` for `None`.
+
+use geo_types::{
+    Coord, Geometry, GeometryCollection, Line, LineString, MultiLineString, MultiPoint,
+    MultiPolygon, Point, Polygon, Rect, Triangle,
+};
+use sqlx::{
+    encode::{Encode, IsNull},
+    Database, Type,
+};
+
+use crate::{compat::ArgumentBufferOf, Dialect};
+
+/// The expression turning a WKB placeholder into a geometry, `None` on MSSQL, which only takes
+/// [`Wkt`] with sqlx.
+pub fn wkb_expression(dialect: Dialect) -> Option<&'static str> {
+    match dialect {
+        Dialect::Sqlite | Dialect::MySql | Dialect::Postgres => Some("ST_GeomFromWKB(?)"),
+        Dialect::Mssql => None,
+    }
+}
+
+/// The expression turning a WKT placeholder into a geometry.
+pub fn wkt_expression(dialect: Dialect) -> &'static str {
+    match dialect {
+        Dialect::Sqlite | Dialect::MySql | Dialect::Postgres => "ST_GeomFromText(?)",
+        Dialect::Mssql => "geometry::STGeomFromText(?, 0)",
+    }
+}
+
+/// A geometry which can be written as WKB and WKT.
+pub trait ToGeometry {
+    /// Append the little-endian WKB of the geometry, or return `false` for `NULL`.
+    fn write_wkb(&self, buf: &mut Vec<u8>) -> bool;
+
+    /// Append the WKT of the geometry, or return `false` for `NULL`.
+    fn write_wkt(&self, buf: &mut String) -> bool;
+}
+
+/// Binds the geometry as WKB bytes.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Wkb<G>(pub G);
+
+/// Binds the geometry as WKT text.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Wkt<G>(pub G);
+
+impl<G: ToGeometry> Wkb<G> {
+    pub fn to_bytes(&self) -> Option<Vec<u8>> {
+        let mut buf = Vec::new();
+        self.0.write_wkb(&mut buf).then_some(buf)
+    }
+}
+
+impl<G: ToGeometry> Wkt<G> {
+    pub fn to_text(&self) -> Option<String> {
+        let mut buf = String::new();
+        self.0.write_wkt(&mut buf).then_some(buf)
+    }
+}
+
+impl<'q, DB: Database, G: ToGeometry> Encode<'q, DB> for Wkb<G>
+where
+    Vec<u8>: Encode<'q, DB>,
+{
+    fn encode_by_ref(&self, buf: &mut ArgumentBufferOf<'q, DB>) -> IsNull {
+        match self.to_bytes() {
+            Some(bytes) => bytes.encode(buf),
+            None => IsNull::Yes,
+        }
+    }
+}
+
+impl<DB: Database, G> Type<DB> for Wkb<G>
+where
+    Vec<u8>: Type<DB>,
+{
+    fn type_info() -> DB::TypeInfo {
+        <Vec<u8> as Type<DB>>::type_info()
+    }
+
+    fn compatible(ty: &DB::TypeInfo) -> bool {
+        <Vec<u8> as Type<DB>>::compatible(ty)
+    }
+}
+
+impl<'q, DB: Database, G: ToGeometry> Encode<'q, DB> for Wkt<G>
+where
+    String: Encode<'q, DB>,
+{
+    fn encode_by_ref(&self, buf: &mut ArgumentBufferOf<'q, DB>) -> IsNull {
+        match self.to_text() {
+            Some(text) => text.encode(buf),
+            None => IsNull::Yes,
+        }
+    }
+}
+
+impl<DB: Database, G> Type<DB> for Wkt<G>
+where
+    String: Type<DB>,
+{
+    fn type_info() -> DB::TypeInfo {
+        <String as Type<DB>>::type_info()
+    }
+
+    fn compatible(ty: &DB::TypeInfo) -> bool {
+        <String as Type<DB>>::compatible(ty)
+    }
+}
+
+impl<G: ToGeometry> ToGeometry for &G {
+    fn write_wkb(&self, buf: &mut Vec<u8>) -> bool {
+        (**self).write_wkb(buf)
+    }
+
+    fn write_wkt(&self, buf: &mut String) -> bool {
+        (**self).write_wkt(buf)
+    }
+}
+
+impl<G: ToGeometry> ToGeometry for Option<G> {
+    fn write_wkb(&self, buf: &mut Vec<u8>) -> bool {
+        self.as_ref().is_some_and(|x| x.write_wkb(buf))
+    }
+
+    fn write_wkt(&self, buf: &mut String) -> bool {
+        self.as_ref().is_some_and(|x| x.write_wkt(buf))
+    }
+}
+
+/// The geometry types of WKB and WKT, their codes are the WKB type numbers.
+#[derive(Clone, Copy)]
+enum Kind {
+    Point = 1,
+    LineString = 2,
+    Polygon = 3,
+    MultiPoint = 4,
+    MultiLineString = 5,
+    MultiPolygon = 6,
+    GeometryCollection = 7,
+}
+
+impl Kind {
+    fn name(self) -> &'static str {
+        match self {
+            Kind::Point => "POINT",
+            Kind::LineString => "LINESTRING",
+            Kind::Polygon => "POLYGON",
+            Kind::MultiPoint => "MULTIPOINT",
+            Kind::MultiLineString => "MULTILINESTRING",
+            Kind::MultiPolygon => "MULTIPOLYGON",
+            Kind::GeometryCollection => "GEOMETRYCOLLECTION",
+        }
+    }
+}
+
+fn write_wkb_header(buf: &mut Vec<u8>, kind: Kind) {
+    buf.push(1);
+    buf.extend_from_slice(&(kind as u32).to_le_bytes());
+}
+
+fn write_wkb_len(buf: &mut Vec<u8>, len: usize) {
+    let len = u32::try_from(len).expect("geometry has more than u32::MAX parts");
+    buf.extend_from_slice(&len.to_le_bytes());
+}
+
+fn write_wkb_coord(buf: &mut Vec<u8>, coord: Coord) {
+    buf.extend_from_slice(&coord.x.to_le_bytes());
+    buf.extend_from_slice(&coord.y.to_le_bytes());
+}
+
+fn write_wkb_coords(buf: &mut Vec<u8>, coords: &[Coord]) {
+    write_wkb_len(buf, coords.len());
+    for coord in coords {
+        write_wkb_coord(buf, *coord);
+    }
+}
+
+fn write_wkb_rings(buf: &mut Vec<u8>, polygon: &Polygon) {
+    let exterior = &polygon.exterior().0;
+    if exterior.is_empty() {
+        write_wkb_len(buf, 0);
+        return;
+    }
+    write_wkb_len(buf, 1 + polygon.interiors().len());
+    write_wkb_coords(buf, exterior);
+    for interior in polygon.interiors() {
+        write_wkb_coords(buf, &interior.0);
+    }
+}
+
+/// `(x y,x y)` with the parentheses, or ` EMPTY` for no coordinates.
+fn write_wkt_coords(buf: &mut String, coords: &[Coord]) {
+    if coords.is_empty() {
+        buf.push_str(" EMPTY");
+        return;
+    }
+    buf.push('(');
+    for (i, coord) in coords.iter().enumerate() {
+        if i > 0 {
+            buf.push(',');
+        }
+        write_wkt_coord(buf, *coord);
+    }
+    buf.push(')');
+}
+
+fn write_wkt_coord(buf: &mut String, coord: Coord) {
+    use std::fmt::Write;
+
+    let _ = write!(buf, "{} {}", coord.x, coord.y);
+}
+
+fn write_wkt_rings(buf: &mut String, polygon: &Polygon) {
+    if polygon.exterior().0.is_empty() {
+        buf.push_str(" EMPTY");
+        return;
+    }
+    buf.push('(');
+    write_wkt_coords(buf, &polygon.exterior().0);
+    for interior in polygon.interiors() {
+        buf.push(',');
+        write_wkt_coords(buf, &interior.0);
+    }
+    buf.push(')');
+}
+
+/// `(part,part)` for the parts of a multi geometry, or ` EMPTY` for no parts.
+fn write_wkt_parts<P>(buf: &mut String, parts: &[P], mut write_part: impl FnMut(&mut String, &P)) {
+    if parts.is_empty() {
+        buf.push_str(" EMPTY");
+        return;
+    }
+    buf.push('(');
+    for (i, part) in parts.iter().enumerate() {
+        if i > 0 {
+            buf.push(',');
+        }
+        write_part(buf, part);
+    }
+    buf.push(')');
+}
+
+impl ToGeometry for Point {
+    fn write_wkb(&self, buf: &mut Vec<u8>) -> bool {
+        write_wkb_header(buf, Kind::Point);
+        write_wkb_coord(buf, self.0);
+        true
+    }
+
+    fn write_wkt(&self, buf: &mut String) -> bool {
+        buf.push_str(Kind::Point.name());
+        write_wkt_coords(buf, &[self.0]);
+        true
+    }
+}
+
+impl ToGeometry for LineString {
+    fn write_wkb(&self, buf: &mut Vec<u8>) -> bool {
+        write_wkb_header(buf, Kind::LineString);
+        write_wkb_coords(buf, &self.0);
+        true
+    }
+
+    fn write_wkt(&self, buf: &mut String) -> bool {
+        buf.push_str(Kind::LineString.name());
+        write_wkt_coords(buf, &self.0);
+        true
+    }
+}
+
+impl ToGeometry for Polygon {
+    fn write_wkb(&self, buf: &mut Vec<u8>) -> bool {
+        write_wkb_header(buf, Kind::Polygon);
+        write_wkb_rings(buf, self);
+        true
+    }
+
+    fn write_wkt(&self, buf: &mut String) -> bool {
+        buf.push_str(Kind::Polygon.name());
+        write_wkt_rings(buf, self);
+        true
+    }
+}
+
+impl ToGeometry for MultiPoint {
+    fn write_wkb(&self, buf: &mut Vec<u8>) -> bool {
+        write_wkb_header(buf, Kind::MultiPoint);
+        write_wkb_len(buf, self.0.len());
+        for point in &self.0 {
+            point.write_wkb(buf);
+        }
+        true
+    }
+
+    fn write_wkt(&self, buf: &mut String) -> bool {
+        buf.push_str(Kind::MultiPoint.name());
+        write_wkt_parts(buf, &self.0, |buf, point| write_wkt_coords(buf, &[point.0]));
+        true
+    }
+}
+
+impl ToGeometry for MultiLineString {
+    fn write_wkb(&self, buf: &mut Vec<u8>) -> bool {
+        write_wkb_header(buf, Kind::MultiLineString);
+        write_wkb_len(buf, self.0.len());
+        for line_string in &self.0 {
+            line_string.write_wkb(buf);
+        }
+        true
+    }
+
+    fn write_wkt(&self, buf: &mut String) -> bool {
+        buf.push_str(Kind::MultiLineString.name());
+        write_wkt_parts(buf, &self.0, |buf, line_string| {
+            write_wkt_coords(buf, &line_string.0)
+        });
+        true
+    }
+}
+
+impl ToGeometry for MultiPolygon {
+    fn write_wkb(&self, buf: &mut Vec<u8>) -> bool {
+        write_wkb_header(buf, Kind::MultiPolygon);
+        write_wkb_len(buf, self.0.len());
+        for polygon in &self.0 {
+            polygon.write_wkb(buf);
+        }
+        true
+    }
+
+    fn write_wkt(&self, buf: &mut String) -> bool {
+        buf.push_str(Kind::MultiPolygon.name());
+        write_wkt_parts(buf, &self.0, write_wkt_rings);
+        true
+    }
+}
+
+impl ToGeometry for GeometryCollection {
+    fn write_wkb(&self, buf: &mut Vec<u8>) -> bool {
+        write_wkb_header(buf, Kind::GeometryCollection);
+        write_wkb_len(buf, self.0.len());
+        for geometry in &self.0 {
+            geometry.write_wkb(buf);
+        }
+        true
+    }
+
+    fn write_wkt(&self, buf: &mut String) -> bool {
+        buf.push_str(Kind::GeometryCollection.name());
+        write_wkt_parts(buf, &self.0, |buf, geometry| {
+            geometry.write_wkt(buf);
+        });
+        true
+    }
+}
+
+// Lines, rectangles and triangles have no WKB type of their own, they are written as a line
+// string and polygons.
+impl ToGeometry for Line {
+    fn write_wkb(&self, buf: &mut Vec<u8>) -> bool {
+        LineString::from(*self).write_wkb(buf)
+    }
+
+    fn write_wkt(&self, buf: &mut String) -> bool {
+        LineString::from(*self).write_wkt(buf)
+    }
+}
+
+impl ToGeometry for Rect {
+    fn write_wkb(&self, buf: &mut Vec<u8>) -> bool {
+        self.to_polygon().write_wkb(buf)
+    }
+
+    fn write_wkt(&self, buf: &mut String) -> bool {
+        self.to_polygon().write_wkt(buf)
+    }
+}
+
+impl ToGeometry for Triangle {
+    fn write_wkb(&self, buf: &mut Vec<u8>) -> bool {
+        self.to_polygon().write_wkb(buf)
+    }
+
+    fn write_wkt(&self, buf: &mut String) -> bool {
+        self.to_polygon().write_wkt(buf)
+    }
+}
+
+impl ToGeometry for Geometry {
+    fn write_wkb(&self, buf: &mut Vec<u8>) -> bool {
+        match self {
+            Geometry::Point(x) => x.write_wkb(buf),
+            Geometry::Line(x) => x.write_wkb(buf),
+            Geometry::LineString(x) => x.write_wkb(buf),
+            Geometry::Polygon(x) => x.write_wkb(buf),
+            Geometry::MultiPoint(x) => x.write_wkb(buf),
+            Geometry::MultiLineString(x) => x.write_wkb(buf),
+            Geometry::MultiPolygon(x) => x.write_wkb(buf),
+            Geometry::GeometryCollection(x) => x.write_wkb(buf),
+            Geometry::Rect(x) => x.write_wkb(buf),
+            Geometry::Triangle(x) => x.write_wkb(buf),
+        }
+    }
+
+    fn write_wkt(&self, buf: &mut String) -> bool {
+        match self {
+            Geometry::Point(x) => x.write_wkt(buf),
+            Geometry::Line(x) => x.write_wkt(buf),
+            Geometry::LineString(x) => x.write_wkt(buf),
+            Geometry::Polygon(x) => x.write_wkt(buf),
+            Geometry::MultiPoint(x) => x.write_wkt(buf),
+            Geometry::MultiLineString(x) => x.write_wkt(buf),
+            Geometry::MultiPolygon(x) => x.write_wkt(buf),
+            Geometry::GeometryCollection(x) => x.write_wkt(buf),
+            Geometry::Rect(x) => x.write_wkt(buf),
+            Geometry::Triangle(x) => x.write_wkt(buf),
+        }
+    }
+}
@@ -0,0 +1,89 @@
+use sqlx::{Executor, FromRow, IntoArguments};
+
+use crate::{
+    bulk_insert_chunks, compat::ArgumentsOf, upsert::ConflictTarget, write_values_cte, Backend,
+    BulkInsertOptions, Insertable, QueryBindExt,
+};
+
+/// Insert the values which don't exist yet and return the rows of all of them, in input order.
+///
+/// Row identity is the conflict target of `options.on_conflict`. Without an action the insert
+/// skips existing rows, `DO UPDATE` refreshes them instead. Instead of selecting first, the
+/// missing rows are inserted with the upsert clause, so concurrent callers don't race between
+/// the select and the insert.
+pub(crate) async fn get_or_create_many<T, O, E, DB>(
+    executor: &mut E,
+    values: &[T],
+    options: &BulkInsertOptions,
+) -> anyhow::Result<Vec<O>>
+where
+    DB: Backend,
+    T: Insertable<Database = DB> + Sync,
+    O: for<'r> FromRow<'r, DB::Row> + Send + Unpin,
+    for<'e> &'e mut E: Executor<'e, Database = DB>,
+    for<'q> ArgumentsOf<'q, DB>: IntoArguments<'q, DB>,
+{
+    let key = match options.on_conflict.as_ref().map(|x| &x.target) {
+        Some(ConflictTarget::Columns(columns)) => columns,
+        _ => anyhow::bail!("get_or_create_many requires an on_conflict clause with key columns"),
+    };
+
+    if values.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    bulk_insert_chunks(executor, values, options, |_, _, result| {
+        result?;
+        Ok(())
+    })
+    .await?;
+
+    let config = options.config();
+    let capabilities = options.capabilities::<DB>();
+    let table_name = options.table_name::<T>();
+    let chunk_size = options.chunk_size::<T>()?;
+    let mut rows = Vec::with_capacity(values.len());
+
+    let positions = options.chunk_positions(values.len().div_ceil(chunk_size));
+    for (chunk, position) in values.chunks(chunk_size).zip(positions) {
+        let mut sql = String::new();
+        write_values_cte::<T>(&mut sql, &capabilities, chunk.len());
+        sql.push_str(" SELECT ");
+        sql.push_str(table_name);
+        sql.push_str(".* FROM ");
+        sql.push_str(table_name);
+        sql.push_str(" JOIN v ON ");
+        for (i, column) in key.iter().enumerate() {
+            if i > 0 {
+                sql.push_str(" AND ");
+            }
+            sql.push_str(table_name);
+            sql.push('.');
+            sql.push_str(column);
+            sql.push_str(" = v.");
+            sql.push_str(column);
+        }
+        sql.push_str(" ORDER BY v.sqlx_plus_ord");
+
+        let statement = config
+            .before_chunk(sql, table_name, chunk.len(), position)
+            .await?;
+        let fetched = statement
+            .run(
+                DB::set_persistent(
+                    sqlx::query(statement.sql()),
+                    statement.persistent(options.persistent),
+                )
+                .bind_multi_fields(chunk)
+                .fetch_all(&mut *executor),
+            )
+            .await;
+        statement.after(&fetched, |rows| rows.len() as u64).await;
+
+        for row in &fetched? {
+            rows.push(O::from_row(row)?);
+        }
+    }
+
+    Ok(rows)
+}
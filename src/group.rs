@@ -0,0 +1,81 @@
+use sqlx::Acquire;
+
+use crate::{Backend, BulkInsertOptions, BulkInsertReport, Insertable, Inserter, TableName};
+
+/// Bulk insert rows whose table is only known at runtime, e.g. monthly partitions:
+///
+/// ```ignore
+/// let reports = sqlx_plus::group_by_table(vec![
+///     ("events_2024_01".to_owned(), january_event),
+///     ("events_2024_02".to_owned(), february_event),
+/// ])
+/// .execute(&pool)
+/// .await?;
+/// ```
+///
+/// Rows are grouped by table, keeping their order within a table, and each group is inserted
+/// with `T`'s options and the table name override. All groups are inserted in one transaction.
+pub fn group_by_table<T: Insertable>(rows: Vec<(String, T)>) -> GroupByTable<T> {
+    GroupByTable {
+        rows,
+        options: BulkInsertOptions::default(),
+    }
+}
+
+/// A builder for [`group_by_table`].
+#[derive(Debug, Clone)]
+pub struct GroupByTable<T> {
+    rows: Vec<(String, T)>,
+    options: BulkInsertOptions,
+}
+
+impl<T> GroupByTable<T>
+where
+    T: Insertable + Sync,
+    T::Database: Backend,
+{
+    /// The options every group is inserted with. `table_name` is replaced per group.
+    pub fn options(mut self, options: BulkInsertOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Insert the groups and return the report of each table, in order of first appearance.
+    pub async fn execute<'c, A>(
+        self,
+        acquire: A,
+    ) -> anyhow::Result<Vec<(TableName, BulkInsertReport)>>
+    where
+        A: Acquire<'c, Database = T::Database>,
+        for<'e> &'e mut <T::Database as sqlx::Database>::Connection: Inserter<T::Database>,
+    {
+        let mut groups: Vec<(TableName, Vec<T>)> = Vec::new();
+        for (table_name, row) in self.rows {
+            match groups
+                .iter_mut()
+                .find(|(name, _)| name.as_str() == table_name)
+            {
+                Some((_, rows)) => rows.push(row),
+                None => groups.push((TableName::new(table_name)?, vec![row])),
+            }
+        }
+
+        let mut tx = acquire.begin().await?;
+        let mut reports = Vec::with_capacity(groups.len());
+
+        for (table_name, rows) in groups {
+            let options = BulkInsertOptions {
+                table_name: Some(table_name.clone()),
+                ..self.options.clone()
+            };
+            let report = (&mut *tx)
+                .bulk_insert_report_with_options(&rows, &options)
+                .await?;
+            reports.push((table_name, report));
+        }
+
+        tx.commit().await?;
+
+        Ok(reports)
+    }
+}
@@ -0,0 +1,67 @@
+use crate::Dialect;
+
+/// Optimizer and locking hints for the generated `INSERT` statements, each applied on its
+/// dialect only, so the same options work against every backend.
+///
+/// ```ignore
+/// BulkInsert::new(&rows)
+///     .hints(Hints::new().mysql("LOW_PRIORITY").mssql("TABLOCK"))
+///     .execute(&mut tx)
+///     .await?;
+/// ```
+///
+/// The hints are inserted into the SQL as is, so they must not contain untrusted input.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct Hints {
+    mysql: Option<String>,
+    mssql: Option<String>,
+    postgres: Option<String>,
+}
+
+impl Hints {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Modifiers after `INSERT`, e.g. `LOW_PRIORITY` or `IGNORE`.
+    pub fn mysql(mut self, modifiers: impl Into<String>) -> Self {
+        self.mysql = Some(modifiers.into());
+        self
+    }
+
+    /// Table hints, rendered as `INSERT INTO t WITH ({hints})`, e.g. `TABLOCK`.
+    pub fn mssql(mut self, table_hints: impl Into<String>) -> Self {
+        self.mssql = Some(table_hints.into());
+        self
+    }
+
+    /// A pg_hint_plan comment, rendered as `/*+ {hints} */` before the statement.
+    pub fn postgres(mut self, hints: impl Into<String>) -> Self {
+        self.postgres = Some(hints.into());
+        self
+    }
+
+    /// Generate an `INSERT` statement with the hints of `dialect`. `statement` generates it for
+    /// a table name, which carries MSSQL's table hints.
+    pub(crate) fn apply(
+        &self,
+        dialect: Dialect,
+        table_name: &str,
+        statement: impl FnOnce(&str) -> anyhow::Result<String>,
+    ) -> anyhow::Result<String> {
+        let sql = match (dialect, &self.mssql) {
+            (Dialect::Mssql, Some(hints)) => {
+                statement(&format!("{} WITH ({})", table_name, hints))?
+            }
+            _ => statement(table_name)?,
+        };
+
+        Ok(match (dialect, &self.postgres, &self.mysql) {
+            (Dialect::Postgres, Some(hints), _) => format!("/*+ {} */ {}", hints, sql),
+            (Dialect::MySql, _, Some(modifiers)) => {
+                sql.replacen("INSERT ", &format!("INSERT {} ", modifiers), 1)
+            }
+            _ => sql,
+        })
+    }
+}
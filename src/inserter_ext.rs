@@ -0,0 +1,102 @@
+use async_trait::async_trait;
+
+use crate::{Backend, BulkInsertOptions, BulkInsertReport, Insertable, Inserter};
+
+/// [`Inserter`] for the concrete receivers `&mut Transaction`, `&mut PoolConnection` and `&Pool`.
+///
+/// `Inserter` is implemented for any `&mut E` whose reborrows are executors, so a wrong receiver
+/// (e.g. a transaction which was moved away) fails with errors about higher-ranked `Executor`
+/// bounds. The impls of this trait name the real types, which keeps the errors readable.
+/// The methods are named differently from `Inserter` so both traits can be imported together.
+#[async_trait]
+pub trait InserterExt<DB: Backend>: Sized {
+    async fn insert_row<T>(self, value: &T) -> anyhow::Result<DB::QueryResult>
+    where
+        T: Insertable<Database = DB> + Sync;
+
+    async fn insert_rows<I>(self, values: I) -> anyhow::Result<Vec<DB::QueryResult>>
+    where
+        I: IntoIterator + Send,
+        I::IntoIter: Send,
+        I::Item: Insertable<Database = DB> + Send + Sync;
+
+    async fn insert_rows_with_options<T>(
+        self,
+        values: &[T],
+        options: &BulkInsertOptions,
+    ) -> anyhow::Result<BulkInsertReport>
+    where
+        T: Insertable<Database = DB> + Sync;
+
+    async fn insert_rows_returning_ids<I>(self, values: I) -> anyhow::Result<Vec<i64>>
+    where
+        I: IntoIterator + Send,
+        I::IntoIter: Send,
+        I::Item: Insertable<Database = DB> + Send + Sync;
+}
+
+macro_rules! impl_inserter_ext_for {
+    ( $db:ty, $receiver:ty ) => {
+        #[async_trait]
+        impl InserterExt<$db> for $receiver {
+            async fn insert_row<T>(
+                self,
+                value: &T,
+            ) -> anyhow::Result<<$db as sqlx::Database>::QueryResult>
+            where
+                T: Insertable<Database = $db> + Sync,
+            {
+                Inserter::insert(self, value).await
+            }
+
+            async fn insert_rows<I>(
+                self,
+                values: I,
+            ) -> anyhow::Result<Vec<<$db as sqlx::Database>::QueryResult>>
+            where
+                I: IntoIterator + Send,
+                I::IntoIter: Send,
+                I::Item: Insertable<Database = $db> + Send + Sync,
+            {
+                Inserter::bulk_insert(self, values).await
+            }
+
+            async fn insert_rows_with_options<T>(
+                self,
+                values: &[T],
+                options: &BulkInsertOptions,
+            ) -> anyhow::Result<BulkInsertReport>
+            where
+                T: Insertable<Database = $db> + Sync,
+            {
+                Inserter::bulk_insert_report_with_options(self, values, options).await
+            }
+
+            async fn insert_rows_returning_ids<I>(self, values: I) -> anyhow::Result<Vec<i64>>
+            where
+                I: IntoIterator + Send,
+                I::IntoIter: Send,
+                I::Item: Insertable<Database = $db> + Send + Sync,
+            {
+                Inserter::bulk_insert_returning_ids(self, values).await
+            }
+        }
+    };
+}
+
+macro_rules! impl_inserter_ext {
+    ( $db:ty ) => {
+        impl_inserter_ext_for!($db, &'_ mut sqlx::Transaction<'_, $db>);
+        impl_inserter_ext_for!($db, &'_ mut sqlx::pool::PoolConnection<$db>);
+        impl_inserter_ext_for!($db, &'_ sqlx::Pool<$db>);
+    };
+}
+
+#[cfg(feature = "sqlite")]
+impl_inserter_ext!(sqlx::Sqlite);
+#[cfg(feature = "mysql")]
+impl_inserter_ext!(sqlx::MySql);
+#[cfg(feature = "postgres")]
+impl_inserter_ext!(sqlx::Postgres);
+#[cfg(feature = "mssql")]
+impl_inserter_ext!(sqlx::Mssql);
@@ -0,0 +1,181 @@
+use std::{
+    any::Any,
+    ops::{Deref, DerefMut},
+    sync::Arc,
+};
+
+use async_trait::async_trait;
+use sqlx::{Acquire, Executor, IntoArguments, Transaction};
+
+use crate::{compat::ArgumentsOf, keyed, Backend, Config, Entity, Keyed};
+
+/// Keeps a cache coherent with the writes sqlx-plus makes, e.g. by deleting Redis entries.
+///
+/// Registered on a [`Config`], it is called after every successful insert, `save` and delete
+/// with the table name and the keys of the written rows, which can be downcast to the table's
+/// [`Keyed::Key`]:
+///
+/// ```ignore
+/// struct UserCache(Arc<Mutex<HashMap<i64, User>>>);
+///
+/// #[async_trait::async_trait]
+/// impl CacheInvalidator for UserCache {
+///     async fn invalidate(&self, table_name: &str, keys: &[&(dyn Any + Send + Sync)]) {
+///         if table_name == "users" {
+///             let mut cache = self.0.lock().unwrap();
+///             for key in keys.iter().filter_map(|key| key.downcast_ref::<UserId>()) {
+///                 cache.remove(&key.0);
+///             }
+///         }
+///     }
+/// }
+///
+/// Config::new().cache_invalidator(UserCache(cache)).set_global();
+/// ```
+///
+/// Inserts pass no keys, the new rows are not known by key. Writes are reported right after
+/// their statement, also inside a transaction. Use a [`CacheTransaction`] to report them after
+/// the commit instead.
+#[async_trait]
+pub trait CacheInvalidator: Send + Sync {
+    async fn invalidate(&self, table_name: &str, keys: &[&(dyn Any + Send + Sync)]);
+}
+
+#[async_trait]
+impl<C: CacheInvalidator + ?Sized> CacheInvalidator for Arc<C> {
+    async fn invalidate(&self, table_name: &str, keys: &[&(dyn Any + Send + Sync)]) {
+        (**self).invalidate(table_name, keys).await
+    }
+}
+
+impl Config {
+    pub(crate) async fn invalidate(&self, table_name: &str, keys: &[&(dyn Any + Send + Sync)]) {
+        for invalidator in self.cache_invalidators() {
+            invalidator.invalidate(table_name, keys).await;
+        }
+    }
+}
+
+/// A transaction which reports its `save`s and deletes to the [`CacheInvalidator`]s only once
+/// it committed, so no reader can refill a cache with the old rows in between:
+///
+/// ```ignore
+/// let mut tx = CacheTransaction::begin(&pool).await?;
+/// tx.save(&user).await?;
+/// tx.delete_by_id::<UserEntity>(&UserId(1)).await?;
+/// tx.commit().await?;
+/// ```
+///
+/// It derefs to the wrapped transaction for everything else. Writes made through it are
+/// reported right away, like with any other executor.
+pub struct CacheTransaction<'c, DB: sqlx::Database> {
+    tx: Transaction<'c, DB>,
+    config: Arc<Config>,
+    pending: Vec<(&'static str, Vec<Box<dyn Any + Send + Sync>>)>,
+}
+
+impl<'c, DB> CacheTransaction<'c, DB>
+where
+    DB: Backend,
+    for<'e> &'e mut DB::Connection: Executor<'e, Database = DB>,
+    for<'q> ArgumentsOf<'q, DB>: IntoArguments<'q, DB>,
+{
+    pub async fn begin<A>(acquire: A) -> anyhow::Result<Self>
+    where
+        A: Acquire<'c, Database = DB>,
+    {
+        Ok(CacheTransaction {
+            tx: acquire.begin().await?,
+            config: Config::global(),
+            pending: Vec::new(),
+        })
+    }
+
+    /// Report to the invalidators of `config` instead of the global ones.
+    pub fn config(mut self, config: impl Into<Arc<Config>>) -> Self {
+        self.config = config.into();
+        self
+    }
+
+    /// Save like `Inserter::save`.
+    pub async fn save<T>(&mut self, value: &T) -> anyhow::Result<DB::QueryResult>
+    where
+        T: Entity<Database = DB> + Sync,
+        T::Key: Clone,
+    {
+        let result = keyed::save_quietly(&mut *self.tx, value).await?;
+        let keys = value.key().cloned().into_iter().collect();
+        self.defer::<T>(keys);
+
+        Ok(result)
+    }
+
+    /// Delete like [`delete_by_id`](crate::delete_by_id).
+    pub async fn delete_by_id<T>(&mut self, key: &T::Key) -> anyhow::Result<bool>
+    where
+        T: Keyed<Database = DB>,
+        T::Key: Clone,
+    {
+        let deleted = keyed::delete_by_id_quietly::<T, _>(&mut *self.tx, key).await?;
+        if deleted {
+            self.defer::<T>(vec![key.clone()]);
+        }
+
+        Ok(deleted)
+    }
+
+    /// Delete like [`delete_by_ids`](crate::delete_by_ids).
+    pub async fn delete_by_ids<T>(&mut self, keys: &[T::Key]) -> anyhow::Result<u64>
+    where
+        T: Keyed<Database = DB>,
+        T::Key: Clone,
+    {
+        let deleted = keyed::delete_by_ids_quietly::<T, _>(&mut *self.tx, keys).await?;
+        if deleted > 0 {
+            self.defer::<T>(keys.to_vec());
+        }
+
+        Ok(deleted)
+    }
+
+    /// Commit, then report the writes.
+    pub async fn commit(self) -> anyhow::Result<()> {
+        self.tx.commit().await?;
+
+        for (table_name, keys) in &self.pending {
+            let keys: Vec<_> = keys.iter().map(|key| &**key).collect();
+            self.config.invalidate(table_name, &keys).await;
+        }
+
+        Ok(())
+    }
+
+    /// Roll back and forget the writes.
+    pub async fn rollback(self) -> anyhow::Result<()> {
+        self.tx.rollback().await?;
+
+        Ok(())
+    }
+
+    fn defer<T: Keyed>(&mut self, keys: Vec<T::Key>) {
+        let keys = keys
+            .into_iter()
+            .map(|key| Box::new(key) as Box<dyn Any + Send + Sync>)
+            .collect();
+        self.pending.push((T::table_name(), keys));
+    }
+}
+
+impl<'c, DB: sqlx::Database> Deref for CacheTransaction<'c, DB> {
+    type Target = Transaction<'c, DB>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.tx
+    }
+}
+
+impl<DB: sqlx::Database> DerefMut for CacheTransaction<'_, DB> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.tx
+    }
+}
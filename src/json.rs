@@ -0,0 +1,116 @@
+//! Predicates on JSON columns, with the path and the value bound rather than spliced into the
+//! SQL. Each function returns an expression for a [`Dialect`], its placeholders numbered from
+//! `start_num` on PostgreSQL:
+//!
+//! ```ignore
+//! let sql = format!(
+//!     "SELECT id FROM events WHERE {} = {} AND {}",
+//!     json::extract(DB::DIALECT, "payload", 1),
+//!     DB::placeholders(1, Some(2)),
+//!     json::array_contains(DB::DIALECT, "payload", 3),
+//! );
+//! let ids: Vec<i64> = sqlx::query_scalar(&sql)
+//!     .bind_json_path(&json::Path::new().key("user").key("country"))
+//!     .bind("JP")
+//!     .bind_json_path(&json::Path::new().key("tags"))
+//!     .bind("beta")
+//!     .fetch_all(&pool)
+//!     .await?;
+//! ```
+//!
+//! Paths are SQL/JSON paths like `$."user"."tags"[0]`, which every dialect understands:
+//! PostgreSQL as a `jsonpath` (12 and later), MSSQL from 2017 on.
+
+use std::fmt;
+
+use crate::{dialect, Dialect};
+
+/// A JSON path, bound with [`QueryBindExt::bind_json_path`](crate::QueryBindExt::bind_json_path).
+/// Keys are quoted, so they may contain any character.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct Path {
+    path: String,
+}
+
+impl Path {
+    /// The root, `$`.
+    pub fn new() -> Self {
+        Path::default()
+    }
+
+    /// The member `key` of an object.
+    pub fn key(mut self, key: &str) -> Self {
+        self.path.push_str(".\"");
+        for c in key.chars() {
+            match c {
+                '"' => self.path.push_str("\\\""),
+                '\\' => self.path.push_str("\\\\"),
+                c => self.path.push(c),
+            }
+        }
+        self.path.push('"');
+        self
+    }
+
+    /// The element `index` of an array.
+    pub fn index(mut self, index: usize) -> Self {
+        self.path.push_str(&format!("[{}]", index));
+        self
+    }
+}
+
+impl fmt::Display for Path {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "${}", self.path)
+    }
+}
+
+fn placeholder(dialect: Dialect, num: usize) -> String {
+    let mut buf = String::new();
+    dialect::write_placeholders(&mut buf, dialect, 1, num);
+    buf
+}
+
+/// The scalar at a bound path of `column`, as text, `NULL` where there is none. One
+/// placeholder, the path.
+///
+/// SQLite returns the SQL value instead, so numbers compare as numbers and `true` is `1`.
+pub fn extract(dialect: Dialect, column: &str, start_num: usize) -> String {
+    let path = placeholder(dialect, start_num);
+    match dialect {
+        Dialect::Sqlite => format!("json_extract({}, {})", column, path),
+        Dialect::MySql => format!("JSON_UNQUOTE(JSON_EXTRACT({}, {}))", column, path),
+        Dialect::Postgres => format!(
+            "(jsonb_path_query_first(CAST({} AS jsonb), CAST({} AS jsonpath)) #>> '{{}}')",
+            column, path
+        ),
+        Dialect::Mssql => format!("JSON_VALUE({}, {})", column, path),
+    }
+}
+
+/// Whether the array at a bound path of `column` contains a bound text. Two placeholders, the
+/// path and the text. A scalar or an object at the path contains nothing.
+pub fn array_contains(dialect: Dialect, column: &str, start_num: usize) -> String {
+    let path = placeholder(dialect, start_num);
+    let value = placeholder(dialect, start_num + 1);
+    match dialect {
+        // `json_each` yields a scalar itself, array elements are the ones with an index.
+        Dialect::Sqlite => format!(
+            "EXISTS (SELECT 1 FROM json_each({}, {}) AS e WHERE typeof(e.key) = 'integer' AND e.type = 'text' AND e.value = {})",
+            column, path, value
+        ),
+        Dialect::MySql => format!(
+            "(JSON_CONTAINS(JSON_EXTRACT({}, {}), JSON_ARRAY({})) = 1)",
+            column, path, value
+        ),
+        Dialect::Postgres => format!(
+            "(jsonb_path_query_first(CAST({} AS jsonb), CAST({} AS jsonpath)) @> jsonb_build_array(CAST({} AS text)))",
+            column, path, value
+        ),
+        // `JSON_QUERY` is `NULL` for scalars.
+        Dialect::Mssql => format!(
+            "EXISTS (SELECT 1 FROM (SELECT JSON_QUERY({}, {}) AS a) AS q CROSS APPLY OPENJSON(q.a) AS e WHERE LEFT(q.a, 1) = '[' AND e.[type] = 1 AND e.[value] = {})",
+            column, path, value
+        ),
+    }
+}
@@ -0,0 +1,424 @@
+use std::any::Any;
+
+use sqlx::{Executor, FromRow, IntoArguments};
+
+use crate::{
+    compat::ArgumentsOf, AsRow, Backend, Column, Config, Dialect, Insertable, PlaceHolders,
+    QueryBindExt,
+};
+
+/// A table with a typed primary key, so key-based helpers can't be handed an id of another
+/// table.
+///
+/// The derive implements it when a key type is given:
+///
+/// ```ignore
+/// #[derive(sqlx::Type)]
+/// #[sqlx(transparent)]
+/// struct UserId(i64);
+///
+/// #[derive(sqlx_plus::Insertable)]
+/// #[insertable(sqlx::Postgres, "users", key = UserId)]
+/// struct NewUser { ... }
+///
+/// let user: Option<User> = sqlx_plus::find_by_id::<NewUser, _, _>(&mut conn, &UserId(1)).await?;
+/// ```
+pub trait Keyed: Insertable {
+    type Key: Send + Sync + 'static;
+
+    /// The key columns, in the order `bind_key` binds them.
+    fn key_columns() -> &'static [&'static str];
+
+    fn bind_key<'q, Q>(key: &'q Self::Key, q: Q) -> Q
+    where
+        Q: QueryBindExt<'q, Self::Database>;
+}
+
+/// A row which knows its own key, i.e. a struct with a key field:
+///
+/// ```ignore
+/// #[derive(sqlx_plus::Insertable)]
+/// #[insertable(sqlx::Postgres, "users")]
+/// struct User {
+///     #[insertable(key)]
+///     id: Option<UserId>,
+///     name: String,
+/// }
+/// ```
+///
+/// The key field is not inserted, the database generates it. A key of `None`, or the default
+/// value for non-`Option` fields, means the row was not inserted yet.
+pub trait Entity: Keyed {
+    fn key(&self) -> Option<&Self::Key>;
+}
+
+/// Insert `value` if it has no key yet, otherwise update the row with its key.
+pub(crate) async fn save<T, E, DB>(executor: &mut E, value: &T) -> anyhow::Result<DB::QueryResult>
+where
+    DB: Backend,
+    T: Entity<Database = DB> + Sync,
+    T::Key: Sync,
+    for<'e> &'e mut E: Executor<'e, Database = DB>,
+    for<'q> ArgumentsOf<'q, DB>: IntoArguments<'q, DB>,
+{
+    let result = save_quietly(executor, value).await?;
+    match value.key() {
+        Some(key) => Config::global().invalidate(T::table_name(), &[key]).await,
+        None => Config::global().invalidate(T::table_name(), &[]).await,
+    }
+
+    Ok(result)
+}
+
+/// [`save`] without reporting to the cache invalidators.
+pub(crate) async fn save_quietly<T, E, DB>(
+    executor: &mut E,
+    value: &T,
+) -> anyhow::Result<DB::QueryResult>
+where
+    DB: Backend,
+    T: Entity<Database = DB> + Sync,
+    T::Key: Sync,
+    for<'e> &'e mut E: Executor<'e, Database = DB>,
+    for<'q> ArgumentsOf<'q, DB>: IntoArguments<'q, DB>,
+{
+    let key = match value.key() {
+        Some(key) => key,
+        None => return crate::insert_quietly(executor, value).await,
+    };
+
+    update_quietly(executor, value, key, T::update_columns()).await
+}
+
+/// Update only the given columns of the row of `value`, e.g. after changing a single field:
+///
+/// ```ignore
+/// user.name = "new name".into();
+/// sqlx_plus::update_fields(&mut conn, &user, &[User::NAME]).await?;
+/// ```
+///
+/// Columns of `#[insertable(immutable)]` fields are rejected at compile time. Fails if `value`
+/// has no key yet.
+pub async fn update_fields<T, E, DB>(
+    executor: &mut E,
+    value: &T,
+    columns: &[Column<T>],
+) -> anyhow::Result<DB::QueryResult>
+where
+    DB: Backend,
+    T: Entity<Database = DB> + Sync,
+    for<'e> &'e mut E: Executor<'e, Database = DB>,
+    for<'q> ArgumentsOf<'q, DB>: IntoArguments<'q, DB>,
+{
+    let key = value
+        .key()
+        .ok_or_else(|| anyhow::anyhow!("{} row has no key to update", T::table_name()))?;
+    let columns: Vec<_> = columns.iter().map(Column::name).collect();
+
+    let result = update_quietly(executor, value, key, &columns).await?;
+    Config::global().invalidate(T::table_name(), &[key]).await;
+
+    Ok(result)
+}
+
+/// Update only the columns whose values differ between `old` and `new`, comparing them with
+/// [`AsRow`]. Nothing is executed when no column changed, which spares sync jobs the write and
+/// its locks:
+///
+/// ```ignore
+/// let old = user.clone();
+/// user.apply(remote_changes);
+/// sqlx_plus::diff_update(&mut conn, &old, &user).await?;
+/// ```
+///
+/// The row is the one of `new`'s key. Columns of `#[insertable(immutable)]` fields are never
+/// updated, like with `save`. Returns `None` when nothing changed.
+pub async fn diff_update<T, E, DB>(
+    executor: &mut E,
+    old: &T,
+    new: &T,
+) -> anyhow::Result<Option<DB::QueryResult>>
+where
+    DB: Backend,
+    T: Entity<Database = DB> + AsRow + Sync,
+    for<'e> &'e mut E: Executor<'e, Database = DB>,
+    for<'q> ArgumentsOf<'q, DB>: IntoArguments<'q, DB>,
+{
+    let key = new
+        .key()
+        .ok_or_else(|| anyhow::anyhow!("{} row has no key to update", T::table_name()))?;
+    let columns: Vec<_> = old
+        .as_row()
+        .into_iter()
+        .zip(new.as_row())
+        .filter(|((column, old), (_, new))| old != new && T::update_columns().contains(column))
+        .map(|((column, _), _)| column)
+        .collect();
+    if columns.is_empty() {
+        return Ok(None);
+    }
+
+    let result = update_quietly(executor, new, key, &columns).await?;
+    Config::global().invalidate(T::table_name(), &[key]).await;
+
+    Ok(Some(result))
+}
+
+async fn update_quietly<T, E, DB>(
+    executor: &mut E,
+    value: &T,
+    key: &T::Key,
+    columns: &[&str],
+) -> anyhow::Result<DB::QueryResult>
+where
+    DB: Backend,
+    T: Entity<Database = DB> + Sync,
+    for<'e> &'e mut E: Executor<'e, Database = DB>,
+    for<'q> ArgumentsOf<'q, DB>: IntoArguments<'q, DB>,
+{
+    if columns.is_empty() {
+        anyhow::bail!("no columns of {} to update", T::table_name());
+    }
+
+    let mut sql = String::from("UPDATE ");
+    sql.push_str(T::table_name());
+    sql.push_str(" SET ");
+    for (i, column) in columns.iter().enumerate() {
+        if i > 0 {
+            sql.push(',');
+        }
+        sql.push_str(column);
+        sql.push_str(" = ");
+        DB::write_placeholders(&mut sql, 1, i + 1);
+    }
+    write_key_predicate::<T>(&mut sql, columns.len() + 1);
+
+    let config = Config::global();
+    let statement = config.before(sql, T::table_name(), 1).await?;
+    let query = sqlx::query(statement.sql()).bind_fields_of(value, columns)?;
+    let result = statement
+        .run(T::bind_key(key, query).execute(executor))
+        .await;
+    statement.after(&result, DB::rows_affected).await;
+
+    result.map_err(T::map_error)
+}
+
+/// Fetch the row of `T`'s table with the given key.
+pub async fn find_by_id<T, O, E>(executor: &mut E, key: &T::Key) -> anyhow::Result<Option<O>>
+where
+    T: Keyed,
+    T::Database: Backend,
+    O: for<'r> FromRow<'r, <T::Database as sqlx::Database>::Row> + Send + Unpin,
+    for<'e> &'e mut E: Executor<'e, Database = T::Database>,
+    for<'q> ArgumentsOf<'q, T::Database>: IntoArguments<'q, T::Database>,
+{
+    let mut sql = String::from("SELECT * FROM ");
+    sql.push_str(T::table_name());
+    write_key_predicate::<T>(&mut sql, 1);
+
+    let config = Config::global();
+    let statement = config.before(sql, T::table_name(), 0).await?;
+    let row = statement
+        .run(T::bind_key(key, sqlx::query_as(statement.sql())).fetch_optional(executor))
+        .await;
+    statement.after(&row, |row| row.is_some() as u64).await;
+
+    Ok(row?)
+}
+
+/// Delete the row of `T`'s table with the given key and return whether it existed.
+pub async fn delete_by_id<T, E>(executor: &mut E, key: &T::Key) -> anyhow::Result<bool>
+where
+    T: Keyed,
+    T::Database: Backend,
+    for<'e> &'e mut E: Executor<'e, Database = T::Database>,
+    for<'q> ArgumentsOf<'q, T::Database>: IntoArguments<'q, T::Database>,
+{
+    let deleted = delete_by_id_quietly::<T, E>(executor, key).await?;
+    if deleted {
+        Config::global().invalidate(T::table_name(), &[key]).await;
+    }
+
+    Ok(deleted)
+}
+
+pub(crate) async fn delete_by_id_quietly<T, E>(
+    executor: &mut E,
+    key: &T::Key,
+) -> anyhow::Result<bool>
+where
+    T: Keyed,
+    T::Database: Backend,
+    for<'e> &'e mut E: Executor<'e, Database = T::Database>,
+    for<'q> ArgumentsOf<'q, T::Database>: IntoArguments<'q, T::Database>,
+{
+    let mut sql = String::from("DELETE FROM ");
+    sql.push_str(T::table_name());
+    write_key_predicate::<T>(&mut sql, 1);
+
+    let config = Config::global();
+    let statement = config.before(sql, T::table_name(), 0).await?;
+    let result = statement
+        .run(T::bind_key(key, sqlx::query(statement.sql())).execute(executor))
+        .await;
+    statement.after(&result, T::Database::rows_affected).await;
+
+    Ok(T::Database::rows_affected(&result?) > 0)
+}
+
+/// Fetch the rows of `T`'s table with the given keys, in no particular order. Missing keys are
+/// skipped.
+pub async fn find_by_ids<T, O, E>(executor: &mut E, keys: &[T::Key]) -> anyhow::Result<Vec<O>>
+where
+    T: Keyed,
+    T::Database: Backend,
+    O: for<'r> FromRow<'r, <T::Database as sqlx::Database>::Row> + Send + Unpin,
+    for<'e> &'e mut E: Executor<'e, Database = T::Database>,
+    for<'q> ArgumentsOf<'q, T::Database>: IntoArguments<'q, T::Database>,
+{
+    let mut rows = Vec::with_capacity(keys.len());
+
+    for chunk in keys.chunks(max_keys_per_statement::<T>()) {
+        let mut sql = String::from("SELECT * FROM ");
+        sql.push_str(T::table_name());
+        write_keys_predicate::<T>(&mut sql, chunk.len());
+
+        let config = Config::global();
+        let statement = config.before(sql, T::table_name(), 0).await?;
+        let fetched = statement
+            .run(
+                sqlx::query_as(statement.sql())
+                    .bind_multi_with(chunk, |q, key| T::bind_key(key, q))
+                    .fetch_all(&mut *executor),
+            )
+            .await;
+        statement.after(&fetched, |rows| rows.len() as u64).await;
+        rows.extend(fetched?);
+    }
+
+    Ok(rows)
+}
+
+/// Delete the rows of `T`'s table with the given keys and return how many existed.
+pub async fn delete_by_ids<T, E>(executor: &mut E, keys: &[T::Key]) -> anyhow::Result<u64>
+where
+    T: Keyed,
+    T::Database: Backend,
+    for<'e> &'e mut E: Executor<'e, Database = T::Database>,
+    for<'q> ArgumentsOf<'q, T::Database>: IntoArguments<'q, T::Database>,
+{
+    let deleted = delete_by_ids_quietly::<T, E>(executor, keys).await?;
+    if deleted > 0 {
+        let keys: Vec<&(dyn Any + Send + Sync)> = keys.iter().map(|key| key as _).collect();
+        Config::global().invalidate(T::table_name(), &keys).await;
+    }
+
+    Ok(deleted)
+}
+
+pub(crate) async fn delete_by_ids_quietly<T, E>(
+    executor: &mut E,
+    keys: &[T::Key],
+) -> anyhow::Result<u64>
+where
+    T: Keyed,
+    T::Database: Backend,
+    for<'e> &'e mut E: Executor<'e, Database = T::Database>,
+    for<'q> ArgumentsOf<'q, T::Database>: IntoArguments<'q, T::Database>,
+{
+    let mut deleted = 0;
+
+    for chunk in keys.chunks(max_keys_per_statement::<T>()) {
+        let mut sql = String::from("DELETE FROM ");
+        sql.push_str(T::table_name());
+        write_keys_predicate::<T>(&mut sql, chunk.len());
+
+        let config = Config::global();
+        let statement = config.before(sql, T::table_name(), 0).await?;
+        let result = statement
+            .run(
+                sqlx::query(statement.sql())
+                    .bind_multi_with(chunk, |q, key| T::bind_key(key, q))
+                    .execute(&mut *executor),
+            )
+            .await;
+        statement.after(&result, T::Database::rows_affected).await;
+        deleted += T::Database::rows_affected(&result?);
+    }
+
+    Ok(deleted)
+}
+
+fn max_keys_per_statement<T>() -> usize
+where
+    T: Keyed,
+    T::Database: Backend,
+{
+    (T::Database::MAX_BIND_PARAMS / T::key_columns().len().max(1)).max(1)
+}
+
+/// Append ` WHERE ...` matching `keys` keys of `T`, numbering the placeholders from 1.
+///
+/// A single key column is matched with `IN`. Composite keys use row values on PostgreSQL and
+/// SQLite, and are expanded to `(k1 = ? AND k2 = ?) OR ...` elsewhere: MSSQL has no row values
+/// and MySQL does not use indexes for them.
+fn write_keys_predicate<T>(buf: &mut String, keys: usize)
+where
+    T: Keyed,
+    T::Database: Backend,
+{
+    let columns = T::key_columns();
+
+    buf.push_str(" WHERE ");
+    match (columns, T::Database::DIALECT) {
+        ([column], _) => {
+            buf.push_str(column);
+            buf.push_str(" IN (");
+            T::Database::write_placeholders(buf, keys, 1);
+            buf.push(')');
+        }
+        (_, Dialect::Postgres | Dialect::Sqlite) => {
+            buf.push('(');
+            buf.push_str(&columns.join(","));
+            buf.push_str(") IN (");
+            T::Database::write_placeholders_for_values(buf, keys, columns.len(), 1);
+            buf.push(')');
+        }
+        (_, Dialect::MySql | Dialect::Mssql) => {
+            for key in 0..keys {
+                if key > 0 {
+                    buf.push_str(" OR ");
+                }
+                buf.push('(');
+                for (i, column) in columns.iter().enumerate() {
+                    if i > 0 {
+                        buf.push_str(" AND ");
+                    }
+                    buf.push_str(column);
+                    buf.push_str(" = ");
+                    T::Database::write_placeholders(buf, 1, 1 + key * columns.len() + i);
+                }
+                buf.push(')');
+            }
+        }
+    }
+}
+
+/// Append ` WHERE k1 = ? AND k2 = ? ...` for the key columns of `T`, numbering the
+/// placeholders from `start_num`.
+fn write_key_predicate<T>(buf: &mut String, start_num: usize)
+where
+    T: Keyed,
+    T::Database: PlaceHolders,
+{
+    buf.push_str(" WHERE ");
+    for (i, column) in T::key_columns().iter().enumerate() {
+        if i > 0 {
+            buf.push_str(" AND ");
+        }
+        buf.push_str(column);
+        buf.push_str(" = ");
+        T::Database::write_placeholders(buf, 1, start_num + i);
+    }
+}
@@ -3,12 +3,139 @@
 //! Please refer [README](https://github.com/sifyfy/sqlx-plus).
 //!
 
+use std::{ops::Range, time::Instant};
+
 use async_trait::async_trait;
-use itertools::Itertools;
-use sqlx::{database::HasArguments, Executor, IntoArguments};
+use sqlx::{Executor, IntoArguments};
+
+use crate::compat::{ArgumentsOf, QueryOf};
 
 pub use sqlx_plus_macros::Insertable;
 
+mod aggregate;
+pub mod anonymize;
+mod archive;
+mod arguments;
+mod backend;
+mod batch;
+#[cfg(feature = "blocking")]
+pub mod blocking;
+mod budget;
+mod builder;
+mod capabilities;
+mod capture;
+#[cfg(feature = "chaos")]
+pub mod chaos;
+mod check;
+mod chunks;
+pub mod clock;
+mod compat;
+mod config;
+pub mod conformance;
+pub mod context;
+mod copy;
+#[cfg(any(feature = "decimal", feature = "bigdecimal"))]
+pub mod decimal;
+mod demux;
+pub mod dialect;
+mod dual_write;
+mod error;
+mod estimate;
+mod format;
+mod gate;
+#[cfg(feature = "geo")]
+pub mod geo;
+mod get_or_create;
+mod group;
+mod hints;
+mod inserter_ext;
+mod invalidate;
+pub mod json;
+mod keyed;
+#[cfg(feature = "load-data")]
+pub mod load_data;
+mod load_hooks;
+mod manual;
+pub mod middleware;
+mod missing;
+#[cfg(feature = "normalize")]
+pub mod normalize;
+mod prepare;
+mod report;
+pub mod returning;
+mod row;
+mod runtime;
+pub mod sanitize;
+mod seed;
+mod select;
+mod snapshot;
+#[cfg(feature = "spill")]
+pub mod spill;
+#[cfg(feature = "sqlcommenter")]
+pub mod sqlcommenter;
+mod statement_stats;
+mod stream;
+mod table_columns;
+mod table_name;
+pub mod timestamp;
+mod type_map;
+pub mod upsert;
+
+pub use anonymize::Anonymizer;
+pub use archive::{archive_rows, ArchiveRows};
+pub use arguments::ArgumentsExt;
+pub use backend::Backend;
+pub use batch::{Batch, BatchReport};
+pub use budget::{BudgetPolicy, MemoryBudget, SizeEstimate};
+pub use builder::{BulkInsert, BulkInsertOptions};
+pub use capabilities::{Capabilities, ServerVersion};
+pub use capture::{capture_changes, Change, ChangeCapture};
+#[cfg(feature = "chaos")]
+pub use chaos::{ChaosExecutor, ChaosPolicy, ChaosStats};
+pub use check::{check_insertable, ensure_writable, roundtrip_check};
+pub use chunks::{chunks_for, Chunks};
+pub use config::Config;
+pub use context::{Context, DeadlineExceeded};
+pub use copy::{copy_table, CopyOptions, CopyProgress};
+pub use demux::Demux;
+pub use dialect::{sql_bool, sql_now, Dialect};
+pub use dual_write::{DualWrite, DualWritePolicy, DualWriteReport};
+pub use error::{
+    BindParamLimitExceeded, ConstraintViolation, ErrorExt, MemoryBudgetExceeded, PartialWrite,
+    SqlLengthExceeded,
+};
+pub use estimate::{estimate_bulk_insert, BulkInsertEstimate};
+pub use format::SqlFormat;
+pub use gate::{ChunkGate, ReplicationLagGate};
+pub use group::{group_by_table, GroupByTable};
+pub use hints::Hints;
+pub use inserter_ext::InserterExt;
+pub use invalidate::{CacheInvalidator, CacheTransaction};
+pub use keyed::{
+    delete_by_id, delete_by_ids, diff_update, find_by_id, find_by_ids, update_fields, Entity, Keyed,
+};
+#[cfg(feature = "load-data")]
+pub use load_data::{load_data, LocalInfile, LocalInfileRefused};
+pub use load_hooks::LoadHooks;
+pub use manual::check_binds;
+pub use middleware::{next_batch_id, ChunkPosition, Middleware, Statement, StatementOutcome};
+pub use prepare::prepare_insert;
+pub use report::{BulkInsertReport, ChunkError};
+pub use row::{AsRow, ValueRef};
+pub use seed::Seed;
+pub use select::{Column, Filter, Immutable, OrderBy, Select, Settable};
+pub use snapshot::SqlSnapshot;
+#[cfg(feature = "spill")]
+pub use spill::Spill;
+#[cfg(feature = "sqlcommenter")]
+pub use sqlcommenter::SqlCommenter;
+pub use statement_stats::{StatementStats, TableStatementStats};
+pub use stream::{chunked, fetch_stream_chunked, FetchChunked};
+pub use table_columns::ColumnCache;
+pub use table_name::TableName;
+pub use type_map::TypeMap;
+pub use upsert::{OnConflict, UpsertOutcome};
+
 pub trait QueryBindExt<'q, DB: sqlx::Database>: Sized {
     fn bind<T>(self, value: T) -> Self
     where
@@ -25,18 +152,51 @@ pub trait QueryBindExt<'q, DB: sqlx::Database>: Sized {
         values.into_iter().fold(self, |q, v| q.bind(v))
     }
 
+    /// Bind a JSON path for the predicates of [`json`].
+    fn bind_json_path(self, path: &json::Path) -> Self
+    where
+        String: 'q + Send + sqlx::Encode<'q, DB> + sqlx::Type<DB>,
+    {
+        self.bind(path.to_string())
+    }
+
+    /// Bind each element of a tuple or array, e.g. the columns of a composite key.
+    fn bind_tuple<T: BindTuple<'q, DB>>(self, values: T) -> Self {
+        values.bind_to(self)
+    }
+
     fn bind_multi_with<T: 'q>(
         self,
         values: impl IntoIterator<Item = &'q T>,
         bind_fn: impl Fn(Self, &'q T) -> Self,
     ) -> Self {
-        values.into_iter().fold(self, |q, x| bind_fn(q, x))
+        values.into_iter().fold(self, bind_fn)
     }
 
+    /// Bind one value per column of `value`.
     fn bind_fields<T: Insertable<Database = DB>>(self, value: &'q T) -> Self {
         value.bind_fields(self)
     }
 
+    /// Bind the given columns of `value`, in the given order. For statements over a subset of
+    /// the columns, e.g. partial updates.
+    fn bind_fields_of<T: Insertable<Database = DB>>(
+        self,
+        value: &'q T,
+        columns: &[&str],
+    ) -> anyhow::Result<Self> {
+        if let Some(column) = columns
+            .iter()
+            .find(|column| !T::insert_columns().contains(column))
+        {
+            anyhow::bail!("{} has no column {}", T::table_name(), column);
+        }
+
+        Ok(columns
+            .iter()
+            .fold(self, |q, column| value.bind_field(column, q)))
+    }
+
     fn bind_multi_fields<T: Insertable<Database = DB> + 'q>(
         self,
         values: impl IntoIterator<Item = &'q T>,
@@ -45,9 +205,47 @@ pub trait QueryBindExt<'q, DB: sqlx::Database>: Sized {
     }
 }
 
-impl<'q, DB: sqlx::Database> QueryBindExt<'q, DB>
-    for sqlx::query::Query<'q, DB, <DB as HasArguments<'q>>::Arguments>
+/// A group of values bound with [`QueryBindExt::bind_tuple`]: tuples of up to 8 elements and
+/// arrays.
+pub trait BindTuple<'q, DB: sqlx::Database> {
+    fn bind_to<Q: QueryBindExt<'q, DB>>(self, q: Q) -> Q;
+}
+
+macro_rules! impl_bind_tuple {
+    ( $($ty:ident),+ ) => {
+        impl<'q, DB: sqlx::Database, $($ty),+> BindTuple<'q, DB> for ($($ty,)+)
+        where
+            $($ty: 'q + Send + sqlx::Encode<'q, DB> + sqlx::Type<DB>,)+
+        {
+            #[allow(non_snake_case)]
+            fn bind_to<Q: QueryBindExt<'q, DB>>(self, q: Q) -> Q {
+                let ($($ty,)+) = self;
+                q $(.bind($ty))+
+            }
+        }
+    };
+}
+
+impl_bind_tuple!(A);
+impl_bind_tuple!(A, B);
+impl_bind_tuple!(A, B, C);
+impl_bind_tuple!(A, B, C, D);
+impl_bind_tuple!(A, B, C, D, E);
+impl_bind_tuple!(A, B, C, D, E, F);
+impl_bind_tuple!(A, B, C, D, E, F, G);
+impl_bind_tuple!(A, B, C, D, E, F, G, H);
+
+impl<'q, DB, T, const N: usize> BindTuple<'q, DB> for [T; N]
+where
+    DB: sqlx::Database,
+    T: 'q + Send + sqlx::Encode<'q, DB> + sqlx::Type<DB>,
 {
+    fn bind_to<Q: QueryBindExt<'q, DB>>(self, q: Q) -> Q {
+        q.bind_multi(self)
+    }
+}
+
+impl<'q, DB: sqlx::Database> QueryBindExt<'q, DB> for QueryOf<'q, DB> {
     fn bind<T>(self, value: T) -> Self
     where
         T: 'q + Send + sqlx::Encode<'q, DB> + sqlx::Type<DB>,
@@ -56,8 +254,7 @@ impl<'q, DB: sqlx::Database> QueryBindExt<'q, DB>
     }
 }
 
-impl<'q, DB, O> QueryBindExt<'q, DB>
-    for sqlx::query::QueryAs<'q, DB, O, <DB as HasArguments<'q>>::Arguments>
+impl<'q, DB, O> QueryBindExt<'q, DB> for sqlx::query::QueryAs<'q, DB, O, ArgumentsOf<'q, DB>>
 where
     DB: sqlx::Database,
 {
@@ -69,8 +266,7 @@ where
     }
 }
 
-impl<'q, DB, O> QueryBindExt<'q, DB>
-    for sqlx::query::QueryScalar<'q, DB, O, <DB as HasArguments<'q>>::Arguments>
+impl<'q, DB, O> QueryBindExt<'q, DB> for sqlx::query::QueryScalar<'q, DB, O, ArgumentsOf<'q, DB>>
 where
     DB: sqlx::Database,
 {
@@ -82,86 +278,589 @@ where
     }
 }
 
+#[doc(hidden)]
+pub mod __private {
+    //! Used by the code generated by the derive.
+
+    pub use anyhow;
+    #[cfg(feature = "json")]
+    pub use sqlx::types::Json;
+    pub use sqlx::{Database, Error};
+
+    pub use crate::manual::check_columns;
+
+    pub const fn str_eq(a: &str, b: &str) -> bool {
+        let (a, b) = (a.as_bytes(), b.as_bytes());
+        if a.len() != b.len() {
+            return false;
+        }
+        let mut i = 0;
+        while i < a.len() {
+            if a[i] != b[i] {
+                return false;
+            }
+            i += 1;
+        }
+        true
+    }
+
+    /// Whether every column of `columns` is in `of`.
+    pub const fn is_subset(columns: &[&str], of: &[&str]) -> bool {
+        let mut i = 0;
+        while i < columns.len() {
+            let mut j = 0;
+            while j < of.len() && !str_eq(columns[i], of[j]) {
+                j += 1;
+            }
+            if j == of.len() {
+                return false;
+            }
+            i += 1;
+        }
+        true
+    }
+}
+
+/// A value which can be inserted into `table_name()`.
+///
+/// The derive generates `insert_columns()` and `bind_fields()` from the same list of fields, so
+/// the binds always match the columns. The order is the declaration order of the fields, or the
+/// alphabetical one with `#[insertable(..., column_order = "alphabetical")]`, which keeps the
+/// generated SQL and thus the statement cache stable when fields are reordered.
+///
+/// Fields marked `#[insertable(period)]` are the period columns of a system-versioned table,
+/// e.g. `ValidFrom` and `ValidTo`. On MSSQL, which generates them, they are left out of the
+/// inserted and updated columns but can still be selected. Elsewhere they are written like any
+/// other column; mark them `#[insertable(period = "generated")]` to leave them out on MariaDB.
+///
+/// The fields may be private and the struct `#[non_exhaustive]`: the generated impls live next
+/// to the struct and read the fields there, so other modules insert, save and select it without
+/// seeing them. The setters of the `builder` keep the visibility of their fields.
+///
+/// Fields are bound by reference, so text fields may borrow: `&str`, `Option<&str>` and
+/// `Cow<'_, str>` bind like `String`, with no lifetime tied to the query. A struct which holds
+/// owned or literal strings needs no lifetime parameter at all with `Cow<'static, str>`.
 pub trait Insertable: Sized {
     type Database: sqlx::Database;
 
+    /// Number of columns in `insert_columns()`.
+    const COLUMN_COUNT: usize;
+
     fn table_name() -> &'static str;
 
-    fn insert_columns() -> Vec<&'static str>;
+    fn insert_columns() -> &'static [&'static str];
+
+    /// The columns `save` sets on an existing row: `insert_columns()` without the
+    /// `#[insertable(immutable)]` ones.
+    fn update_columns() -> &'static [&'static str] {
+        Self::insert_columns()
+    }
+
+    /// The SQL expressions around the placeholders of `insert_columns()`, one per column, like
+    /// `ST_GeomFromWKB(?)` for `#[insertable(geo)]` or `#[insertable(expr = "...")]` fields.
+    /// `None` and an empty slice mean the bare placeholder.
+    fn insert_expressions() -> &'static [Option<&'static str>] {
+        &[]
+    }
+
+    /// The column sets of the table's unique constraints, declared with
+    /// `#[insertable(unique("email"), unique("tenant_id", "slug"))]`. The first one defines row
+    /// identity where none is given, e.g. for [`BulkInsert::on_conflict_default`].
+    fn unique_columns() -> &'static [&'static [&'static str]] {
+        &[]
+    }
 
+    /// Bind one value per column, in the order of `insert_columns()`.
     fn bind_fields<'q, Q>(&'q self, q: Q) -> Q
     where
         Q: QueryBindExt<'q, Self::Database>;
+
+    /// Bind the value of a single column. Unknown columns bind nothing.
+    ///
+    /// Statements over a subset of the columns bind through it, e.g. partial updates and inserts
+    /// leaving `default_if_none` or missing columns out.
+    fn bind_field<'q, Q>(&'q self, column: &str, q: Q) -> Q
+    where
+        Q: QueryBindExt<'q, Self::Database>;
+
+    /// Turn the error of a failed insert into a domain error, like the [`ConstraintViolation`]s
+    /// declared with `#[insertable(on_unique(...))]`.
+    fn map_error(error: sqlx::Error) -> anyhow::Error {
+        error.into()
+    }
+
+    /// The `#[insertable(anonymize = "...")]` columns with the names of their fakers.
+    fn anonymized_columns() -> &'static [(&'static str, &'static str)] {
+        &[]
+    }
+
+    /// Replace the values of `anonymized_columns()` by their fakes.
+    fn anonymize(&mut self, anonymizer: &Anonymizer) -> anyhow::Result<()> {
+        let _ = anonymizer;
+        Ok(())
+    }
+
+    /// The `#[insertable(default_if_none)]` columns, left out of the insert of a row where they
+    /// are `None` so that the column default applies rather than `NULL`.
+    ///
+    /// A bulk insert groups the rows by the columns they leave out and inserts group by group,
+    /// each with a statement of its own, in order of the first row of each group. The rows of
+    /// a group keep their order, and the row ranges of its chunks are positions in this grouped
+    /// order. Upserts and inserts returning ids bind `NULL` instead.
+    fn default_if_none_columns() -> &'static [&'static str] {
+        &[]
+    }
+
+    /// The `default_if_none_columns()` which are `None` in this row.
+    fn defaulted_columns(&self) -> Vec<&'static str> {
+        Vec::new()
+    }
+
+    /// Whether `bind_fields` binds other values than the fields themselves, like the derive's
+    /// `normalize`, `scale`, `skip_if`, `auto_now`, `auto_uuid` and `geo` fields do.
+    fn has_bind_conversions() -> bool {
+        false
+    }
 }
 
 impl<T: Insertable + Sync> Insertable for &T {
     type Database = T::Database;
 
+    const COLUMN_COUNT: usize = T::COLUMN_COUNT;
+
     fn table_name() -> &'static str {
         T::table_name()
     }
 
-    fn insert_columns() -> Vec<&'static str> {
+    fn insert_columns() -> &'static [&'static str] {
         T::insert_columns()
     }
 
+    fn update_columns() -> &'static [&'static str] {
+        T::update_columns()
+    }
+
+    fn insert_expressions() -> &'static [Option<&'static str>] {
+        T::insert_expressions()
+    }
+
+    fn unique_columns() -> &'static [&'static [&'static str]] {
+        T::unique_columns()
+    }
+
     fn bind_fields<'q, Q>(&'q self, q: Q) -> Q
     where
         Q: QueryBindExt<'q, Self::Database>,
     {
         (*self).bind_fields(q)
     }
+
+    fn bind_field<'q, Q>(&'q self, column: &str, q: Q) -> Q
+    where
+        Q: QueryBindExt<'q, Self::Database>,
+    {
+        (*self).bind_field(column, q)
+    }
+
+    fn map_error(error: sqlx::Error) -> anyhow::Error {
+        T::map_error(error)
+    }
+
+    fn anonymized_columns() -> &'static [(&'static str, &'static str)] {
+        T::anonymized_columns()
+    }
+
+    fn anonymize(&mut self, _anonymizer: &Anonymizer) -> anyhow::Result<()> {
+        anyhow::ensure!(
+            T::anonymized_columns().is_empty(),
+            "{} can not be anonymized through a shared reference",
+            std::any::type_name::<T>()
+        );
+        Ok(())
+    }
+
+    fn default_if_none_columns() -> &'static [&'static str] {
+        T::default_if_none_columns()
+    }
+
+    fn defaulted_columns(&self) -> Vec<&'static str> {
+        (*self).defaulted_columns()
+    }
+
+    fn has_bind_conversions() -> bool {
+        T::has_bind_conversions()
+    }
+}
+
+/// `get_mut` gives the value to anonymize, `None` if it is shared.
+macro_rules! impl_insertable_for_pointer {
+    ( $($ptr:ident)::+, $get_mut:expr ) => {
+        impl<T: Insertable> Insertable for $($ptr)::+<T> {
+            type Database = T::Database;
+
+            const COLUMN_COUNT: usize = T::COLUMN_COUNT;
+
+            fn table_name() -> &'static str {
+                T::table_name()
+            }
+
+            fn insert_columns() -> &'static [&'static str] {
+                T::insert_columns()
+            }
+
+            fn update_columns() -> &'static [&'static str] {
+                T::update_columns()
+            }
+
+            fn insert_expressions() -> &'static [Option<&'static str>] {
+                T::insert_expressions()
+            }
+
+            fn unique_columns() -> &'static [&'static [&'static str]] {
+                T::unique_columns()
+            }
+
+            fn bind_fields<'q, Q>(&'q self, q: Q) -> Q
+            where
+                Q: QueryBindExt<'q, Self::Database>,
+            {
+                (**self).bind_fields(q)
+            }
+
+            fn bind_field<'q, Q>(&'q self, column: &str, q: Q) -> Q
+            where
+                Q: QueryBindExt<'q, Self::Database>,
+            {
+                (**self).bind_field(column, q)
+            }
+
+            fn map_error(error: sqlx::Error) -> anyhow::Error {
+                T::map_error(error)
+            }
+
+            fn anonymized_columns() -> &'static [(&'static str, &'static str)] {
+                T::anonymized_columns()
+            }
+
+            fn anonymize(&mut self, anonymizer: &Anonymizer) -> anyhow::Result<()> {
+                if T::anonymized_columns().is_empty() {
+                    return Ok(());
+                }
+                match $get_mut(self) {
+                    Some(value) => T::anonymize(value, anonymizer),
+                    None => anyhow::bail!(
+                        "{} can not be anonymized while it is shared",
+                        std::any::type_name::<T>()
+                    ),
+                }
+            }
+
+            fn default_if_none_columns() -> &'static [&'static str] {
+                T::default_if_none_columns()
+            }
+
+            fn defaulted_columns(&self) -> Vec<&'static str> {
+                (**self).defaulted_columns()
+            }
+
+            fn has_bind_conversions() -> bool {
+                T::has_bind_conversions()
+            }
+        }
+    };
+}
+
+/// A `Box` is never shared.
+fn box_get_mut<T>(value: &mut Box<T>) -> Option<&mut T> {
+    Some(value)
+}
+
+impl_insertable_for_pointer!(Box, box_get_mut);
+impl_insertable_for_pointer!(std::rc::Rc, std::rc::Rc::get_mut);
+impl_insertable_for_pointer!(std::sync::Arc, std::sync::Arc::get_mut);
+
+impl<T> Insertable for std::borrow::Cow<'_, T>
+where
+    T: Insertable + Clone,
+{
+    type Database = T::Database;
+
+    const COLUMN_COUNT: usize = T::COLUMN_COUNT;
+
+    fn table_name() -> &'static str {
+        T::table_name()
+    }
+
+    fn insert_columns() -> &'static [&'static str] {
+        T::insert_columns()
+    }
+
+    fn update_columns() -> &'static [&'static str] {
+        T::update_columns()
+    }
+
+    fn insert_expressions() -> &'static [Option<&'static str>] {
+        T::insert_expressions()
+    }
+
+    fn unique_columns() -> &'static [&'static [&'static str]] {
+        T::unique_columns()
+    }
+
+    fn bind_fields<'q, Q>(&'q self, q: Q) -> Q
+    where
+        Q: QueryBindExt<'q, Self::Database>,
+    {
+        (**self).bind_fields(q)
+    }
+
+    fn bind_field<'q, Q>(&'q self, column: &str, q: Q) -> Q
+    where
+        Q: QueryBindExt<'q, Self::Database>,
+    {
+        (**self).bind_field(column, q)
+    }
+
+    fn map_error(error: sqlx::Error) -> anyhow::Error {
+        T::map_error(error)
+    }
+
+    fn anonymized_columns() -> &'static [(&'static str, &'static str)] {
+        T::anonymized_columns()
+    }
+
+    /// Clones a borrowed value.
+    fn anonymize(&mut self, anonymizer: &Anonymizer) -> anyhow::Result<()> {
+        if T::anonymized_columns().is_empty() {
+            return Ok(());
+        }
+        self.to_mut().anonymize(anonymizer)
+    }
+
+    fn default_if_none_columns() -> &'static [&'static str] {
+        T::default_if_none_columns()
+    }
+
+    fn defaulted_columns(&self) -> Vec<&'static str> {
+        (**self).defaulted_columns()
+    }
+
+    fn has_bind_conversions() -> bool {
+        T::has_bind_conversions()
+    }
 }
 
+/// The receivers of the insert helpers: `&Pool<DB>`, `&mut PoolConnection<DB>`,
+/// `&mut DB::Connection` and `&mut Transaction<DB>`, on every database.
+///
+/// Generic code takes any of them with an `I: Inserter<DB>` parameter. Code generic over the
+/// database bounds the connection instead, which covers the pool connections and transactions
+/// dereferencing to it:
+///
+/// ```ignore
+/// async fn import<DB, T>(conn: &mut PoolConnection<DB>, rows: &[T]) -> anyhow::Result<()>
+/// where
+///     DB: sqlx::Database,
+///     T: Insertable<Database = DB> + Sync,
+///     for<'c> &'c mut DB::Connection: Inserter<DB>,
+/// {
+///     conn.bulk_insert(rows).await?;
+///     Ok(())
+/// }
+/// ```
 #[async_trait]
-pub trait Inserter<DB: sqlx::Database>: Sized {
+pub trait Inserter<DB: sqlx::Database>: Sized + Send {
     async fn insert<T>(self, value: &T) -> anyhow::Result<DB::QueryResult>
     where
         T: Insertable<Database = DB> + Sync;
 
-    async fn bulk_insert_with_table_name_and_chunk_size<T>(
+    /// Insert a small batch with a single statement. Fails with [`BindParamLimitExceeded`] when
+    /// the values don't fit in one statement, use `bulk_insert` for those.
+    async fn insert_all<T>(self, values: &[T]) -> anyhow::Result<DB::QueryResult>
+    where
+        T: Insertable<Database = DB> + Sync;
+
+    async fn bulk_insert_with_options<T>(
         self,
-        table_name: &str,
-        chunk_size: usize,
         values: &[T],
+        options: &BulkInsertOptions,
     ) -> anyhow::Result<Vec<DB::QueryResult>>
     where
         T: Insertable<Database = DB> + Sync;
 
-    async fn bulk_insert<T>(self, values: &[T]) -> anyhow::Result<Vec<DB::QueryResult>>
+    async fn bulk_insert_report_with_options<T>(
+        self,
+        values: &[T],
+        options: &BulkInsertOptions,
+    ) -> anyhow::Result<BulkInsertReport>
+    where
+        T: Insertable<Database = DB> + Sync;
+
+    /// Insert the values and return their auto-generated ids, on MSSQL as a set rather than in
+    /// the order of `values`. See [`returning`].
+    async fn bulk_insert_returning_ids_with_options<T>(
+        self,
+        values: &[T],
+        options: &BulkInsertOptions,
+    ) -> anyhow::Result<Vec<i64>>
+    where
+        T: Insertable<Database = DB> + Sync;
+
+    /// Insert the entity if it has no key yet, otherwise update its row.
+    async fn save<T>(self, value: &T) -> anyhow::Result<DB::QueryResult>
+    where
+        T: Entity<Database = DB> + Sync,
+        T::Key: Sync;
+
+    /// Upsert the values and report what happened to each of them. See [`upsert`].
+    async fn bulk_upsert_returning_with_options<T>(
+        self,
+        values: &[T],
+        options: &BulkInsertOptions,
+    ) -> anyhow::Result<Vec<UpsertOutcome>>
+    where
+        T: Insertable<Database = DB> + Sync;
+
+    /// Insert the missing values and return the rows of all of them. See
+    /// [`BulkInsert::get_or_create_many`].
+    async fn get_or_create_many_with_options<T, O>(
+        self,
+        values: &[T],
+        options: &BulkInsertOptions,
+    ) -> anyhow::Result<Vec<O>>
+    where
+        T: Insertable<Database = DB> + Sync,
+        O: for<'r> sqlx::FromRow<'r, DB::Row> + Send + Unpin;
+
+    /// Insert only the values whose `key_columns` match no existing row. See
+    /// [`Inserter::bulk_insert_missing`].
+    async fn bulk_insert_missing_with_options<T>(
+        self,
+        values: &[T],
+        key_columns: &[&str],
+        options: &BulkInsertOptions,
+    ) -> anyhow::Result<BulkInsertReport>
+    where
+        T: Insertable<Database = DB> + Sync;
+
+    /// The largest value of `column` in the table of `T`, `None` if the table is empty, e.g. to
+    /// check the max key after a load. `column` is a column of `T` or a plain identifier.
+    async fn max_of<T, V>(self, column: &str) -> anyhow::Result<Option<V>>
+    where
+        T: Insertable<Database = DB>,
+        V: for<'r> sqlx::Decode<'r, DB> + sqlx::Type<DB> + Send + Sync + Unpin;
+
+    /// The smallest value of `column` in the table of `T`, like [`max_of`](Self::max_of).
+    async fn min_of<T, V>(self, column: &str) -> anyhow::Result<Option<V>>
+    where
+        T: Insertable<Database = DB>,
+        V: for<'r> sqlx::Decode<'r, DB> + sqlx::Type<DB> + Send + Sync + Unpin;
+
+    /// The sum of `column` in the table of `T`, like [`max_of`](Self::max_of). `V` is the type
+    /// the database sums to, e.g. `i64` for integers on SQLite but `Decimal` on PostgreSQL.
+    async fn sum_of<T, V>(self, column: &str) -> anyhow::Result<Option<V>>
+    where
+        T: Insertable<Database = DB>,
+        V: for<'r> sqlx::Decode<'r, DB> + sqlx::Type<DB> + Send + Sync + Unpin;
+
+    /// Insert only the values whose `key_columns` match no existing row.
+    ///
+    /// The existing keys are looked up first, chunk by chunk, and the remaining values are bulk
+    /// inserted. This makes loads idempotent on backends without usable `ON CONFLICT` support
+    /// (MSSQL, old MySQL), but unlike an upsert it races with concurrent writers, and duplicates
+    /// within `values` are inserted as is. Without `key_columns` the first unique constraint
+    /// declared on the derive is used.
+    async fn bulk_insert_missing<T>(
+        self,
+        values: &[T],
+        key_columns: &[&str],
+    ) -> anyhow::Result<BulkInsertReport>
     where
         T: Insertable<Database = DB> + Sync,
     {
-        self.bulk_insert_with_table_name(T::table_name(), values)
+        self.bulk_insert_missing_with_options(values, key_columns, &BulkInsertOptions::default())
+            .await
+    }
+
+    async fn bulk_insert_returning_ids<I>(self, values: I) -> anyhow::Result<Vec<i64>>
+    where
+        I: IntoIterator + Send,
+        I::IntoIter: Send,
+        I::Item: Insertable<Database = DB> + Send + Sync,
+    {
+        let values = values.into_iter().collect::<Vec<_>>();
+        self.bulk_insert_returning_ids_with_options(&values, &BulkInsertOptions::default())
             .await
     }
 
-    async fn bulk_insert_with_table_name<T>(
+    /// The `bulk_insert*` methods accept anything iterable over insertable values, e.g. `&[T]`,
+    /// `Vec<Arc<T>>` or `values.iter().filter(...)`.
+    async fn bulk_insert_with_table_name_and_chunk_size<I>(
         self,
-        table_name: &str,
-        values: &[T],
+        table_name: &TableName,
+        chunk_size: usize,
+        values: I,
     ) -> anyhow::Result<Vec<DB::QueryResult>>
     where
-        T: Insertable<Database = DB> + Sync,
+        I: IntoIterator + Send,
+        I::IntoIter: Send,
+        I::Item: Insertable<Database = DB> + Send + Sync,
     {
-        self.bulk_insert_with_table_name_and_chunk_size(
-            table_name,
-            30000 / T::insert_columns().len(),
-            values,
-        )
-        .await
+        let values = values.into_iter().collect::<Vec<_>>();
+        let options = BulkInsertOptions {
+            table_name: Some(table_name.clone()),
+            chunk_size: Some(chunk_size),
+            ..Default::default()
+        };
+        self.bulk_insert_with_options(&values, &options).await
     }
 
-    async fn bulk_insert_with_chunk_size<T>(
+    async fn bulk_insert<I>(self, values: I) -> anyhow::Result<Vec<DB::QueryResult>>
+    where
+        I: IntoIterator + Send,
+        I::IntoIter: Send,
+        I::Item: Insertable<Database = DB> + Send + Sync,
+    {
+        self.bulk_insert_with_table_name(&TableName::trusted(<I::Item>::table_name()), values)
+            .await
+    }
+
+    async fn bulk_insert_with_table_name<I>(
+        self,
+        table_name: &TableName,
+        values: I,
+    ) -> anyhow::Result<Vec<DB::QueryResult>>
+    where
+        I: IntoIterator + Send,
+        I::IntoIter: Send,
+        I::Item: Insertable<Database = DB> + Send + Sync,
+    {
+        // Without a chunk size the options pick the one of the config or the largest the
+        // capabilities allow.
+        let values = values.into_iter().collect::<Vec<_>>();
+        let options = BulkInsertOptions {
+            table_name: Some(table_name.clone()),
+            ..Default::default()
+        };
+        self.bulk_insert_with_options(&values, &options).await
+    }
+
+    async fn bulk_insert_with_chunk_size<I>(
         self,
         chunk_size: usize,
-        values: &[T],
+        values: I,
     ) -> anyhow::Result<Vec<DB::QueryResult>>
     where
-        T: Insertable<Database = DB> + Sync,
+        I: IntoIterator + Send,
+        I::IntoIter: Send,
+        I::Item: Insertable<Database = DB> + Send + Sync,
     {
-        self.bulk_insert_with_table_name_and_chunk_size(T::table_name(), chunk_size, values)
-            .await
+        self.bulk_insert_with_table_name_and_chunk_size(
+            &TableName::trusted(<I::Item>::table_name()),
+            chunk_size,
+            values,
+        )
+        .await
     }
 }
 
@@ -183,51 +882,282 @@ macro_rules! impl_inserter {
                 Ok(insert(self, value).await?)
             }
 
-            async fn bulk_insert_with_table_name_and_chunk_size<T>(
+            async fn insert_all<T>(
                 self,
-                table_name: &str,
-                chunk_size: usize,
                 values: &[T],
-            ) -> anyhow::Result<Vec<<$db as sqlx::Database>::QueryResult>>
+            ) -> anyhow::Result<<$db as sqlx::Database>::QueryResult>
             where
                 T: Insertable<Database = $db> + Sync,
             {
-                Ok(
-                    bulk_insert_with_table_name_and_chunk_size(
-                        self, table_name, chunk_size, values,
-                    )
-                    .await?,
-                )
+                insert_all(self, values).await
             }
-        }
 
-        #[async_trait]
-        impl Inserter<$db> for &'_ sqlx::Pool<$db> {
-            async fn insert<T>(
+            async fn bulk_insert_with_options<T>(
                 self,
-                value: &T,
-            ) -> anyhow::Result<<$db as sqlx::Database>::QueryResult>
+                values: &[T],
+                options: &BulkInsertOptions,
+            ) -> anyhow::Result<Vec<<$db as sqlx::Database>::QueryResult>>
             where
                 T: Insertable<Database = $db> + Sync,
             {
-                Ok(self.acquire().await?.insert(value).await?)
+                Ok(bulk_insert_with_options(self, values, options).await?)
             }
 
-            async fn bulk_insert_with_table_name_and_chunk_size<T>(
+            async fn bulk_insert_report_with_options<T>(
                 self,
-                table_name: &str,
-                chunk_size: usize,
                 values: &[T],
-            ) -> anyhow::Result<Vec<<$db as sqlx::Database>::QueryResult>>
+                options: &BulkInsertOptions,
+            ) -> anyhow::Result<BulkInsertReport>
             where
                 T: Insertable<Database = $db> + Sync,
             {
-                Ok(self
-                    .acquire()
+                bulk_insert_report_with_options(self, values, options).await
+            }
+
+            async fn bulk_insert_returning_ids_with_options<T>(
+                self,
+                values: &[T],
+                options: &BulkInsertOptions,
+            ) -> anyhow::Result<Vec<i64>>
+            where
+                T: Insertable<Database = $db> + Sync,
+            {
+                returning::bulk_insert_returning_ids(self, values, options).await
+            }
+
+            async fn save<T>(
+                self,
+                value: &T,
+            ) -> anyhow::Result<<$db as sqlx::Database>::QueryResult>
+            where
+                T: Entity<Database = $db> + Sync,
+                T::Key: Sync,
+            {
+                keyed::save(self, value).await
+            }
+
+            async fn bulk_upsert_returning_with_options<T>(
+                self,
+                values: &[T],
+                options: &BulkInsertOptions,
+            ) -> anyhow::Result<Vec<UpsertOutcome>>
+            where
+                T: Insertable<Database = $db> + Sync,
+            {
+                upsert::bulk_upsert_returning(self, values, options).await
+            }
+
+            async fn get_or_create_many_with_options<T, O>(
+                self,
+                values: &[T],
+                options: &BulkInsertOptions,
+            ) -> anyhow::Result<Vec<O>>
+            where
+                T: Insertable<Database = $db> + Sync,
+                O: for<'r> sqlx::FromRow<'r, <$db as sqlx::Database>::Row> + Send + Unpin,
+            {
+                get_or_create::get_or_create_many(self, values, options).await
+            }
+
+            async fn bulk_insert_missing_with_options<T>(
+                self,
+                values: &[T],
+                key_columns: &[&str],
+                options: &BulkInsertOptions,
+            ) -> anyhow::Result<BulkInsertReport>
+            where
+                T: Insertable<Database = $db> + Sync,
+            {
+                missing::bulk_insert_missing(self, values, key_columns, options).await
+            }
+
+            async fn max_of<T, V>(self, column: &str) -> anyhow::Result<Option<V>>
+            where
+                T: Insertable<Database = $db>,
+                V: for<'r> sqlx::Decode<'r, $db> + sqlx::Type<$db> + Send + Sync + Unpin,
+            {
+                aggregate::aggregate::<T, V, _, _>(self, "MAX", column).await
+            }
+
+            async fn min_of<T, V>(self, column: &str) -> anyhow::Result<Option<V>>
+            where
+                T: Insertable<Database = $db>,
+                V: for<'r> sqlx::Decode<'r, $db> + sqlx::Type<$db> + Send + Sync + Unpin,
+            {
+                aggregate::aggregate::<T, V, _, _>(self, "MIN", column).await
+            }
+
+            async fn sum_of<T, V>(self, column: &str) -> anyhow::Result<Option<V>>
+            where
+                T: Insertable<Database = $db>,
+                V: for<'r> sqlx::Decode<'r, $db> + sqlx::Type<$db> + Send + Sync + Unpin,
+            {
+                aggregate::aggregate::<T, V, _, _>(self, "SUM", column).await
+            }
+        }
+
+        // The receivers the trait docs promise, so a change of the impls can't lose one.
+        const _: () = {
+            fn is_inserter<I: Inserter<$db>>() {}
+
+            #[allow(dead_code)]
+            fn receivers() {
+                is_inserter::<&sqlx::Pool<$db>>();
+                is_inserter::<&mut sqlx::pool::PoolConnection<$db>>();
+                is_inserter::<&mut <$db as sqlx::Database>::Connection>();
+                is_inserter::<&mut sqlx::Transaction<'static, $db>>();
+            }
+        };
+
+        #[async_trait]
+        impl Inserter<$db> for &'_ sqlx::Pool<$db> {
+            async fn insert<T>(
+                self,
+                value: &T,
+            ) -> anyhow::Result<<$db as sqlx::Database>::QueryResult>
+            where
+                T: Insertable<Database = $db> + Sync,
+            {
+                Ok(self.acquire().await?.insert(value).await?)
+            }
+
+            async fn insert_all<T>(
+                self,
+                values: &[T],
+            ) -> anyhow::Result<<$db as sqlx::Database>::QueryResult>
+            where
+                T: Insertable<Database = $db> + Sync,
+            {
+                self.acquire().await?.insert_all(values).await
+            }
+
+            async fn bulk_insert_with_options<T>(
+                self,
+                values: &[T],
+                options: &BulkInsertOptions,
+            ) -> anyhow::Result<Vec<<$db as sqlx::Database>::QueryResult>>
+            where
+                T: Insertable<Database = $db> + Sync,
+            {
+                let options = options.with_capabilities_of(self).await;
+                Ok(self
+                    .acquire()
                     .await?
-                    .bulk_insert_with_table_name_and_chunk_size(table_name, chunk_size, values)
+                    .bulk_insert_with_options(values, &options)
                     .await?)
             }
+
+            async fn bulk_insert_report_with_options<T>(
+                self,
+                values: &[T],
+                options: &BulkInsertOptions,
+            ) -> anyhow::Result<BulkInsertReport>
+            where
+                T: Insertable<Database = $db> + Sync,
+            {
+                let options = options.with_capabilities_of(self).await;
+                self.acquire()
+                    .await?
+                    .bulk_insert_report_with_options(values, &options)
+                    .await
+            }
+
+            async fn bulk_insert_returning_ids_with_options<T>(
+                self,
+                values: &[T],
+                options: &BulkInsertOptions,
+            ) -> anyhow::Result<Vec<i64>>
+            where
+                T: Insertable<Database = $db> + Sync,
+            {
+                let options = options.with_capabilities_of(self).await;
+                self.acquire()
+                    .await?
+                    .bulk_insert_returning_ids_with_options(values, &options)
+                    .await
+            }
+
+            async fn save<T>(
+                self,
+                value: &T,
+            ) -> anyhow::Result<<$db as sqlx::Database>::QueryResult>
+            where
+                T: Entity<Database = $db> + Sync,
+                T::Key: Sync,
+            {
+                self.acquire().await?.save(value).await
+            }
+
+            async fn bulk_upsert_returning_with_options<T>(
+                self,
+                values: &[T],
+                options: &BulkInsertOptions,
+            ) -> anyhow::Result<Vec<UpsertOutcome>>
+            where
+                T: Insertable<Database = $db> + Sync,
+            {
+                let options = options.with_capabilities_of(self).await;
+                self.acquire()
+                    .await?
+                    .bulk_upsert_returning_with_options(values, &options)
+                    .await
+            }
+
+            async fn get_or_create_many_with_options<T, O>(
+                self,
+                values: &[T],
+                options: &BulkInsertOptions,
+            ) -> anyhow::Result<Vec<O>>
+            where
+                T: Insertable<Database = $db> + Sync,
+                O: for<'r> sqlx::FromRow<'r, <$db as sqlx::Database>::Row> + Send + Unpin,
+            {
+                let options = options.with_capabilities_of(self).await;
+                self.acquire()
+                    .await?
+                    .get_or_create_many_with_options(values, &options)
+                    .await
+            }
+
+            async fn bulk_insert_missing_with_options<T>(
+                self,
+                values: &[T],
+                key_columns: &[&str],
+                options: &BulkInsertOptions,
+            ) -> anyhow::Result<BulkInsertReport>
+            where
+                T: Insertable<Database = $db> + Sync,
+            {
+                let options = options.with_capabilities_of(self).await;
+                self.acquire()
+                    .await?
+                    .bulk_insert_missing_with_options(values, key_columns, &options)
+                    .await
+            }
+
+            async fn max_of<T, V>(self, column: &str) -> anyhow::Result<Option<V>>
+            where
+                T: Insertable<Database = $db>,
+                V: for<'r> sqlx::Decode<'r, $db> + sqlx::Type<$db> + Send + Sync + Unpin,
+            {
+                self.acquire().await?.max_of::<T, V>(column).await
+            }
+
+            async fn min_of<T, V>(self, column: &str) -> anyhow::Result<Option<V>>
+            where
+                T: Insertable<Database = $db>,
+                V: for<'r> sqlx::Decode<'r, $db> + sqlx::Type<$db> + Send + Sync + Unpin,
+            {
+                self.acquire().await?.min_of::<T, V>(column).await
+            }
+
+            async fn sum_of<T, V>(self, column: &str) -> anyhow::Result<Option<V>>
+            where
+                T: Insertable<Database = $db>,
+                V: for<'r> sqlx::Decode<'r, $db> + sqlx::Type<$db> + Send + Sync + Unpin,
+            {
+                self.acquire().await?.sum_of::<T, V>(column).await
+            }
         }
     };
 }
@@ -242,20 +1172,61 @@ impl_inserter!(sqlx::Postgres);
 impl_inserter!(sqlx::Mssql);
 
 pub trait PlaceHolders: sqlx::Database {
-    /// `start_num` is for only PostgreSQL, it is ignored in other RDB.
+    /// Append `num` placeholders to `buf`. `start_num` is the number of the first placeholder,
+    /// it is for only PostgreSQL and ignored in other RDB.
     #[allow(unused_variables)]
+    fn write_placeholders(buf: &mut String, num: usize, start_num: usize) {
+        write_placeholders(buf, num)
+    }
+
+    /// Append `rows` groups of `columns` placeholders like `(?,?),(?,?)` to `buf`.
+    fn write_placeholders_for_values(
+        buf: &mut String,
+        rows: usize,
+        columns: usize,
+        start_num: usize,
+    ) {
+        for row in 0..rows {
+            if row > 0 {
+                buf.push(',');
+            }
+            buf.push('(');
+            Self::write_placeholders(buf, columns, start_num + row * columns);
+            buf.push(')');
+        }
+    }
+
+    /// Append the values of `rows` rows of a struct without columns, which the defaults of the
+    /// table fill. `DEFAULT VALUES` is a single row, more than one is an error; insert them one
+    /// statement each.
+    fn write_default_values(buf: &mut String, rows: usize) -> anyhow::Result<()> {
+        anyhow::ensure!(
+            rows <= 1,
+            "DEFAULT VALUES inserts a single row, not {}",
+            rows
+        );
+        buf.push_str("DEFAULT VALUES");
+        Ok(())
+    }
+
+    /// `start_num` is for only PostgreSQL, it is ignored in other RDB.
     fn placeholders(num: usize, start_num: Option<usize>) -> String {
-        placeholders(num)
+        let mut buf = String::with_capacity(num * 8);
+        Self::write_placeholders(&mut buf, num, start_num.unwrap_or(1));
+        buf
     }
 
     /// `start_num` is for only PostgreSQL, it is ignored in other RDB.
-    #[allow(unused_variables)]
     fn placeholders_for_bulk_insert_values<I, T>(values: I, start_num: Option<usize>) -> String
     where
         I: Iterator<Item = T>,
         T: Insertable<Database = Self>,
     {
-        placeholders_for_bulk_insert_values(values)
+        let rows = values.count();
+        let columns = T::COLUMN_COUNT;
+        let mut buf = String::with_capacity(rows * (columns * 8 + 3));
+        Self::write_placeholders_for_values(&mut buf, rows, columns, start_num.unwrap_or(1));
+        buf
     }
 }
 
@@ -263,29 +1234,45 @@ pub trait PlaceHolders: sqlx::Database {
 impl PlaceHolders for sqlx::Sqlite {}
 
 #[cfg(feature = "mysql")]
-impl PlaceHolders for sqlx::MySql {}
+impl PlaceHolders for sqlx::MySql {
+    /// MySQL has no `DEFAULT VALUES` but empty rows, any number of them.
+    fn write_default_values(buf: &mut String, rows: usize) -> anyhow::Result<()> {
+        buf.push_str("() VALUES ");
+        for row in 0..rows {
+            if row > 0 {
+                buf.push(',');
+            }
+            buf.push_str("()");
+        }
+        Ok(())
+    }
+}
 
 #[cfg(feature = "mssql")]
 impl PlaceHolders for sqlx::Mssql {}
 
 #[cfg(feature = "postgres")]
 impl PlaceHolders for sqlx::Postgres {
-    fn placeholders(num: usize, start_num: Option<usize>) -> String {
-        placeholders_postgres(num, start_num)
-    }
-
-    fn placeholders_for_bulk_insert_values<I, T>(values: I, start_num: Option<usize>) -> String
-    where
-        I: Iterator<Item = T>,
-        T: Insertable<Database = Self>,
-    {
-        placeholders_for_bulk_insert_values_postgres(values, start_num)
+    fn write_placeholders(buf: &mut String, num: usize, start_num: usize) {
+        write_placeholders_postgres(buf, num, start_num)
     }
 }
 
 /// Generate placeholders string like `?, ?, ..., ?`.
 pub fn placeholders(num: usize) -> String {
-    (0..num).map(|_| "?").join(",")
+    let mut buf = String::with_capacity(num * 2);
+    write_placeholders(&mut buf, num);
+    buf
+}
+
+/// Append placeholders like `?, ?, ..., ?` to `buf`.
+pub fn write_placeholders(buf: &mut String, num: usize) {
+    for i in 0..num {
+        if i > 0 {
+            buf.push(',');
+        }
+        buf.push('?');
+    }
 }
 
 /// Generate placeholders string like `(?, ?, ..., ?), (?, ?, ..., ?), ..., (?, ?, ..., ?)`.
@@ -294,30 +1281,60 @@ where
     I: Iterator<Item = T>,
     T: Insertable,
 {
-    format!(
-        "({})",
-        values
-            .map(|_| placeholders(T::insert_columns().len()))
-            .join("),(")
-    )
+    let columns = T::COLUMN_COUNT;
+    let mut buf = String::new();
+
+    for (row, _) in values.enumerate() {
+        if row > 0 {
+            buf.push(',');
+        }
+        buf.push('(');
+        write_placeholders(&mut buf, columns);
+        buf.push(')');
+    }
+
+    buf
 }
 
 /// Generate placeholders string like `$1, $2, ..., $n`.
 pub fn placeholders_postgres(num: usize, start_num: Option<usize>) -> String {
-    let start_num = start_num.unwrap_or(1);
+    let mut buf = String::with_capacity(num * 7);
+    write_placeholders_postgres(&mut buf, num, start_num.unwrap_or(1));
+    buf
+}
+
+/// The highest placeholder number PostgreSQL accepts, its protocol counts parameters in 16 bits.
+pub const POSTGRES_MAX_PLACEHOLDER: usize = 65535;
 
-    if usize::MAX - start_num < num {
-        panic!("num > usize::MAX - start_num");
+/// Append placeholders like `$1, $2, ..., $n` to `buf`.
+///
+/// Panics when a placeholder would be numbered above [`POSTGRES_MAX_PLACEHOLDER`]. Chunked
+/// inserts never get there, they split rows by the parameter limit first.
+pub fn write_placeholders_postgres(buf: &mut String, num: usize, start_num: usize) {
+    if num == 0 {
+        return;
+    }
+    match start_num.checked_add(num - 1) {
+        Some(last) if last <= POSTGRES_MAX_PLACEHOLDER => {}
+        _ => panic!(
+            "{} placeholders starting at ${} exceed ${}",
+            num, start_num, POSTGRES_MAX_PLACEHOLDER
+        ),
     }
 
-    (0..num)
-        .zip(start_num..(start_num + num))
-        .map(|(_, i)| format!("${}", i))
-        .join(",")
+    let mut itoa = itoa::Buffer::new();
+
+    for i in start_num..(start_num + num) {
+        if i > start_num {
+            buf.push(',');
+        }
+        buf.push('$');
+        buf.push_str(itoa.format(i));
+    }
 }
 
 /// Generate placeholders string like `($1, $2, ..., $n), ($o, $p, ..., $q), ..., ($r, $s, ..., $u)`.
-pub fn placeholders_for_bulk_insert_values_postgres<'a, I, T>(
+pub fn placeholders_for_bulk_insert_values_postgres<I, T>(
     values: I,
     start_num: Option<usize>,
 ) -> String
@@ -326,72 +1343,673 @@ where
     T: Insertable,
 {
     let start_num = start_num.unwrap_or(1);
+    let columns = T::COLUMN_COUNT;
+    let mut buf = String::new();
 
-    format!(
-        "({})",
-        values
-            .enumerate()
-            .map(|(i, _)| {
-                let num_of_fields = T::insert_columns().len();
-                let start_num = start_num + i * num_of_fields;
-                placeholders_postgres(num_of_fields, Some(start_num))
-            })
-            .join("),(")
-    )
+    for (row, _) in values.enumerate() {
+        if row > 0 {
+            buf.push(',');
+        }
+        buf.push('(');
+        let row_start = row
+            .checked_mul(columns)
+            .and_then(|offset| start_num.checked_add(offset))
+            .expect("placeholder number overflows usize");
+        write_placeholders_postgres(&mut buf, columns, row_start);
+        buf.push(')');
+    }
+
+    buf
+}
+
+/// Generate `INSERT INTO {table_name} ({columns}) VALUES (...), ...` for `rows` rows of `T`.
+///
+/// Fails for more than one row of a `T` without columns, except on MySQL, see
+/// [`PlaceHolders::write_default_values`].
+pub fn bulk_insert_sql<T>(table_name: &str, rows: usize) -> anyhow::Result<String>
+where
+    T: Insertable,
+    T::Database: PlaceHolders,
+{
+    let mut buf = String::with_capacity(
+        32 + table_name.len() + T::COLUMN_COUNT * 16 + rows * (T::COLUMN_COUNT * 8 + 3),
+    );
+
+    buf.push_str("INSERT ");
+    write_insert_into::<T>(&mut buf, table_name, rows, None)?;
+
+    Ok(buf)
+}
+
+/// Append `INTO {table_name} ({columns}) VALUES (...), ...` to `buf`. With an `output` column
+/// MSSQL's `OUTPUT INSERTED.{output}` goes before the values.
+pub(crate) fn write_insert_into<T>(
+    buf: &mut String,
+    table_name: &str,
+    rows: usize,
+    output: Option<&str>,
+) -> anyhow::Result<()>
+where
+    T: Insertable,
+    T::Database: PlaceHolders,
+{
+    buf.push_str("INTO ");
+    buf.push_str(table_name);
+    if T::COLUMN_COUNT == 0 {
+        write_output(buf, output);
+        buf.push(' ');
+        return T::Database::write_default_values(buf, rows);
+    }
+    buf.push_str(" (");
+    for (i, column) in T::insert_columns().iter().enumerate() {
+        if i > 0 {
+            buf.push(',');
+        }
+        buf.push_str(column);
+    }
+    buf.push(')');
+    write_output(buf, output);
+    buf.push_str(" VALUES ");
+    write_insert_values::<T>(buf, rows, 1);
+    Ok(())
+}
+
+fn write_output(buf: &mut String, output: Option<&str>) {
+    if let Some(column) = output {
+        buf.push_str(" OUTPUT INSERTED.");
+        buf.push_str(column);
+    }
+}
+
+/// Like [`write_insert_into`], for only `columns` of `T`'s columns, which keep their expressions.
+pub(crate) fn write_insert_into_columns<T>(
+    buf: &mut String,
+    table_name: &str,
+    columns: &[&str],
+    rows: usize,
+    output: Option<&str>,
+) -> anyhow::Result<()>
+where
+    T: Insertable,
+    T::Database: PlaceHolders,
+{
+    buf.push_str("INTO ");
+    buf.push_str(table_name);
+    if columns.is_empty() {
+        write_output(buf, output);
+        buf.push(' ');
+        return T::Database::write_default_values(buf, rows);
+    }
+    buf.push_str(" (");
+    buf.push_str(&columns.join(","));
+    buf.push(')');
+    write_output(buf, output);
+    buf.push_str(" VALUES ");
+
+    let expressions = columns
+        .iter()
+        .map(|column| {
+            let i = T::insert_columns().iter().position(|x| x == column)?;
+            T::insert_expressions().get(i).copied().flatten()
+        })
+        .collect::<Vec<_>>();
+    for row in 0..rows {
+        if row > 0 {
+            buf.push(',');
+        }
+        buf.push('(');
+        for (i, expression) in expressions.iter().enumerate() {
+            if i > 0 {
+                buf.push(',');
+            }
+            let num = 1 + row * columns.len() + i;
+            match expression.and_then(|x| x.split_once('?')) {
+                Some((before, after)) => {
+                    buf.push_str(before);
+                    T::Database::write_placeholders(buf, 1, num);
+                    buf.push_str(after);
+                }
+                None => T::Database::write_placeholders(buf, 1, num),
+            }
+        }
+        buf.push(')');
+    }
+    Ok(())
+}
+
+/// Append `rows` groups of placeholders for `T` like [`PlaceHolders::write_placeholders_for_values`],
+/// each placeholder wrapped in its expression of [`Insertable::insert_expressions`].
+pub(crate) fn write_insert_values<T>(buf: &mut String, rows: usize, start_num: usize)
+where
+    T: Insertable,
+    T::Database: PlaceHolders,
+{
+    let expressions = T::insert_expressions();
+    if expressions.iter().all(Option::is_none) {
+        T::Database::write_placeholders_for_values(buf, rows, T::COLUMN_COUNT, start_num);
+        return;
+    }
+
+    for row in 0..rows {
+        if row > 0 {
+            buf.push(',');
+        }
+        buf.push('(');
+        for (i, expression) in expressions.iter().enumerate() {
+            if i > 0 {
+                buf.push(',');
+            }
+            let num = start_num + row * T::COLUMN_COUNT + i;
+            match expression.and_then(|x| x.split_once('?')) {
+                Some((before, after)) => {
+                    buf.push_str(before);
+                    T::Database::write_placeholders(buf, 1, num);
+                    buf.push_str(after);
+                }
+                None => T::Database::write_placeholders(buf, 1, num),
+            }
+        }
+        buf.push(')');
+    }
+}
+
+/// Generate `(VALUES (...), ...) AS v ({columns})`, a derived table of `rows` rows of `T` for
+/// joins, `UPDATE ... FROM` or anti-join deletes. Bind the values with `bind_multi_fields`.
+///
+/// ```ignore
+/// let sql = format!(
+///     "DELETE FROM user WHERE NOT EXISTS (SELECT 1 FROM {} WHERE v.name = user.name)",
+///     sqlx_plus::values_table::<UserInsert>(users.len()),
+/// );
+/// sqlx::query(&sql).bind_multi_fields(&users).execute(&mut tx).await?;
+/// ```
+pub fn values_table<T>(rows: usize) -> String
+where
+    T: Insertable,
+    T::Database: Backend,
+{
+    let mut buf =
+        String::with_capacity(32 + T::COLUMN_COUNT * 16 + rows * (T::COLUMN_COUNT * 8 + 3));
+    write_values_table::<T>(
+        &mut buf,
+        &Capabilities::assumed::<T::Database>(),
+        "v",
+        rows,
+        1,
+    );
+    buf
+}
+
+/// Append the derived table of [`values_table`] named `alias` to `buf`, numbering the
+/// placeholders from `start_num`.
+///
+/// SQLite can't name the columns of a derived table, so the values are selected from its
+/// `column1`, `column2`, ... instead. MySQL uses `ROW(...)` constructors.
+pub fn write_values_table<T>(
+    buf: &mut String,
+    capabilities: &Capabilities,
+    alias: &str,
+    rows: usize,
+    start_num: usize,
+) where
+    T: Insertable,
+    T::Database: PlaceHolders,
+{
+    let row_constructor = capabilities.dialect == Dialect::MySql
+        && !capabilities
+            .server_version
+            .as_ref()
+            .is_some_and(ServerVersion::is_mariadb);
+
+    buf.push('(');
+    if capabilities.dialect == Dialect::Sqlite {
+        buf.push_str("SELECT ");
+        let mut itoa = itoa::Buffer::new();
+        for (i, column) in T::insert_columns().iter().enumerate() {
+            if i > 0 {
+                buf.push(',');
+            }
+            buf.push_str("column");
+            buf.push_str(itoa.format(i + 1));
+            buf.push_str(" AS ");
+            buf.push_str(column);
+        }
+        buf.push_str(" FROM (");
+    }
+    buf.push_str("VALUES ");
+    for row in 0..rows {
+        if row > 0 {
+            buf.push(',');
+        }
+        buf.push_str(if row_constructor { "ROW(" } else { "(" });
+        T::Database::write_placeholders(buf, T::COLUMN_COUNT, start_num + row * T::COLUMN_COUNT);
+        buf.push(')');
+    }
+    if capabilities.dialect == Dialect::Sqlite {
+        buf.push_str(")) AS ");
+        buf.push_str(alias);
+    } else {
+        buf.push_str(") AS ");
+        buf.push_str(alias);
+        buf.push_str(" (");
+        buf.push_str(&T::insert_columns().join(","));
+        buf.push(')');
+    }
+}
+
+/// Append `WITH v ({columns},sqlx_plus_ord) AS (...)`, a table of `rows` bound rows of `T`
+/// numbered by `sqlx_plus_ord` in input order.
+pub(crate) fn write_values_cte<T>(buf: &mut String, capabilities: &Capabilities, rows: usize)
+where
+    T: Insertable,
+    T::Database: PlaceHolders,
+{
+    write_values_cte_of::<T::Database>(buf, capabilities, T::insert_columns(), rows)
+}
+
+/// [`write_values_cte`] over the given columns.
+pub(crate) fn write_values_cte_of<DB: PlaceHolders>(
+    buf: &mut String,
+    capabilities: &Capabilities,
+    columns: &[&str],
+    rows: usize,
+) {
+    let column_count = columns.len();
+    let columns = columns.join(",");
+    let row_constructor = capabilities.dialect == Dialect::MySql
+        && !capabilities
+            .server_version
+            .as_ref()
+            .is_some_and(ServerVersion::is_mariadb);
+    let mut itoa = itoa::Buffer::new();
+
+    buf.push_str("WITH v (");
+    buf.push_str(&columns);
+    buf.push_str(",sqlx_plus_ord) AS (");
+    if capabilities.dialect == Dialect::Mssql {
+        buf.push_str("SELECT * FROM (");
+    }
+    buf.push_str("VALUES ");
+    for row in 0..rows {
+        if row > 0 {
+            buf.push(',');
+        }
+        buf.push_str(if row_constructor { "ROW(" } else { "(" });
+        DB::write_placeholders(buf, column_count, 1 + row * column_count);
+        buf.push(',');
+        buf.push_str(itoa.format(row));
+        buf.push(')');
+    }
+    if capabilities.dialect == Dialect::Mssql {
+        buf.push_str(") AS x (");
+        buf.push_str(&columns);
+        buf.push_str(",sqlx_plus_ord)");
+    }
+    buf.push(')');
 }
 
 async fn insert<T, E, DB>(executor: &mut E, value: &T) -> anyhow::Result<DB::QueryResult>
 where
-    DB: sqlx::Database + PlaceHolders,
+    DB: Backend,
     T: Insertable<Database = DB> + Sync,
     for<'e> &'e mut E: Executor<'e, Database = DB>,
-    for<'q> <DB as HasArguments<'q>>::Arguments: IntoArguments<'q, DB>,
+    for<'q> ArgumentsOf<'q, DB>: IntoArguments<'q, DB>,
 {
-    let sql = format!(
-        r#"
-            INSERT INTO {table_name} ({columns}) VALUES ({placeholders})
-        "#,
-        table_name = T::table_name(),
-        columns = T::insert_columns().join(","),
-        placeholders = DB::placeholders(T::insert_columns().len(), None),
-    );
+    let result = insert_quietly(executor, value).await?;
+    Config::global().invalidate(T::table_name(), &[]).await;
 
-    sqlx::query(&sql)
-        .bind_fields(value)
-        .execute(executor)
-        .await
-        .map_err(From::from)
+    Ok(result)
+}
+
+async fn insert_quietly<T, E, DB>(executor: &mut E, value: &T) -> anyhow::Result<DB::QueryResult>
+where
+    DB: Backend,
+    T: Insertable<Database = DB> + Sync,
+    for<'e> &'e mut E: Executor<'e, Database = DB>,
+    for<'q> ArgumentsOf<'q, DB>: IntoArguments<'q, DB>,
+{
+    let config = Config::global();
+    let columns = present_columns(std::slice::from_ref(value))?;
+    let statement = config
+        .before(
+            insert_sql_of::<T>(T::table_name(), 1, columns.as_deref())?,
+            T::table_name(),
+            1,
+        )
+        .await?;
+    let result = statement
+        .run(bind_rows(sqlx::query(statement.sql()), [value], columns.as_deref()).execute(executor))
+        .await;
+    statement.after(&result, DB::rows_affected).await;
+
+    result.map_err(T::map_error)
 }
 
-async fn bulk_insert_with_table_name_and_chunk_size<T, E, DB>(
+async fn insert_all<T, E, DB>(executor: &mut E, values: &[T]) -> anyhow::Result<DB::QueryResult>
+where
+    DB: Backend,
+    T: Insertable<Database = DB> + Sync,
+    for<'e> &'e mut E: Executor<'e, Database = DB>,
+    for<'q> ArgumentsOf<'q, DB>: IntoArguments<'q, DB>,
+{
+    if values.is_empty() {
+        return Ok(Default::default());
+    }
+
+    if values.len() * T::COLUMN_COUNT > DB::MAX_BIND_PARAMS {
+        return Err(BindParamLimitExceeded {
+            chunk_size: values.len(),
+            columns: T::COLUMN_COUNT,
+            max_params: DB::MAX_BIND_PARAMS,
+        }
+        .into());
+    }
+
+    let columns = present_columns(values)?;
+    let inserts_no_columns = columns.as_ref().map_or(T::COLUMN_COUNT == 0, Vec::is_empty);
+    if inserts_no_columns && values.len() > 1 && DB::DIALECT != Dialect::MySql {
+        anyhow::bail!(
+            "the rows of {} insert no columns, a single statement inserts only one of them; use bulk_insert",
+            T::table_name()
+        );
+    }
+
+    let config = Config::global();
+    let statement = config
+        .before(
+            insert_sql_of::<T>(T::table_name(), values.len(), columns.as_deref())?,
+            T::table_name(),
+            values.len(),
+        )
+        .await?;
+    let result = statement
+        .run(bind_rows(sqlx::query(statement.sql()), values, columns.as_deref()).execute(executor))
+        .await;
+    statement.after(&result, DB::rows_affected).await;
+    let result = result.map_err(T::map_error)?;
+    config.invalidate(T::table_name(), &[]).await;
+
+    Ok(result)
+}
+
+async fn bulk_insert_with_options<T, E, DB>(
     executor: &mut E,
-    table_name: &str,
-    chunk_size: usize,
     values: &[T],
+    options: &BulkInsertOptions,
 ) -> anyhow::Result<Vec<DB::QueryResult>>
 where
-    DB: sqlx::Database + PlaceHolders,
+    DB: Backend,
     T: Insertable<Database = DB> + Sync,
     for<'e> &'e mut E: Executor<'e, Database = DB>,
-    for<'q> <DB as HasArguments<'q>>::Arguments: IntoArguments<'q, DB>,
+    for<'q> ArgumentsOf<'q, DB>: IntoArguments<'q, DB>,
 {
-    let mut results = Vec::with_capacity(values.len() / chunk_size);
-
-    for chunk in values.chunks(chunk_size) {
-        let sql = format!(
-            r#"
-                    INSERT INTO {table_name} ({columns}) VALUES {placeholders}
-            "#,
-            columns = T::insert_columns().join(","),
-            placeholders = DB::placeholders_for_bulk_insert_values(chunk.iter(), None),
-        );
-        let result = sqlx::query(&sql)
-            .bind_multi_fields(chunk)
-            .execute(&mut *executor)
-            .await?;
-
-        results.push(result);
+    if values.is_empty() {
+        return Ok(Vec::new());
     }
 
+    let mut results = Vec::with_capacity(values.len() / options.chunk_size::<T>()?);
+
+    bulk_insert_chunks(executor, values, options, |_, _, result| {
+        results.push(result?);
+        Ok(())
+    })
+    .await?;
+
     Ok(results)
 }
+
+/// Execute the chunks of a bulk insert one by one and hand each result to `on_chunk` with the
+/// chunk index and the range of rows it covers. Returning an error from `on_chunk` aborts.
+pub(crate) async fn bulk_insert_chunks<T, E, DB, F>(
+    executor: &mut E,
+    values: &[T],
+    options: &BulkInsertOptions,
+    on_chunk: F,
+) -> anyhow::Result<()>
+where
+    DB: Backend,
+    T: Insertable<Database = DB> + Sync,
+    for<'e> &'e mut E: Executor<'e, Database = DB>,
+    for<'q> ArgumentsOf<'q, DB>: IntoArguments<'q, DB>,
+    F: FnMut(usize, Range<usize>, anyhow::Result<DB::QueryResult>) -> anyhow::Result<()> + Send,
+{
+    if values.is_empty() {
+        return Ok(());
+    }
+
+    options.before_load::<T, E>(&mut *executor).await?;
+    let result = clock::scope(
+        options.batch(),
+        insert_chunks(&mut *executor, values, options, on_chunk),
+    )
+    .await;
+    options.after_load::<T, E, _>(executor, result).await
+}
+
+/// The columns a single statement inserts `values` with, `None` for all of `T`'s. Fails if the
+/// rows leave different columns to their defaults.
+fn present_columns<T: Insertable>(values: &[T]) -> anyhow::Result<Option<Vec<&'static str>>> {
+    if T::default_if_none_columns().is_empty() {
+        return Ok(None);
+    }
+    let mut groups = group_by_defaulted_columns(values, None);
+    anyhow::ensure!(
+        groups.len() <= 1,
+        "the rows of {} leave different columns to their defaults, a single statement can't insert them; use bulk_insert",
+        T::table_name()
+    );
+    Ok(groups.pop().and_then(|(columns, _)| columns))
+}
+
+/// The statement inserting `rows` rows of `columns` of `T`, all of them if `None`.
+fn insert_sql_of<T>(
+    table_name: &str,
+    rows: usize,
+    columns: Option<&[&str]>,
+) -> anyhow::Result<String>
+where
+    T: Insertable,
+    T::Database: PlaceHolders,
+{
+    match columns {
+        None => bulk_insert_sql::<T>(table_name, rows),
+        Some(columns) => {
+            let mut buf = String::from("INSERT ");
+            write_insert_into_columns::<T>(&mut buf, table_name, columns, rows, None)?;
+            Ok(buf)
+        }
+    }
+}
+
+/// Bind `columns` of each of `values`, all of them if `None`.
+fn bind_rows<'q, T, Q>(q: Q, values: impl IntoIterator<Item = &'q T>, columns: Option<&[&str]>) -> Q
+where
+    T: Insertable + 'q,
+    Q: QueryBindExt<'q, T::Database>,
+{
+    match columns {
+        None => q.bind_multi_fields(values),
+        Some(columns) => values.into_iter().fold(q, |q, value| {
+            columns
+                .iter()
+                .fold(q, |q, column| value.bind_field(column, q))
+        }),
+    }
+}
+
+/// `values` grouped by their [`Insertable::defaulted_columns`], in order of their first row,
+/// each with the columns it inserts: `columns`, or all of `T`'s if `None`, without the defaulted
+/// ones.
+fn group_by_defaulted_columns<'v, T: Insertable>(
+    values: &'v [T],
+    columns: Option<Vec<&'static str>>,
+) -> Vec<(Option<Vec<&'static str>>, Vec<&'v T>)> {
+    if T::default_if_none_columns().is_empty() {
+        return vec![(columns, values.iter().collect())];
+    }
+
+    let mut groups: Vec<(Vec<&'static str>, Vec<&'v T>)> = Vec::new();
+    for value in values {
+        let defaulted = value.defaulted_columns();
+        match groups.iter_mut().find(|(x, _)| *x == defaulted) {
+            Some((_, rows)) => rows.push(value),
+            None => groups.push((defaulted, vec![value])),
+        }
+    }
+    groups
+        .into_iter()
+        .map(|(defaulted, rows)| {
+            if defaulted.is_empty() {
+                return (columns.clone(), rows);
+            }
+            let inserted = columns
+                .as_deref()
+                .unwrap_or(T::insert_columns())
+                .iter()
+                .copied()
+                .filter(|column| !defaulted.contains(column))
+                .collect();
+            (Some(inserted), rows)
+        })
+        .collect()
+}
+
+async fn insert_chunks<T, E, DB, F>(
+    executor: &mut E,
+    values: &[T],
+    options: &BulkInsertOptions,
+    mut on_chunk: F,
+) -> anyhow::Result<()>
+where
+    DB: Backend,
+    T: Insertable<Database = DB> + Sync,
+    for<'e> &'e mut E: Executor<'e, Database = DB>,
+    for<'q> ArgumentsOf<'q, DB>: IntoArguments<'q, DB>,
+    F: FnMut(usize, Range<usize>, anyhow::Result<DB::QueryResult>) -> anyhow::Result<()> + Send,
+{
+    let config = options.config();
+    let table_name = options.table_name::<T>();
+    let chunk_size = options.chunk_size::<T>()?;
+    let columns = match &options.skip_missing_columns {
+        Some(cache) => {
+            cache
+                .present_columns::<T, E>(&mut *executor, table_name)
+                .await?
+        }
+        None => None,
+    };
+    let groups = group_by_defaulted_columns(values, columns);
+    let mut chunks = Vec::new();
+    for (columns, rows) in &groups {
+        // Rows leaving every column to its default are inserted one per statement, only MySQL
+        // inserts several of them at once.
+        let chunk_size = match columns {
+            Some(columns) if columns.is_empty() && DB::DIALECT != Dialect::MySql => 1,
+            _ => chunk_size,
+        };
+        chunks.extend(rows.chunks(chunk_size).map(|chunk| (columns, chunk)));
+    }
+    let mut inserted = false;
+    let mut offset = 0;
+    // Every chunk of a group but the last one has the same size, so their statement is built
+    // only once.
+    let mut statement_sql: Option<(&Option<Vec<&str>>, usize, String)> = None;
+
+    let positions = options.chunk_positions(chunks.len());
+    for ((index, (columns, chunk)), position) in chunks.into_iter().enumerate().zip(positions) {
+        let sql = match &statement_sql {
+            Some((group, rows, sql)) if *group == columns && *rows == chunk.len() => sql.clone(),
+            _ => {
+                let sql = match columns {
+                    None => options.insert_sql_offloaded::<T>(chunk.len()).await?,
+                    Some(columns) => {
+                        options.insert_sql_with::<T>(chunk.len(), Some(columns.as_slice()))?
+                    }
+                };
+                statement_sql = Some((columns, chunk.len(), sql.clone()));
+                sql
+            }
+        };
+        let result = match config
+            .before_chunk(sql, table_name, chunk.len(), position)
+            .await
+        {
+            Ok(statement) => {
+                let query = DB::set_persistent(
+                    sqlx::query(statement.sql()),
+                    statement.persistent(options.persistent),
+                );
+                let query = bind_rows(query, chunk.iter().copied(), columns.as_deref());
+                let result = statement.run(query.execute(&mut *executor)).await;
+                statement.after(&result, DB::rows_affected).await;
+                match result {
+                    Ok(result) if options.verifies_rows_affected() => {
+                        let rows_affected = DB::rows_affected(&result);
+                        if rows_affected == chunk.len() as u64 {
+                            Ok(result)
+                        } else {
+                            Err(PartialWrite {
+                                chunk: index,
+                                expected: chunk.len() as u64,
+                                rows_affected,
+                            }
+                            .into())
+                        }
+                    }
+                    result => result.map_err(T::map_error),
+                }
+            }
+            Err(error) => Err(error),
+        };
+
+        inserted |= result.is_ok();
+        let rows = offset..offset + chunk.len();
+        offset += chunk.len();
+        if let Err(error) = on_chunk(index, rows, result) {
+            if inserted {
+                config.invalidate(table_name, &[]).await;
+            }
+            return Err(error);
+        }
+    }
+
+    if inserted {
+        config.invalidate(table_name, &[]).await;
+    }
+
+    Ok(())
+}
+
+pub(crate) async fn bulk_insert_report_with_options<T, E, DB>(
+    executor: &mut E,
+    values: &[T],
+    options: &BulkInsertOptions,
+) -> anyhow::Result<BulkInsertReport>
+where
+    DB: Backend,
+    T: Insertable<Database = DB> + Sync,
+    for<'e> &'e mut E: Executor<'e, Database = DB>,
+    for<'q> ArgumentsOf<'q, DB>: IntoArguments<'q, DB>,
+{
+    let started = Instant::now();
+    let mut report = BulkInsertReport::default();
+
+    bulk_insert_chunks(executor, values, options, |index, rows, result| {
+        report.chunks += 1;
+
+        match result {
+            Ok(result) => report.rows_affected += DB::rows_affected(&result),
+            Err(error) if options.continue_on_error => {
+                report.failed.push(ChunkError { index, rows, error })
+            }
+            Err(error) => return Err(error),
+        }
+
+        Ok(())
+    })
+    .await?;
+
+    report.duration = started.elapsed();
+
+    Ok(report)
+}
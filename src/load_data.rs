@@ -0,0 +1,158 @@
+//! Fast loads into MySQL with `LOAD DATA LOCAL INFILE`.
+//!
+//! The rows are serialized to a tab-separated file in memory and sent as the answer to the
+//! server's request for the local file. sqlx 0.6 doesn't announce local files nor answer that
+//! request, so the statement runs through a [`LocalInfile`] driver of your own, e.g. on
+//! `mysql_async`:
+//!
+//! ```ignore
+//! struct MysqlAsync(mysql_async::Pool);
+//!
+//! #[async_trait]
+//! impl LocalInfile for MysqlAsync {
+//!     async fn load_data(&self, statement: &str, data: Vec<u8>) -> anyhow::Result<u64> {
+//!         let mut conn = self.0.get_conn().await?;
+//!         conn.set_infile_handler(async move { Ok(stream::once(async { Ok(data.into()) }).boxed()) });
+//!         match conn.query_drop(statement).await {
+//!             Ok(()) => Ok(conn.affected_rows()),
+//!             Err(error) if is_local_infile_refusal(&error) => Err(LocalInfileRefused.into()),
+//!             Err(error) => Err(error.into()),
+//!         }
+//!     }
+//! }
+//!
+//! let report = load_data(&MysqlAsync(pool), &mut conn, &rows, &BulkInsertOptions::default()).await?;
+//! ```
+//!
+//! When the driver refuses local files, e.g. because the server runs with `local_infile=0`, the
+//! rows are bulk inserted with multi-row `VALUES` through the sqlx connection instead.
+//!
+//! The fields are written as serde serializes them, keyed by their column names, which would skip
+//! the bind-time conversions of the derive (`normalize`, `scale`, `skip_if`, `auto_now`,
+//! `auto_uuid`, `geo`). Types with such fields, see [`Insertable::has_bind_conversions`], and
+//! types with `expr` or `default_if_none` columns are always bulk inserted.
+
+use std::{fmt, time::Instant};
+
+use async_trait::async_trait;
+use serde::Serialize;
+use serde_json::Value;
+use sqlx::{Executor, MySql};
+
+use crate::{bulk_insert_report_with_options, BulkInsertOptions, BulkInsertReport, Insertable};
+
+/// Runs a `LOAD DATA LOCAL INFILE` statement, see the [module docs](self).
+#[async_trait]
+pub trait LocalInfile: Send + Sync {
+    /// Execute `statement`, answering the server's request for its file with `data`, and return
+    /// the rows affected. Fail with [`LocalInfileRefused`] when the server or the driver doesn't
+    /// allow local files, for [`load_data`] to fall back to a bulk insert.
+    async fn load_data(&self, statement: &str, data: Vec<u8>) -> anyhow::Result<u64>;
+}
+
+/// The server or the driver doesn't allow `LOAD DATA LOCAL INFILE`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct LocalInfileRefused;
+
+impl LocalInfileRefused {
+    /// Whether a MySQL error code is a refusal of local files: `ER_NOT_ALLOWED_COMMAND` (1148),
+    /// `ER_CLIENT_LOCAL_FILES_DISABLED` (3948) or `CR_LOAD_DATA_LOCAL_INFILE_REJECTED` (2068).
+    pub fn is_refusal(code: u16) -> bool {
+        matches!(code, 1148 | 3948 | 2068)
+    }
+}
+
+impl fmt::Display for LocalInfileRefused {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("LOAD DATA LOCAL INFILE is not allowed")
+    }
+}
+
+impl std::error::Error for LocalInfileRefused {}
+
+/// Load `values` into `T`'s table through `infile`, or bulk insert them through `executor` with
+/// `options` if local files are refused. The load is a single statement, reported as one chunk.
+pub async fn load_data<T, E>(
+    infile: &dyn LocalInfile,
+    executor: &mut E,
+    values: &[T],
+    options: &BulkInsertOptions,
+) -> anyhow::Result<BulkInsertReport>
+where
+    T: Insertable<Database = MySql> + Serialize + Sync,
+    for<'e> &'e mut E: Executor<'e, Database = MySql>,
+{
+    let loadable = T::insert_expressions().iter().all(Option::is_none)
+        && T::default_if_none_columns().is_empty()
+        && !T::has_bind_conversions();
+    if values.is_empty() || !loadable {
+        return bulk_insert_report_with_options(executor, values, options).await;
+    }
+
+    let started = Instant::now();
+    let statement = load_data_sql::<T>(options.table_name::<T>());
+    match infile.load_data(&statement, to_tsv(values)?).await {
+        Ok(rows_affected) => Ok(BulkInsertReport {
+            rows_affected,
+            chunks: 1,
+            duration: started.elapsed(),
+            failed: Vec::new(),
+        }),
+        Err(error) if error.is::<LocalInfileRefused>() => {
+            bulk_insert_report_with_options(executor, values, options).await
+        }
+        Err(error) => Err(error),
+    }
+}
+
+/// The `LOAD DATA LOCAL INFILE` statement loading the file of [`to_tsv`] into the insert columns
+/// of `T`.
+pub fn load_data_sql<T: Insertable>(table_name: &str) -> String {
+    format!(
+        "LOAD DATA LOCAL INFILE 'sqlx_plus.tsv' INTO TABLE {} CHARACTER SET utf8mb4 \
+         FIELDS TERMINATED BY '\\t' ESCAPED BY '\\\\' LINES TERMINATED BY '\\n' ({})",
+        table_name,
+        T::insert_columns().join(","),
+    )
+}
+
+/// `values` as tab-separated lines of their insert columns, in the format `LOAD DATA` reads by
+/// default: `\N` for `NULL`, `1` and `0` for booleans, arrays and objects as JSON.
+pub fn to_tsv<T: Insertable + Serialize>(values: &[T]) -> anyhow::Result<Vec<u8>> {
+    let mut buf = String::new();
+    for value in values {
+        let fields = match serde_json::to_value(value)? {
+            Value::Object(fields) => fields,
+            _ => anyhow::bail!("{} doesn't serialize to a map", T::table_name()),
+        };
+        for (i, column) in T::insert_columns().iter().enumerate() {
+            if i > 0 {
+                buf.push('\t');
+            }
+            let column = column.trim_matches(|c| matches!(c, '`' | '"'));
+            match fields.get(column) {
+                None => anyhow::bail!("{} serializes no field {:?}", T::table_name(), column),
+                Some(Value::Null) => buf.push_str("\\N"),
+                Some(Value::Bool(x)) => buf.push(if *x { '1' } else { '0' }),
+                Some(Value::Number(x)) => buf.push_str(&x.to_string()),
+                Some(Value::String(x)) => write_escaped(&mut buf, x),
+                Some(x) => write_escaped(&mut buf, &x.to_string()),
+            }
+        }
+        buf.push('\n');
+    }
+    Ok(buf.into_bytes())
+}
+
+fn write_escaped(buf: &mut String, value: &str) {
+    for c in value.chars() {
+        match c {
+            '\\' => buf.push_str("\\\\"),
+            '\t' => buf.push_str("\\t"),
+            '\n' => buf.push_str("\\n"),
+            '\r' => buf.push_str("\\r"),
+            '\0' => buf.push_str("\\0"),
+            c => buf.push(c),
+        }
+    }
+}
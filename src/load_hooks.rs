@@ -0,0 +1,184 @@
+use sqlx::{Executor, IntoArguments};
+
+use crate::{compat::ArgumentsOf, Backend, Dialect};
+
+/// Statements run once before a bulk insert and once after it, e.g. to suspend index
+/// maintenance or constraint checks during a large load. Each pair applies on its dialect only,
+/// so the same options work against every backend.
+///
+/// ```ignore
+/// BulkInsert::new(&rows)
+///     .load_hooks(LoadHooks::new().disable_keys().defer_constraints())
+///     .execute(&mut conn)
+///     .await?;
+/// ```
+///
+/// The restoring statements run in reverse order even when the insert fails, and when a later
+/// hook fails to apply. After a failed statement PostgreSQL rejects them until the transaction
+/// ends, its rollback restores the settings instead. Settings of a lost connection die with it,
+/// but `DISABLE KEYS` stays on the table.
+///
+/// `{table}` in a statement is replaced by the table name. The statements are inserted into the
+/// SQL as is, so they must not contain untrusted input.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct LoadHooks {
+    hooks: Vec<LoadHook>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct LoadHook {
+    dialect: Dialect,
+    before: String,
+    after: String,
+}
+
+impl LoadHooks {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Run `before` ahead of the insert and `after` once it ended, on `dialect` only.
+    pub fn statement(
+        mut self,
+        dialect: Dialect,
+        before: impl Into<String>,
+        after: impl Into<String>,
+    ) -> Self {
+        self.hooks.push(LoadHook {
+            dialect,
+            before: before.into(),
+            after: after.into(),
+        });
+        self
+    }
+
+    /// MySQL's `ALTER TABLE {table} DISABLE KEYS`, which builds the non-unique indexes of a
+    /// MyISAM table once at `ENABLE KEYS`. InnoDB ignores it.
+    pub fn disable_keys(self) -> Self {
+        self.statement(
+            Dialect::MySql,
+            "ALTER TABLE {table} DISABLE KEYS",
+            "ALTER TABLE {table} ENABLE KEYS",
+        )
+    }
+
+    /// PostgreSQL's `session_replication_role = replica`, which skips triggers and with them
+    /// foreign key checks. Needs superuser, reset to `DEFAULT` after.
+    pub fn replication_role_replica(self) -> Self {
+        self.statement(
+            Dialect::Postgres,
+            "SET session_replication_role = replica",
+            "SET session_replication_role = DEFAULT",
+        )
+    }
+
+    /// Check foreign keys at the end instead of per row, where the database can:
+    ///
+    /// - PostgreSQL: `SET CONSTRAINTS ALL DEFERRED`, for deferrable constraints in a
+    ///   transaction, checked at commit.
+    /// - SQLite: `PRAGMA defer_foreign_keys`, checked at commit.
+    /// - MSSQL: `NOCHECK CONSTRAINT ALL`, all rows checked again after.
+    /// - MySQL: `foreign_key_checks = 0`, not checked at all. Restored to the previous value.
+    pub fn defer_constraints(self) -> Self {
+        self.statement(
+            Dialect::Postgres,
+            "SET CONSTRAINTS ALL DEFERRED",
+            "SET CONSTRAINTS ALL IMMEDIATE",
+        )
+        .statement(
+            Dialect::Sqlite,
+            "PRAGMA defer_foreign_keys = ON",
+            "PRAGMA defer_foreign_keys = OFF",
+        )
+        .statement(
+            Dialect::Mssql,
+            "ALTER TABLE {table} NOCHECK CONSTRAINT ALL",
+            "ALTER TABLE {table} WITH CHECK CHECK CONSTRAINT ALL",
+        )
+        .statement(
+            Dialect::MySql,
+            "SET @sqlx_plus_foreign_key_checks = @@foreign_key_checks, foreign_key_checks = 0",
+            "SET foreign_key_checks = @sqlx_plus_foreign_key_checks",
+        )
+    }
+
+    fn of(&self, dialect: Dialect) -> impl Iterator<Item = &LoadHook> {
+        self.hooks.iter().filter(move |x| x.dialect == dialect)
+    }
+
+    /// Apply the hooks of `DB`. When one fails, the ones applied before it are restored.
+    pub(crate) async fn before<E, DB>(
+        &self,
+        executor: &mut E,
+        table_name: &str,
+    ) -> anyhow::Result<()>
+    where
+        DB: Backend,
+        for<'e> &'e mut E: Executor<'e, Database = DB>,
+        for<'q> ArgumentsOf<'q, DB>: IntoArguments<'q, DB>,
+    {
+        for (applied, hook) in self.of(DB::DIALECT).enumerate() {
+            let sql = hook.before.replace("{table}", table_name);
+            if let Err(error) = sqlx::query(&sql).execute(&mut *executor).await {
+                // The failure to apply is what matters.
+                let _ = self.restore::<E, DB>(executor, table_name, applied).await;
+                return Err(
+                    anyhow::Error::new(error).context(format!("load hook {:?} failed", sql))
+                );
+            }
+        }
+        Ok(())
+    }
+
+    /// Restore the hooks of `DB` and pass on `result`, whose error takes precedence.
+    pub(crate) async fn after<E, DB, R>(
+        &self,
+        executor: &mut E,
+        table_name: &str,
+        result: anyhow::Result<R>,
+    ) -> anyhow::Result<R>
+    where
+        DB: Backend,
+        for<'e> &'e mut E: Executor<'e, Database = DB>,
+        for<'q> ArgumentsOf<'q, DB>: IntoArguments<'q, DB>,
+    {
+        let restored = self
+            .restore::<E, DB>(executor, table_name, self.of(DB::DIALECT).count())
+            .await;
+        let value = result?;
+        restored?;
+        Ok(value)
+    }
+
+    /// Run the restoring statements of the first `applied` hooks in reverse order, all of them
+    /// even when one fails, and return the first failure.
+    async fn restore<E, DB>(
+        &self,
+        executor: &mut E,
+        table_name: &str,
+        applied: usize,
+    ) -> anyhow::Result<()>
+    where
+        DB: Backend,
+        for<'e> &'e mut E: Executor<'e, Database = DB>,
+        for<'q> ArgumentsOf<'q, DB>: IntoArguments<'q, DB>,
+    {
+        let mut restored = Ok(());
+        for hook in self
+            .of(DB::DIALECT)
+            .take(applied)
+            .collect::<Vec<_>>()
+            .into_iter()
+            .rev()
+        {
+            let sql = hook.after.replace("{table}", table_name);
+            if let Err(error) = sqlx::query(&sql).execute(&mut *executor).await {
+                if restored.is_ok() {
+                    restored = Err(anyhow::Error::new(error)
+                        .context(format!("restoring load hook {:?} failed", sql)));
+                }
+            }
+        }
+        restored
+    }
+}
@@ -0,0 +1,124 @@
+use crate::{Insertable, QueryBindExt};
+
+/// Implement [`Insertable`] for a type which can't use the derive, like a struct generated from
+/// protobuf, with one `"column" => value` pair per column. The pairs are both the columns and the
+/// binds, so they can't get out of step:
+///
+/// ```ignore
+/// sqlx_plus::impl_insertable_manually!(proto::User, sqlx::Postgres, "user", |user| {
+///     "name" => &user.name,
+///     "email" => user.email.as_deref(),
+///     "created_at" => user.created_at.as_ref().map(|x| x.seconds),
+/// });
+/// ```
+///
+/// The values borrow from the bound variable, `user` above. A type of another crate needs a
+/// newtype around it first, as for any foreign trait. Debug builds panic on binding a value
+/// whose pairs name a column twice.
+#[macro_export]
+macro_rules! impl_insertable_manually {
+    (
+        $ty:ty, $db:ty, $table:expr, |$this:ident| {
+            $( $column:literal => $value:expr ),* $(,)?
+        } $(,)?
+    ) => {
+        impl $crate::Insertable for $ty {
+            type Database = $db;
+
+            const COLUMN_COUNT: usize = <[&'static str]>::len(&[ $( $column ),* ]);
+
+            fn table_name() -> &'static str {
+                $table
+            }
+
+            fn insert_columns() -> &'static [&'static str] {
+                &[ $( $column ),* ]
+            }
+
+            #[allow(unused_variables)]
+            fn bind_fields<'q, Q>(&'q self, q: Q) -> Q
+            where
+                Q: $crate::QueryBindExt<'q, Self::Database>,
+            {
+                #[cfg(debug_assertions)]
+                if let Err(error) = $crate::__private::check_columns::<Self>() {
+                    panic!("{}", error);
+                }
+                let $this = self;
+                q $( .bind($value) )*
+            }
+
+            #[allow(unused_variables)]
+            fn bind_field<'q, Q>(&'q self, column: &str, q: Q) -> Q
+            where
+                Q: $crate::QueryBindExt<'q, Self::Database>,
+            {
+                let $this = self;
+                match column {
+                    $( $column => q.bind($value), )*
+                    _ => q,
+                }
+            }
+        }
+    };
+}
+
+/// Counts binds instead of binding.
+struct BindCount(usize);
+
+impl<'q, DB: sqlx::Database> QueryBindExt<'q, DB> for BindCount {
+    fn bind<T>(self, _value: T) -> Self
+    where
+        T: 'q + Send + sqlx::Encode<'q, DB> + sqlx::Type<DB>,
+    {
+        BindCount(self.0 + 1)
+    }
+}
+
+/// Check that `value` binds one value per column and that the columns are distinct and agree
+/// with `COLUMN_COUNT` and their expressions. For hand-written impls, e.g. in their tests.
+pub fn check_binds<T: Insertable>(value: &T) -> anyhow::Result<()> {
+    check_columns::<T>()?;
+    let columns = T::insert_columns().len();
+    let binds = value.bind_fields(BindCount(0)).0;
+    if binds != columns {
+        anyhow::bail!(
+            "{} binds {} values for {} columns",
+            std::any::type_name::<T>(),
+            binds,
+            columns
+        );
+    }
+    Ok(())
+}
+
+/// Check that the columns are distinct and agree with `COLUMN_COUNT` and their expressions.
+pub fn check_columns<T: Insertable>() -> anyhow::Result<()> {
+    let columns = T::insert_columns().len();
+    if columns != T::COLUMN_COUNT {
+        anyhow::bail!(
+            "{} has {} insert columns but a COLUMN_COUNT of {}",
+            std::any::type_name::<T>(),
+            columns,
+            T::COLUMN_COUNT
+        );
+    }
+    let expressions = T::insert_expressions().len();
+    if expressions != 0 && expressions != columns {
+        anyhow::bail!(
+            "{} has {} insert expressions for {} columns",
+            std::any::type_name::<T>(),
+            expressions,
+            columns
+        );
+    }
+    let names = T::insert_columns();
+    if let Some(column) = (0..columns).find(|&i| names[..i].contains(&names[i])) {
+        anyhow::bail!(
+            "{} names the column {} twice",
+            std::any::type_name::<T>(),
+            names[column]
+        );
+    }
+    Ok(())
+}
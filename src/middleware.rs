@@ -0,0 +1,205 @@
+//! Hooks around every statement sqlx-plus executes.
+//!
+//! Middlewares are registered on a [`Config`](crate::Config) and run in registration order
+//! before a statement and in reverse order after it, like layers around the execution:
+//!
+//! ```ignore
+//! struct TenantScope(i64);
+//!
+//! #[async_trait::async_trait]
+//! impl Middleware for TenantScope {
+//!     async fn before(&self, statement: &mut Statement) -> anyhow::Result<()> {
+//!         statement.sql = format!("/* tenant={} */ {}", self.0, statement.sql);
+//!         Ok(())
+//!     }
+//! }
+//!
+//! Config::new().middleware(TenantScope(42)).set_global();
+//! ```
+//!
+//! `before` can rewrite the SQL or fail the statement, which fails the chunk like a database
+//! error would. The bind parameters are fixed, so a rewrite must keep the placeholders.
+//!
+//! The statements of a chunked bulk operation carry their [`ChunkPosition`], so a middleware can
+//! tag the SQL or its log lines with the batch and chunk they belong to.
+
+use std::{
+    borrow::Cow,
+    future::Future,
+    sync::atomic::{AtomicU64, Ordering},
+    time::{Duration, Instant},
+};
+
+use async_trait::async_trait;
+
+use crate::{
+    context::{self, Context},
+    Config, SqlFormat,
+};
+
+/// A statement about to be executed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Statement {
+    pub sql: String,
+    pub table_name: String,
+    /// The number of value rows bound to the statement, `0` for statements without values.
+    pub rows: usize,
+    /// Set for the chunks of bulk operations.
+    pub chunk: Option<ChunkPosition>,
+    /// The layout of [`formatted_sql`](Self::formatted_sql), the config's.
+    pub format: SqlFormat,
+    /// Whether the statement may be kept in the connection's prepared statement cache, if
+    /// [`BulkInsertOptions::persistent`](crate::BulkInsertOptions::persistent) allows it. A
+    /// middleware which makes the SQL unique per execution clears it, or every execution would
+    /// add an entry to the cache.
+    pub persistent: bool,
+}
+
+impl Statement {
+    /// `sql` laid out in `format`, for logs. The statement executes `sql` as is.
+    pub fn formatted_sql(&self) -> Cow<'_, str> {
+        self.format.apply(&self.sql)
+    }
+}
+
+/// Where a statement is within a chunked bulk operation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ChunkPosition {
+    /// The same for all chunks of one operation, see [`BulkInsertOptions::batch_id`](crate::BulkInsertOptions::batch_id).
+    pub batch_id: u64,
+    /// Zero-based, like [`ChunkError::index`](crate::ChunkError::index).
+    pub index: usize,
+    /// The number of chunks of the operation.
+    pub total: usize,
+}
+
+static NEXT_BATCH_ID: AtomicU64 = AtomicU64::new(1);
+
+/// A batch id unique within the process, for operations which weren't given one.
+pub fn next_batch_id() -> u64 {
+    NEXT_BATCH_ID.fetch_add(1, Ordering::Relaxed)
+}
+
+/// How a statement went.
+#[derive(Debug)]
+pub struct StatementOutcome<'a> {
+    pub duration: Duration,
+    /// The affected or fetched rows.
+    pub result: Result<u64, &'a sqlx::Error>,
+}
+
+#[async_trait]
+pub trait Middleware: Send + Sync {
+    async fn before(&self, _statement: &mut Statement) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    async fn after(&self, _statement: &Statement, _outcome: &StatementOutcome<'_>) {}
+}
+
+/// Lets the caller keep a handle on a registered middleware, e.g. to read what it collected.
+#[async_trait]
+impl<M: Middleware + ?Sized> Middleware for std::sync::Arc<M> {
+    async fn before(&self, statement: &mut Statement) -> anyhow::Result<()> {
+        (**self).before(statement).await
+    }
+
+    async fn after(&self, statement: &Statement, outcome: &StatementOutcome<'_>) {
+        (**self).after(statement, outcome).await
+    }
+}
+
+/// A statement which went through the `before` hooks and waits for its outcome.
+pub(crate) struct Intercepted<'c> {
+    config: &'c Config,
+    statement: Statement,
+    started: Instant,
+}
+
+impl Config {
+    pub(crate) async fn before(
+        &self,
+        sql: String,
+        table_name: &str,
+        rows: usize,
+    ) -> anyhow::Result<Intercepted<'_>> {
+        self.intercept(sql, table_name, rows, None).await
+    }
+
+    /// [`before`](Self::before) for the chunk of a bulk operation.
+    pub(crate) async fn before_chunk(
+        &self,
+        sql: String,
+        table_name: &str,
+        rows: usize,
+        chunk: ChunkPosition,
+    ) -> anyhow::Result<Intercepted<'_>> {
+        self.intercept(sql, table_name, rows, Some(chunk)).await
+    }
+
+    async fn intercept(
+        &self,
+        sql: String,
+        table_name: &str,
+        rows: usize,
+        chunk: Option<ChunkPosition>,
+    ) -> anyhow::Result<Intercepted<'_>> {
+        if let Some(context) = Context::current() {
+            context.check()?;
+        }
+        if let Some(chunk) = &chunk {
+            for gate in self.chunk_gates() {
+                gate.wait(chunk).await?;
+            }
+        }
+
+        let mut statement = Statement {
+            sql,
+            table_name: table_name.to_owned(),
+            rows,
+            chunk,
+            format: self.sql_format(),
+            persistent: true,
+        };
+
+        for middleware in self.middlewares() {
+            middleware.before(&mut statement).await?;
+        }
+
+        Ok(Intercepted {
+            config: self,
+            statement,
+            started: Instant::now(),
+        })
+    }
+}
+
+impl Intercepted<'_> {
+    pub(crate) fn sql(&self) -> &str {
+        &self.statement.sql
+    }
+
+    /// Whether to cache the prepared statement, where the options ask for `persistent`.
+    pub(crate) fn persistent(&self, persistent: bool) -> bool {
+        persistent && self.statement.persistent
+    }
+
+    /// Execute the statement's query, within the deadline of the current [`Context`].
+    pub(crate) async fn run<R>(
+        &self,
+        query: impl Future<Output = Result<R, sqlx::Error>>,
+    ) -> Result<R, sqlx::Error> {
+        context::with_deadline(query).await
+    }
+
+    pub(crate) async fn after<R>(self, result: &Result<R, sqlx::Error>, count: impl Fn(&R) -> u64) {
+        let outcome = StatementOutcome {
+            duration: self.started.elapsed(),
+            result: result.as_ref().map(count),
+        };
+
+        for middleware in self.config.middlewares().iter().rev() {
+            middleware.after(&self.statement, &outcome).await;
+        }
+    }
+}
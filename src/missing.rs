@@ -0,0 +1,158 @@
+use std::collections::HashSet;
+
+use sqlx::{Executor, FromRow, IntoArguments};
+
+use crate::{
+    bulk_insert_report_with_options, compat::ArgumentsOf, write_values_cte_of, Backend,
+    BulkInsertOptions, BulkInsertReport, Capabilities, Dialect, Insertable, PlaceHolders,
+    QueryBindExt,
+};
+
+/// Look up which values already exist by `key_columns` and insert the others.
+pub(crate) async fn bulk_insert_missing<T, E, DB>(
+    executor: &mut E,
+    values: &[T],
+    key_columns: &[&str],
+    options: &BulkInsertOptions,
+) -> anyhow::Result<BulkInsertReport>
+where
+    DB: Backend,
+    T: Insertable<Database = DB> + Sync,
+    for<'e> &'e mut E: Executor<'e, Database = DB>,
+    for<'q> ArgumentsOf<'q, DB>: IntoArguments<'q, DB>,
+    (i64,): for<'r> FromRow<'r, DB::Row>,
+{
+    let key_columns = match (key_columns, T::unique_columns().first()) {
+        ([], Some(unique)) => unique,
+        ([], None) => anyhow::bail!(
+            "bulk_insert_missing needs key columns or a unique constraint declared on {}",
+            T::table_name()
+        ),
+        (key_columns, _) => key_columns,
+    };
+    if let Some(column) = key_columns
+        .iter()
+        .find(|column| !T::insert_columns().contains(column))
+    {
+        anyhow::bail!("{} is not a column of {}", column, T::table_name());
+    }
+
+    if values.is_empty() {
+        return Ok(BulkInsertReport::default());
+    }
+
+    let config = options.config();
+    let capabilities = options.capabilities::<DB>();
+    let table_name = options.table_name::<T>();
+    let chunk_size = options.chunk_size::<T>()?;
+    let mut missing = Vec::with_capacity(values.len());
+
+    let positions = options.chunk_positions(values.len().div_ceil(chunk_size));
+    for (chunk, position) in values.chunks(chunk_size).zip(positions) {
+        let sql = existing_rows_sql::<DB>(&capabilities, table_name, key_columns, chunk.len());
+
+        let statement = config
+            .before_chunk(sql, table_name, chunk.len(), position)
+            .await?;
+        let existing = statement
+            .run(
+                DB::set_persistent(
+                    sqlx::query(statement.sql()),
+                    statement.persistent(options.persistent),
+                )
+                .bind_multi_with(chunk, |mut q, value| {
+                    for column in key_columns {
+                        q = value.bind_field(column, q);
+                    }
+                    q
+                })
+                .fetch_all(&mut *executor),
+            )
+            .await;
+        statement.after(&existing, |rows| rows.len() as u64).await;
+
+        let existing = existing?
+            .iter()
+            .map(|row| Ok(<(i64,)>::from_row(row)?.0 as usize))
+            .collect::<anyhow::Result<HashSet<_>>>()?;
+        missing.extend(
+            chunk
+                .iter()
+                .enumerate()
+                .filter(|(ord, _)| !existing.contains(ord))
+                .map(|(_, value)| value),
+        );
+    }
+
+    bulk_insert_report_with_options(executor, &missing, options).await
+}
+
+/// The ordinals of the `rows` bound keys which exist in `table_name`.
+fn existing_rows_sql<DB: PlaceHolders>(
+    capabilities: &Capabilities,
+    table_name: &str,
+    key_columns: &[&str],
+    rows: usize,
+) -> String {
+    let values_cte = supports_values_cte(capabilities);
+    let mut sql = String::new();
+    if values_cte {
+        write_values_cte_of::<DB>(&mut sql, capabilities, key_columns, rows);
+        sql.push(' ');
+    }
+    sql.push_str(match capabilities.dialect {
+        Dialect::MySql => "SELECT CAST(v.sqlx_plus_ord AS SIGNED) FROM ",
+        _ => "SELECT CAST(v.sqlx_plus_ord AS BIGINT) FROM ",
+    });
+    if values_cte {
+        sql.push('v');
+    } else {
+        // Old MySQL has neither CTEs nor `VALUES` rows, a derived table of selects does the same.
+        sql.push('(');
+        for row in 0..rows {
+            if row > 0 {
+                sql.push_str(" UNION ALL ");
+            }
+            sql.push_str("SELECT ");
+            for (i, column) in key_columns.iter().enumerate() {
+                DB::write_placeholders(&mut sql, 1, 1 + row * key_columns.len() + i);
+                if row == 0 {
+                    sql.push_str(" AS ");
+                    sql.push_str(column);
+                }
+                sql.push(',');
+            }
+            sql.push_str(&row.to_string());
+            if row == 0 {
+                sql.push_str(" AS sqlx_plus_ord");
+            }
+        }
+        sql.push_str(") AS v");
+    }
+
+    sql.push_str(" WHERE EXISTS (SELECT 1 FROM ");
+    sql.push_str(table_name);
+    sql.push_str(" t WHERE ");
+    for (i, column) in key_columns.iter().enumerate() {
+        if i > 0 {
+            sql.push_str(" AND ");
+        }
+        sql.push_str("t.");
+        sql.push_str(column);
+        sql.push_str(" = v.");
+        sql.push_str(column);
+    }
+    sql.push(')');
+
+    sql
+}
+
+/// Whether the server has `WITH` and `VALUES` rows: MySQL since 8.0.19, MariaDB since 10.3.3.
+/// Undetected servers are assumed to be current.
+fn supports_values_cte(capabilities: &Capabilities) -> bool {
+    match (&capabilities.dialect, &capabilities.server_version) {
+        (Dialect::MySql, Some(version)) if version.is_mariadb() => version.at_least(10, 3, 3),
+        (Dialect::MySql, Some(version)) => version.at_least(8, 0, 19),
+        _ => true,
+    }
+}
@@ -0,0 +1,36 @@
+//! Normalizers for `#[insertable(normalize = "...")]`.
+//!
+//! A normalizer is any function taking a reference to the field and returning the value to
+//! bind, so unique indexes on normalized text see the same form no matter how the input was
+//! written. The functions here can be named by their bare name:
+//!
+//! ```ignore
+//! #[derive(sqlx_plus::Insertable)]
+//! #[insertable(sqlx::Postgres, "users")]
+//! struct NewUser {
+//!     #[insertable(normalize = "nfc_lowercase")]
+//!     email: String,
+//!     #[insertable(normalize = "crate::slug::normalize")]
+//!     slug: String,
+//! }
+//! ```
+
+use unicode_normalization::UnicodeNormalization;
+
+/// Unicode NFC.
+pub fn nfc<S: AsRef<str> + ?Sized>(value: &S) -> String {
+    value.as_ref().nfc().collect()
+}
+
+/// Unicode NFC, then lowercase. For case-insensitive keys like email addresses.
+pub fn nfc_lowercase<S: AsRef<str> + ?Sized>(value: &S) -> String {
+    nfc(value).to_lowercase()
+}
+
+pub fn lowercase<S: AsRef<str> + ?Sized>(value: &S) -> String {
+    value.as_ref().to_lowercase()
+}
+
+pub fn trim<S: AsRef<str> + ?Sized>(value: &S) -> String {
+    value.as_ref().trim().to_owned()
+}
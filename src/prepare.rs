@@ -0,0 +1,49 @@
+use sqlx::Executor;
+
+use crate::{Backend, BulkInsertOptions, Insertable};
+
+/// Prepare the single-row and the `chunk_size`-row insert statement of `T` on a connection, so
+/// the first inserts after a (re)connect don't pay for preparing them:
+///
+/// ```ignore
+/// let mut conn = pool.acquire().await?;
+/// sqlx_plus::prepare_insert::<Event, _>(&mut conn, 500).await?;
+/// ```
+///
+/// The statements go through the middlewares like executed ones, so they match what is
+/// executed later. They land in the connection's statement cache, which evicts the least
+/// recently used statements: keep its capacity above the number of hot statements.
+pub async fn prepare_insert<T, E>(executor: &mut E, chunk_size: usize) -> anyhow::Result<()>
+where
+    T: Insertable,
+    T::Database: Backend,
+    for<'e> &'e mut E: Executor<'e, Database = T::Database>,
+{
+    let options = BulkInsertOptions {
+        chunk_size: Some(chunk_size),
+        ..Default::default()
+    };
+    let chunk_size = options.chunk_size::<T>()?;
+    let config = options.config();
+    let table_name = options.table_name::<T>();
+
+    let sizes: &[usize] = if chunk_size == 1 {
+        &[1]
+    } else {
+        &[1, chunk_size]
+    };
+
+    for &rows in sizes {
+        let statement = config
+            .before(options.insert_sql::<T>(rows)?, table_name, rows)
+            .await?;
+        let result = statement
+            .run(executor.prepare(statement.sql()))
+            .await
+            .map(drop);
+        statement.after(&result, |_| 0).await;
+        result?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,45 @@
+use std::{fmt, ops::Range, time::Duration};
+
+/// The outcome of a bulk insert executed through [`crate::BulkInsert`].
+#[derive(Debug, Default)]
+pub struct BulkInsertReport {
+    /// Rows affected by all successful chunks.
+    pub rows_affected: u64,
+    /// Number of executed chunks, including failed ones.
+    pub chunks: usize,
+    pub duration: Duration,
+    /// Failed chunks. Only filled when `continue_on_error` is enabled, otherwise the first
+    /// failure is returned as an error.
+    pub failed: Vec<ChunkError>,
+}
+
+impl BulkInsertReport {
+    pub fn is_success(&self) -> bool {
+        self.failed.is_empty()
+    }
+
+    /// Number of input rows which belong to failed chunks.
+    pub fn failed_rows(&self) -> usize {
+        self.failed.iter().map(|x| x.rows.len()).sum()
+    }
+}
+
+/// A chunk of a bulk insert which failed.
+#[derive(Debug)]
+pub struct ChunkError {
+    /// Index of the chunk.
+    pub index: usize,
+    /// Indices of the input rows in the chunk.
+    pub rows: Range<usize>,
+    pub error: anyhow::Error,
+}
+
+impl fmt::Display for ChunkError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "chunk {} (rows {}..{}) failed: {}",
+            self.index, self.rows.start, self.rows.end, self.error
+        )
+    }
+}
@@ -0,0 +1,161 @@
+//! Returning auto-generated ids from bulk inserts.
+//!
+//! - PostgreSQL, SQLite >= 3.35 and MariaDB >= 10.5 use `INSERT ... RETURNING {id_column}`.
+//! - SQLite < 3.35 derives the ids from `last_insert_rowid()` and the number of inserted rows.
+//!   A single statement assigns consecutive rowids unless the table has reached the maximum rowid.
+//! - MySQL has no `RETURNING`. The ids are reconstructed from `LAST_INSERT_ID()`, which is the id
+//!   of the first row of the statement, and `ROW_COUNT()`. This is only valid when the server
+//!   hands out consecutive ids to a multi-row insert, i.e. `innodb_autoinc_lock_mode` is `0` or `1`
+//!   and `auto_increment_increment` is `1`. Both are checked before inserting and an error is
+//!   returned when the emulation would be unsafe, or when the table has no `AUTO_INCREMENT`
+//!   column and `LAST_INSERT_ID()` stays `0`.
+//! - MSSQL uses `INSERT ... OUTPUT INSERTED.{id_column} VALUES ...`. SQL Server promises
+//!   neither the order of the output rows nor that identities follow the order of the `VALUES`
+//!   list, so the ids are returned as a set: those of each chunk in ascending order, which need
+//!   not be the order of its rows. Don't match them to the input by position on MSSQL.
+//!   `OUTPUT` without `INTO` fails on tables with enabled triggers.
+//!
+//! With an upsert clause, rows which were skipped by `DO NOTHING` return no id. Use
+//! `execute_upsert_returning` to get an outcome per input row instead.
+
+use anyhow::{anyhow, bail, ensure};
+use sqlx::{Executor, FromRow, IntoArguments};
+
+use crate::{
+    clock, compat::ArgumentsOf, Backend, BulkInsertOptions, Dialect, Insertable, QueryBindExt,
+};
+
+pub(crate) async fn bulk_insert_returning_ids<T, E, DB>(
+    executor: &mut E,
+    values: &[T],
+    options: &BulkInsertOptions,
+) -> anyhow::Result<Vec<i64>>
+where
+    DB: Backend,
+    T: Insertable<Database = DB> + Sync,
+    for<'e> &'e mut E: Executor<'e, Database = DB>,
+    for<'q> ArgumentsOf<'q, DB>: IntoArguments<'q, DB>,
+    (i64,): for<'r> sqlx::FromRow<'r, DB::Row>,
+{
+    if values.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let config = options.config();
+    let table_name = options.table_name::<T>();
+    let capabilities = options.capabilities::<DB>();
+    let chunk_size = options.chunk_size::<T>()?;
+    let mut ids = Vec::with_capacity(values.len());
+
+    let returning = capabilities.supports_returning || DB::DIALECT == Dialect::Mssql;
+    if !returning {
+        match DB::DIALECT {
+            Dialect::MySql if options.on_conflict.is_some() => {
+                bail!("returning generated ids of an upsert is not supported on MySQL")
+            }
+            Dialect::MySql => ensure_consecutive_auto_increment(executor).await?,
+            Dialect::Sqlite => {}
+            dialect => bail!("returning generated ids is not supported on {:?}", dialect),
+        }
+    }
+
+    options.before_load::<T, E>(&mut *executor).await?;
+    let result = clock::scope(options.batch(), async {
+        let positions = options.chunk_positions(values.len().div_ceil(chunk_size));
+        for (chunk, position) in values.chunks(chunk_size).zip(positions) {
+            if returning {
+                let sql = options.returning_sql::<T>(chunk.len())?;
+                let statement = config
+                    .before_chunk(sql, table_name, chunk.len(), position)
+                    .await?;
+                let rows = statement
+                    .run(
+                        DB::set_persistent(
+                            sqlx::query(statement.sql()),
+                            statement.persistent(options.persistent),
+                        )
+                        .bind_multi_fields(chunk)
+                        .fetch_all(&mut *executor),
+                    )
+                    .await;
+                statement.after(&rows, |rows| rows.len() as u64).await;
+
+                let start = ids.len();
+                for row in &rows? {
+                    let (id,) = <(i64,)>::from_row(row)?;
+                    ids.push(id);
+                }
+                if DB::DIALECT == Dialect::Mssql {
+                    ids[start..].sort_unstable();
+                }
+            } else {
+                let sql = options.insert_sql::<T>(chunk.len())?;
+                let statement = config
+                    .before_chunk(sql, table_name, chunk.len(), position)
+                    .await?;
+                let result = statement
+                    .run(
+                        DB::set_persistent(
+                            sqlx::query(statement.sql()),
+                            statement.persistent(options.persistent),
+                        )
+                        .bind_multi_fields(chunk)
+                        .execute(&mut *executor),
+                    )
+                    .await;
+                statement.after(&result, DB::rows_affected).await;
+                let result = result?;
+                let generated = DB::generated_ids(&result).ok_or_else(|| {
+                    anyhow!(
+                        "inserted {} rows into {} but no ids were generated, \
+                         it has no auto-increment column",
+                        chunk.len(),
+                        table_name,
+                    )
+                })?;
+
+                ensure!(
+                    generated.end - generated.start == chunk.len() as i64,
+                    "inserted {} rows but {} ids were generated, cannot reconstruct ids",
+                    chunk.len(),
+                    generated.end - generated.start,
+                );
+
+                ids.extend(generated);
+            }
+        }
+
+        Ok(ids)
+    })
+    .await;
+    options.after_load::<T, E, _>(executor, result).await
+}
+
+async fn ensure_consecutive_auto_increment<E, DB>(executor: &mut E) -> anyhow::Result<()>
+where
+    DB: Backend,
+    for<'e> &'e mut E: Executor<'e, Database = DB>,
+    for<'q> ArgumentsOf<'q, DB>: IntoArguments<'q, DB>,
+    (i64,): for<'r> sqlx::FromRow<'r, DB::Row>,
+{
+    let (lock_mode,): (i64,) = sqlx::query_as("SELECT CAST(@@innodb_autoinc_lock_mode AS SIGNED)")
+        .fetch_one(&mut *executor)
+        .await?;
+    let (increment,): (i64,) = sqlx::query_as("SELECT CAST(@@auto_increment_increment AS SIGNED)")
+        .fetch_one(&mut *executor)
+        .await?;
+
+    ensure!(
+        lock_mode < 2,
+        "cannot emulate RETURNING: innodb_autoinc_lock_mode = {} does not guarantee consecutive ids, \
+         it must be 0 or 1",
+        lock_mode,
+    );
+    ensure!(
+        increment == 1,
+        "cannot emulate RETURNING: auto_increment_increment = {}, it must be 1",
+        increment,
+    );
+
+    Ok(())
+}
@@ -0,0 +1,56 @@
+use std::fmt;
+
+/// The values an [`Insertable`](crate::Insertable) binds, by column, without executing anything.
+/// For generic auditing, diffing or debug output.
+///
+/// Derived with `#[insertable(..., as_row)]`, which requires `Debug` on every inserted field:
+///
+/// ```ignore
+/// #[derive(Debug, sqlx_plus::Insertable)]
+/// #[insertable(sqlx::Postgres, "user", as_row)]
+/// struct UserInsert {
+///     name: String,
+///     #[insertable(normalize = "sqlx_plus::normalize::trim")]
+///     email: String,
+/// }
+///
+/// for (column, value) in user.as_row() {
+///     audit.record(column, format!("{:?}", value));
+/// }
+/// ```
+///
+/// The values are the bound ones: normalized, rounded or `NULL` by `skip_if` like in an insert.
+pub trait AsRow {
+    /// One entry per column of `insert_columns()`, in that order.
+    fn as_row(&self) -> Vec<(&'static str, ValueRef<'_>)>;
+}
+
+impl<T: AsRow + ?Sized> AsRow for &T {
+    fn as_row(&self) -> Vec<(&'static str, ValueRef<'_>)> {
+        (**self).as_row()
+    }
+}
+
+/// A column value of [`AsRow::as_row`].
+///
+/// Values compare by their `Debug` output, which tells apart the values of the usual column
+/// types, but not of a type whose `Debug` leaves out data.
+pub struct ValueRef<'a>(Box<dyn fmt::Debug + 'a>);
+
+impl<'a> ValueRef<'a> {
+    pub fn new<T: fmt::Debug + 'a>(value: T) -> Self {
+        ValueRef(Box::new(value))
+    }
+}
+
+impl fmt::Debug for ValueRef<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl PartialEq for ValueRef<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        format!("{:?}", self) == format!("{:?}", other)
+    }
+}
@@ -0,0 +1,46 @@
+//! The few runtime facilities sqlx-plus needs, from the runtime sqlx is built with.
+
+use std::{future::Future, time::Duration};
+
+#[cfg(any(feature = "runtime-tokio-rustls", feature = "runtime-tokio-native-tls"))]
+pub(crate) async fn timeout<F: Future>(duration: Duration, future: F) -> Option<F::Output> {
+    tokio::time::timeout(duration, future).await.ok()
+}
+
+#[cfg(not(any(feature = "runtime-tokio-rustls", feature = "runtime-tokio-native-tls")))]
+pub(crate) async fn timeout<F: Future>(duration: Duration, future: F) -> Option<F::Output> {
+    async_std::future::timeout(duration, future).await.ok()
+}
+
+#[cfg(any(feature = "runtime-tokio-rustls", feature = "runtime-tokio-native-tls"))]
+pub(crate) async fn sleep(duration: Duration) {
+    tokio::time::sleep(duration).await
+}
+
+#[cfg(not(any(feature = "runtime-tokio-rustls", feature = "runtime-tokio-native-tls")))]
+pub(crate) async fn sleep(duration: Duration) {
+    async_std::task::sleep(duration).await
+}
+
+/// Run `f` on the runtime's thread pool for blocking work.
+#[cfg(any(feature = "runtime-tokio-rustls", feature = "runtime-tokio-native-tls"))]
+pub(crate) async fn spawn_blocking<R, F>(f: F) -> R
+where
+    F: FnOnce() -> R + Send + 'static,
+    R: Send + 'static,
+{
+    match tokio::task::spawn_blocking(f).await {
+        Ok(output) => output,
+        Err(error) => std::panic::resume_unwind(error.into_panic()),
+    }
+}
+
+/// Run `f` on the runtime's thread pool for blocking work.
+#[cfg(not(any(feature = "runtime-tokio-rustls", feature = "runtime-tokio-native-tls")))]
+pub(crate) async fn spawn_blocking<R, F>(f: F) -> R
+where
+    F: FnOnce() -> R + Send + 'static,
+    R: Send + 'static,
+{
+    async_std::task::spawn_blocking(f).await
+}
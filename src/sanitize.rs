@@ -0,0 +1,156 @@
+//! Escaping of values which end up in SQL text instead of bind parameters: identifiers and
+//! `LIKE` patterns.
+//!
+//! The `check_*` functions state the invariants the escaping relies on and panic when one
+//! doesn't hold. The fuzz targets in `fuzz/` feed them arbitrary input, run them against your
+//! own dialect with `cargo fuzz run <target>`.
+
+use crate::{dialect, Dialect};
+
+/// The clause to follow a `LIKE` pattern from [`escape_like`] with. SQLite has no default escape
+/// character and MSSQL's isn't `\`, so always add it.
+pub const LIKE_ESCAPE: &str = " ESCAPE '\\'";
+
+/// Quote `name` as a single identifier for `dialect`, doubling the closing quote inside it.
+/// Dots are kept as part of the name, use [`TableName::quoted`](crate::TableName::quoted) for
+/// qualified names.
+pub fn quote_identifier(dialect: Dialect, name: &str) -> String {
+    let (open, close) = identifier_quotes(dialect);
+    let mut quoted = String::with_capacity(name.len() + 2);
+    write_quoted_identifier(&mut quoted, open, close, name);
+    quoted
+}
+
+pub(crate) fn identifier_quotes(dialect: Dialect) -> (char, char) {
+    match dialect {
+        Dialect::MySql => ('`', '`'),
+        Dialect::Mssql => ('[', ']'),
+        Dialect::Sqlite | Dialect::Postgres => ('"', '"'),
+    }
+}
+
+pub(crate) fn write_quoted_identifier(buf: &mut String, open: char, close: char, name: &str) {
+    buf.push(open);
+    for c in name.chars() {
+        if c == close {
+            buf.push(close);
+        }
+        buf.push(c);
+    }
+    buf.push(close);
+}
+
+/// Escape `%`, `_` and `\` in `value`, and `[` on MSSQL, so it matches literally in a `LIKE`
+/// pattern followed by [`LIKE_ESCAPE`]:
+///
+/// ```ignore
+/// let sql = format!("SELECT * FROM user WHERE name LIKE ?{}", LIKE_ESCAPE);
+/// sqlx::query(&sql).bind(format!("{}%", escape_like(Dialect::Sqlite, prefix)))
+/// ```
+pub fn escape_like(dialect: Dialect, value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        if is_like_special(dialect, c) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+fn is_like_special(dialect: Dialect, c: char) -> bool {
+    matches!(c, '%' | '_' | '\\') || (dialect == Dialect::Mssql && c == '[')
+}
+
+/// Check that [`quote_identifier`] yields one quoted identifier which reads back as `name`.
+pub fn check_identifier(dialect: Dialect, name: &str) {
+    let (open, close) = identifier_quotes(dialect);
+    let quoted = quote_identifier(dialect, name);
+
+    let inner = quoted
+        .strip_prefix(open)
+        .and_then(|x| x.strip_suffix(close))
+        .unwrap_or_else(|| panic!("{:?} is not quoted", quoted));
+    let mut unquoted = String::with_capacity(inner.len());
+    let mut chars = inner.chars();
+    while let Some(c) = chars.next() {
+        if c == close {
+            assert_eq!(
+                chars.next(),
+                Some(close),
+                "{:?} ends the identifier early",
+                quoted
+            );
+        }
+        unquoted.push(c);
+    }
+    assert_eq!(
+        unquoted, name,
+        "{:?} does not read back as {:?}",
+        quoted, name
+    );
+}
+
+/// Check that [`escape_like`] leaves no wildcard unescaped and reads back as `value`.
+pub fn check_like(dialect: Dialect, value: &str) {
+    let escaped = escape_like(dialect, value);
+
+    let mut unescaped = String::with_capacity(value.len());
+    let mut chars = escaped.chars();
+    while let Some(c) = chars.next() {
+        assert!(
+            c == '\\' || !is_like_special(dialect, c),
+            "{:?} has an unescaped {:?}",
+            escaped,
+            c
+        );
+        if c == '\\' {
+            let next = chars
+                .next()
+                .unwrap_or_else(|| panic!("{:?} ends with an escape", escaped));
+            assert!(
+                is_like_special(dialect, next),
+                "{:?} escapes {:?}",
+                escaped,
+                next
+            );
+            unescaped.push(next);
+        } else {
+            unescaped.push(c);
+        }
+    }
+    assert_eq!(
+        unescaped, value,
+        "{:?} does not read back as {:?}",
+        escaped, value
+    );
+}
+
+/// Check that the placeholders of `rows` rows of `columns` values are `rows * columns`
+/// placeholders, numbered `$1` onwards without gaps on PostgreSQL. Sizes beyond PostgreSQL's
+/// placeholder limit are clamped.
+pub fn check_placeholders(dialect: Dialect, rows: usize, columns: usize) {
+    let columns = columns.clamp(1, crate::POSTGRES_MAX_PLACEHOLDER);
+    let rows = rows.min(crate::POSTGRES_MAX_PLACEHOLDER / columns);
+    let sql = dialect::placeholders_for_values(dialect, rows, columns);
+
+    let values: Vec<_> = sql
+        .split([',', '(', ')'])
+        .filter(|x| !x.is_empty())
+        .collect();
+    assert_eq!(values.len(), rows * columns, "{:?}", sql);
+    for (i, value) in values.iter().enumerate() {
+        match dialect {
+            Dialect::Postgres => assert_eq!(*value, format!("${}", i + 1), "{:?}", sql),
+            Dialect::Sqlite | Dialect::MySql | Dialect::Mssql => {
+                assert_eq!(*value, "?", "{:?}", sql)
+            }
+        }
+    }
+    assert_eq!(
+        sql.matches('(').count(),
+        rows,
+        "{:?} has the wrong number of rows",
+        sql
+    );
+}
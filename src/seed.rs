@@ -0,0 +1,169 @@
+use async_trait::async_trait;
+use sqlx::Acquire;
+
+use crate::{Backend, BulkInsertOptions, Insertable, Inserter};
+
+/// Rows for a set of tables, inserted in one transaction in an order which satisfies the
+/// declared references. Meant for small fixtures in integration tests, see also [`seed!`].
+///
+/// ```ignore
+/// Seed::new()
+///     .table(vec![PostInsert { user_id: 1, title: "hello".into() }])
+///     .references(["user"])
+///     .table(vec![UserInsert { name: "alice".into() }])
+///     .execute(&pool)
+///     .await?;
+/// ```
+pub struct Seed<DB> {
+    tables: Vec<SeedTable<DB>>,
+}
+
+struct SeedTable<DB> {
+    table_name: &'static str,
+    references: Vec<String>,
+    rows: Box<dyn SeedRows<DB>>,
+}
+
+impl<DB> Seed<DB>
+where
+    DB: Backend,
+    for<'c> &'c mut DB::Connection: Inserter<DB>,
+{
+    pub fn new() -> Self {
+        Seed { tables: Vec::new() }
+    }
+
+    /// Add rows of `T`'s table.
+    pub fn table<T>(mut self, rows: Vec<T>) -> Self
+    where
+        T: Insertable<Database = DB> + Send + Sync + 'static,
+    {
+        self.tables.push(SeedTable {
+            table_name: T::table_name(),
+            references: Vec::new(),
+            rows: Box::new(rows),
+        });
+        self
+    }
+
+    /// The tables the rows added last refer to, they are inserted first. Tables without rows in
+    /// the seed are assumed to exist already.
+    pub fn references<I, S>(mut self, tables: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        if let Some(table) = self.tables.last_mut() {
+            table.references.extend(tables.into_iter().map(Into::into));
+        }
+        self
+    }
+
+    /// Insert all rows in one transaction and return the number of inserted rows.
+    pub async fn execute<'c, A>(self, acquire: A) -> anyhow::Result<u64>
+    where
+        A: Acquire<'c, Database = DB>,
+    {
+        let order = self.order()?;
+        let mut tx = acquire.begin().await?;
+        let mut rows = 0;
+
+        for index in order {
+            rows += self.tables[index].rows.insert(&mut *tx).await?;
+        }
+
+        tx.commit().await?;
+
+        Ok(rows)
+    }
+
+    /// The table indices in insertion order: a table comes after every table it references and
+    /// otherwise keeps its declaration order.
+    fn order(&self) -> anyhow::Result<Vec<usize>> {
+        let mut order = Vec::with_capacity(self.tables.len());
+        let mut inserted = vec![false; self.tables.len()];
+
+        while order.len() < self.tables.len() {
+            let ready = (0..self.tables.len()).find(|&index| {
+                !inserted[index]
+                    && self.tables[index].references.iter().all(|reference| {
+                        self.tables
+                            .iter()
+                            .zip(&inserted)
+                            .all(|(table, inserted)| *inserted || table.table_name != reference)
+                    })
+            });
+
+            match ready {
+                Some(index) => {
+                    inserted[index] = true;
+                    order.push(index);
+                }
+                None => {
+                    let cycle = self
+                        .tables
+                        .iter()
+                        .zip(&inserted)
+                        .filter(|(_, inserted)| !**inserted)
+                        .map(|(table, _)| table.table_name)
+                        .collect::<Vec<_>>();
+                    anyhow::bail!("the seed tables reference each other: {}", cycle.join(", "));
+                }
+            }
+        }
+
+        Ok(order)
+    }
+}
+
+impl<DB> Default for Seed<DB>
+where
+    DB: Backend,
+    for<'c> &'c mut DB::Connection: Inserter<DB>,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+trait SeedRows<DB: Backend>: Send + Sync {
+    async fn insert(&self, conn: &mut DB::Connection) -> anyhow::Result<u64>;
+}
+
+#[async_trait]
+impl<DB, T> SeedRows<DB> for Vec<T>
+where
+    DB: Backend,
+    T: Insertable<Database = DB> + Send + Sync,
+    for<'c> &'c mut DB::Connection: Inserter<DB>,
+{
+    async fn insert(&self, conn: &mut DB::Connection) -> anyhow::Result<u64> {
+        let report = conn
+            .bulk_insert_report_with_options(self, &BulkInsertOptions::default())
+            .await?;
+        Ok(report.rows_affected)
+    }
+}
+
+/// Build a [`Seed`] from inline rows, one bracketed list per table, optionally followed by the
+/// tables it references.
+///
+/// ```ignore
+/// sqlx_plus::seed![
+///     [PostInsert { user_id: 1, title: "hello".into() }] references "user";
+///     [UserInsert { name: "alice".into() }, UserInsert { name: "bob".into() }];
+/// ]
+/// .execute(&pool)
+/// .await?;
+/// ```
+#[macro_export]
+macro_rules! seed {
+    ( $( [ $($row:expr),* $(,)? ] $( references $($table:expr),+ )? );* $(;)? ) => {
+        $crate::Seed::new()
+            $(
+                .table(::std::vec![ $($row),* ])
+                $( .references([ $($table),+ ]) )?
+            )*
+    };
+}
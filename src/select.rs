@@ -0,0 +1,372 @@
+use std::marker::PhantomData;
+
+use sqlx::{Encode, Executor, FromRow, IntoArguments, Type};
+
+use crate::{
+    compat::{ArgumentsOf, QueryAsOf},
+    Backend, Config, Dialect, Insertable, PlaceHolders,
+};
+
+/// A column of `T`'s table. The derive generates one per field, named after the field in upper
+/// case, e.g. `User::NAME`.
+///
+/// Fields marked `#[insertable(immutable)]` get a `Column<T, Immutable>`, which
+/// [`update_fields`](crate::update_fields) does not accept.
+pub struct Column<T, M = Settable> {
+    name: &'static str,
+    _marker: PhantomData<fn() -> (T, M)>,
+}
+
+/// The marker of columns which updates may set.
+#[derive(Debug, Clone, Copy)]
+pub enum Settable {}
+
+/// The marker of columns which are written once by the insert, like `created_at`.
+#[derive(Debug, Clone, Copy)]
+pub enum Immutable {}
+
+impl<T, M> Clone for Column<T, M> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T, M> Copy for Column<T, M> {}
+
+impl<T, M> std::fmt::Debug for Column<T, M> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("Column").field(&self.name).finish()
+    }
+}
+
+impl<T, M> Column<T, M> {
+    pub const fn new(name: &'static str) -> Self {
+        Column {
+            name,
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    pub fn asc(self) -> OrderBy<T> {
+        OrderBy {
+            column: Column::new(self.name),
+            descending: false,
+        }
+    }
+
+    pub fn desc(self) -> OrderBy<T> {
+        OrderBy {
+            column: Column::new(self.name),
+            descending: true,
+        }
+    }
+}
+
+macro_rules! impl_comparisons {
+    ($($method:ident => $op:literal),* $(,)?) => {
+        impl<T: Insertable, M> Column<T, M> {
+            $(
+                #[doc = concat!("`column ", $op, " value`")]
+                pub fn $method<V>(self, value: V) -> Filter<T>
+                where
+                    V: for<'q> Encode<'q, T::Database> + Type<T::Database> + Send + 'static,
+                {
+                    Filter {
+                        column: self.name,
+                        op: $op,
+                        value: Some(Box::new(Value(value))),
+                    }
+                }
+            )*
+
+            pub fn is_null(self) -> Filter<T> {
+                Filter {
+                    column: self.name,
+                    op: "IS NULL",
+                    value: None,
+                }
+            }
+
+            pub fn is_not_null(self) -> Filter<T> {
+                Filter {
+                    column: self.name,
+                    op: "IS NOT NULL",
+                    value: None,
+                }
+            }
+        }
+    };
+}
+
+impl_comparisons! {
+    eq => "=",
+    ne => "<>",
+    lt => "<",
+    le => "<=",
+    gt => ">",
+    ge => ">=",
+    like => "LIKE",
+}
+
+/// A condition on a column, see [`Select::filter`].
+pub struct Filter<T: Insertable> {
+    column: &'static str,
+    op: &'static str,
+    value: Option<Box<dyn BindValue<T::Database, T>>>,
+}
+
+impl<T: Insertable> std::fmt::Debug for Filter<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Filter")
+            .field("column", &self.column)
+            .field("op", &self.op)
+            .finish_non_exhaustive()
+    }
+}
+
+/// An owned value which is bound when the query runs.
+trait BindValue<DB: sqlx::Database, O>: Send {
+    fn bind<'q>(self: Box<Self>, q: QueryAsOf<'q, DB, O>) -> QueryAsOf<'q, DB, O>;
+}
+
+struct Value<V>(V);
+
+impl<DB, O, V> BindValue<DB, O> for Value<V>
+where
+    DB: sqlx::Database,
+    V: for<'q> Encode<'q, DB> + Type<DB> + Send + 'static,
+{
+    fn bind<'q>(self: Box<Self>, q: QueryAsOf<'q, DB, O>) -> QueryAsOf<'q, DB, O> {
+        q.bind(self.0)
+    }
+}
+
+/// An `ORDER BY` item, see [`Select::order_by`].
+pub struct OrderBy<T> {
+    column: Column<T>,
+    descending: bool,
+}
+
+impl<T> Clone for OrderBy<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for OrderBy<T> {}
+
+impl<T> std::fmt::Debug for OrderBy<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("OrderBy")
+            .field("column", &self.column)
+            .field("descending", &self.descending)
+            .finish()
+    }
+}
+
+/// A `SELECT * FROM` the table of `T`, built from the columns the derive generates:
+///
+/// ```ignore
+/// let users = User::select()
+///     .filter(User::NAME.eq(name))
+///     .order_by(User::CREATED_AT.desc())
+///     .limit(10)
+///     .fetch_all(&pool)
+///     .await?;
+/// ```
+///
+/// Filters are combined with `AND`. Anything more involved is better written as plain SQL.
+pub struct Select<T: Insertable> {
+    filters: Vec<Filter<T>>,
+    order_by: Vec<OrderBy<T>>,
+    limit: Option<u64>,
+    offset: Option<u64>,
+}
+
+impl<T: Insertable> std::fmt::Debug for Select<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Select")
+            .field("filters", &self.filters)
+            .field("order_by", &self.order_by)
+            .field("limit", &self.limit)
+            .field("offset", &self.offset)
+            .finish()
+    }
+}
+
+impl<T: Insertable> Default for Select<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Insertable> Select<T> {
+    pub fn new() -> Self {
+        Select {
+            filters: Vec::new(),
+            order_by: Vec::new(),
+            limit: None,
+            offset: None,
+        }
+    }
+
+    pub fn filter(mut self, filter: Filter<T>) -> Self {
+        self.filters.push(filter);
+        self
+    }
+
+    pub fn order_by(mut self, order_by: OrderBy<T>) -> Self {
+        self.order_by.push(order_by);
+        self
+    }
+
+    pub fn limit(mut self, limit: u64) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    pub fn offset(mut self, offset: u64) -> Self {
+        self.offset = Some(offset);
+        self
+    }
+}
+
+impl<T> Select<T>
+where
+    T: Insertable,
+    T::Database: Backend,
+{
+    /// The SQL of the query.
+    pub fn sql(&self) -> String {
+        let dialect = T::Database::DIALECT;
+        let mut sql = String::from("SELECT ");
+        if let (Dialect::Mssql, Some(limit), None) = (dialect, self.limit, self.offset) {
+            sql.push_str(&format!("TOP {} ", limit));
+        }
+        sql.push_str("* FROM ");
+        sql.push_str(T::table_name());
+
+        let mut num = 1;
+        for (i, filter) in self.filters.iter().enumerate() {
+            sql.push_str(if i == 0 { " WHERE " } else { " AND " });
+            sql.push_str(filter.column);
+            sql.push(' ');
+            sql.push_str(filter.op);
+            if filter.value.is_some() {
+                sql.push(' ');
+                T::Database::write_placeholders(&mut sql, 1, num);
+                num += 1;
+            }
+        }
+
+        for (i, order_by) in self.order_by.iter().enumerate() {
+            sql.push_str(if i == 0 { " ORDER BY " } else { "," });
+            sql.push_str(order_by.column.name);
+            if order_by.descending {
+                sql.push_str(" DESC");
+            }
+        }
+
+        match dialect {
+            Dialect::Mssql => {
+                if let Some(offset) = self.offset {
+                    // `OFFSET` needs an `ORDER BY`.
+                    if self.order_by.is_empty() {
+                        sql.push_str(" ORDER BY (SELECT NULL)");
+                    }
+                    sql.push_str(&format!(" OFFSET {} ROWS", offset));
+                    if let Some(limit) = self.limit {
+                        sql.push_str(&format!(" FETCH NEXT {} ROWS ONLY", limit));
+                    }
+                }
+            }
+            _ => {
+                match (self.limit, self.offset) {
+                    (Some(limit), _) => sql.push_str(&format!(" LIMIT {}", limit)),
+                    // MySQL and SQLite have no `OFFSET` without `LIMIT`.
+                    (None, Some(_)) if dialect != Dialect::Postgres => {
+                        sql.push_str(" LIMIT 18446744073709551615")
+                    }
+                    (None, _) => {}
+                }
+                if let Some(offset) = self.offset {
+                    sql.push_str(&format!(" OFFSET {}", offset));
+                }
+            }
+        }
+
+        sql
+    }
+
+    fn bind_filters<'q>(
+        filters: Vec<Filter<T>>,
+        mut q: QueryAsOf<'q, T::Database, T>,
+    ) -> QueryAsOf<'q, T::Database, T> {
+        for value in filters.into_iter().filter_map(|filter| filter.value) {
+            q = value.bind(q);
+        }
+        q
+    }
+
+    pub async fn fetch_all<'e, E>(self, executor: E) -> anyhow::Result<Vec<T>>
+    where
+        E: Executor<'e, Database = T::Database>,
+        T: for<'r> FromRow<'r, <T::Database as sqlx::Database>::Row> + Send + Unpin,
+        for<'q> ArgumentsOf<'q, T::Database>: IntoArguments<'q, T::Database>,
+    {
+        let config = Config::global();
+        let statement = config.before(self.sql(), T::table_name(), 0).await?;
+        let rows = statement
+            .run(
+                Self::bind_filters(self.filters, sqlx::query_as(statement.sql()))
+                    .fetch_all(executor),
+            )
+            .await;
+        statement.after(&rows, |rows| rows.len() as u64).await;
+
+        Ok(rows?)
+    }
+
+    pub async fn fetch_optional<'e, E>(self, executor: E) -> anyhow::Result<Option<T>>
+    where
+        E: Executor<'e, Database = T::Database>,
+        T: for<'r> FromRow<'r, <T::Database as sqlx::Database>::Row> + Send + Unpin,
+        for<'q> ArgumentsOf<'q, T::Database>: IntoArguments<'q, T::Database>,
+    {
+        let config = Config::global();
+        let statement = config.before(self.sql(), T::table_name(), 0).await?;
+        let row = statement
+            .run(
+                Self::bind_filters(self.filters, sqlx::query_as(statement.sql()))
+                    .fetch_optional(executor),
+            )
+            .await;
+        statement.after(&row, |row| row.is_some() as u64).await;
+
+        Ok(row?)
+    }
+
+    /// Like [`Select::fetch_optional`], but no row is an error.
+    pub async fn fetch_one<'e, E>(self, executor: E) -> anyhow::Result<T>
+    where
+        E: Executor<'e, Database = T::Database>,
+        T: for<'r> FromRow<'r, <T::Database as sqlx::Database>::Row> + Send + Unpin,
+        for<'q> ArgumentsOf<'q, T::Database>: IntoArguments<'q, T::Database>,
+    {
+        let config = Config::global();
+        let statement = config.before(self.sql(), T::table_name(), 0).await?;
+        let row = statement
+            .run(
+                Self::bind_filters(self.filters, sqlx::query_as(statement.sql()))
+                    .fetch_one(executor),
+            )
+            .await;
+        statement.after(&row, |_| 1).await;
+
+        Ok(row?)
+    }
+}
@@ -0,0 +1,103 @@
+use std::{fmt, path::Path};
+
+use crate::{Backend, BulkInsertOptions, Insertable, Select};
+
+/// The SQL generated for a set of types and operations, to pin it in a snapshot file and notice
+/// changes when upgrading sqlx-plus.
+///
+/// ```ignore
+/// #[test]
+/// fn generated_sql() {
+///     sqlx_plus::SqlSnapshot::new()
+///         .insertable::<UserInsert>("UserInsert", &BulkInsertOptions::default())
+///         .add("users by name", User::select().filter(User::NAME.eq(name)).sql())
+///         .assert_matches("tests/snapshots/sql.snap");
+/// }
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SqlSnapshot {
+    entries: Vec<(String, String)>,
+}
+
+impl SqlSnapshot {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record `sql` under `name`.
+    pub fn add(mut self, name: impl Into<String>, sql: impl Into<String>) -> Self {
+        self.entries.push((name.into(), sql.into()));
+        self
+    }
+
+    /// Record the statements sqlx-plus generates for `T` with `options`: a single row insert, a
+    /// bulk insert of two rows and a select of the table.
+    pub fn insertable<T>(self, name: &str, options: &BulkInsertOptions) -> Self
+    where
+        T: Insertable,
+        T::Database: Backend,
+    {
+        let render = |rows| {
+            options
+                .insert_sql::<T>(rows)
+                .unwrap_or_else(|error| format!("error: {}", error))
+        };
+
+        self.add(format!("{}: insert", name), render(1))
+            .add(format!("{}: bulk insert", name), render(2))
+            .add(format!("{}: select", name), Select::<T>::new().sql())
+    }
+
+    /// Compare with the snapshot at `path` and panic with the first difference.
+    ///
+    /// A missing snapshot is written instead, as is any snapshot while the
+    /// `SQLX_PLUS_UPDATE_SNAPSHOTS` environment variable is set.
+    #[track_caller]
+    pub fn assert_matches(&self, path: impl AsRef<Path>) {
+        let path = path.as_ref();
+        let actual = self.to_string();
+
+        if std::env::var_os("SQLX_PLUS_UPDATE_SNAPSHOTS").is_some() || !path.exists() {
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent).unwrap();
+            }
+            std::fs::write(path, &actual)
+                .unwrap_or_else(|error| panic!("cannot write {}: {}", path.display(), error));
+            return;
+        }
+
+        let expected = std::fs::read_to_string(path)
+            .unwrap_or_else(|error| panic!("cannot read {}: {}", path.display(), error));
+        if expected == actual {
+            return;
+        }
+
+        let mut expected_lines = expected.lines();
+        let mut actual_lines = actual.lines();
+        for line in 1.. {
+            match (expected_lines.next(), actual_lines.next()) {
+                (Some(expected), Some(actual)) if expected == actual => continue,
+                (expected, actual) => panic!(
+                    "generated SQL differs from {} at line {}\n  expected: {}\n  actual:   {}\n\
+                     set SQLX_PLUS_UPDATE_SNAPSHOTS=1 to accept the change",
+                    path.display(),
+                    line,
+                    expected.unwrap_or("<end of file>"),
+                    actual.unwrap_or("<end of file>"),
+                ),
+            }
+        }
+    }
+}
+
+/// One `-- name` comment line followed by the statement and a blank line per entry.
+impl fmt::Display for SqlSnapshot {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (name, sql) in &self.entries {
+            writeln!(f, "-- {}", name)?;
+            writeln!(f, "{};", sql)?;
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
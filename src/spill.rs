@@ -0,0 +1,129 @@
+//! An append-only file to park rows in while the database is unavailable.
+//!
+//! ```ignore
+//! let mut spill = Spill::<Event>::open("events.spill")?;
+//!
+//! if let Err(error) = pool.bulk_insert(&events).await {
+//!     log::warn!("spilling {} events: {}", events.len(), error);
+//!     spill.append(&events)?;
+//! }
+//!
+//! // Later, once the database is back:
+//! spill.replay(&pool).await?;
+//! ```
+//!
+//! Rows are stored as JSON lines and synced to disk on every append, so they survive a crash
+//! of the process. The file is written with blocking I/O.
+
+use std::{
+    fs::{File, OpenOptions},
+    io::{BufRead, BufReader, Seek, Write},
+    marker::PhantomData,
+    path::{Path, PathBuf},
+};
+
+use serde::{de::DeserializeOwned, Serialize};
+use sqlx::Acquire;
+
+use crate::{Backend, BulkInsertOptions, BulkInsertReport, Insertable, Inserter};
+
+pub struct Spill<T> {
+    path: PathBuf,
+    file: File,
+    /// Rows read back and inserted per statement.
+    chunk_size: usize,
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<T> Spill<T>
+where
+    T: Insertable + Serialize + DeserializeOwned + Send + Sync,
+    T::Database: Backend,
+{
+    /// Open the file at `path`, keeping rows spilled by an earlier process.
+    pub fn open(path: impl AsRef<Path>) -> anyhow::Result<Self> {
+        let path = path.as_ref().to_owned();
+        let file = OpenOptions::new()
+            .read(true)
+            .append(true)
+            .create(true)
+            .open(&path)?;
+
+        Ok(Spill {
+            path,
+            file,
+            chunk_size: 1000,
+            _marker: PhantomData,
+        })
+    }
+
+    /// Rows read back per bulk insert when replaying, bounding the memory used. Defaults to 1000.
+    pub fn chunk_size(mut self, chunk_size: usize) -> Self {
+        self.chunk_size = chunk_size.max(1);
+        self
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn is_empty(&self) -> anyhow::Result<bool> {
+        Ok(self.file.metadata()?.len() == 0)
+    }
+
+    /// Append `values` and sync them to disk.
+    pub fn append(&mut self, values: &[T]) -> anyhow::Result<()> {
+        let mut buf = Vec::new();
+        for value in values {
+            serde_json::to_writer(&mut buf, value)?;
+            buf.push(b'\n');
+        }
+        self.file.write_all(&buf)?;
+        self.file.sync_data()?;
+
+        Ok(())
+    }
+
+    /// Insert the spilled rows in one transaction and empty the file once it committed. Returns
+    /// the number of inserted rows.
+    ///
+    /// A failed replay leaves the file as it was, so it can be retried.
+    pub async fn replay<'c, A>(&mut self, acquire: A) -> anyhow::Result<u64>
+    where
+        A: Acquire<'c, Database = T::Database>,
+        for<'e> &'e mut <T::Database as sqlx::Database>::Connection: Inserter<T::Database>,
+    {
+        if self.is_empty()? {
+            return Ok(0);
+        }
+
+        let options = BulkInsertOptions::default();
+        let mut tx = acquire.begin().await?;
+        let mut reader = BufReader::new(&self.file);
+        reader.rewind()?;
+        let mut lines = reader.lines();
+        let mut rows = 0;
+
+        loop {
+            let chunk = lines
+                .by_ref()
+                .take(self.chunk_size)
+                .map(|line| Ok(serde_json::from_str(&line?)?))
+                .collect::<anyhow::Result<Vec<T>>>()?;
+            if chunk.is_empty() {
+                break;
+            }
+
+            let report: BulkInsertReport = (&mut *tx)
+                .bulk_insert_report_with_options(&chunk, &options)
+                .await?;
+            rows += report.rows_affected;
+        }
+
+        tx.commit().await?;
+        self.file.set_len(0)?;
+        self.file.sync_data()?;
+
+        Ok(rows)
+    }
+}
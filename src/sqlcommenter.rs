@@ -0,0 +1,133 @@
+//! [sqlcommenter](https://google.github.io/sqlcommenter/) comments on every statement, so slow
+//! query logs and `pg_stat_activity` show where a statement came from.
+//!
+//! Register the middleware once:
+//!
+//! ```ignore
+//! Config::new()
+//!     .middleware(SqlCommenter::new().tag("application", "importer"))
+//!     .set_global();
+//! ```
+//!
+//! and every statement ends with a comment like `/*application='importer'*/`. With the
+//! `tracing` feature the current span is added as `local_span_id` and `span_name`, so statements
+//! run inside an instrumented request carry its span without any per-call configuration. The id
+//! is the one of the `tracing` subscriber of this process: it matches the process's own logs, but
+//! it is no distributed trace context like the spec's `traceparent`.
+//!
+//! The chunks of bulk operations also get `batch_id`, `chunk_index` and `chunk_total` from their
+//! [`ChunkPosition`], which ties a slow query log entry to the ingestion job and chunk it came
+//! from:
+//!
+//! ```ignore
+//! BulkInsert::new(&rows).batch_id(job.id).execute(&mut tx).await?;
+//! // INSERT INTO ... /*application='importer',batch_id='42',chunk_index='3',chunk_total='12'*/
+//! ```
+//!
+//! The chunk and span tags make the SQL of every execution unique, so the statements carrying
+//! them aren't kept in the prepared statement cache, see [`Statement::persistent`]. Fixed tags
+//! alone leave the cache alone.
+
+use std::collections::BTreeMap;
+
+use async_trait::async_trait;
+
+use crate::{ChunkPosition, Middleware, Statement};
+
+/// A [`Middleware`] appending a sqlcommenter comment, see the [module](self) docs.
+#[derive(Debug, Clone, Default)]
+pub struct SqlCommenter {
+    tags: BTreeMap<String, String>,
+}
+
+impl SqlCommenter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a fixed `key='value'` to every comment.
+    pub fn tag(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.tags.insert(key.into(), value.into());
+        self
+    }
+
+    /// The comment for the current statement, `None` without any tags.
+    pub fn comment(&self) -> Option<String> {
+        self.comment_for(None).map(|(comment, _)| comment)
+    }
+
+    /// The comment for the current statement in the given chunk, and whether it is unique to
+    /// this execution.
+    fn comment_for(&self, chunk: Option<&ChunkPosition>) -> Option<(String, bool)> {
+        let mut tags = self.tags.clone();
+        #[cfg_attr(not(feature = "tracing"), allow(unused_mut))]
+        let mut unique = chunk.is_some();
+        if let Some(chunk) = chunk {
+            tags.insert("batch_id".to_owned(), chunk.batch_id.to_string());
+            tags.insert("chunk_index".to_owned(), chunk.index.to_string());
+            tags.insert("chunk_total".to_owned(), chunk.total.to_string());
+        }
+        #[cfg(feature = "tracing")]
+        {
+            let span = tracing::Span::current();
+            if let Some(id) = span.id() {
+                tags.insert("local_span_id".to_owned(), id.into_u64().to_string());
+                unique = true;
+            }
+            if let Some(metadata) = span.metadata() {
+                tags.insert("span_name".to_owned(), metadata.name().to_owned());
+            }
+        }
+
+        if tags.is_empty() {
+            return None;
+        }
+
+        // The spec sorts the keys, which the BTreeMap already does.
+        let mut comment = String::from("/*");
+        for (i, (key, value)) in tags.iter().enumerate() {
+            if i > 0 {
+                comment.push(',');
+            }
+            write_encoded(&mut comment, key);
+            comment.push_str("='");
+            write_encoded(&mut comment, value);
+            comment.push('\'');
+        }
+        comment.push_str("*/");
+
+        Some((comment, unique))
+    }
+}
+
+#[async_trait]
+impl Middleware for SqlCommenter {
+    async fn before(&self, statement: &mut Statement) -> anyhow::Result<()> {
+        if let Some((comment, unique)) = self.comment_for(statement.chunk.as_ref()) {
+            statement.sql.push(' ');
+            statement.sql.push_str(&comment);
+            if unique {
+                statement.persistent = false;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// URL-encode like the spec asks, which also keeps `'` and `*/` out of the comment.
+fn write_encoded(buf: &mut String, value: &str) {
+    for byte in value.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                buf.push(byte as char)
+            }
+            _ => {
+                const HEX: &[u8; 16] = b"0123456789ABCDEF";
+                buf.push('%');
+                buf.push(HEX[usize::from(byte >> 4)] as char);
+                buf.push(HEX[usize::from(byte & 0xf)] as char);
+            }
+        }
+    }
+}
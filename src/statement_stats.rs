@@ -0,0 +1,107 @@
+use std::{
+    collections::{hash_map::DefaultHasher, HashMap, HashSet},
+    hash::{Hash, Hasher},
+    sync::Mutex,
+};
+
+use async_trait::async_trait;
+
+use crate::{Middleware, Statement, StatementOutcome};
+
+/// A [`Middleware`] counting per table how often a statement repeats the SQL of an earlier one,
+/// which is how often a prepared statement cache can reuse it.
+///
+/// Every distinct chunk size renders distinct SQL, so a low reuse rate points at chunk sizes
+/// which vary, e.g. a configured one that doesn't divide the usual batch. Keep a handle to read
+/// the numbers:
+///
+/// ```ignore
+/// let stats = Arc::new(StatementStats::new());
+/// Config::new().middleware(stats.clone()).set_global();
+/// // ...
+/// for (table, stats) in stats.snapshot() {
+///     println!("{}: {:.0}% reused", table, stats.reuse_rate() * 100.0);
+/// }
+/// ```
+///
+/// The SQL is compared as executed, after the rewrites of all middlewares, so comments which
+/// differ per statement make every statement distinct.
+#[derive(Debug, Default)]
+pub struct StatementStats {
+    tables: Mutex<HashMap<String, Table>>,
+}
+
+#[derive(Debug, Default)]
+struct Table {
+    stats: TableStatementStats,
+    /// Hashes of the SQL seen, the statements themselves can be megabytes.
+    seen: HashSet<u64>,
+}
+
+/// The statements on one table, see [`StatementStats`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct TableStatementStats {
+    pub statements: u64,
+    /// Statements whose SQL was executed before.
+    pub reused: u64,
+    /// Statements with SQL not executed before, each one is prepared anew.
+    pub prepared: u64,
+}
+
+impl TableStatementStats {
+    /// The share of statements reusing earlier SQL, `0.0` before any statement.
+    pub fn reuse_rate(&self) -> f64 {
+        if self.statements == 0 {
+            return 0.0;
+        }
+        self.reused as f64 / self.statements as f64
+    }
+}
+
+impl StatementStats {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The statistics of `table_name`, empty if no statement ran on it.
+    pub fn table(&self, table_name: &str) -> TableStatementStats {
+        self.tables
+            .lock()
+            .unwrap()
+            .get(table_name)
+            .map(|table| table.stats)
+            .unwrap_or_default()
+    }
+
+    /// The statistics of every table a statement ran on.
+    pub fn snapshot(&self) -> HashMap<String, TableStatementStats> {
+        self.tables
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(name, table)| (name.clone(), table.stats))
+            .collect()
+    }
+
+    pub fn reset(&self) {
+        self.tables.lock().unwrap().clear();
+    }
+}
+
+#[async_trait]
+impl Middleware for StatementStats {
+    async fn after(&self, statement: &Statement, _outcome: &StatementOutcome<'_>) {
+        let mut hasher = DefaultHasher::new();
+        statement.sql.hash(&mut hasher);
+        let hash = hasher.finish();
+
+        let mut tables = self.tables.lock().unwrap();
+        let table = tables.entry(statement.table_name.clone()).or_default();
+        table.stats.statements += 1;
+        if table.seen.insert(hash) {
+            table.stats.prepared += 1;
+        } else {
+            table.stats.reused += 1;
+        }
+    }
+}
@@ -0,0 +1,168 @@
+use std::{marker::PhantomData, pin::Pin};
+
+use futures_core::Stream;
+use futures_util::{stream, TryStreamExt};
+use sqlx::{Executor, FromRow, IntoArguments, Row};
+
+use crate::{compat::ArgumentsOf, Backend, Config, Dialect, Insertable, PlaceHolders, TableName};
+
+/// Collect the rows of a `fetch()` stream into chunks of up to `chunk_size` rows, e.g. to feed
+/// them to `bulk_insert` without buffering the whole result:
+///
+/// ```ignore
+/// let mut chunks = sqlx_plus::chunked(sqlx::query_as::<_, User>("SELECT ...").fetch(&pool), 1000);
+/// while let Some(users) = chunks.try_next().await? {
+///     destination.bulk_insert(&users).await?;
+/// }
+/// ```
+///
+/// The stream keeps its connection busy until it ends. Use [`fetch_stream_chunked`] where that
+/// is not possible.
+pub fn chunked<S, T, E>(stream: S, chunk_size: usize) -> impl Stream<Item = Result<Vec<T>, E>>
+where
+    S: Stream<Item = Result<T, E>>,
+{
+    stream
+        .try_chunks(chunk_size.max(1))
+        .map_err(|error| error.1)
+}
+
+/// Read the rows of `T`'s table as `O` in chunks of `chunk_size` rows, one keyset paginated
+/// query per chunk, so neither the rows nor a server-side cursor are held between chunks:
+///
+/// ```ignore
+/// let mut chunks = sqlx_plus::fetch_stream_chunked::<User, User>(1000).stream(&mut conn);
+/// while let Some(users) = chunks.try_next().await? {
+///     ...
+/// }
+/// ```
+pub fn fetch_stream_chunked<T: Insertable, O>(chunk_size: usize) -> FetchChunked<T, O> {
+    FetchChunked {
+        chunk_size,
+        source_table: None,
+        key_column: None,
+        after: None,
+        _marker: PhantomData,
+    }
+}
+
+/// A builder for [`fetch_stream_chunked`].
+#[derive(Debug, Clone)]
+pub struct FetchChunked<T, O> {
+    chunk_size: usize,
+    source_table: Option<TableName>,
+    key_column: Option<String>,
+    after: Option<i64>,
+    _marker: PhantomData<fn() -> (T, O)>,
+}
+
+impl<T, O> FetchChunked<T, O>
+where
+    T: Insertable,
+    T::Database: Backend,
+{
+    /// Defaults to `T`'s table.
+    pub fn source_table(mut self, source_table: TableName) -> Self {
+        self.source_table = Some(source_table);
+        self
+    }
+
+    /// The integer key the rows are paginated by. Defaults to `id`.
+    pub fn key_column(mut self, key_column: impl Into<String>) -> Self {
+        self.key_column = Some(key_column.into());
+        self
+    }
+
+    /// Only read rows with a greater key.
+    pub fn after(mut self, key: i64) -> Self {
+        self.after = Some(key);
+        self
+    }
+
+    /// The chunks in key order. The stream ends after the first short chunk or error.
+    pub fn stream<'a, E>(
+        self,
+        executor: &'a mut E,
+    ) -> Pin<Box<dyn Stream<Item = anyhow::Result<Vec<O>>> + Send + 'a>>
+    where
+        T: 'a,
+        O: for<'r> FromRow<'r, <T::Database as sqlx::Database>::Row> + Send + 'a,
+        E: Send,
+        for<'e> &'e mut E: Executor<'e, Database = T::Database>,
+        for<'q> ArgumentsOf<'q, T::Database>: IntoArguments<'q, T::Database>,
+        i64: for<'q> sqlx::Encode<'q, T::Database>
+            + for<'r> sqlx::Decode<'r, T::Database>
+            + sqlx::Type<T::Database>,
+        for<'c> &'c str: sqlx::ColumnIndex<<T::Database as sqlx::Database>::Row>,
+    {
+        let chunk_size = self.chunk_size.max(1);
+        let table_name = self.source_table.map_or_else(
+            || T::table_name().to_owned(),
+            |table| table.as_str().to_owned(),
+        );
+        let key_column = self.key_column.unwrap_or_else(|| "id".to_owned());
+
+        let mut placeholder = String::new();
+        T::Database::write_placeholders(&mut placeholder, 1, 1);
+        let sql = match T::Database::DIALECT {
+            Dialect::Mssql => format!(
+                "SELECT TOP {} * FROM {} WHERE {key} > {} ORDER BY {key}",
+                chunk_size,
+                table_name,
+                placeholder,
+                key = key_column,
+            ),
+            _ => format!(
+                "SELECT * FROM {} WHERE {key} > {} ORDER BY {key} LIMIT {}",
+                table_name,
+                placeholder,
+                chunk_size,
+                key = key_column,
+            ),
+        };
+
+        let state = Some((executor, self.after.unwrap_or(i64::MIN)));
+        Box::pin(stream::unfold(state, move |state| {
+            let sql = sql.clone();
+            let table_name = table_name.clone();
+            let key_column = key_column.clone();
+            async move {
+                let (executor, after) = state?;
+                let chunk = async {
+                    let config = Config::global();
+                    let statement = config.before(sql, &table_name, 0).await?;
+                    let rows = statement
+                        .run(
+                            sqlx::query(statement.sql())
+                                .bind(after)
+                                .fetch_all(&mut *executor),
+                        )
+                        .await;
+                    statement.after(&rows, |rows| rows.len() as u64).await;
+                    let rows = rows?;
+
+                    let last_key = match rows.last() {
+                        Some(row) => Some(row.try_get::<i64, _>(key_column.as_str())?),
+                        None => None,
+                    };
+                    let values = rows
+                        .iter()
+                        .map(|row| O::from_row(row))
+                        .collect::<Result<Vec<_>, _>>()?;
+
+                    anyhow::Ok((values, last_key))
+                }
+                .await;
+
+                match chunk {
+                    Ok((values, _)) if values.is_empty() => None,
+                    Ok((values, Some(last_key))) if values.len() == chunk_size => {
+                        Some((Ok(values), Some((executor, last_key))))
+                    }
+                    Ok((values, _)) => Some((Ok(values), None)),
+                    Err(error) => Some((Err(error), None)),
+                }
+            }
+        }))
+    }
+}
@@ -0,0 +1,133 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use sqlx::{Column, Executor};
+
+use crate::{Backend, Insertable};
+
+/// The columns tables actually have, for
+/// [`BulkInsert::skip_missing_columns`](crate::BulkInsert::skip_missing_columns).
+///
+/// During a rolling deploy new code may insert into a table the migration hasn't reached yet.
+/// With a cache set, the columns of `T` the table lacks are left out of the insert, and get
+/// their defaults once the migration adds them:
+///
+/// ```ignore
+/// static COLUMNS: Lazy<Arc<ColumnCache>> = Lazy::new(|| Arc::new(ColumnCache::new()));
+///
+/// BulkInsert::new(&users)
+///     .skip_missing_columns(COLUMNS.clone())
+///     .execute(&pool)
+///     .await?;
+/// ```
+///
+/// Keep one cache per pool, the tables are only keyed by name. Entries are kept for a minute by
+/// default, [`invalidate`](Self::invalidate) one after running a migration to pick it up at once.
+#[derive(Debug)]
+pub struct ColumnCache {
+    ttl: Duration,
+    tables: Mutex<HashMap<String, Table>>,
+}
+
+#[derive(Debug)]
+struct Table {
+    looked_up: Instant,
+    columns: Arc<[String]>,
+}
+
+impl Default for ColumnCache {
+    fn default() -> Self {
+        ColumnCache {
+            ttl: Duration::from_secs(60),
+            tables: Mutex::default(),
+        }
+    }
+}
+
+impl ColumnCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Look the columns of a table up again `ttl` after the last time.
+    pub fn ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = ttl;
+        self
+    }
+
+    pub fn invalidate(&self, table_name: &str) {
+        self.tables.lock().unwrap().remove(table_name);
+    }
+
+    pub fn clear(&self) {
+        self.tables.lock().unwrap().clear();
+    }
+
+    /// The columns of `table_name`, from the cache or described by the database.
+    pub async fn columns<E, DB>(
+        &self,
+        executor: &mut E,
+        table_name: &str,
+    ) -> anyhow::Result<Arc<[String]>>
+    where
+        DB: Backend,
+        for<'e> &'e mut E: Executor<'e, Database = DB>,
+    {
+        if let Some(table) = self.tables.lock().unwrap().get(table_name) {
+            if table.looked_up.elapsed() < self.ttl {
+                return Ok(table.columns.clone());
+            }
+        }
+
+        let sql = format!("SELECT * FROM {} WHERE 1 = 0", table_name);
+        let describe = (&mut *executor).describe(&sql).await?;
+        let columns: Arc<[String]> = describe
+            .columns()
+            .iter()
+            .map(|column| unquote(column.name()).to_owned())
+            .collect();
+        self.tables.lock().unwrap().insert(
+            table_name.to_owned(),
+            Table {
+                looked_up: Instant::now(),
+                columns: columns.clone(),
+            },
+        );
+        Ok(columns)
+    }
+
+    /// The insert columns of `T` which `table_name` has, `None` if it has all of them.
+    pub(crate) async fn present_columns<T, E>(
+        &self,
+        executor: &mut E,
+        table_name: &str,
+    ) -> anyhow::Result<Option<Vec<&'static str>>>
+    where
+        T: Insertable,
+        T::Database: Backend,
+        for<'e> &'e mut E: Executor<'e, Database = T::Database>,
+    {
+        let columns = self.columns(executor, table_name).await?;
+        let has = |column: &str| {
+            let column = unquote(column);
+            columns.iter().any(|x| x.eq_ignore_ascii_case(column))
+        };
+        if T::insert_columns().iter().all(|column| has(column)) {
+            return Ok(None);
+        }
+        Ok(Some(
+            T::insert_columns()
+                .iter()
+                .copied()
+                .filter(|column| has(column))
+                .collect(),
+        ))
+    }
+}
+
+fn unquote(column: &str) -> &str {
+    column.trim_matches(|c| matches!(c, '"' | '`' | '[' | ']'))
+}
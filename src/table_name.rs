@@ -0,0 +1,85 @@
+use std::fmt;
+
+use anyhow::ensure;
+
+use crate::Dialect;
+
+/// A table name passed at runtime, checked to be safe to interpolate into SQL.
+///
+/// ```ignore
+/// let table = TableName::new(format!("events_{}", shard))?;
+/// tx.bulk_insert_with_table_name(&table, &events).await?;
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct TableName(String);
+
+impl TableName {
+    /// Accepts plain identifiers, optionally qualified with a schema: ASCII letters, digits, `_`
+    /// and `$`, not starting with a digit.
+    pub fn new(name: impl Into<String>) -> anyhow::Result<Self> {
+        let name = name.into();
+        for part in name.split('.') {
+            ensure!(
+                part.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+                    && part
+                        .chars()
+                        .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$'),
+                "invalid table name {:?}",
+                name
+            );
+        }
+        Ok(TableName(name))
+    }
+
+    /// Quote each dot-separated part of `name` for `dialect`, which accepts any name without a
+    /// dot in a part.
+    pub fn quoted(dialect: Dialect, name: &str) -> Self {
+        let (open, close) = crate::sanitize::identifier_quotes(dialect);
+
+        let mut quoted = String::with_capacity(name.len() + 2);
+        for (i, part) in name.split('.').enumerate() {
+            if i > 0 {
+                quoted.push('.');
+            }
+            crate::sanitize::write_quoted_identifier(&mut quoted, open, close, part);
+        }
+        TableName(quoted)
+    }
+
+    /// Skip the checks, for names which never come from untrusted input.
+    pub fn trusted(name: impl Into<String>) -> Self {
+        TableName(name.into())
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl AsRef<str> for TableName {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for TableName {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl TryFrom<&str> for TableName {
+    type Error = anyhow::Error;
+
+    fn try_from(name: &str) -> anyhow::Result<Self> {
+        TableName::new(name)
+    }
+}
+
+impl TryFrom<String> for TableName {
+    type Error = anyhow::Error;
+
+    fn try_from(name: String) -> anyhow::Result<Self> {
+        TableName::new(name)
+    }
+}
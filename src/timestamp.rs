@@ -0,0 +1,77 @@
+//! Timestamps for `#[insertable(auto_now)]` fields, e.g. `created_at`.
+//!
+//! A bulk insert reads the clock once and fills every `auto_now` field of every chunk with that
+//! timestamp, so a batch is temporally consistent however long it takes. Pin it with
+//! [`BulkInsertOptions::now`](crate::BulkInsertOptions::now) for reproducible tests:
+//!
+//! ```ignore
+//! #[derive(sqlx_plus::Insertable)]
+//! #[insertable(sqlx::Postgres, "orders")]
+//! struct NewOrder {
+//!     item: String,
+//!     #[insertable(auto_now)]
+//!     created_at: Option<chrono::DateTime<chrono::Utc>>,
+//! }
+//!
+//! BulkInsert::new(&orders).now(frozen).execute(&pool).await?;
+//! ```
+//!
+//! A field which is already set keeps its value. Outside of a bulk insert, e.g. in `insert`, the
+//! clock is read per statement. The clock is the one of the config, see [`clock`].
+
+use std::time::SystemTime;
+
+use crate::clock;
+
+/// A timestamp type of an `auto_now` field.
+pub trait Timestamp: Sized {
+    fn from_system_time(time: SystemTime) -> Self;
+}
+
+#[cfg(feature = "chrono")]
+impl Timestamp for sqlx::types::chrono::DateTime<sqlx::types::chrono::Utc> {
+    fn from_system_time(time: SystemTime) -> Self {
+        time.into()
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl Timestamp for sqlx::types::chrono::DateTime<sqlx::types::chrono::Local> {
+    fn from_system_time(time: SystemTime) -> Self {
+        time.into()
+    }
+}
+
+/// In UTC.
+#[cfg(feature = "chrono")]
+impl Timestamp for sqlx::types::chrono::NaiveDateTime {
+    fn from_system_time(time: SystemTime) -> Self {
+        sqlx::types::chrono::DateTime::<sqlx::types::chrono::Utc>::from(time).naive_utc()
+    }
+}
+
+#[cfg(feature = "time")]
+impl Timestamp for sqlx::types::time::OffsetDateTime {
+    fn from_system_time(time: SystemTime) -> Self {
+        time.into()
+    }
+}
+
+/// In UTC.
+#[cfg(feature = "time")]
+impl Timestamp for sqlx::types::time::PrimitiveDateTime {
+    fn from_system_time(time: SystemTime) -> Self {
+        let time = sqlx::types::time::OffsetDateTime::from(time);
+        sqlx::types::time::PrimitiveDateTime::new(time.date(), time.time())
+    }
+}
+
+/// The value bound for an `auto_now` field: its own value, or [`clock::now`] if it is `None`.
+#[doc(hidden)]
+pub fn auto_now<T: Timestamp + Clone>(value: &Option<T>) -> Option<T> {
+    Some(
+        value
+            .clone()
+            .unwrap_or_else(|| T::from_system_time(clock::now())),
+    )
+}
@@ -0,0 +1,177 @@
+use std::collections::HashMap;
+
+use crate::Dialect;
+
+/// The SQL type of each Rust type on each dialect, like `i64` to `BIGINT` on PostgreSQL.
+///
+/// It is the one mapping for everything which writes DDL or code from structs or the other way
+/// round, so they agree on the types. Overrides take precedence in both directions:
+///
+/// ```ignore
+/// let types = TypeMap::new(Dialect::Postgres).override_type("String", "VARCHAR(255)");
+/// assert_eq!(types.sql_type("Option<String>"), Some("VARCHAR(255)"));
+/// assert_eq!(types.rust_type("bigint"), Some("i64"));
+/// ```
+///
+/// Rust types are matched by their last path segments, so `chrono::NaiveDateTime` and
+/// `NaiveDateTime` are the same. `Option` is looked through, nullability is up to the caller.
+///
+/// The types of sqlx's type features are all mapped, whether their feature is enabled or not.
+/// A SQL type several of them map to, like `DATE` for chrono's `NaiveDate` and time's `Date`,
+/// maps back to one whose feature is enabled.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TypeMap {
+    dialect: Dialect,
+    overrides: HashMap<String, String>,
+}
+
+/// Rust type, then SQLite, MySQL, PostgreSQL and MSSQL types. For the reverse lookup the first
+/// row with a SQL type wins, so the preferred Rust type of a SQL type comes first.
+#[rustfmt::skip]
+const DEFAULT_TYPES: &[(&str, [Option<&str>; 4])] = &[
+    ("bool", [Some("BOOLEAN"), Some("BOOLEAN"), Some("BOOLEAN"), Some("BIT")]),
+    ("i64", [Some("INTEGER"), Some("BIGINT"), Some("BIGINT"), Some("BIGINT")]),
+    ("i32", [Some("INTEGER"), Some("INT"), Some("INTEGER"), Some("INT")]),
+    ("i16", [Some("INTEGER"), Some("SMALLINT"), Some("SMALLINT"), Some("SMALLINT")]),
+    ("i8", [Some("INTEGER"), Some("TINYINT"), Some("SMALLINT"), Some("TINYINT")]),
+    ("u64", [None, Some("BIGINT UNSIGNED"), Some("NUMERIC(20)"), Some("DECIMAL(20)")]),
+    ("u32", [Some("INTEGER"), Some("INT UNSIGNED"), Some("BIGINT"), Some("BIGINT")]),
+    ("u16", [Some("INTEGER"), Some("SMALLINT UNSIGNED"), Some("INTEGER"), Some("INT")]),
+    ("u8", [Some("INTEGER"), Some("TINYINT UNSIGNED"), Some("SMALLINT"), Some("TINYINT")]),
+    ("f64", [Some("REAL"), Some("DOUBLE"), Some("DOUBLE PRECISION"), Some("FLOAT")]),
+    ("f32", [Some("REAL"), Some("FLOAT"), Some("REAL"), Some("REAL")]),
+    ("String", [Some("TEXT"), Some("TEXT"), Some("TEXT"), Some("NVARCHAR(MAX)")]),
+    ("Vec<u8>", [Some("BLOB"), Some("BLOB"), Some("BYTEA"), Some("VARBINARY(MAX)")]),
+    ("Decimal", [None, Some("DECIMAL"), Some("NUMERIC"), Some("DECIMAL")]),
+    ("BigDecimal", [None, Some("DECIMAL"), Some("NUMERIC"), Some("DECIMAL")]),
+    ("Uuid", [Some("BLOB"), Some("BINARY(16)"), Some("UUID"), Some("UNIQUEIDENTIFIER")]),
+    ("NaiveDate", [Some("DATE"), Some("DATE"), Some("DATE"), Some("DATE")]),
+    ("NaiveTime", [Some("TIME"), Some("TIME(6)"), Some("TIME"), Some("TIME")]),
+    ("NaiveDateTime", [Some("DATETIME"), Some("DATETIME(6)"), Some("TIMESTAMP"), Some("DATETIME2")]),
+    ("DateTime<Utc>", [Some("DATETIME"), Some("TIMESTAMP(6)"), Some("TIMESTAMPTZ"), Some("DATETIMEOFFSET")]),
+    ("DateTime<FixedOffset>", [Some("DATETIME"), Some("TIMESTAMP(6)"), Some("TIMESTAMPTZ"), Some("DATETIMEOFFSET")]),
+    ("Date", [Some("DATE"), Some("DATE"), Some("DATE"), Some("DATE")]),
+    ("Time", [Some("TIME"), Some("TIME(6)"), Some("TIME"), Some("TIME")]),
+    ("PrimitiveDateTime", [Some("DATETIME"), Some("DATETIME(6)"), Some("TIMESTAMP"), Some("DATETIME2")]),
+    ("OffsetDateTime", [Some("DATETIME"), Some("TIMESTAMP(6)"), Some("TIMESTAMPTZ"), Some("DATETIMEOFFSET")]),
+    ("IpNetwork", [None, None, Some("INET"), None]),
+    ("IpAddr", [None, None, Some("INET"), None]),
+    ("MacAddress", [None, None, Some("MACADDR"), None]),
+    ("BitVec", [None, None, Some("VARBIT"), None]),
+    ("Value", [Some("TEXT"), Some("JSON"), Some("JSONB"), Some("NVARCHAR(MAX)")]),
+];
+
+/// Whether the feature binding `rust_type` is enabled. The reverse lookup prefers those types,
+/// so `DATE` maps to the `time` crate's `Date` with only the `time` feature.
+fn is_enabled(rust_type: &str) -> bool {
+    match rust_type {
+        "Decimal" => cfg!(feature = "decimal"),
+        "BigDecimal" => cfg!(feature = "bigdecimal"),
+        "Uuid" => cfg!(feature = "uuid"),
+        "NaiveDate" | "NaiveTime" | "NaiveDateTime" | "DateTime<Utc>" | "DateTime<FixedOffset>" => {
+            cfg!(feature = "chrono")
+        }
+        "Date" | "Time" | "PrimitiveDateTime" | "OffsetDateTime" => cfg!(feature = "time"),
+        "IpNetwork" | "IpAddr" => cfg!(feature = "ipnetwork"),
+        "MacAddress" => cfg!(feature = "mac_address"),
+        "BitVec" => cfg!(feature = "bit-vec"),
+        "Value" => cfg!(feature = "json"),
+        _ => true,
+    }
+}
+
+impl TypeMap {
+    pub fn new(dialect: Dialect) -> Self {
+        TypeMap {
+            dialect,
+            overrides: HashMap::new(),
+        }
+    }
+
+    pub fn dialect(&self) -> Dialect {
+        self.dialect
+    }
+
+    /// Map `rust_type` to `sql_type` instead of the default, e.g. a newtype or a sized `VARCHAR`.
+    pub fn override_type(mut self, rust_type: &str, sql_type: impl Into<String>) -> Self {
+        self.overrides
+            .insert(normalize_rust_type(rust_type), sql_type.into());
+        self
+    }
+
+    /// The SQL type of `rust_type`, or `None` if it has no mapping on the dialect.
+    pub fn sql_type(&self, rust_type: &str) -> Option<&str> {
+        let rust_type = normalize_rust_type(rust_type);
+        if let Some(sql_type) = self.overrides.get(&rust_type) {
+            return Some(sql_type);
+        }
+
+        DEFAULT_TYPES
+            .iter()
+            .find(|(name, _)| *name == rust_type)
+            .and_then(|(_, sql_types)| sql_types[self.column()])
+    }
+
+    /// The Rust type of `sql_type`, compared case-insensitively, or `None` if no Rust type maps
+    /// to it.
+    pub fn rust_type(&self, sql_type: &str) -> Option<&str> {
+        let sql_type = sql_type.trim();
+        let mut overrides: Vec<_> = self
+            .overrides
+            .iter()
+            .filter(|(_, x)| x.eq_ignore_ascii_case(sql_type))
+            .map(|(rust_type, _)| rust_type.as_str())
+            .collect();
+        // Several overrides may share a SQL type, pick one deterministically.
+        overrides.sort_unstable();
+        if let Some(rust_type) = overrides.first() {
+            return Some(rust_type);
+        }
+
+        let mut candidates = DEFAULT_TYPES
+            .iter()
+            .filter(|(_, sql_types)| {
+                sql_types[self.column()].is_some_and(|x| x.eq_ignore_ascii_case(sql_type))
+            })
+            .map(|(rust_type, _)| *rust_type)
+            .peekable();
+        let first = candidates.peek().copied();
+        candidates.find(|x| is_enabled(x)).or(first)
+    }
+
+    fn column(&self) -> usize {
+        match self.dialect {
+            Dialect::Sqlite => 0,
+            Dialect::MySql => 1,
+            Dialect::Postgres => 2,
+            Dialect::Mssql => 3,
+        }
+    }
+}
+
+/// `Option<chrono::DateTime<chrono::Utc>>` to `DateTime<Utc>`: no whitespace, no paths, no
+/// `Option`.
+fn normalize_rust_type(rust_type: &str) -> String {
+    let mut buf = String::with_capacity(rust_type.len());
+    let mut segment_start = 0;
+    let mut chars = rust_type.chars().filter(|c| !c.is_whitespace()).peekable();
+    while let Some(c) = chars.next() {
+        if c == ':' && chars.peek() == Some(&':') {
+            chars.next();
+            buf.truncate(segment_start);
+            continue;
+        }
+        buf.push(c);
+        if !(c.is_alphanumeric() || c == '_') {
+            segment_start = buf.len();
+        }
+    }
+
+    match buf
+        .strip_prefix("Option<")
+        .and_then(|x| x.strip_suffix('>'))
+    {
+        Some(inner) => inner.to_owned(),
+        None => buf,
+    }
+}
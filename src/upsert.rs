@@ -0,0 +1,323 @@
+//! Upserts: `ON CONFLICT` on PostgreSQL and SQLite, `ON DUPLICATE KEY UPDATE` on MySQL.
+//!
+//! MSSQL has no upsert clause for `INSERT` and is not supported.
+
+use anyhow::bail;
+use sqlx::{Executor, FromRow, IntoArguments};
+
+use crate::{
+    clock, compat::ArgumentsOf, write_values_cte, Backend, BulkInsertOptions, Capabilities,
+    Dialect, Insertable, QueryBindExt,
+};
+
+/// Which conflicts an upsert handles.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum ConflictTarget {
+    /// Any unique constraint. MySQL always behaves like this.
+    Any,
+    /// The unique index on these columns.
+    Columns(Vec<String>),
+    /// The named constraint, e.g. an exclusion or unique constraint whose semantics a column list
+    /// can't express. PostgreSQL only.
+    Constraint(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum ConflictAction {
+    DoNothing,
+    /// Overwrite these columns with the values of the conflicting row.
+    DoUpdate(Vec<String>),
+}
+
+/// The upsert clause of a bulk insert.
+///
+/// ```ignore
+/// OnConflict::columns(["email"]).do_update(["name", "updated_at"])
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct OnConflict {
+    pub target: ConflictTarget,
+    pub action: ConflictAction,
+}
+
+impl OnConflict {
+    pub fn any() -> Self {
+        OnConflict {
+            target: ConflictTarget::Any,
+            action: ConflictAction::DoNothing,
+        }
+    }
+
+    pub fn columns<I, S>(columns: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        OnConflict {
+            target: ConflictTarget::Columns(columns.into_iter().map(Into::into).collect()),
+            action: ConflictAction::DoNothing,
+        }
+    }
+
+    /// Conflicts on the first of [`Insertable::unique_columns`], or on any constraint if `T`
+    /// declares none.
+    pub fn unique_of<T: Insertable>() -> Self {
+        match T::unique_columns().first() {
+            Some(columns) => Self::columns(columns.iter().copied()),
+            None => Self::any(),
+        }
+    }
+
+    /// `ON CONFLICT ON CONSTRAINT {name}`, PostgreSQL only.
+    pub fn constraint(name: impl Into<String>) -> Self {
+        OnConflict {
+            target: ConflictTarget::Constraint(name.into()),
+            action: ConflictAction::DoNothing,
+        }
+    }
+
+    pub fn do_nothing(mut self) -> Self {
+        self.action = ConflictAction::DoNothing;
+        self
+    }
+
+    pub fn do_update<I, S>(mut self, columns: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.action = ConflictAction::DoUpdate(columns.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Write an `INSERT` statement of `columns` with this upsert clause to `buf`. `statement`
+    /// writes the part after `INSERT`, i.e. `INTO table (...) VALUES ...`.
+    pub(crate) fn write_sql(
+        &self,
+        buf: &mut String,
+        capabilities: &Capabilities,
+        columns: &[&str],
+        statement: impl FnOnce(&mut String) -> anyhow::Result<()>,
+    ) -> anyhow::Result<()> {
+        match capabilities.dialect {
+            Dialect::Sqlite if matches!(self.target, ConflictTarget::Constraint(_)) => {
+                bail!("SQLite can't target a conflict by constraint name")
+            }
+            Dialect::Postgres | Dialect::Sqlite if capabilities.supports_on_conflict => {
+                buf.push_str("INSERT ");
+                statement(buf)?;
+                buf.push_str(" ON CONFLICT");
+                match &self.target {
+                    ConflictTarget::Any => {}
+                    ConflictTarget::Columns(columns) => {
+                        buf.push_str(" (");
+                        buf.push_str(&columns.join(","));
+                        buf.push(')');
+                    }
+                    ConflictTarget::Constraint(name) => {
+                        buf.push_str(" ON CONSTRAINT ");
+                        buf.push_str(name);
+                    }
+                }
+                match &self.action {
+                    ConflictAction::DoNothing => buf.push_str(" DO NOTHING"),
+                    ConflictAction::DoUpdate(_) if self.target == ConflictTarget::Any => {
+                        bail!("ON CONFLICT DO UPDATE requires conflict target columns")
+                    }
+                    ConflictAction::DoUpdate(update) => {
+                        buf.push_str(" DO UPDATE SET ");
+                        write_assignments(buf, update, |buf, column| {
+                            buf.push_str("excluded.");
+                            buf.push_str(column);
+                        });
+                    }
+                }
+            }
+            // SQLite < 3.24 has no upsert clause, but can still skip conflicting rows.
+            Dialect::Sqlite => match self.action {
+                ConflictAction::DoNothing => {
+                    buf.push_str("INSERT OR IGNORE ");
+                    statement(buf)?;
+                }
+                ConflictAction::DoUpdate(_) => {
+                    bail!("ON CONFLICT DO UPDATE requires SQLite 3.24 or later")
+                }
+            },
+            Dialect::MySql => {
+                let row_alias = capabilities
+                    .server_version
+                    .as_ref()
+                    .is_some_and(|x| !x.is_mariadb() && x.at_least(8, 0, 19));
+
+                buf.push_str("INSERT ");
+                statement(buf)?;
+                if row_alias {
+                    buf.push_str(" AS new");
+                }
+                buf.push_str(" ON DUPLICATE KEY UPDATE ");
+                match &self.action {
+                    // A no-op assignment keeps the existing row without `INSERT IGNORE`, which
+                    // would also swallow unrelated errors. It needs a column to assign.
+                    ConflictAction::DoNothing => {
+                        let column = match (columns.first(), &self.target) {
+                            (Some(column), _) => *column,
+                            (None, ConflictTarget::Columns(target)) if !target.is_empty() => {
+                                target[0].as_str()
+                            }
+                            (None, _) => bail!(
+                                "MySQL can't skip conflicting rows of an insert without columns, \
+                                 give conflict target columns to assign"
+                            ),
+                        };
+                        buf.push_str(column);
+                        buf.push('=');
+                        buf.push_str(column);
+                    }
+                    ConflictAction::DoUpdate(update) => {
+                        write_assignments(buf, update, |buf, column| {
+                            if row_alias {
+                                buf.push_str("new.");
+                                buf.push_str(column);
+                            } else {
+                                buf.push_str("VALUES(");
+                                buf.push_str(column);
+                                buf.push(')');
+                            }
+                        })
+                    }
+                }
+            }
+            dialect => bail!("upsert is not supported on {:?}", dialect),
+        }
+
+        Ok(())
+    }
+}
+
+fn write_assignments(buf: &mut String, columns: &[String], value: impl Fn(&mut String, &str)) {
+    for (i, column) in columns.iter().enumerate() {
+        if i > 0 {
+            buf.push(',');
+        }
+        buf.push_str(column);
+        buf.push('=');
+        value(buf, column);
+    }
+}
+
+/// What an upsert did with an input row.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum UpsertOutcome {
+    Inserted(i64),
+    Updated(i64),
+    /// The row conflicted and `DO NOTHING` kept the existing one.
+    Skipped,
+}
+
+/// Upsert the values and report per input row whether it was inserted, updated or skipped.
+///
+/// PostgreSQL only. The rows are fed through a `VALUES` list numbered in input order and joined
+/// back to the `RETURNING` rows on the conflict target columns, `xmax = 0` tells inserted rows
+/// from updated ones.
+pub(crate) async fn bulk_upsert_returning<T, E, DB>(
+    executor: &mut E,
+    values: &[T],
+    options: &BulkInsertOptions,
+) -> anyhow::Result<Vec<UpsertOutcome>>
+where
+    DB: Backend,
+    T: Insertable<Database = DB> + Sync,
+    for<'e> &'e mut E: Executor<'e, Database = DB>,
+    for<'q> ArgumentsOf<'q, DB>: IntoArguments<'q, DB>,
+    (Option<i64>, Option<bool>): for<'r> FromRow<'r, DB::Row>,
+{
+    if DB::DIALECT != Dialect::Postgres {
+        bail!("upsert outcomes are only supported on PostgreSQL");
+    }
+
+    let on_conflict = match &options.on_conflict {
+        Some(on_conflict) => on_conflict,
+        None => bail!("upsert outcomes require an on_conflict clause"),
+    };
+    let target = match &on_conflict.target {
+        ConflictTarget::Columns(columns) => columns,
+        _ => bail!("upsert outcomes require conflict target columns"),
+    };
+
+    if values.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let config = options.config();
+    let capabilities = options.capabilities::<DB>();
+    let table_name = options.table_name::<T>();
+    let chunk_size = options.chunk_size::<T>()?;
+    let columns = T::insert_columns().join(",");
+    let mut outcomes = Vec::with_capacity(values.len());
+
+    options.before_load::<T, E>(&mut *executor).await?;
+    let result = clock::scope(options.batch(), async {
+        let positions = options.chunk_positions(values.len().div_ceil(chunk_size));
+        for (chunk, position) in values.chunks(chunk_size).zip(positions) {
+            let mut sql = String::new();
+            write_values_cte::<T>(&mut sql, &capabilities, chunk.len());
+            sql.push_str(", ins AS (");
+            on_conflict.write_sql(&mut sql, &capabilities, T::insert_columns(), |sql| {
+                sql.push_str("INTO ");
+                sql.push_str(table_name);
+                sql.push_str(" (");
+                sql.push_str(&columns);
+                sql.push_str(") SELECT ");
+                sql.push_str(&columns);
+                sql.push_str(" FROM v ORDER BY sqlx_plus_ord");
+                Ok(())
+            })?;
+            sql.push_str(" RETURNING ");
+            sql.push_str(options.id_column());
+            for column in target {
+                sql.push(',');
+                sql.push_str(column);
+            }
+            sql.push_str(",(xmax = 0) AS sqlx_plus_inserted) SELECT ins.");
+            sql.push_str(options.id_column());
+            sql.push_str(",ins.sqlx_plus_inserted FROM v LEFT JOIN ins ON ");
+            for (i, column) in target.iter().enumerate() {
+                if i > 0 {
+                    sql.push_str(" AND ");
+                }
+                sql.push_str("v.");
+                sql.push_str(column);
+                sql.push_str("=ins.");
+                sql.push_str(column);
+            }
+            sql.push_str(" ORDER BY v.sqlx_plus_ord");
+
+            let statement = config
+                .before_chunk(sql, table_name, chunk.len(), position)
+                .await?;
+            let rows = statement
+                .run(
+                    DB::set_persistent(
+                        sqlx::query(statement.sql()),
+                        statement.persistent(options.persistent),
+                    )
+                    .bind_multi_fields(chunk)
+                    .fetch_all(&mut *executor),
+                )
+                .await;
+            statement.after(&rows, |rows| rows.len() as u64).await;
+
+            for row in &rows? {
+                outcomes.push(match <(Option<i64>, Option<bool>)>::from_row(row)? {
+                    (Some(id), Some(true)) => UpsertOutcome::Inserted(id),
+                    (Some(id), _) => UpsertOutcome::Updated(id),
+                    (None, _) => UpsertOutcome::Skipped,
+                });
+            }
+        }
+
+        Ok(outcomes)
+    })
+    .await;
+    options.after_load::<T, E, _>(executor, result).await
+}